@@ -0,0 +1,46 @@
+//! Frame times of the default view with a large log: 10k requests, 1M lines,
+//! and with a few requests of 200k lines each.
+//!
+//! `cargo bench --bench render`
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use lucy::headless::{self, Headless};
+use lucy::simple_formatter::Verbosity;
+
+fn render(c: &mut Criterion) {
+    let mut app = headless::populated_app(10_000, 100);
+    let mut screen = Headless::new(200, 60);
+    let mut group = c.benchmark_group("render");
+
+    group.bench_function("default", |b| {
+        b.iter(|| {
+            screen.draw(&mut app);
+        });
+    });
+
+    app.verbosity = Verbosity::Simple;
+    group.bench_function("simple mode", |b| {
+        b.iter(|| {
+            screen.draw(&mut app);
+        });
+    });
+    app.verbosity = Verbosity::Full;
+
+    app.app_view.show_stats = true;
+    group.bench_function("stats", |b| {
+        b.iter(|| {
+            screen.draw(&mut app);
+        });
+    });
+
+    let mut app = headless::populated_app(3, 200_000);
+    group.bench_function("huge request", |b| {
+        b.iter(|| {
+            screen.draw(&mut app);
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
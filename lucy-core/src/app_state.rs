@@ -0,0 +1,2165 @@
+//! Lines grouped into the requests, jobs and connections they belong to,
+//! with each group's status, duration and totals, and the policies that
+//! decide which groups are kept: retention, sampling and the overall cap.
+//!
+//! Each entry can carry what the caller makes of it when it joins its
+//! group, such as the lines a UI draws; see `Render`.
+
+use crate::{
+    cable::{self, CableEvent},
+    cache_info::CacheInfo,
+    http_info::HttpInfo,
+    journal::JournalRef,
+    log_parser,
+    memory_info::MemoryInfo,
+    model::ParsedLine,
+    render_info::RenderInfo,
+    retention::RetentionConfig,
+    sampling::Sampler,
+    sql_info::SqlQueryInfo,
+    stats::RequestStats,
+    status::StatusConfig,
+    timeline::{GapNote, GapTracker},
+    title::{Title, TitleFields, TitleTemplate},
+    verbosity::{Verbosity, is_simple_log_line, is_summary_log_line},
+};
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+type RequestIds = VecDeque<String>;
+
+pub use crate::model::{GroupKind, JobEvent, JobOutcome, StatusType};
+
+/// Key of the group collecting lines without a request id.
+pub const UNASSIGNED_ID: &str = "(unassigned)";
+/// Lines the unassigned group keeps; older ones are dropped.
+const UNASSIGNED_MAX_LINES: usize = 5000;
+/// Requests with the same fingerprint started this close together are
+/// flagged as a likely double submit.
+const DUPLICATE_WINDOW: TimeDelta = TimeDelta::seconds(2);
+/// How long a finished request stays in the fingerprint index.
+const DUPLICATE_INDEX_AGE: TimeDelta = TimeDelta::seconds(60);
+
+/// What the caller keeps with each entry, made once when the entry joins
+/// its group rather than every time it is shown. lucy keeps the lines its
+/// detail panel draws; `()` keeps nothing.
+pub trait Render: Clone + Default {
+    /// `simple` and `summary` tell whether those modes show the line.
+    fn render(message: &str, simple: bool, summary: bool) -> Self;
+
+    /// Leads the rendering with the id of the request the line came from,
+    /// for a group merged from several requests.
+    fn tag_origin(&mut self, _request_id: &str) {}
+}
+
+impl Render for () {
+    fn render(_message: &str, _simple: bool, _summary: bool) -> Self {}
+}
+
+/// `message` rendered, and whether simple and summary mode show it.
+fn render<R: Render>(message: &str) -> (R, bool, bool) {
+    let simple = is_simple_log_line(message);
+    let summary = is_summary_log_line(message);
+    (R::render(message, simple, summary), simple, summary)
+}
+
+/// What became of the lines handed to `AppState`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IngestCounters {
+    /// Every parsed line
+    pub lines: u64,
+    /// Lines without a request id, collected in the unassigned group
+    pub orphan_lines: u64,
+    /// Lines of requests sampling dropped
+    pub dropped_lines: u64,
+}
+
+/// A note the user wrote on a request, kept across runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Note {
+    pub text: String,
+    /// When the note was written, in seconds since the epoch
+    pub written: i64,
+    /// The request's first line time in RFC 3339, telling apart requests
+    /// that share a `--generic` key such as `worker-1`
+    pub started: String,
+}
+
+pub struct AppState<R = ()> {
+    pub logs_by_request_id: HashMap<String, LogGroup<R>>,
+    pub request_ids: RequestIds,
+    pub selected_index: usize,
+    pub stats: RequestStats,
+    pub sampler: Option<Sampler>,
+    pub retention: RetentionConfig,
+    /// Status codes the config file maps to other classes
+    pub statuses: StatusConfig,
+    /// Compact groups once they finish
+    pub compaction: bool,
+    /// Most failed requests pinned at the top of the list at once
+    pub max_pinned: usize,
+    /// Newest timestamp seen, the reference point for retention ages
+    latest_timestamp: Option<DateTime<FixedOffset>>,
+    /// A request finished or the newest timestamp moved since retention last ran
+    retention_due: bool,
+    /// Sum of `held_bytes` over the groups, kept up to date as they change
+    /// so the status bar can read it without walking every line
+    held_bytes: usize,
+    pub ingest: IngestCounters,
+    /// Request the visual selection of the list started at; the selection
+    /// runs from it to the selected request
+    pub visual_anchor: Option<String>,
+    /// When the server was seen restarting, oldest first
+    pub restarts: Vec<DateTime<FixedOffset>>,
+    /// Request that last finished with each fingerprint, and when it
+    /// started, to find double submits
+    fingerprints: HashMap<String, (String, DateTime<FixedOffset>)>,
+    /// Notes by request id, including requests not held now, so one noted
+    /// in an earlier run gets its note back when its lines are read again
+    pub notes: BTreeMap<String, Note>,
+}
+
+pub struct LogGroup<R = ()> {
+    pub kind: GroupKind,
+    /// Input of the group's first line
+    pub source: usize,
+    pub title: String,
+    /// Method, path, controller and action, for the `[list] title` template
+    pub title_fields: TitleFields,
+    pub entries: VecDeque<LogEntry<R>>,
+    pub finished: bool,
+    pub status_type: StatusType,
+    /// HTTP status a request completed with
+    pub status_code: Option<u16>,
+    pub sql_query_info: SqlQueryInfo,
+    pub render_info: RenderInfo,
+    pub http_info: HttpInfo,
+    pub cache_info: CacheInfo,
+    pub memory_info: MemoryInfo,
+    pub first_timestamp: chrono::DateTime<chrono::FixedOffset>,
+    pub duration_ms: Option<u64>,
+    /// Set once entries were dropped by compaction
+    pub compacted: bool,
+    /// Journal location of every entry, including compacted ones
+    pub journal_refs: Vec<JournalRef>,
+    /// Marked by the user; never dropped by retention or clearing
+    pub starred: bool,
+    /// Free text the user attached to the request
+    pub note: Option<String>,
+    /// What follows `Parameters: `, the params the request was sent with
+    pub params: Option<String>,
+    /// Sent again with the same method, path and params right after another
+    /// request, or followed by such a request: likely a double submit
+    pub duplicate: bool,
+    /// Failed while watched, and kept above the other requests until
+    /// dismissed or pushed out by newer failures
+    pub pinned: bool,
+    /// A line named an error or exception
+    pub has_error_line: bool,
+    /// A line came from a command's stderr
+    pub has_stderr: bool,
+    /// Combined from other requests, whose ids lead its lines
+    pub merged: bool,
+    /// When lucy saw it finish, by the wall clock rather than the log's
+    /// timestamps, for `[auto_clear]`
+    pub finished_at: Option<Instant>,
+    gap_tracker: GapTracker,
+    /// Entries ever added, numbering them so that `simple_lines` and
+    /// `summary_lines` stay valid when the oldest ones are dropped
+    added: u64,
+    /// Numbers of the entries simple mode shows, oldest first
+    simple_lines: VecDeque<u64>,
+    /// Numbers of the entries summary mode shows, oldest first
+    summary_lines: VecDeque<u64>,
+    /// `entry_bytes` of every entry held
+    entry_bytes: usize,
+}
+
+/// The entries a view of the group lists, oldest first: all of them, or only
+/// those simple or summary mode shows. Lookups go by index rather than walking the
+/// group, so a request with a huge number of lines costs only the ones on
+/// screen.
+#[derive(Clone, Copy)]
+pub struct DisplayedEntries<'a, R = ()> {
+    group: &'a LogGroup<R>,
+    verbosity: Verbosity,
+}
+
+impl<'a, R: Render> DisplayedEntries<'a, R> {
+    /// Numbers of the entries shown, or None when all of them are.
+    fn numbers(&self) -> Option<&'a VecDeque<u64>> {
+        match self.verbosity {
+            Verbosity::Full => None,
+            Verbosity::Simple => Some(&self.group.simple_lines),
+            Verbosity::Summary => Some(&self.group.summary_lines),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self.numbers() {
+            Some(numbers) => numbers.len(),
+            None => self.group.entries.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Chronological index of the entry shown at `position`.
+    pub fn index(&self, position: usize) -> Option<usize> {
+        match self.numbers() {
+            Some(numbers) => {
+                let number = numbers.get(position)?;
+                Some((number - self.group.first_number()) as usize)
+            }
+            None => (position < self.len()).then_some(position),
+        }
+    }
+
+    /// Where the entry at chronological `index` is shown, if it is.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        match self.numbers() {
+            Some(numbers) => {
+                let number = self.group.first_number() + index as u64;
+                numbers.binary_search(&number).ok()
+            }
+            None => (index < self.len()).then_some(index),
+        }
+    }
+
+    /// Entries shown from `position` on, with their chronological index.
+    pub fn from(self, position: usize) -> impl Iterator<Item = (usize, &'a LogEntry<R>)> {
+        (position..self.len()).map_while(move |position| {
+            let index = self.index(position)?;
+            Some((index, self.group.entry(index)?))
+        })
+    }
+}
+
+impl<R: Render> LogGroup<R> {
+    pub fn new(log_entry: LogEntry<R>) -> Self {
+        let mut group = Self {
+            kind: log_entry.kind,
+            source: log_entry.source,
+            title: "...".to_string(),
+            title_fields: TitleFields::default(),
+            entries: VecDeque::with_capacity(10),
+            finished: false,
+            status_type: StatusType::Unknown,
+            status_code: None,
+            sql_query_info: SqlQueryInfo::new(),
+            render_info: RenderInfo::new(),
+            http_info: HttpInfo::new(),
+            cache_info: CacheInfo::new(),
+            memory_info: MemoryInfo::new(),
+            first_timestamp: log_entry.timestamp,
+            duration_ms: None,
+            compacted: false,
+            journal_refs: Vec::new(),
+            gap_tracker: GapTracker::default(),
+            starred: false,
+            note: None,
+            params: None,
+            duplicate: false,
+            pinned: false,
+            has_error_line: false,
+            has_stderr: false,
+            merged: false,
+            finished_at: None,
+            added: 0,
+            simple_lines: VecDeque::new(),
+            summary_lines: VecDeque::new(),
+            entry_bytes: 0,
+        };
+        // Nothing marks the end of a generic or unassigned group, so it counts as done from the start
+        group.finished = matches!(group.kind, GroupKind::Generic | GroupKind::Unassigned);
+        match group.kind {
+            GroupKind::Unassigned => group.title = "Unassigned lines".to_string(),
+            GroupKind::Cable => group.title = cable::CONNECTION_TITLE.to_string(),
+            _ => {}
+        }
+
+        group.add_entry(log_entry);
+        group
+    }
+
+    /// Counts the line toward the group's markers and SQL, then keeps it as
+    /// the newest entry, or folds it into that entry when it repeats it.
+    pub fn add_entry(&mut self, log_entry: LogEntry<R>) {
+        let gap_note = self
+            .gap_tracker
+            .observe(log_entry.timestamp, &log_entry.message);
+        let message = &log_entry.message;
+
+        // A connection mounted elsewhere than /cable shows up as a request until upgraded
+        if self.kind == GroupKind::Request
+            && log_entry.kind == GroupKind::Cable
+            && cable::parse_event(message) == Some(CableEvent::Opened)
+        {
+            self.kind = GroupKind::Cable;
+            self.title = cable::CONNECTION_TITLE.to_string();
+        }
+
+        match self.kind {
+            GroupKind::Request => self.apply_request_markers(message),
+            GroupKind::Job => self.apply_job_markers(message),
+            GroupKind::Cable => self.apply_cable_markers(message, log_entry.timestamp),
+            GroupKind::Generic => self.apply_generic_markers(message, log_entry.timestamp),
+            GroupKind::Unassigned => {}
+        }
+
+        if self.kind != GroupKind::Unassigned && log_parser::is_error_line(message) {
+            self.has_error_line = true;
+        }
+        if let Some(new_sql_info) = SqlQueryInfo::from_message(message) {
+            self.sql_query_info.merge(&new_sql_info);
+        } else if !self.render_info.record(message)
+            && !self.http_info.record(message)
+            && !self.cache_info.record(message)
+        {
+            self.memory_info.record(message);
+        }
+
+        if let Some(journal_ref) = log_entry.journal_ref {
+            self.journal_refs.push(journal_ref);
+        }
+        self.has_stderr |= log_entry.stderr;
+        let Some(mut log_entry) = fold_repeat(&mut self.entries, log_entry) else {
+            return;
+        };
+        let (rendered, simple, summary) = render(&log_entry.message);
+        log_entry.rendered = rendered;
+        log_entry.gap_note = gap_note;
+        if simple {
+            self.simple_lines.push_back(self.added);
+        }
+        if summary {
+            self.summary_lines.push_back(self.added);
+        }
+        self.added += 1;
+        self.entry_bytes += entry_bytes(&log_entry);
+        self.entries.push_front(log_entry);
+    }
+
+    /// Lines held, counting the repeats folded into entries.
+    pub fn line_count(&self) -> usize {
+        self.entries.iter().map(LogEntry::count).sum()
+    }
+
+    /// The entry at chronological `index`, counting from the oldest.
+    pub fn entry(&self, index: usize) -> Option<&LogEntry<R>> {
+        self.entries.get(self.entries.len().checked_sub(index + 1)?)
+    }
+
+    pub fn displayed(&self, verbosity: Verbosity) -> DisplayedEntries<'_, R> {
+        DisplayedEntries {
+            group: self,
+            verbosity,
+        }
+    }
+
+    /// Rough heap size of the group. Each line's rendered forms are taken
+    /// to be about twice its text.
+    pub fn memory_estimate(&self) -> usize {
+        size_of::<Self>() + self.entry_bytes
+    }
+
+    /// Number of the oldest entry held.
+    fn first_number(&self) -> u64 {
+        self.added - self.entries.len() as u64
+    }
+
+    /// Drops all but the newest `count` entries.
+    pub fn keep_newest(&mut self, count: usize) {
+        let dropped = count.min(self.entries.len());
+        self.entry_bytes -= self.entries.range(dropped..).map(entry_bytes).sum::<usize>();
+        self.entries.truncate(count);
+        let first = self.first_number();
+        for numbers in [&mut self.simple_lines, &mut self.summary_lines] {
+            while numbers.front().is_some_and(|&number| number < first) {
+                numbers.pop_front();
+            }
+        }
+    }
+
+    /// Renumbers the entries after some were dropped from the middle.
+    fn reindex(&mut self) {
+        self.added = self.entries.len() as u64;
+        self.entry_bytes = self.entries.iter().map(entry_bytes).sum();
+        let numbers = |shown: fn(&str) -> bool| {
+            self.entries
+                .iter()
+                .rev()
+                .zip(0..)
+                .filter(|(entry, _)| shown(&entry.message))
+                .map(|(_, number)| number)
+                .collect()
+        };
+        self.simple_lines = numbers(is_simple_log_line);
+        self.summary_lines = numbers(is_summary_log_line);
+    }
+
+    /// Leads each line with the id of the request it came from.
+    fn tag_origins(&mut self) {
+        for entry in &mut self.entries {
+            entry.rendered.tag_origin(&entry.request_id);
+        }
+    }
+
+    /// Failed with a 5xx (or an error level), or logged an exception.
+    pub fn is_error(&self) -> bool {
+        self.status_type == StatusType::Error || self.has_error_line
+    }
+
+    /// The line a finished request or job ends with (`Completed ...`, `Performed ...`).
+    pub fn summary_entry(&self) -> Option<&LogEntry<R>> {
+        // Entries are stored newest-first
+        self.entries
+            .iter()
+            .find(|entry| entry.message.contains("Completed ") || entry.message.contains("Performed "))
+    }
+
+    /// The earliest line naming an error or exception.
+    pub fn first_error_entry(&self) -> Option<&LogEntry<R>> {
+        self.entry(self.first_error_index()?)
+    }
+
+    /// Title the list shows: `template` filled in for a request, or `title`
+    /// without a template or before the template has anything to show.
+    pub fn list_title(&self, template: Option<&TitleTemplate>) -> Title {
+        template
+            .filter(|_| self.kind == GroupKind::Request)
+            .and_then(|template| template.render(&self.title_fields))
+            .unwrap_or_else(|| Title {
+                text: self.title.clone(),
+                path: None,
+            })
+    }
+
+    /// Chronological index of the earliest line naming an error or exception.
+    pub fn first_error_index(&self) -> Option<usize> {
+        self.entries
+            .iter()
+            .rev()
+            .position(|entry| log_parser::is_error_line(&entry.message))
+    }
+
+    /// Drops entries other than simple-mode lines, SQL and errors.
+    /// SQL counts and the title are already aggregated and stay intact.
+    pub fn compact(&mut self) {
+        let before = self.entries.len();
+        self.entries.retain(|entry| {
+            is_simple_log_line(&entry.message)
+                || SqlQueryInfo::from_message(&entry.message).is_some()
+                || log_parser::is_error_line(&entry.message)
+        });
+        if self.entries.len() < before {
+            self.entries.shrink_to_fit();
+            self.compacted = true;
+            self.reindex();
+        }
+    }
+
+    /// Replaces compacted entries with the full set read back from the journal.
+    pub fn restore(&mut self, chronological_entries: Vec<LogEntry<R>>) {
+        let mut gap_tracker = GapTracker::default();
+        self.entries = VecDeque::with_capacity(chronological_entries.len());
+        for entry in chronological_entries {
+            let gap_note = gap_tracker.observe(entry.timestamp, &entry.message);
+            let Some(mut entry) = fold_repeat(&mut self.entries, entry) else {
+                continue;
+            };
+            entry.rendered = render(&entry.message).0;
+            entry.gap_note = gap_note;
+            self.entries.push_front(entry);
+        }
+        self.gap_tracker = gap_tracker;
+        self.compacted = false;
+        self.reindex();
+    }
+
+    fn apply_request_markers(&mut self, message: &str) {
+        if let Some(start_pos) = message.find("Started ") {
+            self.title = message[(start_pos + 8)..].to_string();
+        }
+        self.title_fields.observe(message);
+        if self.params.is_none()
+            && let Some(start) = message.find("Parameters: ")
+        {
+            self.params = Some(message[start + 12..].trim_end().to_string());
+        }
+
+        if message.contains("Completed ") {
+            self.finished = true;
+            if let Some(caps) = log_parser::RE_COMPLETED.captures(message) {
+                if let Some(status_str) = caps.name("status")
+                    && let Ok(status_code) = status_str.as_str().parse::<u16>()
+                {
+                    self.status_code = Some(status_code);
+                    self.status_type = StatusType::from_code(status_code);
+                }
+                if let Some(ms_str) = caps.name("duration") {
+                    self.duration_ms = ms_str.as_str().parse::<u64>().ok();
+                }
+            }
+        }
+    }
+
+    /// Method, path and params, the same for requests sent twice. None for
+    /// other groups and until the `Started` line is seen.
+    pub fn fingerprint(&self) -> Option<String> {
+        if self.kind != GroupKind::Request {
+            return None;
+        }
+        Some(format!(
+            "{} {} {}",
+            self.title_fields.method.as_deref()?,
+            self.title_fields.path.as_deref()?,
+            self.params.as_deref().unwrap_or_default()
+        ))
+    }
+
+    /// Title from the first line, status from the most severe log level seen
+    /// and duration from the time covered so far.
+    fn apply_generic_markers(&mut self, message: &str, timestamp: DateTime<FixedOffset>) {
+        if self.entries.is_empty() {
+            self.title = log_parser::strip_ansi_for_parsing(message).trim().to_string();
+        }
+        if let Some(status_type) = log_parser::detect_level(message)
+            && status_type.severity() > self.status_type.severity()
+        {
+            self.status_type = status_type;
+        }
+        self.duration_ms = u64::try_from((timestamp - self.first_timestamp).num_milliseconds())
+            .ok()
+            .max(self.duration_ms);
+    }
+
+    fn apply_cable_markers(&mut self, message: &str, timestamp: DateTime<FixedOffset>) {
+        match cable::parse_event(message) {
+            Some(CableEvent::Opened) => self.finished = false,
+            Some(CableEvent::Subscribed(channel)) => {
+                if self.title == cable::CONNECTION_TITLE {
+                    self.title = channel.to_string();
+                } else if !self.title.split(", ").any(|shown| shown == channel) {
+                    self.title.push_str(", ");
+                    self.title.push_str(channel);
+                }
+            }
+            Some(CableEvent::Rejected) if self.status_type.severity() < StatusType::Warning.severity() => {
+                self.status_type = StatusType::Warning;
+            }
+            Some(CableEvent::Failed) => self.status_type = StatusType::Error,
+            Some(CableEvent::Closed) => {
+                self.finished = true;
+                if self.status_type == StatusType::Unknown {
+                    self.status_type = StatusType::Success;
+                }
+                self.duration_ms =
+                    u64::try_from((timestamp - self.first_timestamp).num_milliseconds()).ok();
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_job_markers(&mut self, message: &str) {
+        let Some(event) = log_parser::parse_job_event(message) else {
+            return;
+        };
+
+        self.title = event.job_class;
+        match event.outcome {
+            JobOutcome::Started => {}
+            JobOutcome::Succeeded => {
+                self.finished = true;
+                self.status_type = StatusType::Success;
+            }
+            JobOutcome::Failed => {
+                self.finished = true;
+                self.status_type = StatusType::Error;
+            }
+        }
+        if let Some(duration_ms) = event.duration_ms {
+            self.duration_ms = Some(duration_ms);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogEntry<R = ()> {
+    pub timestamp: chrono::DateTime<chrono::FixedOffset>,
+    pub request_id: String,
+    pub message: String,
+    pub kind: GroupKind,
+    /// Index of the input the line was read from
+    pub source: usize,
+    pub journal_ref: Option<JournalRef>,
+    /// Filled in by `LogGroup::add_entry` and `LogGroup::restore`
+    pub rendered: R,
+    /// Set on a line that ends a long pause in its request
+    pub gap_note: Option<GapNote>,
+    /// Read from a command's stderr rather than its stdout
+    pub stderr: bool,
+    /// When the identical lines right after this one were logged; they are
+    /// shown once with a `×N` counter and written out again on export
+    pub repeats: Vec<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl<R> LogEntry<R> {
+    /// Lines the entry stands for, counting the repeats folded into it.
+    pub fn count(&self) -> usize {
+        1 + self.repeats.len()
+    }
+}
+
+/// Rough heap size of an entry, its rendered forms included.
+fn entry_bytes<R>(entry: &LogEntry<R>) -> usize {
+    size_of::<LogEntry<R>>() + entry.message.capacity() * 3
+}
+
+/// Folds `entry` into the newest of `entries` when it is the same line from
+/// the same request, such as retry spam. Gives `entry` back otherwise.
+fn fold_repeat<R>(
+    entries: &mut VecDeque<LogEntry<R>>,
+    entry: LogEntry<R>,
+) -> Option<LogEntry<R>> {
+    match entries.front_mut() {
+        Some(newest)
+            if newest.message == entry.message
+                && newest.request_id == entry.request_id
+                && newest.stderr == entry.stderr =>
+        {
+            newest.repeats.push(entry.timestamp);
+            newest.repeats.extend(entry.repeats);
+            None
+        }
+        _ => Some(entry),
+    }
+}
+
+impl<R: Default> From<ParsedLine> for LogEntry<R> {
+    fn from(line: ParsedLine) -> Self {
+        Self {
+            timestamp: line.timestamp,
+            request_id: line.request_id,
+            message: line.message,
+            kind: line.kind,
+            ..Default::default()
+        }
+    }
+}
+
+/// What the group held under `request_id` adds to `AppState::memory_estimate`.
+fn held_bytes<R: Render>(request_id: &str, group: &LogGroup<R>) -> usize {
+    request_id.len() * 2 + group.memory_estimate()
+}
+
+impl<R: Render> Default for AppState<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Render> AppState<R> {
+    pub fn new() -> Self {
+        Self {
+            logs_by_request_id: HashMap::new(),
+            request_ids: VecDeque::new(),
+            selected_index: 0,
+            stats: RequestStats::new(),
+            sampler: None,
+            retention: RetentionConfig::default(),
+            statuses: StatusConfig::default(),
+            compaction: false,
+            max_pinned: 0,
+            latest_timestamp: None,
+            retention_due: false,
+            held_bytes: 0,
+            ingest: IngestCounters::default(),
+            visual_anchor: None,
+            restarts: Vec::new(),
+            fingerprints: HashMap::new(),
+            notes: BTreeMap::new(),
+        }
+    }
+
+    pub fn selected_request_id(&self) -> Option<&String> {
+        self.request_ids.get(self.selected_index)
+    }
+
+    /// Newest timestamp of any line seen so far.
+    pub fn latest_timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        self.latest_timestamp
+    }
+
+    pub fn log_group_count(&self) -> usize {
+        self.logs_by_request_id.len()
+    }
+
+    pub fn selected_group(&self) -> Option<&LogGroup<R>> {
+        let request_id = self.selected_request_id()?;
+        self.logs_by_request_id.get(request_id)
+    }
+
+    pub fn select_request(&mut self, index: usize) -> bool {
+        if index < self.request_ids.len() {
+            self.selected_index = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn next_request(&mut self, n: usize) -> bool {
+        if self.request_ids.is_empty() || n == 0 {
+            return false;
+        }
+        let new_index = (self.selected_index + n).min(self.request_ids.len() - 1);
+        self.select_request(new_index)
+    }
+
+    pub fn previous_request(&mut self, n: usize) -> bool {
+        if self.request_ids.is_empty() || n == 0 {
+            return false;
+        }
+        let new_index = self.selected_index.saturating_sub(n);
+        self.select_request(new_index)
+    }
+
+    pub fn selected_entries_count(&self) -> usize {
+        self.selected_group().map_or(0, |group| group.entries.len())
+    }
+
+    pub fn selected_sql_line_count(&self) -> usize {
+        self.selected_group().map_or(0, |group| {
+            group.sql_query_info.display_line_count()
+                + group.render_info.display_line_count()
+                + group.http_info.display_line_count()
+                + group.cache_info.display_line_count()
+                + group.memory_info.display_line_count()
+        })
+    }
+
+    /// Returns (is_new_request, removal_occurred). Requests are removed when
+    /// evicted or dropped by sampling.
+    pub fn add_log_entry(&mut self, log_entry: LogEntry<R>) -> (bool, bool) {
+        self.ingest.lines += 1;
+        if log_parser::is_boot_marker(&log_entry.message) {
+            self.note_restart(log_entry.timestamp);
+        }
+        if log_entry.request_id.is_empty() {
+            self.ingest.orphan_lines += 1;
+            return (self.add_unassigned(log_entry), false);
+        }
+        if self
+            .sampler
+            .as_ref()
+            .is_some_and(|sampler| sampler.was_dropped(&log_entry.request_id))
+        {
+            self.ingest.dropped_lines += 1;
+            return (false, false);
+        }
+
+        self.retire_if_restarted(&log_entry.request_id, log_entry.timestamp);
+        let is_new_request = !self.logs_by_request_id.contains_key(&log_entry.request_id);
+        let timestamp = log_entry.timestamp;
+        let request_id = log_entry.request_id.clone();
+        if self.latest_timestamp.is_none_or(|latest| timestamp > latest) {
+            self.latest_timestamp = Some(timestamp);
+            self.retention_due = true;
+        }
+
+        let completed = if is_new_request {
+            let mut group = LogGroup::new(log_entry);
+            group.note = self.saved_note(&request_id, &group);
+            self.statuses.apply(&mut group);
+            let completed = group
+                .finished
+                .then_some((group.status_type, group.duration_ms));
+            self.insert_group(request_id.clone(), group);
+            completed
+        } else if let Some(group) = self.logs_by_request_id.get_mut(&request_id) {
+            let was_finished = group.finished;
+            let before = group.memory_estimate();
+            group.add_entry(log_entry);
+            if request_id == cable::CABLE_ID {
+                // Untagged connections share one group, held like unassigned lines
+                group.keep_newest(UNASSIGNED_MAX_LINES);
+            }
+            self.held_bytes = self.held_bytes + group.memory_estimate() - before;
+            self.statuses.apply(group);
+            (!was_finished && group.finished).then_some((group.status_type, group.duration_ms))
+        } else {
+            None
+        };
+
+        let mut removed = false;
+        if let Some((status_type, duration_ms)) = completed {
+            if let Some(group) = self.logs_by_request_id.get_mut(&request_id) {
+                group.finished_at = Some(Instant::now());
+            }
+            self.match_fingerprint(&request_id);
+            // Aggregates count every request, including the ones sampling drops.
+            // Connections last minutes and would swamp the durations.
+            if !self
+                .logs_by_request_id
+                .get(&request_id)
+                .is_some_and(|group| group.kind == GroupKind::Cable)
+            {
+                self.stats.record(status_type, duration_ms, timestamp);
+            }
+            removed |= self.sample_out(&request_id, status_type, duration_ms);
+            self.retention_due = true;
+            if self.compaction
+                && let Some(group) = self.logs_by_request_id.get_mut(&request_id)
+            {
+                let before = group.memory_estimate();
+                group.compact();
+                self.held_bytes = self.held_bytes + group.memory_estimate() - before;
+            }
+        }
+
+        // Evict oldest requests to cap memory usage
+        removed |= self.evict_overflow();
+        (is_new_request, removed)
+    }
+
+    /// Marks a finished request and the one last finished with the same
+    /// fingerprint as duplicates when they started within
+    /// `DUPLICATE_WINDOW` of each other.
+    fn match_fingerprint(&mut self, request_id: &str) {
+        let Some(group) = self.logs_by_request_id.get(request_id) else {
+            return;
+        };
+        let Some(fingerprint) = group.fingerprint() else {
+            return;
+        };
+        let started = group.first_timestamp;
+        if let Some(latest) = self.latest_timestamp {
+            self.fingerprints
+                .retain(|_, (_, other_started)| latest - *other_started <= DUPLICATE_INDEX_AGE);
+        }
+        let previous = self.fingerprints.get(&fingerprint).cloned();
+        if let Some((other_id, other_started)) = &previous
+            && (started - *other_started).abs() <= DUPLICATE_WINDOW
+        {
+            for id in [request_id, other_id.as_str()] {
+                if let Some(group) = self.logs_by_request_id.get_mut(id) {
+                    group.duplicate = true;
+                }
+            }
+        }
+        if previous.is_none_or(|(_, other_started)| started >= other_started) {
+            self.fingerprints
+                .insert(fingerprint, (request_id.to_string(), started));
+        }
+    }
+
+    /// Adds `group` as the newest request, below the pinned ones.
+    fn insert_group(&mut self, request_id: String, group: LogGroup<R>) {
+        let position = self.pinned_count();
+        self.held_bytes += held_bytes(&request_id, &group);
+        self.request_ids.insert(position, request_id.clone());
+        self.logs_by_request_id.insert(request_id, group);
+
+        // Shift the selected index to keep the same request selected
+        if self.request_ids.len() > 1 && self.selected_index >= position {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Records a restart at `timestamp`, unless nothing was logged since
+    /// the last one: a boot prints several markers.
+    fn note_restart(&mut self, timestamp: DateTime<FixedOffset>) {
+        let last = self.restarts.last().copied();
+        let requests_since = self.request_ids.iter().any(|request_id| {
+            self.logs_by_request_id.get(request_id).is_some_and(|group| {
+                group.kind != GroupKind::Unassigned
+                    && last.is_none_or(|last| group.first_timestamp >= last)
+            })
+        });
+        if requests_since {
+            self.restarts.push(timestamp);
+        }
+    }
+
+    /// The restart `timestamp` comes after, if any.
+    pub fn restart_before(&self, timestamp: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        self.restarts
+            .iter()
+            .rev()
+            .find(|&&restart| restart <= timestamp)
+            .copied()
+    }
+
+    /// Ids of the requests started before the latest restart, except the
+    /// starred ones.
+    pub fn ids_before_restart(&self) -> Vec<String> {
+        let Some(&restart) = self.restarts.last() else {
+            return Vec::new();
+        };
+        self.request_ids
+            .iter()
+            .filter(|request_id| {
+                self.logs_by_request_id.get(*request_id).is_some_and(|group| {
+                    group.kind != GroupKind::Unassigned
+                        && !group.starred
+                        && group.first_timestamp < restart
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Moves the group of `request_id` to a key of its own when it started
+    /// before a restart `timestamp` comes after, as a restarted server may
+    /// hand out the same ids again. The old group keeps its place in the
+    /// list as `<id>~1`, `<id>~2` and so on.
+    fn retire_if_restarted(&mut self, request_id: &str, timestamp: DateTime<FixedOffset>) {
+        if request_id == cable::CABLE_ID {
+            return;
+        }
+        let Some(restart) = self.restart_before(timestamp) else {
+            return;
+        };
+        if self
+            .logs_by_request_id
+            .get(request_id)
+            .is_none_or(|group| group.first_timestamp >= restart)
+        {
+            return;
+        }
+        let Some(retired_id) = (1..)
+            .map(|n| format!("{}~{}", request_id, n))
+            .find(|id| !self.logs_by_request_id.contains_key(id))
+        else {
+            return;
+        };
+        if let Some(group) = self.logs_by_request_id.remove(request_id) {
+            self.held_bytes -= held_bytes(request_id, &group);
+            self.held_bytes += held_bytes(&retired_id, &group);
+            self.logs_by_request_id.insert(retired_id.clone(), group);
+        }
+        for id in self.request_ids.iter_mut().filter(|id| *id == request_id) {
+            *id = retired_id.clone();
+        }
+        if self.visual_anchor.as_deref() == Some(request_id) {
+            self.visual_anchor = Some(retired_id);
+        }
+    }
+
+    /// Pinned requests, which come first in `request_ids`.
+    pub fn pinned_count(&self) -> usize {
+        self.request_ids
+            .iter()
+            .take_while(|request_id| {
+                self.logs_by_request_id
+                    .get(*request_id)
+                    .is_some_and(|group| group.pinned)
+            })
+            .count()
+    }
+
+    /// Moves a failed request to the top of the list and keeps it there.
+    /// Past `max_pinned`, the oldest pinned request goes back to its place.
+    /// Returns whether the order changed.
+    pub fn pin(&mut self, request_id: &str) -> bool {
+        if self.max_pinned == 0 {
+            return false;
+        }
+        let Some(group) = self.logs_by_request_id.get_mut(request_id) else {
+            return false;
+        };
+        if group.pinned {
+            return false;
+        }
+        group.pinned = true;
+        let selected_id = self.selected_request_id().cloned();
+        self.request_ids.retain(|id| id != request_id);
+        self.request_ids.push_front(request_id.to_string());
+
+        let pinned = self.pinned_count();
+        if pinned > self.max_pinned {
+            let unpinned_id = self.request_ids.remove(pinned - 1).unwrap_or_default();
+            if let Some(group) = self.logs_by_request_id.get_mut(&unpinned_id) {
+                group.pinned = false;
+                // Back among the requests that started around the same time
+                let started = group.first_timestamp;
+                let position = (pinned - 1..self.request_ids.len())
+                    .find(|&i| {
+                        self.logs_by_request_id
+                            .get(&self.request_ids[i])
+                            .is_some_and(|other| other.first_timestamp < started)
+                    })
+                    .unwrap_or(self.request_ids.len());
+                self.request_ids.insert(position, unpinned_id);
+            }
+        }
+
+        self.selected_index = selected_id
+            .and_then(|selected_id| self.request_ids.iter().position(|id| *id == selected_id))
+            .unwrap_or(0);
+        true
+    }
+
+    /// Files a line without a request id under `UNASSIGNED_ID`, keeping only
+    /// the newest `UNASSIGNED_MAX_LINES`. Returns whether the group was created.
+    fn add_unassigned(&mut self, mut log_entry: LogEntry<R>) -> bool {
+        log_entry.request_id = UNASSIGNED_ID.to_string();
+        log_entry.kind = GroupKind::Unassigned;
+        if let Some(group) = self.logs_by_request_id.get_mut(UNASSIGNED_ID) {
+            let before = group.memory_estimate();
+            group.add_entry(log_entry);
+            group.keep_newest(UNASSIGNED_MAX_LINES);
+            self.held_bytes = self.held_bytes + group.memory_estimate() - before;
+            // Trimmed in batches, as dropping from the front shifts the rest
+            if group.journal_refs.len() > 2 * UNASSIGNED_MAX_LINES {
+                let excess = group.journal_refs.len() - UNASSIGNED_MAX_LINES;
+                group.journal_refs.drain(..excess);
+            }
+            return false;
+        }
+        self.insert_group(UNASSIGNED_ID.to_string(), LogGroup::new(log_entry));
+        true
+    }
+
+    /// Removes finished requests exceeding their class's count or age limit,
+    /// if any finished or aged since the last call. The selected request is
+    /// kept. Run on the tick rather than per line, as it scans every request.
+    pub fn apply_retention(&mut self) -> bool {
+        if !std::mem::take(&mut self.retention_due) || !self.retention.has_class_limits() {
+            return false;
+        }
+
+        let mut counts: HashMap<StatusType, usize> = HashMap::new();
+        let mut expired = Vec::new();
+        for (index, request_id) in self.request_ids.iter().enumerate() {
+            let Some(group) = self.logs_by_request_id.get(request_id) else {
+                continue;
+            };
+            if !group.finished || group.starred || group.pinned || index == self.selected_index {
+                continue;
+            }
+
+            let rule = self.retention.rule(group.status_type);
+            let count = counts.entry(group.status_type).or_insert(0);
+            *count += 1;
+
+            let over_count = rule.max_count.is_some_and(|max| *count > max);
+            let too_old = rule.max_age_minutes.is_some_and(|minutes| {
+                self.latest_timestamp.is_some_and(|latest| {
+                    latest - group.first_timestamp > TimeDelta::minutes(minutes as i64)
+                })
+            });
+            if over_count || too_old {
+                expired.push(index);
+            }
+        }
+
+        // Remove from the back so earlier indices stay valid
+        for &index in expired.iter().rev() {
+            self.remove_request_at(index);
+        }
+        !expired.is_empty()
+    }
+
+    /// Drops a completed request if the sampler doesn't keep it. The selected
+    /// request is never dropped from under the user.
+    fn sample_out(&mut self, request_id: &str, status_type: StatusType, duration_ms: Option<u64>) -> bool {
+        if self.selected_request_id().is_some_and(|id| id == request_id) {
+            return false;
+        }
+        let Some(sampler) = &mut self.sampler else {
+            return false;
+        };
+        if sampler.keep(request_id, status_type, duration_ms) {
+            return false;
+        }
+        match self.request_ids.iter().position(|id| id == request_id) {
+            Some(index) => {
+                if let Some(group) = self.remove_request_at(index) {
+                    self.ingest.dropped_lines += group.entries.len() as u64;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a request, keeping the same request selected when possible.
+    pub fn remove_request_at(&mut self, index: usize) -> Option<LogGroup<R>> {
+        let request_id = self.request_ids.remove(index)?;
+        if index < self.selected_index
+            || (self.selected_index >= self.request_ids.len() && self.selected_index > 0)
+        {
+            self.selected_index -= 1;
+        }
+        let group = self.logs_by_request_id.remove(&request_id)?;
+        self.held_bytes -= held_bytes(&request_id, &group);
+        Some(group)
+    }
+
+    /// Rough heap size of the held groups, kept as they change rather than
+    /// counted here.
+    pub fn memory_estimate(&self) -> usize {
+        self.held_bytes
+    }
+
+    /// Replaces the compacted entries of `request_id` with the full set read
+    /// back from the journal.
+    pub fn restore_group(&mut self, request_id: &str, chronological_entries: Vec<LogEntry<R>>) {
+        if let Some(group) = self.logs_by_request_id.get_mut(request_id) {
+            let before = group.memory_estimate();
+            group.restore(chronological_entries);
+            self.held_bytes = self.held_bytes + group.memory_estimate() - before;
+        }
+    }
+
+    /// Renders every entry again, such as after lucy's theme changed.
+    pub fn rerender_lines(&mut self) {
+        for group in self.logs_by_request_id.values_mut() {
+            for entry in &mut group.entries {
+                entry.rendered = render(&entry.message).0;
+            }
+            if group.merged {
+                group.tag_origins();
+            }
+        }
+    }
+
+    /// Stars or unstars the selected request. Returns the new state.
+    pub fn toggle_star_selected(&mut self) -> Option<bool> {
+        let request_id = self.request_ids.get(self.selected_index)?;
+        let group = self.logs_by_request_id.get_mut(request_id)?;
+        group.starred = !group.starred;
+        Some(group.starred)
+    }
+
+    /// Attaches `text` to a request as its note, or removes the note when
+    /// `text` is blank.
+    pub fn set_note(&mut self, request_id: &str, text: &str) {
+        let text = text.trim();
+        let note = (!text.is_empty()).then(|| text.to_string());
+        let Some(group) = self.logs_by_request_id.get_mut(request_id) else {
+            return;
+        };
+        group.note = note.clone();
+        match note {
+            Some(text) => {
+                let note = Note {
+                    text,
+                    written: Utc::now().timestamp(),
+                    started: group.first_timestamp.to_rfc3339(),
+                };
+                self.notes.insert(request_id.to_string(), note)
+            }
+            None => self.notes.remove(request_id),
+        };
+    }
+
+    /// The note written on `request_id` in an earlier run. A `--generic`
+    /// key names other requests in other runs, so there the note must also
+    /// have been written on a request started at the same time.
+    fn saved_note(&self, request_id: &str, group: &LogGroup<R>) -> Option<String> {
+        self.notes
+            .get(request_id)
+            .filter(|note| {
+                group.kind != GroupKind::Generic
+                    || note.started == group.first_timestamp.to_rfc3339()
+            })
+            .map(|note| note.text.clone())
+    }
+
+    /// Stars every given request. Returns how many were not starred yet.
+    pub fn star_requests(&mut self, request_ids: &[String]) -> usize {
+        let mut starred = 0;
+        for request_id in request_ids {
+            if let Some(group) = self.logs_by_request_id.get_mut(request_id)
+                && !group.starred
+            {
+                group.starred = true;
+                starred += 1;
+            }
+        }
+        starred
+    }
+
+    /// Adds a group with the lines of `request_ids` in the order they were
+    /// logged, each led by the id of its request, and selects it. Returns
+    /// its id, or None when none of the requests is held anymore.
+    pub fn merge_requests(&mut self, request_ids: &[String], title: String) -> Option<String> {
+        let groups: Vec<&LogGroup<R>> = request_ids
+            .iter()
+            .filter_map(|request_id| self.logs_by_request_id.get(request_id))
+            .collect();
+        let status_type = groups
+            .iter()
+            .map(|group| group.status_type)
+            .max_by_key(|status_type| status_type.severity())?;
+        let mut entries: Vec<LogEntry<R>> = groups
+            .iter()
+            .flat_map(|group| group.entries.iter().rev().cloned())
+            .collect();
+        // Stable, so lines logged at the same time keep their request's order
+        entries.sort_by_key(|entry| entry.timestamp);
+
+        let mut entries = entries.into_iter().map(|mut entry| {
+            entry.kind = GroupKind::Generic;
+            entry.journal_ref = None;
+            entry
+        });
+        let mut group = LogGroup::new(entries.next()?);
+        for entry in entries {
+            group.add_entry(entry);
+        }
+        for entry in &mut group.entries {
+            // Pauses between lines of different requests say nothing
+            entry.gap_note = None;
+        }
+        group.title = title;
+        group.status_type = status_type;
+        group.merged = true;
+        group.tag_origins();
+
+        let merged_id = (1..)
+            .map(|n| format!("merged-{}", n))
+            .find(|id| !self.logs_by_request_id.contains_key(id))?;
+        self.insert_group(merged_id.clone(), group);
+        self.selected_index = self.pinned_count();
+        Some(merged_id)
+    }
+
+    /// Removes every finished group.
+    pub fn clear_finished(&mut self) -> usize {
+        self.remove_where(|_, group| group.finished && !group.starred)
+    }
+
+    /// Removes requests that finished before `cutoff`, except the starred,
+    /// pinned and selected ones.
+    pub fn clear_finished_before(&mut self, cutoff: Instant) -> usize {
+        let selected_id = self.selected_request_id().cloned();
+        self.remove_where(|request_id, group| {
+            group.kind != GroupKind::Unassigned
+                && !group.starred
+                && !group.pinned
+                && group.finished_at.is_some_and(|finished_at| finished_at < cutoff)
+                && selected_id.as_deref() != Some(request_id)
+        })
+    }
+
+    /// Removes the given requests, starred or not.
+    pub fn remove_requests(&mut self, request_ids: &[String]) -> usize {
+        let request_ids: HashSet<&str> = request_ids.iter().map(String::as_str).collect();
+        self.remove_where(|request_id, _| request_ids.contains(request_id))
+    }
+
+    /// Removes the groups `remove` picks. The selection follows the selected
+    /// request if it is kept, and falls back to the newest otherwise.
+    fn remove_where(&mut self, remove: impl Fn(&str, &LogGroup<R>) -> bool) -> usize {
+        let selected_id = self.selected_request_id().cloned();
+        let before = self.request_ids.len();
+
+        let logs = &mut self.logs_by_request_id;
+        let held = &mut self.held_bytes;
+        self.request_ids.retain(|request_id| {
+            let removed = logs
+                .get(request_id)
+                .is_some_and(|group| remove(request_id, group));
+            if removed && let Some(group) = logs.remove(request_id) {
+                *held -= held_bytes(request_id, &group);
+            }
+            !removed
+        });
+
+        self.selected_index = selected_id
+            .and_then(|selected_id| self.request_ids.iter().position(|id| *id == selected_id))
+            .unwrap_or(0);
+        before - self.request_ids.len()
+    }
+
+    /// Aggregates over the finished requests among `request_ids`, computed
+    /// from the groups still held.
+    pub fn stats_for(&self, request_ids: &[String]) -> RequestStats {
+        let mut stats = RequestStats::new();
+        for request_id in request_ids {
+            let Some(group) = self.logs_by_request_id.get(request_id) else {
+                continue;
+            };
+            if group.finished {
+                // Entries are stored newest-first
+                let completed_at = group.entries.front().map_or(group.first_timestamp, |e| e.timestamp);
+                stats.record(group.status_type, group.duration_ms, completed_at);
+            }
+        }
+        stats
+    }
+
+    /// Returns whether any request was evicted. Starred and pinned requests
+    /// and those whose class is kept `forever` are skipped.
+    fn evict_overflow(&mut self) -> bool {
+        let mut evicted = false;
+        while self.request_ids.len() > self.retention.max_requests {
+            let oldest_evictable = self.request_ids.iter().rposition(|request_id| {
+                self.logs_by_request_id.get(request_id).is_none_or(|group| {
+                    !(group.starred
+                        || group.pinned
+                        || group.finished && self.retention.rule(group.status_type).forever)
+                })
+            });
+            let Some(index) = oldest_evictable else {
+                break;
+            };
+            self.remove_request_at(index);
+            evicted = true;
+        }
+
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    // Expressions do not fall back to the default `R`
+    type AppState = super::AppState;
+    type LogGroup = super::LogGroup;
+    type LogEntry = super::LogEntry;
+
+    fn parse(message: &str) -> LogEntry {
+        log_parser::parse(message, None).unwrap().into()
+    }
+
+    #[test]
+    fn test_app_state_new() {
+        let state = AppState::new();
+        assert_eq!(state.selected_index, 0);
+        assert!(state.request_ids.is_empty());
+        assert!(state.logs_by_request_id.is_empty());
+    }
+
+    #[test]
+    fn test_select_request() {
+        let mut state = AppState::new();
+
+        // Cannot select in empty state
+        assert!(!state.select_request(0));
+
+        // Add a request
+        let log_entry = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "test-id".to_string(),
+            message: "Started GET /test".to_string(),
+            ..Default::default()
+        };
+        state.add_log_entry(log_entry);
+
+        // Select valid index
+        assert!(state.select_request(0));
+        assert_eq!(state.selected_index, 0);
+
+        // Cannot select out of range index
+        assert!(!state.select_request(1));
+    }
+
+    #[test]
+    fn test_add_log_entry() {
+        let mut state = AppState::new();
+
+        // Add new request
+        let log_entry = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-1".to_string(),
+            message: "Started GET /test".to_string(),
+            ..Default::default()
+        };
+
+        let (is_new, _) = state.add_log_entry(log_entry);
+        assert!(is_new);
+        assert_eq!(state.request_ids.len(), 1);
+        assert_eq!(state.request_ids[0], "req-1");
+        assert_eq!(state.logs_by_request_id.values().map(|g| g.entries.len()).sum::<usize>(), 1);
+        assert_eq!(state.selected_index, 0);
+
+        // Add entry with same request ID
+        let log_entry2 = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-1".to_string(),
+            message: "Processing by TestController".to_string(),
+            ..Default::default()
+        };
+
+        let (is_new2, _) = state.add_log_entry(log_entry2);
+        assert!(!is_new2);
+        assert_eq!(state.request_ids.len(), 1);
+        assert_eq!(state.logs_by_request_id.values().map(|g| g.entries.len()).sum::<usize>(), 2);
+        assert_eq!(state.selected_index, 0);
+
+        // Add entry with different request ID
+        let log_entry3 = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-2".to_string(),
+            message: "Started GET /another".to_string(),
+            ..Default::default()
+        };
+
+        let (is_new3, _) = state.add_log_entry(log_entry3);
+        assert!(is_new3);
+        assert_eq!(state.request_ids.len(), 2);
+        assert_eq!(state.request_ids[0], "req-2");
+        assert_eq!(state.request_ids[1], "req-1");
+        assert_eq!(state.logs_by_request_id.values().map(|g| g.entries.len()).sum::<usize>(), 3);
+        assert_eq!(state.selected_index, 1);
+    }
+
+    #[test]
+    fn test_selected_index_adjustment() {
+        let mut state = AppState::new();
+        assert_eq!(state.selected_index, 0);
+
+        // Add the first request
+        let log_entry1 = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-1".to_string(),
+            message: "Started GET /test1".to_string(),
+            ..Default::default()
+        };
+        state.add_log_entry(log_entry1);
+        assert_eq!(state.selected_index, 0);
+
+        // Add second request (index adjusts to 1)
+        let log_entry2 = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-2".to_string(),
+            message: "Started GET /test2".to_string(),
+            ..Default::default()
+        };
+        state.add_log_entry(log_entry2);
+        assert_eq!(state.selected_index, 1);
+
+        // Manually select the latest (index 0)
+        state.select_request(0);
+        assert_eq!(state.selected_index, 0);
+
+        // Add third request (was viewing latest, so index adjusts to 1)
+        let log_entry3 = LogEntry {
+            timestamp: Local::now().fixed_offset(),
+            request_id: "req-3".to_string(),
+            message: "Started GET /test3".to_string(),
+            ..Default::default()
+        };
+        state.add_log_entry(log_entry3);
+        assert_eq!(state.selected_index, 1);
+    }
+
+    #[test]
+    fn test_time_order_preservation() {
+        let mut state = AppState::new();
+
+        let requests = ["req-3", "req-2", "req-1"];
+
+        for &req_id in &requests {
+            let log_entry = LogEntry {
+                timestamp: Local::now().fixed_offset(),
+                request_id: req_id.to_string(),
+                message: format!("Started GET /{}", req_id),
+                ..Default::default()
+            };
+            state.add_log_entry(log_entry);
+        }
+
+        assert_eq!(state.request_ids[0], "req-1");
+        assert_eq!(state.request_ids[1], "req-2");
+        assert_eq!(state.request_ids[2], "req-3");
+
+        let ids = &state.request_ids;
+        assert_eq!(ids[0], "req-1");
+        assert_eq!(ids[1], "req-2");
+        assert_eq!(ids[2], "req-3");
+    }
+
+    #[test]
+    fn test_job_group() {
+        let mut state = AppState::new();
+        for message in [
+            "[ActiveJob] [SendEmailJob] [jid-1] Performing SendEmailJob (Job ID: jid-1) from Async(default)",
+            "[ActiveJob] [SendEmailJob] [jid-1] Performed SendEmailJob (Job ID: jid-1) from Async(default) in 12.6ms",
+        ] {
+            state.add_log_entry(parse(message));
+        }
+
+        let group = state.selected_group().unwrap();
+        assert_eq!(group.kind, GroupKind::Job);
+        assert_eq!(group.title, "SendEmailJob");
+        assert!(group.finished);
+        assert_eq!(group.status_type, StatusType::Success);
+        assert_eq!(group.duration_ms, Some(13));
+    }
+
+    #[test]
+    fn test_remove_request_at_keeps_selection() {
+        let mut state = AppState::new();
+        for id in ["req-1", "req-2", "req-3"] {
+            state.add_log_entry(LogEntry {
+                request_id: id.to_string(),
+                message: format!("Started GET /{}", id),
+                ..Default::default()
+            });
+        }
+        // [req-3, req-2, req-1], req-1 selected
+        assert_eq!(state.selected_request_id().unwrap(), "req-1");
+
+        state.remove_request_at(0);
+        assert_eq!(state.selected_request_id().unwrap(), "req-1");
+
+        // Removing the selected request selects the next older one, or the last
+        state.remove_request_at(1);
+        assert_eq!(state.selected_request_id().unwrap(), "req-2");
+        assert!(!state.logs_by_request_id.contains_key("req-1"));
+    }
+
+    #[test]
+    fn test_sampling_keeps_stats_exact() {
+        let mut state = AppState::new();
+        state.sampler = Some(Sampler::new(2, 500));
+        for i in 0..5 {
+            let id = format!("req-{}", i);
+            for message in ["Started GET /", "Completed 200 OK in 5ms"] {
+                state.add_log_entry(LogEntry {
+                    request_id: id.clone(),
+                    message: message.to_string(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        assert_eq!(state.stats.total(), 5);
+        // req-0 stays selected; of the other four, every second is kept
+        assert_eq!(state.request_ids.len(), 3);
+        assert_eq!(state.sampler.as_ref().unwrap().dropped(), 2);
+
+        // Late lines of a dropped request are ignored
+        let dropped = state
+            .sampler
+            .as_ref()
+            .map(|sampler| (1..5).find(|i| sampler.was_dropped(&format!("req-{}", i))).unwrap())
+            .unwrap();
+        let (is_new, _) = state.add_log_entry(LogEntry {
+            request_id: format!("req-{}", dropped),
+            message: "late line".to_string(),
+            ..Default::default()
+        });
+        assert!(!is_new);
+
+        state.add_log_entry(LogEntry {
+            message: "no request id".to_string(),
+            ..Default::default()
+        });
+        let expected = IngestCounters {
+            lines: 12,
+            orphan_lines: 1,
+            // Two lines of each of the two dropped requests, and the late line
+            dropped_lines: 5,
+        };
+        assert_eq!(state.ingest, expected);
+    }
+
+    #[test]
+    fn test_unassigned_lines() {
+        let mut state = AppState::new();
+        let (is_new, _) = state.add_log_entry(LogEntry {
+            message: "=> Booting Puma".to_string(),
+            ..Default::default()
+        });
+        assert!(is_new);
+        add_request(&mut state, "req-1", "200 OK in 5ms", "2024-05-01T12:00:00+09:00");
+        for i in 0..UNASSIGNED_MAX_LINES {
+            let (is_new, _) = state.add_log_entry(LogEntry {
+                message: format!("webpack {}", i),
+                ..Default::default()
+            });
+            assert!(!is_new);
+        }
+
+        assert_eq!(state.request_ids, [String::from("req-1"), UNASSIGNED_ID.to_string()]);
+        let group = &state.logs_by_request_id[UNASSIGNED_ID];
+        assert_eq!(group.kind, GroupKind::Unassigned);
+        assert!(group.finished);
+        assert_eq!(group.entries.len(), UNASSIGNED_MAX_LINES);
+        // The oldest line made room for the newest
+        assert!(group.entries.iter().all(|entry| entry.message != "=> Booting Puma"));
+        assert_eq!(state.stats.total(), 1);
+    }
+
+    fn add_request(state: &mut AppState, id: &str, completed: &str, timestamp: &str) {
+        for message in ["Started GET /".to_string(), format!("Completed {}", completed)] {
+            state.add_log_entry(LogEntry {
+                request_id: id.to_string(),
+                message,
+                timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+                ..Default::default()
+            });
+        }
+    }
+
+    #[test]
+    fn test_memory_estimate_is_kept() {
+        // What the estimate would be counted from scratch
+        let counted = |state: &AppState| -> usize {
+            state
+                .logs_by_request_id
+                .iter()
+                .map(|(request_id, group)| {
+                    let entries: usize = group.entries.iter().map(entry_bytes).sum();
+                    size_of::<LogGroup>() + request_id.len() * 2 + entries
+                })
+                .sum()
+        };
+        let mut state = AppState::new();
+        state.retention.max_requests = 3;
+        state.retention.success.max_count = Some(1);
+        state.compaction = true;
+        for i in 0..5 {
+            let id = format!("req-{}", i);
+            state.add_log_entry(parse(&format!("[{}] Started GET \"/\" for 127.0.0.1", id)));
+            state.add_log_entry(parse(&format!("[{}]   Rendered index.erb (Duration: 1.2ms)", id)));
+            state.add_log_entry(parse(&format!("[{}] Completed 200 OK in 5ms", id)));
+            assert_eq!(state.memory_estimate(), counted(&state));
+        }
+        state.add_log_entry(parse("=> Booting Puma"));
+        state.apply_retention();
+        assert_eq!(state.memory_estimate(), counted(&state));
+
+        let id = state.request_ids[0].clone();
+        state.restore_group(&id, vec![parse(&format!("[{}] Started GET \"/\"", id))]);
+        state.remove_request_at(1);
+        assert_eq!(state.memory_estimate(), counted(&state));
+        state.clear_finished();
+        assert!(state.request_ids.is_empty());
+        assert_eq!(state.memory_estimate(), 0);
+    }
+
+    #[test]
+    fn test_retention_max_count() {
+        let mut state = AppState::new();
+        state.retention.success.max_count = Some(2);
+        for i in 0..4 {
+            add_request(&mut state, &format!("ok-{}", i), "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        }
+        add_request(&mut state, "err", "500 Internal Server Error in 5ms", "2024-05-01T12:00:00Z");
+        assert!(state.apply_retention());
+        // Nothing finished since
+        assert!(!state.apply_retention());
+
+        // ok-0 is selected and kept; only the latest two others survive
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["err", "ok-3", "ok-2", "ok-0"]);
+        assert_eq!(state.selected_request_id().unwrap(), "ok-0");
+    }
+
+    #[test]
+    fn test_retention_max_age() {
+        let mut state = AppState::new();
+        state.retention.warning.max_age_minutes = Some(60);
+        add_request(&mut state, "selected", "200 OK in 5ms", "2024-05-01T10:00:00Z");
+        add_request(&mut state, "old-404", "404 Not Found in 5ms", "2024-05-01T10:00:00Z");
+        add_request(&mut state, "old-200", "200 OK in 5ms", "2024-05-01T10:00:00Z");
+        add_request(&mut state, "new-404", "404 Not Found in 5ms", "2024-05-01T11:30:00Z");
+        assert!(state.apply_retention());
+
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["new-404", "old-200", "selected"]);
+    }
+
+    #[test]
+    fn test_overall_cap_skips_forever() {
+        let mut state = AppState::new();
+        state.retention.max_requests = 2;
+        state.retention.error.forever = true;
+        add_request(&mut state, "err", "500 Internal Server Error in 5ms", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "ok-1", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "ok-2", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["ok-2", "err"]);
+    }
+
+    #[test]
+    fn test_clear_finished() {
+        let mut state = AppState::new();
+        add_request(&mut state, "done-1", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        state.add_log_entry(LogEntry {
+            request_id: "running".to_string(),
+            message: "Started GET /slow".to_string(),
+            ..Default::default()
+        });
+        add_request(&mut state, "done-2", "500 Internal Server Error in 5ms", "2024-05-01T12:00:00Z");
+
+        state.select_request(1);
+        assert_eq!(state.clear_finished(), 2);
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["running"]);
+        assert_eq!(state.selected_request_id().unwrap(), "running");
+        assert_eq!(state.log_group_count(), 1);
+    }
+
+    #[test]
+    fn test_starred_requests_are_kept() {
+        let mut state = AppState::new();
+        state.retention.max_requests = 2;
+        add_request(&mut state, "starred", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        state.select_request(0);
+        assert_eq!(state.toggle_star_selected(), Some(true));
+
+        add_request(&mut state, "req-2", "200 OK in 5ms", "2024-05-01T12:00:01Z");
+        add_request(&mut state, "req-3", "200 OK in 5ms", "2024-05-01T12:00:02Z");
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["req-3", "starred"]);
+
+        assert_eq!(state.clear_finished(), 1);
+        assert_eq!(state.selected_request_id().unwrap(), "starred");
+        assert_eq!(state.toggle_star_selected(), Some(false));
+        assert_eq!(state.clear_finished(), 1);
+    }
+
+    #[test]
+    fn test_cable_connection_groups() {
+        let mut state = AppState::new();
+        for line in [
+            r#"[req-1] Started GET "/posts" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#,
+            r#"[req-2] Started GET "/cable" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#,
+            r#"[req-2] Started GET "/cable/" [WebSocket] for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#,
+            "[req-2] Successfully upgraded to WebSocket (REQUEST_METHOD: GET, HTTP_CONNECTION: Upgrade, HTTP_UPGRADE: websocket)",
+            "[req-1] [ActionCable] Broadcasting to chat_1: {\"body\"=>\"hi\"}",
+            "[req-1] Completed 200 OK in 5ms",
+            "[req-2] ChatChannel is transmitting the subscription confirmation",
+            "[req-2] Turbo::StreamsChannel is transmitting the subscription confirmation",
+            "[req-2] ChatChannel is transmitting the subscription confirmation",
+            "ChatChannel is streaming from chat_1",
+            r#"[req-2] Finished "/cable/" [WebSocket] for 127.0.0.1 at 2024-05-01 12:05:00 +0900"#,
+        ] {
+            state.add_log_entry(parse(line));
+        }
+
+        let connection = &state.logs_by_request_id["req-2"];
+        assert_eq!(connection.kind, GroupKind::Cable);
+        assert_eq!(connection.title, "ChatChannel, Turbo::StreamsChannel");
+        assert!(connection.finished);
+        assert_eq!(connection.status_type, StatusType::Success);
+        // A broadcast made by a request stays with it
+        let request = &state.logs_by_request_id["req-1"];
+        assert_eq!(request.kind, GroupKind::Request);
+        assert_eq!(request.entries.len(), 3);
+        // Untagged connection lines share a group
+        let shared = &state.logs_by_request_id[cable::CABLE_ID];
+        assert_eq!(shared.kind, GroupKind::Cable);
+        assert_eq!(shared.title, cable::CONNECTION_TITLE);
+        assert_eq!(state.ingest.orphan_lines, 0);
+    }
+
+    #[test]
+    fn test_merge_requests() {
+        let mut state = AppState::new();
+        add_request(&mut state, "req-1", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "req-2", "500 Internal Server Error in 5ms", "2024-05-01T12:00:01Z");
+        let request_ids = ["req-2".to_string(), "req-1".to_string(), "gone".to_string()];
+
+        let merged_id = state
+            .merge_requests(&request_ids, "Merged".to_string())
+            .unwrap();
+        assert_eq!(merged_id, "merged-1");
+        assert_eq!(state.selected_request_id().unwrap(), "merged-1");
+        let group = &state.logs_by_request_id["merged-1"];
+        let origins: Vec<&str> = group
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| entry.request_id.as_str())
+            .collect();
+        assert_eq!(origins, ["req-1", "req-1", "req-2", "req-2"]);
+        assert_eq!(group.title, "Merged");
+        assert_eq!(group.status_type, StatusType::Error);
+        assert_eq!(group.duration_ms, Some(1000));
+        // The originals stay
+        assert_eq!(state.request_ids.len(), 3);
+
+        assert_eq!(
+            state.merge_requests(&request_ids[..1], "Again".to_string()).as_deref(),
+            Some("merged-2")
+        );
+        assert!(state.merge_requests(&[], "None".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_clear_finished_before() {
+        let mut state = AppState::new();
+        let before = Instant::now();
+        add_request(&mut state, "req-1", "200 OK in 5ms", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "req-2", "200 OK in 5ms", "2024-05-01T12:00:01Z");
+        add_request(&mut state, "req-3", "200 OK in 5ms", "2024-05-01T12:00:02Z");
+        state.add_log_entry(LogEntry {
+            request_id: "req-4".to_string(),
+            message: "Started GET /".to_string(),
+            ..Default::default()
+        });
+        state.logs_by_request_id.get_mut("req-2").unwrap().starred = true;
+        state.select_request(1);
+
+        assert_eq!(state.clear_finished_before(before), 0);
+        let later = Instant::now() + std::time::Duration::from_secs(60);
+        // The running, starred and selected requests stay
+        assert_eq!(state.clear_finished_before(later), 1);
+        assert_eq!(state.request_ids, ["req-4", "req-3", "req-2"]);
+        assert_eq!(state.selected_request_id().unwrap(), "req-3");
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut state = AppState::new();
+        let note = |text: &str, started: &str| Note {
+            text: text.to_string(),
+            started: started.to_string(),
+            ..Default::default()
+        };
+        state.notes.insert(
+            "req-2".to_string(),
+            note("same failure as req-1", "2024-05-01T12:00:01+00:00"),
+        );
+        add_request(&mut state, "req-1", "500 Internal Server Error in 5ms", "2024-05-01T12:00:00Z");
+        state.set_note("req-1", "  flaky ");
+        assert_eq!(state.logs_by_request_id["req-1"].note.as_deref(), Some("flaky"));
+
+        // Noted in an earlier run
+        add_request(&mut state, "req-2", "500 Internal Server Error in 5ms", "2024-05-01T12:00:01Z");
+        assert_eq!(
+            state.logs_by_request_id["req-2"].note.as_deref(),
+            Some("same failure as req-1")
+        );
+
+        state.set_note("req-1", " ");
+        assert_eq!(state.logs_by_request_id["req-1"].note, None);
+        assert_eq!(state.notes.keys().collect::<Vec<_>>(), ["req-2"]);
+
+        // A `--generic` key gets its note back only on the request it was written on
+        for worker in ["worker-1", "worker-2"] {
+            state.notes.insert(worker.to_string(), note("stuck", "2024-05-01T12:00:02+00:00"));
+        }
+        let workers = [("worker-1", "2024-05-01T12:00:05Z"), ("worker-2", "2024-05-01T12:00:02Z")];
+        for (worker, timestamp) in workers {
+            state.add_log_entry(LogEntry {
+                request_id: worker.to_string(),
+                message: "job started".to_string(),
+                timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+                kind: GroupKind::Generic,
+                ..Default::default()
+            });
+        }
+        assert_eq!(state.logs_by_request_id["worker-1"].note, None);
+        assert_eq!(state.logs_by_request_id["worker-2"].note.as_deref(), Some("stuck"));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut state = AppState::new();
+        let submit = |state: &mut AppState, id: &str, title: &str, timestamp: &str| {
+            for message in [
+                format!("Started POST \"/posts\" for 127.0.0.1 at {}", timestamp),
+                format!("Parameters: {{\"title\"=>\"{}\"}}", title),
+                "Completed 302 Found in 5ms".to_string(),
+            ] {
+                state.add_log_entry(LogEntry {
+                    request_id: id.to_string(),
+                    message,
+                    timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+                    ..Default::default()
+                });
+            }
+        };
+        submit(&mut state, "req-1", "Hello", "2024-05-01T12:00:00Z");
+        submit(&mut state, "req-2", "Other", "2024-05-01T12:00:00Z");
+        submit(&mut state, "req-3", "Hello", "2024-05-01T12:00:01Z");
+        // Same params, but long after the first
+        submit(&mut state, "req-4", "Other", "2024-05-01T12:00:30Z");
+
+        let duplicates: Vec<&str> = ["req-1", "req-2", "req-3", "req-4"]
+            .into_iter()
+            .filter(|id| state.logs_by_request_id[*id].duplicate)
+            .collect();
+        assert_eq!(duplicates, ["req-1", "req-3"]);
+        assert_eq!(
+            state.logs_by_request_id["req-1"].fingerprint().as_deref(),
+            Some("POST /posts {\"title\"=>\"Hello\"}")
+        );
+    }
+
+    #[test]
+    fn test_restarts() {
+        let mut state = AppState::new();
+        let boot = |state: &mut AppState, message: &str, timestamp: &str| {
+            state.add_log_entry(LogEntry {
+                message: message.to_string(),
+                timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+                ..Default::default()
+            });
+        };
+        // Booting before any request is no restart
+        boot(&mut state, "=> Booting Puma", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "1", "200 OK in 5ms", "2024-05-01T12:00:01Z");
+        add_request(&mut state, "2", "200 OK in 5ms", "2024-05-01T12:00:02Z");
+        boot(&mut state, "=> Booting Puma", "2024-05-01T12:01:00Z");
+        boot(&mut state, "* Listening on http://127.0.0.1:3000", "2024-05-01T12:01:01Z");
+        assert_eq!(
+            state.restarts,
+            [DateTime::parse_from_rfc3339("2024-05-01T12:01:00Z").unwrap()]
+        );
+        assert_eq!(state.ids_before_restart(), ["2", "1"]);
+
+        // The same id after the restart is a request of its own
+        add_request(&mut state, "1", "500 Internal Server Error in 5ms", "2024-05-01T12:01:02Z");
+        assert_eq!(state.request_ids, ["1", "2", "1~1", UNASSIGNED_ID]);
+        assert_eq!(state.logs_by_request_id["1"].status_type, StatusType::Error);
+        assert_eq!(state.logs_by_request_id["1~1"].status_type, StatusType::Success);
+        assert_eq!(state.ids_before_restart(), ["2", "1~1"]);
+    }
+
+    #[test]
+    fn test_pinned_requests() {
+        let mut state = AppState::new();
+        state.max_pinned = 2;
+        let ids = |state: &AppState| -> Vec<String> { state.request_ids.iter().cloned().collect() };
+        for i in 0..4 {
+            let timestamp = format!("2024-05-01T12:00:0{}Z", i);
+            add_request(&mut state, &format!("req-{}", i), "200 OK in 5ms", &timestamp);
+        }
+        state.select_request(1);
+        assert!(state.pin("req-1"));
+        assert!(!state.pin("req-1"));
+        assert!(state.pin("req-3"));
+        assert_eq!(ids(&state), ["req-3", "req-1", "req-2", "req-0"]);
+        assert_eq!(state.selected_request_id().unwrap(), "req-2");
+
+        // New requests go below the pinned ones
+        add_request(&mut state, "req-4", "200 OK in 5ms", "2024-05-01T12:00:04Z");
+        assert_eq!(ids(&state), ["req-3", "req-1", "req-4", "req-2", "req-0"]);
+        assert_eq!(state.selected_request_id().unwrap(), "req-2");
+
+        // The oldest pin goes back to its place
+        assert!(state.pin("req-0"));
+        assert_eq!(ids(&state), ["req-0", "req-3", "req-4", "req-2", "req-1"]);
+        assert_eq!(state.pinned_count(), 2);
+        assert!(!state.logs_by_request_id["req-1"].pinned);
+        assert_eq!(state.selected_request_id().unwrap(), "req-2");
+    }
+
+    #[test]
+    fn test_bulk_operations() {
+        let mut state = AppState::new();
+        add_request(&mut state, "ok", "200 OK in 10ms", "2024-05-01T12:00:00Z");
+        add_request(&mut state, "slow", "200 OK in 900ms", "2024-05-01T12:00:01Z");
+        add_request(&mut state, "failed", "500 Internal Server Error in 30ms", "2024-05-01T12:00:02Z");
+        state.add_log_entry(LogEntry {
+            request_id: "running".to_string(),
+            message: "Started GET /slow".to_string(),
+            ..Default::default()
+        });
+        let matching = ["slow", "failed", "running"].map(String::from);
+
+        let stats = state.stats_for(&matching);
+        assert_eq!(stats.total(), 2);
+        assert_eq!(stats.count(StatusType::Error), 1);
+        assert_eq!(stats.max_duration(), Some(900));
+
+        assert_eq!(state.star_requests(&matching[..1]), 1);
+        assert_eq!(state.star_requests(&matching), 2);
+
+        state.select_request(3);
+        assert_eq!(state.selected_request_id().unwrap(), "ok");
+        assert_eq!(state.remove_requests(&matching), 3);
+        let ids: Vec<&str> = state.request_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["ok"]);
+        assert_eq!(state.selected_request_id().unwrap(), "ok");
+    }
+
+    #[test]
+    fn test_generic_groups() {
+        let group_key = log_parser::compile_group_key(log_parser::DEFAULT_GENERIC_GROUP_KEY).unwrap();
+        let mut state = AppState::new();
+        for line in [
+            "2024-05-01T12:00:00Z INFO trace_id=abc worker picked up batch 7",
+            "2024-05-01T12:00:01Z WARN trace_id=abc retrying upstream call",
+            "2024-05-01T12:00:02Z INFO trace_id=abc batch 7 done",
+            "2024-05-01T12:00:03Z level=debug trace_id=def heartbeat",
+        ] {
+            let line = log_parser::parse_generic(line, &group_key, &Default::default()).unwrap();
+            state.add_log_entry(line.into());
+        }
+
+        let group = &state.logs_by_request_id["abc"];
+        assert_eq!(group.kind, GroupKind::Generic);
+        assert_eq!(group.title, "INFO trace_id=abc worker picked up batch 7");
+        assert!(group.finished);
+        assert_eq!(group.status_type, StatusType::Warning);
+        assert_eq!(group.duration_ms, Some(2000));
+        assert_eq!(state.logs_by_request_id["def"].status_type, StatusType::Success);
+    }
+
+    #[test]
+    fn test_compaction() {
+        let mut state = AppState::new();
+        state.compaction = true;
+        for message in [
+            "[req-1] Started GET \"/users\" for 127.0.0.1",
+            "[req-1] Processing by UsersController#index as HTML",
+            "[req-1]   User Load (0.4ms)  SELECT \"users\".* FROM \"users\"",
+            "[req-1]   Rendered users/index.html.erb within layouts/application (Duration: 1.2ms)",
+            "[req-1] NoMethodError (undefined method `name' for nil):",
+            "[req-1] Completed 500 Internal Server Error in 12ms",
+        ] {
+            state.add_log_entry(parse(message));
+        }
+
+        let group = state.selected_group().unwrap();
+        assert!(group.compacted);
+        assert_eq!(group.entries.len(), 5);
+        assert!(!group.entries.iter().any(|entry| entry.message.contains("Rendered")));
+        assert_eq!(group.sql_query_info.total_queries(), 1);
+
+        // Already minimal groups are not marked
+        for message in ["[req-2] Started GET \"/\"", "[req-2] Completed 200 OK in 5ms"] {
+            state.add_log_entry(parse(message));
+        }
+        assert!(!state.logs_by_request_id["req-2"].compacted);
+    }
+
+    #[test]
+    fn test_repeated_lines() {
+        let mut state = AppState::new();
+        for message in [
+            "[req-1] Started GET \"/users\" for 127.0.0.1",
+            "[req-1]   Redis (0.3ms)  GET session:1",
+            "[req-1]   Redis (0.3ms)  GET session:1",
+            "[req-1]   Redis (0.3ms)  GET session:1",
+            "[req-1] Retrying upstream call",
+            "[req-1]   Redis (0.3ms)  GET session:1",
+            "[req-1] Completed 200 OK in 5ms",
+        ] {
+            state.add_log_entry(parse(message));
+        }
+
+        let group = state.selected_group().unwrap();
+        let counts: Vec<usize> = group.entries.iter().rev().map(LogEntry::count).collect();
+        assert_eq!(counts, [1, 3, 1, 1, 1]);
+        assert_eq!(group.line_count(), 7);
+        // Every line still counts toward the group
+        assert_eq!(group.cache_info.redis_count(), 4);
+
+        // Lines read back from the journal are folded the same way
+        let lines: Vec<LogEntry> = group
+            .entries
+            .iter()
+            .rev()
+            .flat_map(|entry| {
+                let mut line = entry.clone();
+                line.repeats.clear();
+                std::iter::repeat_n(line, entry.count())
+            })
+            .collect();
+        let mut restored = LogGroup::new(lines[0].clone());
+        restored.restore(lines);
+        let restored_counts: Vec<usize> = restored.entries.iter().rev().map(LogEntry::count).collect();
+        assert_eq!(restored_counts, counts);
+    }
+
+    #[test]
+    fn test_stderr_lines() {
+        let mut state = AppState::new();
+        for (message, stderr) in [
+            ("[req-1] Started GET \"/users\" for 127.0.0.1", false),
+            ("[req-1] Deprecation warning", false),
+            ("[req-1] Deprecation warning", true),
+        ] {
+            let mut entry = parse(message);
+            entry.stderr = stderr;
+            state.add_log_entry(entry);
+        }
+        state.add_log_entry(parse("[req-2] Started GET \"/\""));
+
+        let group = &state.logs_by_request_id["req-1"];
+        assert!(group.has_stderr);
+        // The same text on both streams stays two lines
+        let streams: Vec<bool> = group.entries.iter().rev().map(|entry| entry.stderr).collect();
+        assert_eq!(streams, [false, false, true]);
+        assert!(!state.logs_by_request_id["req-2"].has_stderr);
+    }
+
+    #[test]
+    fn test_summary_and_first_error() {
+        let mut state = AppState::new();
+        for message in [
+            "[req-1] Started GET \"/users\" for 127.0.0.1",
+            "[req-1] ArgumentError (wrong number of arguments):",
+            "[req-1] NoMethodError (undefined method `name' for nil):",
+            "[req-1] Completed 500 Internal Server Error in 12ms",
+        ] {
+            state.add_log_entry(parse(message));
+        }
+
+        let group = state.selected_group().unwrap();
+        let summary = group.summary_entry().unwrap();
+        assert!(summary.message.contains("Completed 500"));
+        let error = group.first_error_entry().unwrap();
+        assert!(error.message.contains("ArgumentError"));
+
+        state.add_log_entry(parse("[req-2] Started GET \"/\""));
+        let group = &state.logs_by_request_id["req-2"];
+        assert!(group.summary_entry().is_none());
+        assert!(group.first_error_entry().is_none());
+    }
+
+    #[test]
+    fn test_displayed_entries() {
+        let mut group = LogGroup::new(parse("[req-1] Started GET \"/users\" for 127.0.0.1"));
+        for message in [
+            "[req-1]   Rendered users/_user.html.erb (Duration: 0.1ms)",
+            "[req-1]   User Load (0.4ms)  SELECT \"users\".* FROM \"users\"",
+            "[req-1]   Rendered users/index.html.erb (Duration: 1.2ms)",
+            "[req-1] Completed 200 OK in 12ms",
+        ] {
+            group.add_entry(parse(message));
+        }
+
+        let all = group.displayed(Verbosity::Full);
+        assert_eq!(all.len(), 5);
+        assert_eq!(all.index(4), Some(4));
+        assert_eq!(all.index(5), None);
+
+        let summary = group.displayed(Verbosity::Summary);
+        assert_eq!(summary.from(0).map(|(index, _)| index).collect::<Vec<_>>(), vec![0, 4]);
+
+        let simple = group.displayed(Verbosity::Simple);
+        assert_eq!(simple.len(), 3);
+        assert_eq!(simple.index(1), Some(2));
+        assert_eq!(simple.position(2), Some(1));
+        assert_eq!(simple.position(1), None);
+        let shown: Vec<usize> = simple.from(1).map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![2, 4]);
+        assert!(simple.from(1).next().unwrap().1.message.contains("User Load"));
+
+        // Dropping the oldest lines keeps the rest in place
+        group.keep_newest(3);
+        let simple = group.displayed(Verbosity::Simple);
+        assert_eq!(simple.len(), 2);
+        assert_eq!(simple.index(1), Some(2));
+
+        group.compact();
+        let simple = group.displayed(Verbosity::Simple);
+        assert_eq!(group.entries.len(), 2);
+        assert_eq!(simple.len(), 2);
+        assert_eq!(simple.index(1), Some(1));
+    }
+}
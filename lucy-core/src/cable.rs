@@ -0,0 +1,158 @@
+//! ActionCable lines: WebSocket connections, channel subscriptions and
+//! broadcasts, Turbo Streams included. They get groups of their own, keyed
+//! by the connection's tag when ActionCable's `log_tags` set one.
+
+use crate::log_parser::{leading_tags, strip_tags};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Key of the group collecting ActionCable lines that carry no connection tag.
+pub const CABLE_ID: &str = "(action cable)";
+
+/// Title of a connection before it subscribes to a channel.
+pub const CONNECTION_TITLE: &str = "WebSocket";
+
+// Started GET "/cable" for 127.0.0.1 at ...
+// Started GET "/cable/" [WebSocket] for 127.0.0.1 at ...
+// Finished "/cable/" [WebSocket] for 127.0.0.1 at ...
+static RE_WEBSOCKET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^(?:Started GET "/cable/?"|(?P<verb>Started GET|Finished) "[^"]*" \[WebSocket\])"#)
+        .unwrap()
+});
+
+// ChatChannel is transmitting the subscription confirmation
+// ChatChannel is streaming from chat_1
+// ChatChannel stopped streaming from chat_1
+// ChatChannel transmitting {"body"=>"hi"} (via streamed from chat_1)
+// ChatChannel#speak({"body"=>"hi", "action"=>"speak"})
+static RE_CHANNEL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?P<channel>[A-Z][\w:]*Channel)(?:#(?P<action>\w+)\(| is transmitting the subscription (?P<subscription>confirmation|rejection)| is streaming from | stopped streaming from | transmitting )",
+    )
+    .unwrap()
+});
+
+// [ActionCable] Broadcasting to chat_1: {...}
+static RE_BROADCAST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[ActionCable\] Broadcasting to \S").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CableEvent<'a> {
+    /// The connection was opened or upgraded to a WebSocket
+    Opened,
+    Subscribed(&'a str),
+    /// The channel refused the subscription, or the connection was refused
+    Rejected,
+    /// Streaming, transmitting, broadcasting or an action of a channel
+    Message,
+    /// A channel action raised
+    Failed,
+    Closed,
+}
+
+/// The ActionCable event `message` logs, if it is an ActionCable line.
+pub fn parse_event(message: &str) -> Option<CableEvent<'_>> {
+    // Checked first, as nearly every line is something else
+    if !(message.contains("Channel")
+        || message.contains("WebSocket")
+        || message.contains("ActionCable")
+        || message.contains("\"/cable")
+        || message.contains("connection")
+        || message.contains("Could not execute"))
+    {
+        return None;
+    }
+    let text = strip_tags(message);
+    if let Some(caps) = RE_WEBSOCKET.captures(text) {
+        return Some(match caps.name("verb").map(|verb| verb.as_str()) {
+            Some("Finished") => CableEvent::Closed,
+            _ => CableEvent::Opened,
+        });
+    }
+    if text.starts_with("Successfully upgraded to WebSocket") {
+        return Some(CableEvent::Opened);
+    }
+    if text.starts_with("An unauthorized connection attempt was rejected") {
+        return Some(CableEvent::Rejected);
+    }
+    if text.starts_with("Could not execute command from") {
+        return Some(CableEvent::Failed);
+    }
+    if let Some(caps) = RE_CHANNEL.captures(text) {
+        let channel = caps.name("channel")?.as_str();
+        return Some(match caps.name("subscription").map(|kind| kind.as_str()) {
+            Some("confirmation") => CableEvent::Subscribed(channel),
+            Some(_) => CableEvent::Rejected,
+            None => CableEvent::Message,
+        });
+    }
+    RE_BROADCAST.is_match(message).then_some(CableEvent::Message)
+}
+
+/// Key of the connection an ActionCable line belongs to: its first tag
+/// other than `ActionCable`, or `CABLE_ID` without one.
+pub fn connection_id(message: &str) -> String {
+    leading_tags(message)
+        .into_iter()
+        .find(|tag| *tag != "ActionCable")
+        .unwrap_or(CABLE_ID)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(
+            parse_event(r#"[req-1] Started GET "/cable" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#),
+            Some(CableEvent::Opened)
+        );
+        assert_eq!(
+            parse_event(r#"Started GET "/cable/" [WebSocket] for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#),
+            Some(CableEvent::Opened)
+        );
+        assert_eq!(
+            parse_event("Successfully upgraded to WebSocket (REQUEST_METHOD: GET, HTTP_CONNECTION: Upgrade, HTTP_UPGRADE: websocket)"),
+            Some(CableEvent::Opened)
+        );
+        assert_eq!(
+            parse_event("[ActionCable] [conn-1] ChatChannel is transmitting the subscription confirmation"),
+            Some(CableEvent::Subscribed("ChatChannel"))
+        );
+        assert_eq!(
+            parse_event("Turbo::StreamsChannel is streaming from Z2lkOi8vYXBwL1Jvb20vMQ"),
+            Some(CableEvent::Message)
+        );
+        assert_eq!(
+            parse_event(r#"ChatChannel#speak({"body"=>"hi", "action"=>"speak"})"#),
+            Some(CableEvent::Message)
+        );
+        assert_eq!(
+            parse_event("ChatChannel is transmitting the subscription rejection"),
+            Some(CableEvent::Rejected)
+        );
+        assert_eq!(
+            parse_event(r#"[req-1] [ActionCable] Broadcasting to chat_1: {"body"=>"hi"}"#),
+            Some(CableEvent::Message)
+        );
+        assert_eq!(
+            parse_event(r#"Finished "/cable/" [WebSocket] for 127.0.0.1 at 2024-05-01 12:05:00 +0900"#),
+            Some(CableEvent::Closed)
+        );
+
+        assert_eq!(parse_event(r#"[req-1] Started GET "/posts" for 127.0.0.1"#), None);
+        assert_eq!(parse_event("[req-1] Rendered posts/index.html.erb (Duration: 1.0ms)"), None);
+        // A controller mentioning a channel is not ActionCable
+        assert_eq!(parse_event("[req-1] Processing by ChannelsController#index as HTML"), None);
+    }
+
+    #[test]
+    fn test_connection_id() {
+        assert_eq!(connection_id("[ActionCable] [conn-1] ChatChannel#speak({})"), "conn-1");
+        assert_eq!(connection_id("[req-1] Successfully upgraded to WebSocket"), "req-1");
+        assert_eq!(connection_id("[ActionCable] Broadcasting to chat_1: {}"), CABLE_ID);
+        assert_eq!(connection_id("ChatChannel is streaming from chat_1"), CABLE_ID);
+    }
+}
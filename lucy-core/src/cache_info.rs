@@ -0,0 +1,207 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// Cache read: views/posts/1-20240501120000/abc123
+// Cache fetch_hit: posts/count ({:expires_in=>1 hour})
+// Cache exist?: session/42 (0.2ms)
+static RE_CACHE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bCache (?P<op>[a-z_]+\??):").unwrap());
+
+// Read fragment views/posts/index:abcd/posts/1 (0.1ms)   (fragment caching, Rails < 7)
+static RE_FRAGMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?P<op>Read|Write|Expire|Exist\?) fragment ").unwrap());
+
+// [Redis] command=GET args="session:42"   (redis-rb's logger)
+static RE_REDIS_COMMAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[Redis\] command=(?P<command>\w+)").unwrap());
+
+// [Redis] call_time=0.19 ms
+static RE_REDIS_TIME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[Redis\] call_time=(?P<ms>\d+(?:\.\d+)?) ms").unwrap());
+
+// Redis (0.4ms) GET session:42   (instrumentation in the style of SQL lines)
+static RE_REDIS_INSTRUMENTED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bRedis \((?P<ms>\d+(?:\.\d+)?)ms\)\s+(?P<command>\w+)").unwrap()
+});
+
+static RE_MS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\((?P<ms>\d+(?:\.\d+)?)ms\)").unwrap());
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CommandTiming {
+    pub count: usize,
+    /// Commands whose time was logged
+    pub timed: usize,
+    pub total_ms: f64,
+}
+
+impl CommandTiming {
+    fn add(&mut self, ms: Option<f64>) {
+        self.count += 1;
+        if let Some(ms) = ms {
+            self.timed += 1;
+            self.total_ms += ms;
+        }
+    }
+}
+
+/// Rails cache operations and Redis commands of a request, by name.
+#[derive(Debug, Default)]
+pub struct CacheInfo {
+    /// `read`, `write`, `fetch_hit`, ... as Rails logs them
+    pub cache: HashMap<String, CommandTiming>,
+    /// `GET`, `SET`, ... in upper case
+    pub redis: HashMap<String, CommandTiming>,
+    /// Command that a following `call_time` line times
+    last_redis: Option<String>,
+}
+
+impl CacheInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a cache operation or Redis command, or the time of the
+    /// previous command. Returns false for other lines.
+    pub fn record(&mut self, message: &str) -> bool {
+        if message.contains("Redis") {
+            return self.record_redis(message);
+        }
+        let op = if let Some(caps) = RE_CACHE.captures(message) {
+            caps["op"].to_string()
+        } else if let Some(caps) = RE_FRAGMENT.captures(message) {
+            caps["op"].to_lowercase()
+        } else {
+            return false;
+        };
+        let ms = RE_MS
+            .captures(message)
+            .and_then(|caps| caps["ms"].parse().ok());
+        self.cache.entry(op).or_default().add(ms);
+        true
+    }
+
+    fn record_redis(&mut self, message: &str) -> bool {
+        if let Some(caps) = RE_REDIS_INSTRUMENTED.captures(message) {
+            let command = caps["command"].to_uppercase();
+            self.redis
+                .entry(command)
+                .or_default()
+                .add(caps["ms"].parse().ok());
+            return true;
+        }
+        if let Some(caps) = RE_REDIS_COMMAND.captures(message) {
+            let command = caps["command"].to_uppercase();
+            self.redis.entry(command.clone()).or_default().add(None);
+            self.last_redis = Some(command);
+            return true;
+        }
+        let Some(ms) = RE_REDIS_TIME
+            .captures(message)
+            .and_then(|caps| caps["ms"].parse::<f64>().ok())
+        else {
+            return false;
+        };
+        let Some(timing) = self
+            .last_redis
+            .take()
+            .and_then(|command| self.redis.get_mut(&command))
+        else {
+            return false;
+        };
+        timing.timed += 1;
+        timing.total_ms += ms;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty() && self.redis.is_empty()
+    }
+
+    pub fn cache_count(&self) -> usize {
+        self.cache.values().map(|timing| timing.count).sum()
+    }
+
+    pub fn redis_count(&self) -> usize {
+        self.redis.values().map(|timing| timing.count).sum()
+    }
+
+    pub fn display_line_count(&self) -> usize {
+        // blank line(1) + heading(1) + rows, for each section shown
+        [self.cache.len(), self.redis.len()]
+            .into_iter()
+            .filter(|&rows| rows > 0)
+            .map(|rows| 2 + rows)
+            .sum()
+    }
+}
+
+/// Slowest first, then the most frequent.
+pub fn by_total_time(commands: &HashMap<String, CommandTiming>) -> Vec<(&String, &CommandTiming)> {
+    let mut commands: Vec<_> = commands.iter().collect();
+    commands.sort_by(|a, b| {
+        b.1.total_ms
+            .total_cmp(&a.1.total_ms)
+            .then(b.1.count.cmp(&a.1.count))
+            .then(a.0.cmp(b.0))
+    });
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_cache_lines() {
+        let mut info = CacheInfo::new();
+        for message in [
+            "[r1] Cache read: views/posts/1-20240501120000/abc123",
+            "[r1] Cache read: views/posts/2-20240501120000/abc123",
+            "[r1] Cache fetch_hit: posts/count ({:expires_in=>1 hour})",
+            "[r1] Cache exist?: session/42 (0.2ms)",
+            "[r1]   Write fragment views/posts/index:abcd/posts/1 (0.3ms)",
+        ] {
+            assert!(info.record(message), "{}", message);
+        }
+        // The SQL query cache is counted with the queries
+        assert!(!info.record(r#"[r1]   CACHE Post Load (0.0ms)  SELECT "posts".* FROM "posts""#));
+        assert!(!info.record("[r1] Completed 200 OK in 5ms"));
+
+        assert_eq!(info.cache["read"].count, 2);
+        assert_eq!(info.cache["fetch_hit"].count, 1);
+        assert_eq!(info.cache["exist?"].timed, 1);
+        assert_eq!(info.cache["write"].count, 1);
+        assert_eq!(info.cache_count(), 5);
+        assert_eq!(info.display_line_count(), 2 + 4);
+    }
+
+    #[test]
+    fn test_record_redis_lines() {
+        let mut info = CacheInfo::new();
+        for message in [
+            r#"[r1] [Redis] command=GET args="session:42""#,
+            "[r1] [Redis] call_time=0.19 ms",
+            r#"[r1] [Redis] command=get args="session:43""#,
+            "[r1] [Redis] call_time=0.21 ms",
+            "[r1]   Redis (1.5ms) SET cart:7 {...}",
+        ] {
+            assert!(info.record(message), "{}", message);
+        }
+        // A time without a command before it
+        assert!(!CacheInfo::new().record("[Redis] call_time=0.19 ms"));
+
+        let get = info.redis["GET"];
+        assert_eq!((get.count, get.timed), (2, 2));
+        assert!((get.total_ms - 0.4).abs() < 1e-9);
+        assert_eq!(info.redis_count(), 3);
+        let order: Vec<&str> = by_total_time(&info.redis)
+            .iter()
+            .map(|(command, _)| command.as_str())
+            .collect();
+        assert_eq!(order, ["SET", "GET"]);
+        assert_eq!(info.display_line_count(), 2 + 2);
+        assert_eq!(CacheInfo::new().display_line_count(), 0);
+    }
+}
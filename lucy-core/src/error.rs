@@ -0,0 +1,82 @@
+//! What can go wrong in lucy-core: a pattern the user supplied that cannot
+//! be used, or a journal file that cannot be created.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A regex from the config or command line that does not compile
+    InvalidPattern {
+        /// What the pattern is for, such as `group key regex`
+        name: &'static str,
+        pattern: String,
+        source: regex::Error,
+    },
+    /// A regex without the capture group its match is read from
+    MissingCapture {
+        name: &'static str,
+        pattern: String,
+    },
+    /// The journal file could not be created
+    Journal { path: PathBuf, source: io::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Compiles `pattern`, which needs at least one capture group.
+pub(crate) fn compile_with_capture(name: &'static str, pattern: &str) -> Result<regex::Regex> {
+    let regex = regex::Regex::new(pattern).map_err(|source| Error::InvalidPattern {
+        name,
+        pattern: pattern.to_string(),
+        source,
+    })?;
+    if regex.captures_len() < 2 {
+        return Err(Error::MissingCapture {
+            name,
+            pattern: pattern.to_string(),
+        });
+    }
+    Ok(regex)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPattern { name, pattern, .. } => {
+                write!(f, "Invalid {}: {}", name, pattern)
+            }
+            Error::MissingCapture { name, pattern } => {
+                let mut chars = name.chars();
+                let first = chars.next().map(|c| c.to_ascii_uppercase()).unwrap_or_default();
+                write!(f, "{}{} needs a capture group: {}", first, chars.as_str(), pattern)
+            }
+            Error::Journal { path, .. } => write!(f, "Failed to create journal {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidPattern { source, .. } => Some(source),
+            Error::Journal { source, .. } => Some(source),
+            Error::MissingCapture { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_with_capture() {
+        assert!(compile_with_capture("group key regex", r"id=(\w+)").is_ok());
+        let error = compile_with_capture("group key regex", r"id=\w+").unwrap_err();
+        assert_eq!(error.to_string(), r"Group key regex needs a capture group: id=\w+");
+        let error = compile_with_capture("timestamp pattern", "(").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid timestamp pattern: (");
+    }
+}
@@ -0,0 +1,209 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// I, [2024-05-01T12:00:00.123 #1]  INFO -- request: GET https://api.example.com/v1/users   (Faraday)
+// [HTTParty] [2024-05-01 12:00:00 +0900] 200 "GET https://api.example.com/users" 512       (HTTParty)
+// [httplog] Sending: POST http://api.example.com:8080/charges                               (httplog)
+// POST https://api.stripe.com/v1/charges (200) 184.2ms                                      (detailed loggers)
+static RE_CALL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\b(?:GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS) "?https?://(?P<host>[\w.-]+)(?::\d+)?[^\s"]*"?(?P<rest>.*)"#,
+    )
+    .unwrap()
+});
+
+// opening connection to api.example.com:443...   (Net::HTTP#set_debug_output)
+static RE_NET_HTTP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bopening connection to (?P<host>[\w.-]+):\d+").unwrap());
+
+// [httplog] Benchmark: 0.184 seconds
+static RE_BENCHMARK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[httplog\] Benchmark: (?P<seconds>\d+(?:\.\d+)?) seconds").unwrap()
+});
+
+static RE_DURATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?P<value>\d+(?:\.\d+)?) ?(?P<unit>ms|s|seconds)\b").unwrap());
+
+/// One outbound HTTP call a line logs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpCall<'a> {
+    pub host: &'a str,
+    /// None when the client does not log how long the call took
+    pub ms: Option<f64>,
+}
+
+pub fn parse_http_call(message: &str) -> Option<HttpCall<'_>> {
+    if !message.contains("http") && !message.contains("opening connection") {
+        return None;
+    }
+    if let Some(caps) = RE_CALL.captures(message) {
+        return Some(HttpCall {
+            host: caps.name("host")?.as_str(),
+            ms: caps
+                .name("rest")
+                .and_then(|rest| duration_ms(rest.as_str())),
+        });
+    }
+    let caps = RE_NET_HTTP.captures(message)?;
+    Some(HttpCall {
+        host: caps.name("host")?.as_str(),
+        ms: None,
+    })
+}
+
+fn duration_ms(text: &str) -> Option<f64> {
+    let caps = RE_DURATION.captures(text)?;
+    let value = caps["value"].parse::<f64>().ok()?;
+    Some(match &caps["unit"] {
+        "ms" => value,
+        _ => value * 1000.0,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HostTiming {
+    pub count: usize,
+    /// Calls whose time was logged
+    pub timed: usize,
+    pub total_ms: f64,
+}
+
+/// Per-host outbound HTTP calls of a request, from the logs of HTTP clients.
+#[derive(Debug, Default)]
+pub struct HttpInfo {
+    pub hosts: HashMap<String, HostTiming>,
+    /// Host of the last call, which a following `Benchmark` line times
+    last_host: Option<String>,
+}
+
+impl HttpInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a line logging an outbound call, or the time of the previous
+    /// one. Returns false for other lines.
+    pub fn record(&mut self, message: &str) -> bool {
+        if let Some(call) = parse_http_call(message) {
+            let timing = self.hosts.entry(call.host.to_string()).or_default();
+            timing.count += 1;
+            if let Some(ms) = call.ms {
+                timing.timed += 1;
+                timing.total_ms += ms;
+            }
+            self.last_host = Some(call.host.to_string());
+            return true;
+        }
+        let Some(caps) = RE_BENCHMARK.captures(message) else {
+            return false;
+        };
+        let Some(timing) = self
+            .last_host
+            .take()
+            .and_then(|host| self.hosts.get_mut(&host))
+        else {
+            return false;
+        };
+        timing.timed += 1;
+        timing.total_ms += caps["seconds"].parse::<f64>().unwrap_or_default() * 1000.0;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    pub fn total_calls(&self) -> usize {
+        self.hosts.values().map(|timing| timing.count).sum()
+    }
+
+    /// Hosts by total time, slowest first, then by number of calls.
+    pub fn by_total_time(&self) -> Vec<(&String, &HostTiming)> {
+        let mut hosts: Vec<_> = self.hosts.iter().collect();
+        hosts.sort_by(|a, b| {
+            b.1.total_ms
+                .total_cmp(&a.1.total_ms)
+                .then(b.1.count.cmp(&a.1.count))
+                .then(a.0.cmp(b.0))
+        });
+        hosts
+    }
+
+    pub fn display_line_count(&self) -> usize {
+        if self.hosts.is_empty() {
+            0
+        } else {
+            // blank line(1) + heading(1) + host rows
+            2 + self.hosts.len()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_call() {
+        let call = |message| parse_http_call(message).map(|call| (call.host, call.ms));
+        assert_eq!(
+            call(
+                "[r1] I, [2024-05-01T12:00:00.123 #1]  INFO -- request: GET https://api.example.com/v1/users?page=2"
+            ),
+            Some(("api.example.com", None))
+        );
+        assert_eq!(
+            call(
+                r#"[r1] [HTTParty] [2024-05-01 12:00:00 +0900] 200 "GET https://api.github.com/users" 512"#
+            ),
+            Some(("api.github.com", None))
+        );
+        assert_eq!(
+            call("[r1] [httplog] Sending: POST http://localhost:8080/charges"),
+            Some(("localhost", None))
+        );
+        assert_eq!(
+            call("[r1] POST https://api.stripe.com/v1/charges (200) 184.2ms"),
+            Some(("api.stripe.com", Some(184.2)))
+        );
+        assert_eq!(
+            call("[r1] opening connection to s3.amazonaws.com:443..."),
+            Some(("s3.amazonaws.com", None))
+        );
+        // Requests to the app itself, and redirects
+        assert_eq!(call(r#"[r1] Started GET "/posts" for 127.0.0.1"#), None);
+        assert_eq!(call("[r1] Redirected to https://example.com/login"), None);
+    }
+
+    #[test]
+    fn test_record() {
+        let mut info = HttpInfo::new();
+        for message in [
+            "[httplog] Sending: GET https://api.example.com/users/1",
+            "[httplog] Status: 200",
+            "[httplog] Benchmark: 0.120 seconds",
+            "[httplog] Sending: GET https://api.example.com/users/2",
+            "[httplog] Benchmark: 0.080 seconds",
+            "POST https://hooks.slack.com/services/T0 (200) 40ms",
+        ] {
+            info.record(message);
+        }
+        assert!(!info.record("[r1] Completed 200 OK in 400ms"));
+
+        let api = info.hosts["api.example.com"];
+        assert_eq!((api.count, api.timed), (2, 2));
+        assert!((api.total_ms - 200.0).abs() < 1e-9);
+        assert_eq!(info.total_calls(), 3);
+        let order: Vec<&str> = info
+            .by_total_time()
+            .iter()
+            .map(|(host, _)| host.as_str())
+            .collect();
+        assert_eq!(order, ["api.example.com", "hooks.slack.com"]);
+        assert_eq!(info.display_line_count(), 2 + 2);
+        assert_eq!(HttpInfo::new().display_line_count(), 0);
+        // A benchmark without a call before it
+        assert!(!HttpInfo::new().record("[httplog] Benchmark: 1.0 seconds"));
+    }
+}
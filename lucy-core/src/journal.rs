@@ -0,0 +1,108 @@
+use crate::app_state::LogEntry;
+use crate::error::{Error, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+/// Location of one entry in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalRef {
+    offset: u64,
+    len: u32,
+}
+
+/// Append-only copy of every parsed entry, so lines dropped from memory by
+/// compaction can be read back.
+///
+/// Each entry is written as `<rfc3339 timestamp> <message>`, which
+/// `log_parser::parse` reads back with the original timestamp.
+pub struct Journal {
+    writer: BufWriter<File>,
+    len: u64,
+}
+
+impl Journal {
+    /// Creates the journal, truncating any previous session's file.
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|source| Error::Journal {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            len: 0,
+        })
+    }
+
+    pub fn append<R>(&mut self, entry: &LogEntry<R>) -> io::Result<JournalRef> {
+        let mut record = format!("{} {}", entry.timestamp.to_rfc3339(), entry.message);
+        if !record.ends_with('\n') {
+            record.push('\n');
+        }
+        self.writer.write_all(record.as_bytes())?;
+
+        let journal_ref = JournalRef {
+            offset: self.len,
+            len: record.len() as u32,
+        };
+        self.len += record.len() as u64;
+        Ok(journal_ref)
+    }
+
+    pub fn read(&mut self, journal_ref: JournalRef) -> io::Result<String> {
+        self.writer.flush()?;
+        let mut buffer = vec![0; journal_ref.len as usize];
+        self.writer
+            .get_ref()
+            .read_exact_at(&mut buffer, journal_ref.offset)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_parser;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("lucy-journal-{}.log", std::process::id()));
+        let mut journal = Journal::create(&path).unwrap();
+
+        let entries: Vec<LogEntry> = ["[req-1] Started GET /\n", "[req-1] Completed 200 OK in 5ms"]
+            .iter()
+            .map(|message| LogEntry {
+                request_id: "req-1".to_string(),
+                message: message.to_string(),
+                timestamp: DateTime::parse_from_rfc3339("2024-05-01T12:00:00+09:00").unwrap(),
+                ..Default::default()
+            })
+            .collect();
+        let refs: Vec<JournalRef> = entries
+            .iter()
+            .map(|entry| journal.append(entry).unwrap())
+            .collect();
+
+        let line = journal.read(refs[1]).unwrap();
+        let restored = log_parser::parse(&line, None).unwrap();
+        assert_eq!(restored.request_id, "req-1");
+        assert_eq!(restored.message.trim_end(), entries[1].message);
+        assert_eq!(restored.timestamp, entries[1].timestamp);
+
+        let line = journal.read(refs[0]).unwrap();
+        assert_eq!(
+            log_parser::parse(&line, None).unwrap().message,
+            entries[0].message
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
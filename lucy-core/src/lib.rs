@@ -0,0 +1,52 @@
+//! The Rails log model behind lucy, without the terminal UI: reading lines
+//! into the request, job or ActionCable connection they belong to, what
+//! each line says about SQL, caching, rendering, HTTP calls and memory, and
+//! which of the groups are kept.
+//!
+//! ```
+//! use lucy_core::{log_parser, sql_info::SqlQueryInfo};
+//!
+//! let line = log_parser::parse(r#"[req-1]   Post Load (0.5ms)  SELECT "posts".* FROM "posts""#, None).unwrap();
+//! assert_eq!(line.request_id, "req-1");
+//! let sql = SqlQueryInfo::from_message(&line.message).unwrap();
+//! assert_eq!(sql.total_queries(), 1);
+//! ```
+//!
+//! `app_state::AppState` groups the lines and applies retention:
+//!
+//! ```
+//! use lucy_core::{app_state::AppState, log_parser};
+//!
+//! let mut state: AppState = AppState::new();
+//! for line in [
+//!     r#"[req-1] Started GET "/posts" for 127.0.0.1"#,
+//!     "[req-1] Completed 200 OK in 12ms",
+//! ] {
+//!     state.add_log_entry(log_parser::parse(line, None).unwrap().into());
+//! }
+//! let group = &state.logs_by_request_id["req-1"];
+//! assert!(group.finished);
+//! assert_eq!(group.duration_ms, Some(12));
+//! ```
+
+pub mod app_state;
+pub mod cable;
+pub mod cache_info;
+mod error;
+pub mod http_info;
+pub mod journal;
+pub mod log_parser;
+pub mod memory_info;
+pub mod model;
+pub mod render_info;
+pub mod retention;
+pub mod sampling;
+pub mod sql_info;
+pub mod stats;
+pub mod status;
+pub mod timeline;
+pub mod timestamp;
+pub mod title;
+pub mod verbosity;
+
+pub use error::{Error, Result};
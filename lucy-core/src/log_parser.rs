@@ -0,0 +1,531 @@
+//! Reading a log line: the group it belongs to, when it was written, and
+//! the job events and log levels it carries.
+
+use crate::cable;
+use crate::model::{GroupKind, JobEvent, JobOutcome, ParsedLine, StatusType};
+use crate::timestamp::Timestamps;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone};
+use crate::error::{self, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static ANSI_ESCAPE_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;]*[mK]").expect("Invalid ANSI escape sequence regex"));
+
+pub static RE_COMPLETED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Completed (?P<status>\d+) .+ in (?P<duration>\d+)ms").unwrap()
+});
+
+static RE_ACTIVE_JOB: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<event>Performing|Performed|Error performing) (?P<class>[\w:]+) ").unwrap()
+});
+
+static RE_ACTIVE_JOB_DURATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" in (?P<duration>[\d.]+)ms\b").unwrap());
+
+static RE_SIDEKIQ: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\bclass=(?P<class>[\w:]+) jid=(?P<jid>\w+)(?:.*?\belapsed=(?P<elapsed>[\d.]+))?.*?\b\w+: (?P<event>start|done|fail)\b",
+    )
+    .unwrap()
+});
+
+static RE_ERROR_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:ERROR|FATAL|[A-Z]\w*(?:Error|Exception))\b|Error performing").unwrap()
+});
+
+static RE_LEVEL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\b(?P<token>FATAL|CRITICAL|ERROR|WARN|WARNING|INFO|DEBUG|TRACE)\b|\blevel[=:]\s*"?(?P<field>\w+)"#,
+    )
+    .unwrap()
+});
+
+/// Key used by `--generic` when no `group_key_regex` is configured.
+pub const DEFAULT_GENERIC_GROUP_KEY: &str =
+    r#"\b(?:request_id|trace_id|correlation_id|req_id)[=:]\s*"?(?P<key>[\w.-]+)"#;
+
+// Prefix written by `kubectl logs --timestamps`, `docker logs -t` and similar tools
+static RE_LEADING_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?P<ts>\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?P<zone>Z|[+-]\d{2}:?\d{2})?)\s+",
+    )
+    .unwrap()
+});
+
+/// Compiles a user supplied grouping key. The first capture group (or one
+/// named `key`) defines which group a line belongs to.
+pub fn compile_group_key(pattern: &str) -> Result<Regex> {
+    error::compile_with_capture("group key regex", pattern)
+}
+
+fn extract_group_key(line: &str, group_key: &Regex) -> Option<String> {
+    let caps = group_key.captures(line)?;
+    let key = caps.name("key").or_else(|| caps.get(1))?.as_str().trim();
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// Lines matching `group_key` are grouped by its capture instead of the
+/// built-in request and job id detection.
+pub fn parse(line: &str, group_key: Option<&Regex>) -> Option<ParsedLine> {
+    parse_with(line, group_key, &Timestamps::default())
+}
+
+/// `parse`, reading when the line was written with `timestamps`.
+pub fn parse_with(
+    line: &str,
+    group_key: Option<&Regex>,
+    timestamps: &Timestamps,
+) -> Option<ParsedLine> {
+    if line.trim_start().is_empty() {
+        return None;
+    }
+
+    let (timestamp, line) = timestamps.split(line);
+
+    let custom_key =
+        group_key.and_then(|regex| extract_group_key(&strip_ansi_for_parsing(line), regex));
+
+    let trimmed = line.trim_start();
+    let (request_id, kind) = if let Some(key) = custom_key {
+        (key, GroupKind::Request)
+    } else if trimmed.starts_with('[') || line.contains("jid=") {
+        let cleaned = strip_ansi_for_parsing(line);
+        match extract_job_id(&cleaned) {
+            Some(job_id) => (job_id, GroupKind::Job),
+            None if cable::parse_event(&cleaned).is_some() => {
+                (cable::connection_id(&cleaned), GroupKind::Cable)
+            }
+            None => (
+                extract_request_id(&cleaned).unwrap_or_default(),
+                GroupKind::Request,
+            ),
+        }
+    } else if cable::parse_event(&strip_ansi_for_parsing(line)).is_some() {
+        (cable::CABLE_ID.to_string(), GroupKind::Cable)
+    } else {
+        (String::new(), GroupKind::Request)
+    };
+
+    Some(ParsedLine {
+        request_id,
+        timestamp,
+        message: close_ansi(line),
+        kind,
+    })
+}
+
+/// Parses a line for `--generic`, where nothing Rails specific is assumed:
+/// lines are grouped only by `group_key`.
+pub fn parse_generic(line: &str, group_key: &Regex, timestamps: &Timestamps) -> Option<ParsedLine> {
+    if line.trim_start().is_empty() {
+        return None;
+    }
+
+    let (timestamp, line) = timestamps.split(line);
+
+    Some(ParsedLine {
+        request_id: extract_group_key(&strip_ansi_for_parsing(line), group_key).unwrap_or_default(),
+        timestamp,
+        message: close_ansi(line),
+        kind: GroupKind::Generic,
+    })
+}
+
+/// Status implied by a log level token (`ERROR`, `WARN`, `INFO`, ...) or a
+/// `level=` field.
+pub fn detect_level(message: &str) -> Option<StatusType> {
+    let caps = RE_LEVEL.captures(message)?;
+    let level = caps
+        .name("token")
+        .or_else(|| caps.name("field"))?
+        .as_str()
+        .to_ascii_lowercase();
+    match level.as_str() {
+        "fatal" | "critical" | "error" => Some(StatusType::Error),
+        "warn" | "warning" => Some(StatusType::Warning),
+        "info" | "debug" | "trace" | "notice" => Some(StatusType::Success),
+        _ => None,
+    }
+}
+
+/// Error level lines and lines naming an exception class.
+pub fn is_error_line(message: &str) -> bool {
+    RE_ERROR_LINE.is_match(&strip_ansi_for_parsing(message))
+}
+
+/// Lines a Rails server prints as it boots, so a restart can be told apart
+/// from the requests around it:
+///
+/// ```text
+/// => Booting Puma
+/// * Listening on http://127.0.0.1:3000
+/// ```
+///
+/// Found anywhere in the line, as `bin/dev` prefixes it with the process name.
+pub fn is_boot_marker(message: &str) -> bool {
+    let message = strip_ansi_for_parsing(message);
+    ["=> Booting ", "Booting Puma", "* Listening on "]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+pub fn strip_ansi_for_parsing(text: &str) -> String {
+    if !text.contains("\x1b[") {
+        return text.to_string();
+    }
+    ANSI_ESCAPE_PATTERN.replace_all(text, "").to_string()
+}
+
+/// `text` with a reset appended when it sets a color or style it does not
+/// reset, so a message cut off mid-color does not tint the ones after it.
+pub fn close_ansi(text: &str) -> String {
+    if !text.contains("\x1b[") {
+        return text.to_string();
+    }
+    let mut open = false;
+    for sequence in ANSI_ESCAPE_PATTERN.find_iter(text) {
+        let Some(params) = sequence.as_str().strip_suffix('m') else {
+            continue;
+        };
+        // `ESC[m` and `ESC[0m` reset everything, `ESC[0;31m` then sets red
+        let mut params = params[2..].split(';');
+        while let Some(param) = params.next() {
+            open = !matches!(param, "" | "0");
+            // The color of `38;5;n` and `38;2;r;g;b` may end in a 0 that resets nothing
+            if matches!(param, "38" | "48") {
+                let components = match params.next() {
+                    Some("5") => 1,
+                    Some("2") => 3,
+                    _ => 0,
+                };
+                for _ in 0..components {
+                    params.next();
+                }
+            }
+        }
+    }
+    if open {
+        format!("{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Returns the timestamp and the byte length of the prefix it occupied.
+/// Timestamps without an offset are taken to be in the local zone.
+pub fn extract_leading_timestamp(line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+    let caps = RE_LEADING_TIMESTAMP.captures(line)?;
+    let prefix_len = caps.get(0)?.end();
+    let raw = caps.name("ts")?.as_str().replacen(' ', "T", 1);
+
+    let timestamp = match caps.name("zone").map(|m| m.as_str()) {
+        Some("Z") => DateTime::parse_from_str(
+            &raw.replace('Z', "+00:00"),
+            "%Y-%m-%dT%H:%M:%S%.f%#z",
+        )
+        .ok()?,
+        Some(_) => DateTime::parse_from_str(&raw, "%Y-%m-%dT%H:%M:%S%.f%#z").ok()?,
+        None => {
+            let naive = NaiveDateTime::parse_from_str(&raw, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+            Local.from_local_datetime(&naive).earliest()?.fixed_offset()
+        }
+    };
+
+    Some((timestamp, prefix_len))
+}
+
+/// Job id of an ActiveJob line tagged `[ActiveJob] [JobClass] [job-id]`
+/// or of a Sidekiq line carrying `class=... jid=...`.
+fn extract_job_id(line: &str) -> Option<String> {
+    let tags = leading_tags(line);
+    if tags.len() >= 3 && tags[0] == "ActiveJob" {
+        return Some(tags[2].to_string());
+    }
+    RE_SIDEKIQ
+        .captures(line)
+        .and_then(|caps| caps.name("jid"))
+        .map(|jid| jid.as_str().to_string())
+}
+
+/// The `[tag]`s a line starts with, as Rails' tagged logging writes them.
+pub fn leading_tags(line: &str) -> Vec<&str> {
+    let mut tags = Vec::new();
+    let mut rest = line.trim_start();
+    while let Some(after_bracket) = rest.strip_prefix('[') {
+        let Some(end) = after_bracket.find(']') else {
+            break;
+        };
+        tags.push(after_bracket[..end].trim());
+        rest = after_bracket[end + 1..].trim_start();
+    }
+    tags
+}
+
+/// `message` without its leading `[tag]`s.
+pub fn strip_tags(message: &str) -> &str {
+    let mut rest = message.trim_start();
+    while let Some(after_bracket) = rest.strip_prefix('[') {
+        let Some(end) = after_bracket.find(']') else {
+            break;
+        };
+        rest = after_bracket[end + 1..].trim_start();
+    }
+    rest
+}
+
+pub fn parse_job_event(message: &str) -> Option<JobEvent> {
+    let message = strip_ansi_for_parsing(message);
+
+    if let Some(caps) = RE_ACTIVE_JOB.captures(&message) {
+        let outcome = match &caps["event"] {
+            "Performing" => JobOutcome::Started,
+            "Performed" => JobOutcome::Succeeded,
+            _ => JobOutcome::Failed,
+        };
+        return Some(JobEvent {
+            job_class: caps["class"].to_string(),
+            outcome,
+            duration_ms: RE_ACTIVE_JOB_DURATION
+                .captures(&message)
+                .and_then(|duration| duration["duration"].parse::<f64>().ok())
+                .map(|ms| ms.round() as u64),
+        });
+    }
+
+    let caps = RE_SIDEKIQ.captures(&message)?;
+    let outcome = match &caps["event"] {
+        "start" => JobOutcome::Started,
+        "done" => JobOutcome::Succeeded,
+        _ => JobOutcome::Failed,
+    };
+    Some(JobEvent {
+        job_class: caps["class"].to_string(),
+        outcome,
+        duration_ms: caps
+            .name("elapsed")
+            .and_then(|sec| sec.as_str().parse::<f64>().ok())
+            .map(|sec| (sec * 1000.0).round() as u64),
+    })
+}
+
+fn extract_request_id(line: &str) -> Option<String> {
+    if !line.starts_with('[') {
+        return None;
+    }
+
+    let end = line.find(']')?;
+    let request_id = line[1..end].trim();
+
+    if !request_id.is_empty() {
+        Some(request_id.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_for_parsing() {
+        // Test with no ANSI codes
+        let text = "This is plain text";
+        assert_eq!(strip_ansi_for_parsing(text), text);
+
+        // Test with ANSI codes
+        let text_with_ansi = "\x1b[31mThis is red text\x1b[0m";
+        assert_eq!(strip_ansi_for_parsing(text_with_ansi), "This is red text");
+
+        // Test with multiple ANSI codes
+        let complex_ansi = "\x1b[1m\x1b[32mBold green\x1b[0m and \x1b[36mcyan\x1b[0m";
+        assert_eq!(strip_ansi_for_parsing(complex_ansi), "Bold green and cyan");
+    }
+
+    #[test]
+    fn test_close_ansi() {
+        // Closed or without color, the line is kept as is
+        for text in [
+            "plain",
+            "\x1b[31mred\x1b[0m",
+            "\x1b[1m\x1b[32mbold green\x1b[m",
+            "\x1b[31mred\x1b[0m\x1b[K",
+        ] {
+            assert_eq!(close_ansi(text), text);
+        }
+        assert_eq!(close_ansi("\x1b[31mred"), "\x1b[31mred\x1b[0m");
+        assert_eq!(close_ansi("\x1b[38;2;255;128;0morange"), "\x1b[38;2;255;128;0morange\x1b[0m");
+        assert_eq!(close_ansi("\x1b[38;5;0mblack"), "\x1b[38;5;0mblack\x1b[0m");
+        assert_eq!(close_ansi("\x1b[48;5;0;0mreset"), "\x1b[48;5;0;0mreset");
+        assert_eq!(close_ansi("\x1b[1mbold\x1b[0;36m cyan"), "\x1b[1mbold\x1b[0;36m cyan\x1b[0m");
+
+        let entry = parse("[req-1] \x1b[35mStarted GET \"/\"", None).unwrap();
+        assert_eq!(entry.request_id, "req-1");
+        assert!(entry.message.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn test_extract_request_id() {
+        // Valid request ID
+        let line = "[abc-123] Some log message";
+        assert_eq!(extract_request_id(line), Some("abc-123".to_string()));
+
+        // No request ID
+        let line_without_id = "Some log message";
+        assert_eq!(extract_request_id(line_without_id), None);
+
+        // Empty brackets
+        let empty_brackets = "[] Some log message";
+        assert_eq!(extract_request_id(empty_brackets), None);
+
+        // Only whitespace in brackets
+        let whitespace_brackets = "[   ] Some log message";
+        assert_eq!(extract_request_id(whitespace_brackets), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        // Normal log line with request ID
+        let line = "[req-123] Started GET /test";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "req-123");
+        assert_eq!(entry.message, line);
+
+        // Log line with ANSI codes
+        let ansi_line = "\x1b[32m[req-456]\x1b[0m Processing data";
+        let entry = parse(ansi_line, None).unwrap();
+        // The ANSI codes affect how the request ID is extracted
+        // So we won't assert on the exact request ID here
+        assert_eq!(entry.message, ansi_line);
+
+        // Empty line
+        assert!(parse("", None).is_none());
+        assert!(parse("   ", None).is_none());
+
+        // Line without request ID
+        let no_id_line = "Log message without request ID";
+        let entry = parse(no_id_line, None).unwrap();
+        assert_eq!(entry.request_id, "");
+        assert_eq!(entry.message, no_id_line);
+    }
+
+    #[test]
+    fn test_parse_group_key() {
+        let group_key = compile_group_key(r"request_id=(\w+)").unwrap();
+        let line = "method=GET path=/ request_id=abc123 status=200";
+        let entry = parse(line, Some(&group_key)).unwrap();
+        assert_eq!(entry.request_id, "abc123");
+
+        // Lines the regex doesn't match fall back to the built-in detection
+        let entry = parse("[req-1] Started GET /", Some(&group_key)).unwrap();
+        assert_eq!(entry.request_id, "req-1");
+
+        let named = compile_group_key(r"(trace|span)=(?P<key>[\w-]+)").unwrap();
+        let entry = parse("span=s-1 done", Some(&named)).unwrap();
+        assert_eq!(entry.request_id, "s-1");
+
+        assert!(compile_group_key(r"request_id=\w+").is_err());
+        assert!(compile_group_key(r"(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_parse_generic() {
+        let group_key = compile_group_key(DEFAULT_GENERIC_GROUP_KEY).unwrap();
+        let timestamps = Timestamps::default();
+        let entry =
+            parse_generic("[req-1] Started GET \"/\" trace_id=t-1", &group_key, &timestamps).unwrap();
+        assert_eq!(entry.request_id, "t-1");
+        assert_eq!(entry.kind, GroupKind::Generic);
+
+        // No Rails request id detection
+        let entry = parse_generic("[req-1] Completed 200 OK in 5ms", &group_key, &timestamps).unwrap();
+        assert_eq!(entry.request_id, "");
+    }
+
+    #[test]
+    fn test_detect_level() {
+        assert_eq!(detect_level("2024-05-01 ERROR db down"), Some(StatusType::Error));
+        assert_eq!(detect_level("[WARN] slow"), Some(StatusType::Warning));
+        assert_eq!(detect_level("level=info msg=ok"), Some(StatusType::Success));
+        assert_eq!(detect_level(r#"{"level": "fatal"}"#), None);
+        assert_eq!(detect_level(r#"level:"warning""#), Some(StatusType::Warning));
+        // Prose is not a level
+        assert_eq!(detect_level("retrying after error"), None);
+    }
+
+    #[test]
+    fn test_is_error_line() {
+        assert!(is_error_line("[req-1] ActionController::RoutingError (No route matches [GET] \"/x\"):"));
+        assert!(is_error_line("[req-1] NoMethodError (undefined method `name' for nil):"));
+        assert!(is_error_line("E, [2024-05-01T12:00:00] ERROR -- : boom"));
+        assert!(!is_error_line("[req-1] Rendered users/show.html.erb (Duration: 1.2ms)"));
+        assert!(!is_error_line("[req-1] Parameters: {\"errors\"=>\"none\"}"));
+    }
+
+    #[test]
+    fn test_is_boot_marker() {
+        assert!(is_boot_marker("=> Booting Puma"));
+        assert!(is_boot_marker("web.1  | * Listening on http://127.0.0.1:3000"));
+        assert!(is_boot_marker("\x1b[1m* Listening on tcp://0.0.0.0:3000\x1b[0m"));
+        assert!(!is_boot_marker("[req-1] Started GET \"/listening\" for 127.0.0.1"));
+    }
+
+    #[test]
+    fn test_parse_leading_timestamp() {
+        let line = "2024-05-01T03:00:00.123456789Z [req-1] Started GET /test";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "req-1");
+        assert_eq!(entry.message, "[req-1] Started GET /test");
+        assert_eq!(
+            entry.timestamp.to_rfc3339(),
+            "2024-05-01T03:00:00.123456789+00:00"
+        );
+
+        // The original offset is kept
+        let line = "2024-05-01 12:00:00+0900 [req-2] Completed 200 OK in 5ms";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "req-2");
+        assert_eq!(entry.timestamp.to_rfc3339(), "2024-05-01T12:00:00+09:00");
+
+        // Not a timestamp prefix
+        assert!(extract_leading_timestamp("2024-05-01 is a date").is_none());
+    }
+
+    #[test]
+    fn test_parse_job_lines() {
+        let line = "[ActiveJob] [SendEmailJob] [3f2a-11] Performing SendEmailJob (Job ID: 3f2a-11) from Async(default)";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "3f2a-11");
+        assert_eq!(entry.kind, GroupKind::Job);
+
+        // A job enqueued from a request stays with the request
+        let line = "[req-1] [ActiveJob] Enqueued SendEmailJob (Job ID: 3f2a-11) to Async(default)";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "req-1");
+        assert_eq!(entry.kind, GroupKind::Request);
+
+        let line = "pid=1 tid=gk class=HardJob jid=b4a577edbccf1d805744efa9 elapsed=0.25 INFO: done";
+        let entry = parse(line, None).unwrap();
+        assert_eq!(entry.request_id, "b4a577edbccf1d805744efa9");
+        assert_eq!(entry.kind, GroupKind::Job);
+    }
+
+    #[test]
+    fn test_parse_job_event() {
+        let event = parse_job_event(
+            "[ActiveJob] [SendEmailJob] [1] Error performing SendEmailJob (Job ID: 1) from Async(default) in 3.2ms: RuntimeError (boom)",
+        )
+        .unwrap();
+        assert_eq!(event.job_class, "SendEmailJob");
+        assert_eq!(event.outcome, JobOutcome::Failed);
+        assert_eq!(event.duration_ms, Some(3));
+
+        let event =
+            parse_job_event("class=HardJob jid=abc elapsed=0.25 INFO: done").unwrap();
+        assert_eq!(event.outcome, JobOutcome::Succeeded);
+        assert_eq!(event.duration_ms, Some(250));
+
+        assert!(parse_job_event("Started GET /posts").is_none());
+    }
+}
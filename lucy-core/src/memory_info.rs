@@ -0,0 +1,203 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+// Completed 200 OK in 42ms (Views: 14.8ms | ActiveRecord: 1.3ms | GC: 0.5ms | Allocations: 5120)
+static RE_ALLOCATIONS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bAllocations: (?P<count>\d+)").unwrap());
+static RE_GC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bGC: (?P<ms>\d+(?:\.\d+)?)ms").unwrap());
+
+// Memory usage: 316516 | PID: 19913   (oink, in KB)
+static RE_MEMORY_USAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\bMemory usage: (?P<value>\d+(?:\.\d+)?)(?: ?(?P<unit>[KMG]B))?(?: \| PID: (?P<pid>\d+))?",
+    )
+    .unwrap()
+});
+
+// Instantiation Breakdown: Total: 73 | User: 34 | Post: 39   (oink)
+static RE_INSTANTIATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bInstantiation Breakdown: Total: (?P<count>\d+)").unwrap());
+
+// Total allocated: 1234567 bytes (12345 objects)   (memory_profiler, rack-mini-profiler)
+static RE_TOTAL_ALLOCATED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bTotal allocated: (?P<bytes>\d+) bytes \((?P<objects>\d+) objects\)").unwrap()
+});
+
+// Memory delta: +12.3 MB / mem_delta=-512KB
+static RE_DELTA: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\bmem(?:ory)?[ _]delta[:=] ?(?P<value>[+-]?\d+(?:\.\d+)?) ?(?P<unit>[KMG]?B)\b",
+    )
+    .unwrap()
+});
+
+/// Allocation and memory figures of a request, from Rails' Completed line
+/// and from memory instrumentation such as oink when it is installed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryInfo {
+    /// Objects allocated while serving the request
+    pub allocations: Option<u64>,
+    /// Bytes allocated while serving the request
+    pub allocated_bytes: Option<u64>,
+    /// Time spent in GC (Rails 7.2+)
+    pub gc_ms: Option<f64>,
+    /// Process memory after the request, in bytes
+    pub memory_bytes: Option<u64>,
+    pub pid: Option<u32>,
+    /// How much process memory grew over the request, in bytes
+    pub delta_bytes: Option<i64>,
+    /// ActiveRecord objects instantiated
+    pub instantiated: Option<u64>,
+    /// First process memory the request logged, to derive the delta from
+    /// when the instrumentation logs it more than once
+    first_memory_bytes: Option<u64>,
+}
+
+impl MemoryInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the figures `message` logs. Returns false for other lines.
+    pub fn record(&mut self, message: &str) -> bool {
+        if message.contains("Completed ") {
+            return self.record_completed(message);
+        }
+        if let Some(caps) = RE_MEMORY_USAGE.captures(message) {
+            let value = caps["value"].parse::<f64>().unwrap_or_default();
+            // oink logs KB without a unit
+            let bytes = to_bytes(value, caps.name("unit").map_or("KB", |unit| unit.as_str()));
+            let bytes = bytes as u64;
+            let first = *self.first_memory_bytes.get_or_insert(bytes);
+            if first != bytes {
+                self.delta_bytes = Some(bytes as i64 - first as i64);
+            }
+            self.memory_bytes = Some(bytes);
+            self.pid = caps.name("pid").and_then(|pid| pid.as_str().parse().ok());
+            return true;
+        }
+        if let Some(caps) = RE_INSTANTIATION.captures(message) {
+            self.instantiated = caps["count"].parse().ok();
+            return true;
+        }
+        if let Some(caps) = RE_TOTAL_ALLOCATED.captures(message) {
+            self.allocated_bytes = caps["bytes"].parse().ok();
+            self.allocations = caps["objects"].parse().ok();
+            return true;
+        }
+        if let Some(caps) = RE_DELTA.captures(message) {
+            let value = caps["value"].parse::<f64>().unwrap_or_default();
+            self.delta_bytes = Some(to_bytes(value, &caps["unit"].to_uppercase()) as i64);
+            return true;
+        }
+        false
+    }
+
+    fn record_completed(&mut self, message: &str) -> bool {
+        let allocations = RE_ALLOCATIONS
+            .captures(message)
+            .and_then(|caps| caps["count"].parse().ok());
+        let gc_ms = RE_GC
+            .captures(message)
+            .and_then(|caps| caps["ms"].parse().ok());
+        if allocations.is_none() && gc_ms.is_none() {
+            return false;
+        }
+        self.allocations = allocations.or(self.allocations);
+        self.gc_ms = gc_ms.or(self.gc_ms);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.row_count() == 0
+    }
+
+    fn row_count(&self) -> usize {
+        [
+            self.allocations.is_some(),
+            self.gc_ms.is_some(),
+            self.memory_bytes.is_some(),
+            self.delta_bytes.is_some(),
+            self.instantiated.is_some(),
+        ]
+        .into_iter()
+        .filter(|&shown| shown)
+        .count()
+    }
+
+    pub fn display_line_count(&self) -> usize {
+        match self.row_count() {
+            0 => 0,
+            // blank line(1) + heading(1) + rows
+            rows => 2 + rows,
+        }
+    }
+}
+
+fn to_bytes(value: f64, unit: &str) -> f64 {
+    let scale: f64 = match unit {
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    value * scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_completed_line() {
+        let mut info = MemoryInfo::new();
+        assert!(info.record(
+            "[r1] Completed 200 OK in 42ms (Views: 14.8ms | ActiveRecord: 1.3ms (3 queries, 0 cached) | GC: 0.5ms)"
+        ));
+        assert!(info.record(
+            "[r1] Completed 200 OK in 42ms (Views: 14.8ms | ActiveRecord: 1.3ms | Allocations: 5120)"
+        ));
+        assert_eq!(info.allocations, Some(5120));
+        assert_eq!(info.gc_ms, Some(0.5));
+        // Rails before 6 logs neither
+        assert!(!MemoryInfo::new().record("[r1] Completed 200 OK in 42ms (Views: 14.8ms)"));
+        assert_eq!(info.display_line_count(), 2 + 2);
+        assert_eq!(MemoryInfo::new().display_line_count(), 0);
+    }
+
+    #[test]
+    fn test_record_oink_lines() {
+        let mut info = MemoryInfo::new();
+        for message in [
+            "[r1] Oink Action: posts#index",
+            "[r1] Memory usage: 316516 | PID: 19913",
+            "[r1] Instantiation Breakdown: Total: 73 | User: 34 | Post: 39",
+            "[r1] Oink Log Entry Complete",
+        ] {
+            info.record(message);
+        }
+        assert_eq!(info.memory_bytes, Some(316516 * 1024));
+        assert_eq!(info.pid, Some(19913));
+        assert_eq!(info.instantiated, Some(73));
+        // A single figure has nothing to compare with
+        assert_eq!(info.delta_bytes, None);
+
+        info.record("[r1] Memory usage: 316528 | PID: 19913");
+        assert_eq!(info.delta_bytes, Some(12 * 1024));
+    }
+
+    #[test]
+    fn test_record_profiler_lines() {
+        let mut info = MemoryInfo::new();
+        assert!(info.record("Total allocated: 1234567 bytes (12345 objects)"));
+        assert!(info.record("Memory delta: +1.5 MB"));
+        assert_eq!(info.allocated_bytes, Some(1234567));
+        assert_eq!(info.allocations, Some(12345));
+        assert_eq!(info.delta_bytes, Some(1536 * 1024));
+
+        assert!(info.record("mem_delta=-512KB"));
+        assert_eq!(info.delta_bytes, Some(-512 * 1024));
+        assert!(!info.record("[r1] Started GET \"/posts\" for 127.0.0.1"));
+    }
+}
@@ -0,0 +1,76 @@
+//! What a parsed line says about the request, job or connection it belongs to.
+
+/// A line with the group it belongs to and the time it was written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedLine {
+    pub timestamp: chrono::DateTime<chrono::FixedOffset>,
+    /// Key of the group, empty for lines without a request id
+    pub request_id: String,
+    /// The line without the timestamp prefix it was read from, if any
+    pub message: String,
+    pub kind: GroupKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusType {
+    Success, // 2xx
+    /// Nothing by default; codes mapped to it in `[status]`, like a 404
+    /// that is expected
+    Info,
+    Warning, // 4xx
+    Error,   // 5xx
+    Unknown,
+}
+
+impl StatusType {
+    pub fn severity(self) -> u8 {
+        match self {
+            StatusType::Unknown => 0,
+            StatusType::Success | StatusType::Info => 1,
+            StatusType::Warning => 2,
+            StatusType::Error => 3,
+        }
+    }
+
+    /// Class of an HTTP status code, before `[status]` in the config file
+    /// remaps it.
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            200..=299 => StatusType::Success,
+            400..=499 => StatusType::Warning,
+            500..=599 => StatusType::Error,
+            _ => StatusType::Unknown,
+        }
+    }
+}
+
+/// What a group of log lines represents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupKind {
+    #[default]
+    Request,
+    /// ActiveJob / Sidekiq job, keyed by its job id.
+    Job,
+    /// ActionCable connection, keyed by its tag, or `cable::CABLE_ID` for
+    /// untagged lines.
+    Cable,
+    /// Lines of any format grouped by the key regex (`--generic`).
+    Generic,
+    /// Lines without a request id (boot messages, server output), all in one group.
+    Unassigned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+/// A job lifecycle line such as `Performed SendEmailJob ... in 12.3ms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobEvent {
+    pub job_class: String,
+    pub outcome: JobOutcome,
+    pub duration_ms: Option<u64>,
+}
@@ -0,0 +1,140 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// Rendered posts/_post.html.erb (Duration: 2.1ms | Allocations: 120)
+// Rendered layout layouts/application.html.erb (Duration: 9.8ms | Allocations: 2211)
+// Rendered collection of posts/_post.html.erb [10 times] (Duration: 4.0ms | Allocations: 900)
+// Rendered posts/index.html.erb within layouts/application (3.2ms)  -- Rails < 6
+static RE_RENDERED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"Rendered (?:layout |collection of )?(?P<template>[\w/.\-]+)(?: within [\w/.\-]+)?(?: \[(?:(?P<times>\d+) times|[^\]]*)\])? \((?:Duration: )?(?P<ms>[\d.]+)ms",
+    )
+    .unwrap()
+});
+
+/// One `Rendered` line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rendered<'a> {
+    pub template: &'a str,
+    /// How many times a collection rendered the template
+    pub times: usize,
+    pub ms: f64,
+}
+
+pub fn parse_rendered(message: &str) -> Option<Rendered<'_>> {
+    if !message.contains("Rendered ") {
+        return None;
+    }
+    let caps = RE_RENDERED.captures(message)?;
+    let ms = caps["ms"].parse::<f64>().ok()?;
+    let times = caps
+        .name("times")
+        .and_then(|times| times.as_str().parse().ok())
+        .unwrap_or(1);
+    Some(Rendered {
+        template: caps.name("template")?.as_str(),
+        times,
+        ms,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TemplateTiming {
+    pub count: usize,
+    pub total_ms: f64,
+}
+
+/// Per-template render timings of a request, from `Rendered ...` lines.
+/// Nested partials are also included in their parent's time.
+#[derive(Debug, Default)]
+pub struct RenderInfo {
+    pub templates: HashMap<String, TemplateTiming>,
+}
+
+impl RenderInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a `Rendered` line. Returns false for other lines.
+    pub fn record(&mut self, message: &str) -> bool {
+        let Some(rendered) = parse_rendered(message) else {
+            return false;
+        };
+        let timing = self
+            .templates
+            .entry(rendered.template.to_string())
+            .or_default();
+        timing.count += rendered.times;
+        timing.total_ms += rendered.ms;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Templates by total time, slowest first.
+    pub fn by_total_time(&self) -> Vec<(&String, &TemplateTiming)> {
+        let mut templates: Vec<_> = self.templates.iter().collect();
+        templates.sort_by(|a, b| b.1.total_ms.total_cmp(&a.1.total_ms).then(a.0.cmp(b.0)));
+        templates
+    }
+
+    pub fn display_line_count(&self) -> usize {
+        if self.templates.is_empty() {
+            0
+        } else {
+            // blank line(1) + heading(1) + template rows
+            2 + self.templates.len()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_rendered_lines() {
+        let mut info = RenderInfo::new();
+        for message in [
+            "[req-1]   Rendered posts/_post.html.erb (Duration: 2.1ms | Allocations: 120)",
+            "[req-1]   Rendered posts/_post.html.erb (Duration: 1.9ms | Allocations: 118)",
+            "[req-1]   Rendered collection of comments/_comment.html.erb [10 times] (Duration: 4.0ms | Allocations: 900)",
+            "[req-1]   Rendered posts/index.html.erb within layouts/application (Duration: 12.5ms | Allocations: 3000)",
+            "[req-1]   Rendered layout layouts/application.html.erb (Duration: 20.0ms | Allocations: 5000)",
+            "[req-1]   Rendered shared/_nav.html.erb (0.8ms)",
+        ] {
+            assert!(info.record(message), "{}", message);
+        }
+        assert!(!info.record("[req-1] Completed 200 OK in 25ms (Views: 20.0ms)"));
+
+        let post = info.templates["posts/_post.html.erb"];
+        assert_eq!(post.count, 2);
+        assert!((post.total_ms - 4.0).abs() < 1e-9);
+        assert_eq!(info.templates["comments/_comment.html.erb"].count, 10);
+        assert!(info.templates.contains_key("layouts/application.html.erb"));
+        assert!(info.templates.contains_key("shared/_nav.html.erb"));
+
+        let order: Vec<&str> = info
+            .by_total_time()
+            .iter()
+            .map(|(template, _)| template.as_str())
+            .collect();
+        assert_eq!(order[0], "layouts/application.html.erb");
+        assert_eq!(order[1], "posts/index.html.erb");
+        assert_eq!(info.display_line_count(), 2 + 5);
+    }
+
+    #[test]
+    fn test_collection_with_cache_hits() {
+        let mut info = RenderInfo::new();
+        assert!(info.record(
+            "Rendered collection of posts/_post.html.erb [3 / 10 cache hits] (Duration: 5.0ms | Allocations: 10)"
+        ));
+        assert_eq!(info.templates["posts/_post.html.erb"].count, 1);
+        assert_eq!(RenderInfo::new().display_line_count(), 0);
+    }
+}
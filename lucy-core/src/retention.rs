@@ -0,0 +1,66 @@
+//! `[retention]`: how many finished requests of each status class are kept,
+//! and for how long.
+
+use crate::model::StatusType;
+use serde::Deserialize;
+
+/// Retention rule for finished requests of one status class.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RetentionRule {
+    /// Keep only the latest `max_count` requests of this class.
+    pub max_count: Option<usize>,
+    /// Drop requests older than this, relative to the newest log line.
+    pub max_age_minutes: Option<u64>,
+    /// Never evict, even when `max_requests` is exceeded.
+    pub forever: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Overall cap on the number of requests kept in memory.
+    pub max_requests: usize,
+    /// 2xx
+    pub success: RetentionRule,
+    /// 4xx
+    pub warning: RetentionRule,
+    /// 5xx
+    pub error: RetentionRule,
+    /// Codes mapped to info in `[status]`
+    pub info: RetentionRule,
+    /// Any other status
+    pub unknown: RetentionRule,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_requests: 10_000,
+            success: RetentionRule::default(),
+            warning: RetentionRule::default(),
+            error: RetentionRule::default(),
+            info: RetentionRule::default(),
+            unknown: RetentionRule::default(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn rule(&self, status_type: StatusType) -> &RetentionRule {
+        match status_type {
+            StatusType::Success => &self.success,
+            StatusType::Warning => &self.warning,
+            StatusType::Error => &self.error,
+            StatusType::Info => &self.info,
+            StatusType::Unknown => &self.unknown,
+        }
+    }
+
+    /// Whether any class has a count or age limit.
+    pub fn has_class_limits(&self) -> bool {
+        [&self.success, &self.warning, &self.error, &self.info, &self.unknown]
+            .iter()
+            .any(|rule| rule.max_count.is_some() || rule.max_age_minutes.is_some())
+    }
+}
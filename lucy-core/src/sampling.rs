@@ -0,0 +1,103 @@
+use crate::app_state::StatusType;
+use std::collections::{HashSet, VecDeque};
+
+/// How many dropped request ids to remember, so late lines of a dropped
+/// request don't start a new group.
+const DROPPED_ID_MEMORY: usize = 1024;
+
+/// Keeps 1 in `rate` fast successful requests; errors and slow requests are always kept.
+pub struct Sampler {
+    rate: u32,
+    slow_threshold_ms: u64,
+    seen: u64,
+    dropped: u64,
+    dropped_ids: HashSet<String>,
+    dropped_order: VecDeque<String>,
+}
+
+impl Sampler {
+    pub fn new(rate: u32, slow_threshold_ms: u64) -> Self {
+        Self {
+            rate: rate.max(1),
+            slow_threshold_ms,
+            seen: 0,
+            dropped: 0,
+            dropped_ids: HashSet::new(),
+            dropped_order: VecDeque::new(),
+        }
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Decides whether a completed request is kept, and remembers it if not.
+    pub fn keep(&mut self, request_id: &str, status: StatusType, duration_ms: Option<u64>) -> bool {
+        let sampled = matches!(status, StatusType::Success | StatusType::Unknown)
+            && duration_ms.is_none_or(|ms| ms < self.slow_threshold_ms);
+        if !sampled {
+            return true;
+        }
+
+        self.seen += 1;
+        if (self.seen - 1).is_multiple_of(self.rate as u64) {
+            return true;
+        }
+
+        self.dropped += 1;
+        if self.dropped_order.len() >= DROPPED_ID_MEMORY
+            && let Some(oldest) = self.dropped_order.pop_front()
+        {
+            self.dropped_ids.remove(&oldest);
+        }
+        self.dropped_ids.insert(request_id.to_string());
+        self.dropped_order.push_back(request_id.to_string());
+        false
+    }
+
+    pub fn was_dropped(&self, request_id: &str) -> bool {
+        self.dropped_ids.contains(request_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_one_in_n_successes() {
+        let mut sampler = Sampler::new(3, 500);
+        let kept: Vec<bool> = (0..6)
+            .map(|i| sampler.keep(&format!("req-{}", i), StatusType::Success, Some(10)))
+            .collect();
+        assert_eq!(kept, [true, false, false, true, false, false]);
+        assert_eq!(sampler.dropped(), 4);
+        assert!(sampler.was_dropped("req-1"));
+        assert!(!sampler.was_dropped("req-0"));
+    }
+
+    #[test]
+    fn test_keeps_errors_and_slow_requests() {
+        let mut sampler = Sampler::new(100, 500);
+        sampler.keep("first", StatusType::Success, Some(10));
+
+        assert!(sampler.keep("error", StatusType::Error, Some(10)));
+        assert!(sampler.keep("client-error", StatusType::Warning, Some(10)));
+        assert!(sampler.keep("slow", StatusType::Success, Some(500)));
+        assert!(!sampler.keep("fast", StatusType::Success, Some(499)));
+    }
+
+    #[test]
+    fn test_forgets_old_dropped_ids() {
+        let mut sampler = Sampler::new(2, 500);
+        for i in 0..(DROPPED_ID_MEMORY * 2 + 2) {
+            sampler.keep(&format!("req-{}", i), StatusType::Success, None);
+        }
+        assert!(!sampler.was_dropped("req-1"));
+        assert!(sampler.was_dropped(&format!("req-{}", DROPPED_ID_MEMORY * 2 + 1)));
+    }
+}
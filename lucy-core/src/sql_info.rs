@@ -9,6 +9,9 @@ static TABLE_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     .unwrap()
 });
 
+static DURATION_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\((\d+(?:\.\d+)?)ms\)").unwrap());
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum QueryType {
     Select,
@@ -17,14 +20,70 @@ pub enum QueryType {
     Delete,
 }
 
+impl QueryType {
+    /// Order in which query types are listed in the SQL panel.
+    pub const DISPLAY_ORDER: [QueryType; 4] = [
+        QueryType::Select,
+        QueryType::Insert,
+        QueryType::Update,
+        QueryType::Delete,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QueryType::Select => "SELECT",
+            QueryType::Insert => "INSERT",
+            QueryType::Update => "UPDATE",
+            QueryType::Delete => "DELETE",
+        }
+    }
+}
+
+/// What a rendered row of the SQL panel refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlRow {
+    QueryType(QueryType),
+    Cache,
+    Table(String),
+}
+
+impl SqlRow {
+    /// Whether a log message is a query counted under this row.
+    pub fn matches(&self, message: &str) -> bool {
+        let Some(query_type) = classify_query(message) else {
+            return false;
+        };
+        if is_cache_hit(message) {
+            return *self == SqlRow::Cache;
+        }
+        match self {
+            SqlRow::QueryType(row_type) => *row_type == query_type,
+            SqlRow::Cache => false,
+            SqlRow::Table(table) => TABLE_PATTERN.captures_iter(message).any(|cap| {
+                cap.get(1)
+                    .or_else(|| cap.get(2))
+                    .is_some_and(|m| m.as_str() == table)
+            }),
+        }
+    }
+}
+
 const N_PLUS_ONE_THRESHOLD: usize = 5;
 
 pub struct SqlQueryInfo {
     pub query_counts: HashMap<QueryType, usize>,
+    /// `CACHE` lines, served by the query cache and not counted as queries
+    pub cache_hits: usize,
     pub table_counts: HashMap<String, usize>,
     pub select_per_table: HashMap<String, usize>,
 }
 
+impl Default for SqlQueryInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SqlQueryInfo {
     pub fn new() -> Self {
         let mut query_counts = HashMap::new();
@@ -35,6 +94,7 @@ impl SqlQueryInfo {
 
         Self {
             query_counts,
+            cache_hits: 0,
             table_counts: HashMap::new(),
             select_per_table: HashMap::new(),
         }
@@ -54,6 +114,7 @@ impl SqlQueryInfo {
     }
 
     pub fn merge(&mut self, other: &SqlQueryInfo) {
+        self.cache_hits += other.cache_hits;
         for (query_type, count) in &other.query_counts {
             if *count > 0 {
                 *self.query_counts.entry(*query_type).or_insert(0) += count;
@@ -80,6 +141,19 @@ impl SqlQueryInfo {
         *self.query_counts.get(&query_type).unwrap_or(&0)
     }
 
+    /// Whether any INSERT, UPDATE or DELETE ran.
+    pub fn has_writes(&self) -> bool {
+        [QueryType::Insert, QueryType::Update, QueryType::Delete]
+            .into_iter()
+            .any(|query_type| self.query_count(query_type) > 0)
+    }
+
+    /// Share of SELECTs answered by the query cache, 0.0..=1.0.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.query_count(QueryType::Select);
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+
     pub fn sorted_tables(&self) -> Vec<(&String, &usize)> {
         let mut tables: Vec<_> = self.table_counts.iter().collect();
         tables.sort_by(|a, b| a.0.cmp(b.0));
@@ -87,8 +161,8 @@ impl SqlQueryInfo {
     }
 
     pub fn display_line_count(&self) -> usize {
-        // blank line(1) + SELECT/INSERT/UPDATE/DELETE(4) + table section
-        let base = 1 + 4;
+        // blank line(1) + SELECT/INSERT/UPDATE/DELETE(4) + CACHE(1) + table section
+        let base = 1 + 4 + 1;
         if self.table_counts.is_empty() {
             base
         } else {
@@ -108,19 +182,11 @@ pub fn parse_sql_from_logs(logs: &[&str]) -> SqlQueryInfo {
     let mut sql_info = SqlQueryInfo::new();
 
     for msg in logs {
-        let query_type = if msg.contains("SELECT ") {
-            Some(QueryType::Select)
-        } else if msg.contains("UPDATE ") {
-            Some(QueryType::Update)
-        } else if msg.contains("INSERT ") {
-            Some(QueryType::Insert)
-        } else if msg.contains("DELETE ") {
-            Some(QueryType::Delete)
-        } else {
-            None
-        };
-
-        if let Some(query_type) = query_type {
+        if let Some(query_type) = classify_query(msg) {
+            if is_cache_hit(msg) {
+                sql_info.cache_hits += 1;
+                continue;
+            }
             *sql_info.query_counts.entry(query_type).or_insert(0) += 1;
             for cap in TABLE_PATTERN.captures_iter(msg) {
                 let table_name = cap.get(1).or_else(|| cap.get(2)).map(|m| m.as_str());
@@ -145,10 +211,75 @@ pub fn parse_sql_from_logs(logs: &[&str]) -> SqlQueryInfo {
     sql_info
 }
 
+/// Time a logged query took, as in `User Load (0.4ms)  SELECT ...`.
+/// None for lines that are not queries; cache hits took no database time.
+pub fn query_duration_ms(msg: &str) -> Option<f64> {
+    classify_query(msg)?;
+    if is_cache_hit(msg) {
+        return Some(0.0);
+    }
+    Some(
+        DURATION_PATTERN
+            .captures(msg)
+            .and_then(|caps| caps[1].parse().ok())
+            .unwrap_or(0.0),
+    )
+}
+
+/// The statement of a logged query, from its first keyword on, as in
+/// `SELECT * FROM users` for `User Load (0.4ms)  SELECT * FROM users`.
+pub fn statement(msg: &str) -> Option<&str> {
+    classify_query(msg)?;
+    let start = ["SELECT ", "INSERT ", "UPDATE ", "DELETE "]
+        .iter()
+        .filter_map(|keyword| msg.find(keyword))
+        .min()?;
+    Some(msg[start..].trim_end())
+}
+
+/// `CACHE User Load (0.0ms)  SELECT ...`
+fn is_cache_hit(msg: &str) -> bool {
+    msg.contains("CACHE ")
+}
+
+fn classify_query(msg: &str) -> Option<QueryType> {
+    if msg.contains("SELECT ") {
+        Some(QueryType::Select)
+    } else if msg.contains("UPDATE ") {
+        Some(QueryType::Update)
+    } else if msg.contains("INSERT ") {
+        Some(QueryType::Insert)
+    } else if msg.contains("DELETE ") {
+        Some(QueryType::Delete)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_query_duration_ms() {
+        assert_eq!(query_duration_ms("User Load (12.5ms)  SELECT * FROM users"), Some(12.5));
+        assert_eq!(query_duration_ms("CACHE User Load (0.1ms)  SELECT * FROM users"), Some(0.0));
+        assert_eq!(query_duration_ms("Rendered users/index.html.erb (Duration: 3.0ms)"), None);
+    }
+
+    #[test]
+    fn test_statement() {
+        assert_eq!(
+            statement("User Load (12.5ms)  SELECT * FROM users WHERE id IN (SELECT 1)\n"),
+            Some("SELECT * FROM users WHERE id IN (SELECT 1)")
+        );
+        assert_eq!(
+            statement(r#"Post Update (0.3ms)  UPDATE "posts" SET "title" = 'SELECT this'"#),
+            Some(r#"UPDATE "posts" SET "title" = 'SELECT this'"#)
+        );
+        assert_eq!(statement("Completed 200 OK in 5ms"), None);
+    }
+
     #[test]
     fn test_sql_query_info_new() {
         let info = SqlQueryInfo::new();
@@ -175,6 +306,7 @@ mod tests {
         assert_eq!(info.query_count(QueryType::Insert), 0);
         assert_eq!(info.total_queries(), 1);
         assert!(info.table_counts.contains_key("users"));
+        assert!(!info.has_writes());
 
         // INSERT query
         let insert_msg = "SQL (0.8ms) INSERT INTO products (name, price) VALUES ('Test', 9.99)";
@@ -182,6 +314,7 @@ mod tests {
         assert_eq!(info.query_count(QueryType::Insert), 1);
         assert_eq!(info.total_queries(), 1);
         assert!(info.table_counts.contains_key("products"));
+        assert!(info.has_writes());
 
         // Non-SQL message
         let non_sql_msg = "Processing request";
@@ -240,13 +373,13 @@ mod tests {
     #[test]
     fn test_display_line_count() {
         let mut info = SqlQueryInfo::new();
-        assert_eq!(info.display_line_count(), 5); // 空行(1) + 4クエリタイプ行
+        assert_eq!(info.display_line_count(), 6); // 空行(1) + 4クエリタイプ行 + CACHE行
 
         info.table_counts.insert("users".to_string(), 1);
-        assert_eq!(info.display_line_count(), 7); // 5 + 空行(1) + 1テーブル
+        assert_eq!(info.display_line_count(), 8); // 6 + 空行(1) + 1テーブル
 
         info.table_counts.insert("orders".to_string(), 1);
-        assert_eq!(info.display_line_count(), 8); // 5 + 空行(1) + 2テーブル
+        assert_eq!(info.display_line_count(), 9); // 6 + 空行(1) + 2テーブル
     }
 
     #[test]
@@ -317,4 +450,44 @@ mod tests {
         // Check that JOIN tables are counted
         assert_eq!(*info.table_counts.get("orders").unwrap(), 2); // One from UPDATE, one from SELECT...JOIN
     }
+
+    #[test]
+    fn test_sql_row_matches() {
+        let select = "SQL (0.5ms) SELECT * FROM users WHERE id = 1";
+        assert!(SqlRow::QueryType(QueryType::Select).matches(select));
+        assert!(!SqlRow::QueryType(QueryType::Insert).matches(select));
+        assert!(SqlRow::Table("users".to_string()).matches(select));
+        assert!(!SqlRow::Table("orders".to_string()).matches(select));
+        assert!(!SqlRow::Table("users".to_string()).matches("Rendering users/index"));
+
+        let cached = "CACHE User Load (0.0ms)  SELECT \"users\".* FROM \"users\"";
+        assert!(SqlRow::Cache.matches(cached));
+        assert!(!SqlRow::QueryType(QueryType::Select).matches(cached));
+        assert!(!SqlRow::Table("users".to_string()).matches(cached));
+        assert!(!SqlRow::Cache.matches(select));
+    }
+
+    #[test]
+    fn test_cache_hits() {
+        let logs = [
+            "User Load (0.4ms)  SELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1",
+            "\x1b[1m\x1b[36mCACHE User Load (0.0ms)\x1b[0m  \x1b[1m\x1b[34mSELECT \"users\".* FROM \"users\"\x1b[0m",
+            "CACHE User Load (0.0ms)  SELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1",
+            "CACHE Post Load (0.0ms)  SELECT \"posts\".* FROM \"posts\"",
+        ];
+        let info = parse_sql_from_logs(&logs);
+
+        assert_eq!(info.cache_hits, 3);
+        assert_eq!(info.query_count(QueryType::Select), 1);
+        assert_eq!(info.total_queries(), 1);
+        assert_eq!(info.table_counts.get("users"), Some(&1));
+        assert!(!info.table_counts.contains_key("posts"));
+        assert_eq!(info.cache_hit_ratio(), Some(0.75));
+        assert_eq!(SqlQueryInfo::new().cache_hit_ratio(), None);
+
+        let mut merged = SqlQueryInfo::new();
+        merged.merge(&info);
+        merged.merge(&info);
+        assert_eq!(merged.cache_hits, 6);
+    }
 }
@@ -0,0 +1,243 @@
+use crate::app_state::StatusType;
+use chrono::{DateTime, FixedOffset};
+use std::collections::VecDeque;
+
+/// Upper bounds (exclusive) of the duration histogram buckets, in ms.
+const HISTOGRAM_BOUNDS: [u64; 6] = [50, 100, 250, 500, 1000, 3000];
+const HISTOGRAM_LABELS: [&str; 7] = ["<50", "<100", "<250", "<500", "<1s", "<3s", "3s+"];
+const RATE_WINDOW_MINUTES: usize = 60;
+/// Durations below this are counted to the millisecond.
+const EXACT_MS: u64 = 1024;
+/// Buckets per doubling above `EXACT_MS`, each within 0.2% of its values.
+const SUB_BUCKETS: u64 = EXACT_MS / 2;
+
+/// Aggregates over every finished request, updated as groups complete.
+/// Evicted requests stay counted.
+pub struct RequestStats {
+    status_counts: [usize; 5],
+    durations: DurationCounts,
+    histogram: [u64; HISTOGRAM_LABELS.len()],
+    /// (minute since epoch, completed requests), oldest first
+    per_minute: VecDeque<(i64, u64)>,
+}
+
+impl Default for RequestStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestStats {
+    pub fn new() -> Self {
+        Self {
+            status_counts: [0; 5],
+            durations: DurationCounts::default(),
+            histogram: [0; HISTOGRAM_LABELS.len()],
+            per_minute: VecDeque::with_capacity(RATE_WINDOW_MINUTES),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        status: StatusType,
+        duration_ms: Option<u64>,
+        completed_at: DateTime<FixedOffset>,
+    ) {
+        self.status_counts[status_index(status)] += 1;
+
+        if let Some(ms) = duration_ms {
+            self.durations.record(ms);
+            let bucket = HISTOGRAM_BOUNDS.partition_point(|&bound| bound <= ms);
+            self.histogram[bucket] += 1;
+        }
+
+        let minute = completed_at.timestamp().div_euclid(60);
+        match self.per_minute.iter_mut().rev().find(|(m, _)| *m == minute) {
+            Some((_, count)) => *count += 1,
+            None => {
+                // Input is mostly chronological; keep the buckets ordered anyway
+                let pos = self.per_minute.partition_point(|(m, _)| *m < minute);
+                self.per_minute.insert(pos, (minute, 1));
+                while self.per_minute.len() > RATE_WINDOW_MINUTES {
+                    self.per_minute.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.status_counts.iter().sum()
+    }
+
+    pub fn count(&self, status: StatusType) -> usize {
+        self.status_counts[status_index(status)]
+    }
+
+    /// Nearest-rank percentile of durations, `p` in 0.0..=1.0: exact below
+    /// a second, and rounded down by at most 0.2% above.
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        self.durations.percentile(p)
+    }
+
+    pub fn max_duration(&self) -> Option<u64> {
+        self.durations.max
+    }
+
+    pub fn histogram(&self) -> Vec<(&'static str, u64)> {
+        HISTOGRAM_LABELS
+            .iter()
+            .copied()
+            .zip(self.histogram.iter().copied())
+            .collect()
+    }
+
+    /// Completed requests per minute over the last hour of activity, oldest first.
+    pub fn requests_per_minute(&self) -> Vec<u64> {
+        let Some(&(latest, _)) = self.per_minute.back() else {
+            return Vec::new();
+        };
+        let start = latest - RATE_WINDOW_MINUTES as i64 + 1;
+        let mut counts = vec![0; RATE_WINDOW_MINUTES];
+        for &(minute, count) in &self.per_minute {
+            if minute >= start {
+                counts[(minute - start) as usize] = count;
+            }
+        }
+        counts
+    }
+}
+
+/// How many requests took each duration, in buckets that stay few however
+/// long lucy runs, rather than every duration kept.
+#[derive(Default)]
+struct DurationCounts {
+    buckets: Vec<u64>,
+    total: u64,
+    max: Option<u64>,
+}
+
+impl DurationCounts {
+    fn record(&mut self, ms: u64) {
+        let index = bucket_index(ms);
+        if index >= self.buckets.len() {
+            self.buckets.resize(index + 1, 0);
+        }
+        self.buckets[index] += 1;
+        self.total += 1;
+        self.max = self.max.max(Some(ms));
+    }
+
+    fn percentile(&self, p: f64) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((p * self.total as f64).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        self.buckets.iter().enumerate().find_map(|(index, &count)| {
+            seen += count;
+            (seen >= rank).then(|| bucket_floor(index))
+        })
+    }
+}
+
+fn bucket_index(ms: u64) -> usize {
+    if ms < EXACT_MS {
+        return ms as usize;
+    }
+    // Keeps the top 10 bits, so `ms >> shift` is in SUB_BUCKETS..EXACT_MS
+    let shift = u64::from(63 - ms.leading_zeros()) - 9;
+    (EXACT_MS + (shift - 1) * SUB_BUCKETS + (ms >> shift) - SUB_BUCKETS) as usize
+}
+
+/// The smallest duration counted in bucket `index`.
+fn bucket_floor(index: usize) -> u64 {
+    let index = index as u64;
+    if index < EXACT_MS {
+        return index;
+    }
+    let above = index - EXACT_MS;
+    let shift = above / SUB_BUCKETS + 1;
+    (above % SUB_BUCKETS + SUB_BUCKETS) << shift
+}
+
+fn status_index(status: StatusType) -> usize {
+    match status {
+        StatusType::Success => 0,
+        StatusType::Warning => 1,
+        StatusType::Error => 2,
+        StatusType::Unknown => 3,
+        StatusType::Info => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap()
+    }
+
+    #[test]
+    fn test_counts_and_percentiles() {
+        let mut stats = RequestStats::new();
+        assert_eq!(stats.percentile(0.5), None);
+
+        for ms in 1..=100 {
+            stats.record(StatusType::Success, Some(ms), at("2024-05-01T12:00:00Z"));
+        }
+        stats.record(StatusType::Error, None, at("2024-05-01T12:00:00Z"));
+
+        assert_eq!(stats.total(), 101);
+        assert_eq!(stats.count(StatusType::Success), 100);
+        assert_eq!(stats.count(StatusType::Error), 1);
+        assert_eq!(stats.percentile(0.5), Some(50));
+        assert_eq!(stats.percentile(0.95), Some(95));
+        assert_eq!(stats.max_duration(), Some(100));
+    }
+
+    #[test]
+    fn test_long_durations() {
+        for ms in [1023, 1024, 2047, 2048, 123_456, u64::MAX] {
+            let floor = bucket_floor(bucket_index(ms));
+            assert!(floor <= ms && ms - floor <= ms / SUB_BUCKETS, "{}", ms);
+        }
+
+        let mut stats = RequestStats::new();
+        for ms in (1..=10_000).map(|i| i * 10) {
+            stats.record(StatusType::Success, Some(ms), at("2024-05-01T12:00:00Z"));
+        }
+        assert_eq!(stats.percentile(0.5), Some(49_984));
+        assert_eq!(stats.max_duration(), Some(100_000));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut stats = RequestStats::new();
+        for ms in [10, 49, 50, 999, 1000, 5000] {
+            stats.record(StatusType::Success, Some(ms), at("2024-05-01T12:00:00Z"));
+        }
+        let histogram = stats.histogram();
+        assert_eq!(histogram[0], ("<50", 2));
+        assert_eq!(histogram[1], ("<100", 1));
+        assert_eq!(histogram[4], ("<1s", 1));
+        assert_eq!(histogram[5], ("<3s", 1));
+        assert_eq!(histogram[6], ("3s+", 1));
+    }
+
+    #[test]
+    fn test_requests_per_minute() {
+        let mut stats = RequestStats::new();
+        assert!(stats.requests_per_minute().is_empty());
+
+        stats.record(StatusType::Success, None, at("2024-05-01T12:00:10Z"));
+        stats.record(StatusType::Success, None, at("2024-05-01T12:00:50Z"));
+        stats.record(StatusType::Success, None, at("2024-05-01T12:02:00Z"));
+        // Out of order line
+        stats.record(StatusType::Success, None, at("2024-05-01T12:01:00Z"));
+
+        let rate = stats.requests_per_minute();
+        assert_eq!(rate.len(), RATE_WINDOW_MINUTES);
+        assert_eq!(&rate[RATE_WINDOW_MINUTES - 3..], &[2, 1, 1]);
+    }
+}
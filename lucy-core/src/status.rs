@@ -0,0 +1,104 @@
+//! `[status]`: which class a status code counts as.
+
+use crate::app_state::LogGroup;
+use crate::model::StatusType;
+use serde::Deserialize;
+
+/// Status codes written in the config file: `404`, `4xx` or `400-403`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "StatusCodesSpec")]
+pub struct StatusCodes {
+    pub first: u16,
+    pub last: u16,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StatusCodesSpec {
+    Code(u16),
+    Text(String),
+}
+
+impl TryFrom<StatusCodesSpec> for StatusCodes {
+    type Error = String;
+
+    fn try_from(spec: StatusCodesSpec) -> Result<Self, Self::Error> {
+        match spec {
+            StatusCodesSpec::Code(code) => Ok(StatusCodes { first: code, last: code }),
+            StatusCodesSpec::Text(text) => StatusCodes::parse(&text),
+        }
+    }
+}
+
+impl StatusCodes {
+    /// `404`, `4xx` or `400-403`, as in the config file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parse = |code: &str| code.trim().parse::<u16>().ok();
+        let class = text.strip_suffix("xx").and_then(parse).filter(|&class| class <= 9);
+        let codes = if let Some(class) = class {
+            Some((class * 100, class * 100 + 99))
+        } else if let Some((first, last)) = text.split_once('-') {
+            parse(first).zip(parse(last))
+        } else {
+            parse(text).map(|code| (code, code))
+        };
+        match codes {
+            Some((first, last)) if first <= last => Ok(StatusCodes { first, last }),
+            _ => Err(format!("invalid status codes {:?}", text)),
+        }
+    }
+
+    pub fn contains(self, code: u16) -> bool {
+        (self.first..=self.last).contains(&code)
+    }
+}
+
+impl std::fmt::Display for StatusCodes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else if self.first.is_multiple_of(100) && self.last == self.first + 99 {
+            write!(f, "{}xx", self.first / 100)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+/// `[status]`: which class a status code counts as, where it should differ
+/// from 2xx success, 4xx warning and 5xx error.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    pub success: Vec<StatusCodes>,
+    pub info: Vec<StatusCodes>,
+    pub warning: Vec<StatusCodes>,
+    pub error: Vec<StatusCodes>,
+    /// Show the status code of each request in the list.
+    pub show_codes: bool,
+}
+
+impl StatusConfig {
+    /// Class of `code`. When several entries match, the narrowest wins, so
+    /// `info = ["4xx"]` and `error = [422]` make every 4xx but 422 info.
+    pub fn classify(&self, code: u16) -> StatusType {
+        [
+            (&self.success, StatusType::Success),
+            (&self.info, StatusType::Info),
+            (&self.warning, StatusType::Warning),
+            (&self.error, StatusType::Error),
+        ]
+        .into_iter()
+        .flat_map(|(codes, status)| codes.iter().map(move |codes| (codes, status)))
+        .filter(|(codes, _)| codes.contains(code))
+        .min_by_key(|(codes, _)| codes.last - codes.first)
+        .map_or_else(|| StatusType::from_code(code), |(_, status)| status)
+    }
+
+    /// Reclassifies a finished request by its status code.
+    pub fn apply<R>(&self, group: &mut LogGroup<R>) {
+        if let Some(code) = group.status_code {
+            group.status_type = self.classify(code);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::sql_info::query_duration_ms;
+use chrono::{DateTime, FixedOffset};
+
+/// Gaps shorter than this are not worth a note.
+const MIN_GAP_MS: i64 = 100;
+
+/// Wall-clock time between two non-SQL lines of a request, and the SQL logged
+/// in between, to tell database time from Ruby time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GapNote {
+    pub gap_ms: i64,
+    pub queries: usize,
+    pub sql_ms: f64,
+}
+
+impl GapNote {
+    /// Share of the gap spent in SQL, in percent.
+    pub fn sql_percent(&self) -> f64 {
+        (self.sql_ms / self.gap_ms as f64 * 100.0).min(100.0)
+    }
+}
+
+/// Follows a request's lines in order and notes the gaps they close.
+#[derive(Debug, Clone, Default)]
+pub struct GapTracker {
+    /// Timestamp of the last non-SQL line
+    anchor: Option<DateTime<FixedOffset>>,
+    queries: usize,
+    sql_ms: f64,
+}
+
+impl GapTracker {
+    /// Feeds the next line, returning a note when it closes a long gap.
+    pub fn observe(&mut self, timestamp: DateTime<FixedOffset>, message: &str) -> Option<GapNote> {
+        if let Some(duration_ms) = query_duration_ms(message) {
+            self.queries += 1;
+            self.sql_ms += duration_ms;
+            return None;
+        }
+
+        let note = self.anchor.and_then(|anchor| {
+            let gap_ms = (timestamp - anchor).num_milliseconds();
+            (gap_ms >= MIN_GAP_MS).then_some(GapNote {
+                gap_ms,
+                queries: self.queries,
+                sql_ms: self.sql_ms,
+            })
+        });
+        *self = Self {
+            anchor: Some(timestamp),
+            ..Self::default()
+        };
+        note
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: i64) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-05-01T12:00:00+00:00").unwrap()
+            + chrono::TimeDelta::milliseconds(ms)
+    }
+
+    #[test]
+    fn test_gap_notes() {
+        let mut tracker = GapTracker::default();
+        assert_eq!(tracker.observe(at(0), "Processing by UsersController#index"), None);
+        assert_eq!(tracker.observe(at(300), "User Load (200.0ms)  SELECT * FROM users"), None);
+        assert_eq!(tracker.observe(at(700), "Post Load (400.0ms)  SELECT * FROM posts"), None);
+        assert_eq!(tracker.observe(at(710), "CACHE User Load (0.0ms)  SELECT * FROM users"), None);
+
+        let note = tracker.observe(at(800), "Rendered users/index.html.erb").unwrap();
+        assert_eq!(note.gap_ms, 800);
+        assert_eq!(note.queries, 3);
+        assert_eq!(note.sql_ms, 600.0);
+        assert_eq!(note.sql_percent(), 75.0);
+
+        // Short gaps go unnoticed, and each gap starts afresh
+        assert_eq!(tracker.observe(at(850), "Completed 200 OK"), None);
+        let note = tracker.observe(at(1050), "Rendered layout").unwrap();
+        assert_eq!((note.queries, note.sql_ms), (0, 0.0));
+    }
+}
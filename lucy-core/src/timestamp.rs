@@ -0,0 +1,240 @@
+//! When a line was written, read from the line itself so that buffered or
+//! batched input keeps its original times. Lines none of the extractors
+//! recognize are stamped with the time they arrive.
+//!
+//! Formats the built-in extractors miss can be described in the config:
+//!
+//! ```toml
+//! [timestamps]
+//! pattern = '^\[(?P<ts>[^\]]+)\] '
+//! format = "%d/%b/%Y:%H:%M:%S %z"
+//! strip = true
+//! ```
+
+use crate::log_parser::extract_leading_timestamp;
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, TimeDelta, TimeZone};
+use crate::error::{self, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+// May  1 12:00:00 web1 puma[123]: [req-1] Started GET "/"   (BSD syslog)
+static RE_SYSLOG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<ts>[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}) \S+ [^:\s]+: ").unwrap()
+});
+
+// I, [2024-05-01T12:00:00.123456 #1234]  INFO -- : [req-1] Started GET "/"   (Ruby's Logger)
+static RE_RUBY_LOGGER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[DIWEFAU], \[(?P<ts>\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?) #\d+\]")
+        .unwrap()
+});
+
+/// Finds the time a line was written in the line itself.
+pub trait TimestampExtractor: Sync {
+    /// The time `line` was written, and the length in bytes of the prefix
+    /// it took, which is left out of the message. The length is 0 for a
+    /// timestamp that stays part of the message.
+    fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)>;
+}
+
+/// Built-in extractors, in the order they are tried.
+pub static EXTRACTORS: &[&dyn TimestampExtractor] = &[&Leading, &Syslog, &RubyLogger];
+
+/// ISO 8601 prefix written by `kubectl logs --timestamps`, `docker logs -t`
+/// and similar tools.
+pub struct Leading;
+
+impl TimestampExtractor for Leading {
+    fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+        extract_leading_timestamp(line)
+    }
+}
+
+/// The `May  1 12:00:00 host tag:` prefix of syslog, which carries no year:
+/// the current one is assumed, or the last one for dates that would be
+/// ahead of now.
+pub struct Syslog;
+
+impl TimestampExtractor for Syslog {
+    fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+        let caps = RE_SYSLOG.captures(line)?;
+        let now = Local::now();
+        let parse = |year: i32| {
+            let text = format!("{} {}", year, caps["ts"].replace("  ", " "));
+            NaiveDateTime::parse_from_str(&text, "%Y %b %e %H:%M:%S").ok()
+        };
+        let mut naive = parse(now.year())?;
+        if naive > now.naive_local() + TimeDelta::days(1) {
+            naive = parse(now.year() - 1)?;
+        }
+        let timestamp = Local.from_local_datetime(&naive).earliest()?.fixed_offset();
+        Some((timestamp, caps.get(0)?.end()))
+    }
+}
+
+/// The `I, [2024-05-01T12:00:00.123456 #1234]` of Ruby's default Logger
+/// format, which stays in the message for its level.
+pub struct RubyLogger;
+
+impl TimestampExtractor for RubyLogger {
+    fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+        let caps = RE_RUBY_LOGGER.captures(line)?;
+        let naive = NaiveDateTime::parse_from_str(&caps["ts"], "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        let timestamp = Local.from_local_datetime(&naive).earliest()?.fixed_offset();
+        Some((timestamp, 0))
+    }
+}
+
+/// `[timestamps]`: a timestamp format of the application's own.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimestampConfig {
+    /// Regex whose `ts` group (or first group) is the timestamp.
+    pub pattern: String,
+    /// chrono format of the timestamp, such as `%d/%b/%Y:%H:%M:%S %z`.
+    /// Times without an offset are taken to be in the local zone.
+    pub format: String,
+    /// Leave the match out of the message when it starts the line.
+    #[serde(default)]
+    pub strip: bool,
+}
+
+pub struct CustomTimestamp {
+    regex: Regex,
+    format: String,
+    strip: bool,
+}
+
+impl CustomTimestamp {
+    pub fn new(config: &TimestampConfig) -> Result<Self> {
+        Ok(Self {
+            regex: error::compile_with_capture("timestamp pattern", &config.pattern)?,
+            format: config.format.clone(),
+            strip: config.strip,
+        })
+    }
+}
+
+impl TimestampExtractor for CustomTimestamp {
+    fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+        let caps = self.regex.captures(line)?;
+        let text = caps.name("ts").or_else(|| caps.get(1))?.as_str();
+        let timestamp = DateTime::parse_from_str(text, &self.format)
+            .ok()
+            .or_else(|| {
+                let naive = NaiveDateTime::parse_from_str(text, &self.format).ok()?;
+                Some(Local.from_local_datetime(&naive).earliest()?.fixed_offset())
+            })?;
+        let matched = caps.get(0)?;
+        let prefix_len = if self.strip && matched.start() == 0 {
+            matched.end()
+        } else {
+            0
+        };
+        Some((timestamp, prefix_len))
+    }
+}
+
+/// The configured extractor, if any, then the built-in ones.
+#[derive(Default)]
+pub struct Timestamps {
+    custom: Option<CustomTimestamp>,
+}
+
+impl Timestamps {
+    pub fn new(config: Option<&TimestampConfig>) -> Result<Self> {
+        Ok(Self {
+            custom: config.map(CustomTimestamp::new).transpose()?,
+        })
+    }
+
+    pub fn extract(&self, line: &str) -> Option<(DateTime<FixedOffset>, usize)> {
+        let custom = self
+            .custom
+            .iter()
+            .map(|custom| custom as &dyn TimestampExtractor);
+        custom
+            .chain(EXTRACTORS.iter().copied())
+            .find_map(|extractor| extractor.extract(line))
+    }
+
+    /// When `line` was written, falling back to now, and the rest of it.
+    pub fn split<'a>(&self, line: &'a str) -> (DateTime<FixedOffset>, &'a str) {
+        match self.extract(line) {
+            Some((timestamp, prefix_len)) => (timestamp, &line[prefix_len..]),
+            None => (Local::now().fixed_offset(), line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_extractors() {
+        let timestamps = Timestamps::default();
+        let (timestamp, rest) =
+            timestamps.split("2024-05-01T03:00:00.100Z [req-1] Started GET \"/posts\"");
+        assert_eq!(timestamp.to_rfc3339(), "2024-05-01T03:00:00.100+00:00");
+        assert_eq!(rest, "[req-1] Started GET \"/posts\"");
+
+        let (timestamp, rest) =
+            timestamps.split("May  1 12:00:00 web1 puma[123]: [req-1] Started GET \"/\"");
+        assert_eq!(
+            timestamp.format("%m-%d %H:%M:%S").to_string(),
+            "05-01 12:00:00"
+        );
+        assert_eq!(rest, "[req-1] Started GET \"/\"");
+
+        let line = "I, [2024-05-01T12:00:00.123456 #1234]  INFO -- : [req-1] Started";
+        let (timestamp, rest) = timestamps.split(line);
+        assert_eq!(
+            timestamp.naive_local().to_string(),
+            "2024-05-01 12:00:00.123456"
+        );
+        assert_eq!(rest, line);
+
+        assert!(timestamps.extract("[req-1] Started GET \"/\"").is_none());
+    }
+
+    #[test]
+    fn test_custom_extractor() {
+        let config = TimestampConfig {
+            pattern: r"^\[(?P<ts>[^\]]+)\] ".to_string(),
+            format: "%d/%b/%Y:%H:%M:%S %z".to_string(),
+            strip: true,
+        };
+        let timestamps = Timestamps::new(Some(&config)).unwrap();
+        let (timestamp, rest) = timestamps.split("[01/May/2024:12:00:00 +0900] [req-1] Started");
+        assert_eq!(timestamp.to_rfc3339(), "2024-05-01T12:00:00+09:00");
+        assert_eq!(rest, "[req-1] Started");
+        // Built-in formats are still read
+        assert!(
+            timestamps
+                .extract("2024-05-01T03:00:00Z [req-1] Started")
+                .is_some()
+        );
+
+        let config = TimestampConfig {
+            pattern: r"at=(\S+)".to_string(),
+            format: "%Y-%m-%dT%H:%M:%S%.f".to_string(),
+            strip: true,
+        };
+        let timestamps = Timestamps::new(Some(&config)).unwrap();
+        let line = "[req-1] done at=2024-05-01T12:00:00.5";
+        let (timestamp, rest) = timestamps.split(line);
+        assert_eq!(
+            timestamp.naive_local().to_string(),
+            "2024-05-01 12:00:00.500"
+        );
+        // Only a match at the start of the line is stripped
+        assert_eq!(rest, line);
+
+        let config = TimestampConfig {
+            pattern: "no group".to_string(),
+            format: "%s".to_string(),
+            strip: false,
+        };
+        assert!(Timestamps::new(Some(&config)).is_err());
+    }
+}
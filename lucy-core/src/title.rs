@@ -0,0 +1,249 @@
+//! Request titles built from a template, such as
+//! `{method} {path} → {controller}#{action}`, filled in from the request's
+//! `Started` and `Processing by` lines.
+
+use regex::Regex;
+use serde::Deserialize;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+// Started GET "/posts?page=2" for 127.0.0.1 at 2024-05-01 12:00:00 +0900
+static RE_STARTED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"Started (?P<method>[A-Z]+) "(?P<path>[^"]*)""#).unwrap());
+
+// Processing by Admin::PostsController#index as HTML
+static RE_PROCESSING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Processing by (?P<controller>[\w:]+)#(?P<action>\w+)(?: as (?P<format>\S+))?")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Method,
+    /// As requested, query string included
+    Path,
+    Controller,
+    Action,
+    /// Format the action responds with, such as `HTML`
+    Format,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [
+        Field::Method,
+        Field::Path,
+        Field::Controller,
+        Field::Action,
+        Field::Format,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Method => "method",
+            Field::Path => "path",
+            Field::Controller => "controller",
+            Field::Action => "action",
+            Field::Format => "format",
+        }
+    }
+}
+
+/// What the lines of a request have told about it so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleFields {
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub controller: Option<String>,
+    pub action: Option<String>,
+    pub format: Option<String>,
+}
+
+impl TitleFields {
+    /// Takes the fields `message` carries, if it is a `Started` or
+    /// `Processing by` line. Other lines cost a substring search, so every
+    /// line of a request can go through it.
+    pub fn observe(&mut self, message: &str) {
+        if message.contains("Started ")
+            && let Some(caps) = RE_STARTED.captures(message)
+        {
+            self.method = Some(caps["method"].to_string());
+            self.path = Some(caps["path"].to_string());
+        } else if message.contains("Processing by ")
+            && let Some(caps) = RE_PROCESSING.captures(message)
+        {
+            self.controller = Some(caps["controller"].to_string());
+            self.action = Some(caps["action"].to_string());
+            self.format = caps.name("format").map(|format| format.as_str().to_string());
+        }
+    }
+
+    /// `Admin::Posts#index` for `Admin::PostsController#index`, once the
+    /// `Processing by` line was seen.
+    pub fn controller_action(&self) -> Option<String> {
+        let controller = self.controller.as_deref()?;
+        let action = self.action.as_deref()?;
+        Some(format!("{}#{}", controller_name(controller), action))
+    }
+
+    /// Whether the request was handled by `controller`, named with or
+    /// without its `Controller` suffix, in any case.
+    pub fn handled_by(&self, controller: &str) -> bool {
+        self.controller.as_deref().is_some_and(|handler| {
+            controller_name(handler).eq_ignore_ascii_case(controller_name(controller))
+        })
+    }
+
+    pub fn get(&self, field: Field) -> Option<&str> {
+        match field {
+            Field::Method => self.method.as_deref(),
+            Field::Path => self.path.as_deref(),
+            Field::Controller => self.controller.as_deref(),
+            Field::Action => self.action.as_deref(),
+            Field::Format => self.format.as_deref(),
+        }
+    }
+}
+
+/// `controller` without its `Controller` suffix.
+pub fn controller_name(controller: &str) -> &str {
+    controller.strip_suffix("Controller").unwrap_or(controller)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// A title template: text with `{field}`s in it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TitleTemplate {
+    parts: Vec<Part>,
+}
+
+impl TryFrom<String> for TitleTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        TitleTemplate::parse(&template)
+    }
+}
+
+/// A filled in template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Title {
+    pub text: String,
+    /// Bytes of `text` the path took, the part to shorten when the title
+    /// does not fit
+    pub path: Option<Range<usize>>,
+}
+
+impl TitleTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(Part::Text(rest[..open].to_string()));
+            }
+            let Some(close) = rest[open..].find('}') else {
+                return Err(format!("unclosed {{ in title template {:?}", template));
+            };
+            let name = &rest[open + 1..open + close];
+            let field = Field::ALL
+                .into_iter()
+                .find(|field| field.name() == name)
+                .ok_or_else(|| {
+                    let names: Vec<_> = Field::ALL.iter().map(|field| field.name()).collect();
+                    format!("unknown field {{{}}}, expected one of {}", name, names.join(", "))
+                })?;
+            parts.push(Part::Field(field));
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        if !parts.iter().any(|part| matches!(part, Part::Field(_))) {
+            return Err(format!("title template {:?} has no {{field}}", template));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The template filled in from `fields`. It stops before the first
+    /// field not known yet, along with the text leading up to it, so a
+    /// request that has not reached its controller reads `GET /posts`
+    /// rather than `GET /posts → #`. None until the first field is known.
+    pub fn render(&self, fields: &TitleFields) -> Option<Title> {
+        let mut text = String::new();
+        let mut path = None;
+        let mut pending = "";
+        let mut complete = true;
+        for part in &self.parts {
+            match part {
+                Part::Text(literal) => pending = literal,
+                Part::Field(field) => {
+                    let Some(value) = fields.get(*field) else {
+                        complete = false;
+                        break;
+                    };
+                    text.push_str(pending);
+                    pending = "";
+                    if *field == Field::Path {
+                        path = Some(text.len()..text.len() + value.len());
+                    }
+                    text.push_str(value);
+                }
+            }
+        }
+        if text.is_empty() {
+            return None;
+        }
+        if complete {
+            text.push_str(pending);
+        }
+        Some(Title { text, path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template = TitleTemplate::parse("{method} {path} → {controller}#{action}").unwrap();
+        let mut fields = TitleFields::default();
+        assert_eq!(template.render(&fields), None);
+
+        fields.observe(r#"[req-1] Started GET "/posts?page=2" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#);
+        assert_eq!(
+            template.render(&fields),
+            Some(Title {
+                text: "GET /posts?page=2".to_string(),
+                path: Some(4..17),
+            })
+        );
+
+        fields.observe("[req-1] Processing by Admin::PostsController#index as HTML");
+        assert_eq!(fields.format.as_deref(), Some("HTML"));
+        let title = template.render(&fields).unwrap();
+        assert_eq!(title.text, "GET /posts?page=2 → Admin::PostsController#index");
+        assert_eq!(&title.text[title.path.unwrap()], "/posts?page=2");
+
+        assert_eq!(fields.controller_action().as_deref(), Some("Admin::Posts#index"));
+        assert!(fields.handled_by("admin::posts"));
+        assert!(fields.handled_by("Admin::PostsController"));
+        assert!(!fields.handled_by("Posts"));
+
+        let template = TitleTemplate::parse("[{format}] {action}").unwrap();
+        assert_eq!(template.render(&fields).unwrap().text, "[HTML] index");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(TitleTemplate::parse("{method} {route}").is_err());
+        assert!(TitleTemplate::parse("{method").is_err());
+        assert!(TitleTemplate::parse("no fields").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Which lines the shorter views of a request show: simple mode keeps the
+//! request, its parameters and queries, summary mode only its start, end,
+//! exceptions and slow queries.
+
+use crate::log_parser::{RE_COMPLETED, is_error_line};
+use crate::sql_info::query_duration_ms;
+use regex::Regex;
+use std::sync::LazyLock;
+
+static RE_STARTED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"Started (?P<method>[A-Z]+) "(?P<path>[^"]+)""#).unwrap());
+static RE_PROCESSING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"Processing by (?P<controller>[\w:]+)#(?P<action>\w+) as (?P<format>\w+)"#)
+        .unwrap()
+});
+static RE_PARAMETERS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"Parameters: \{(?P<params>.*)\}"#).unwrap());
+static RE_SQL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\(\d+\.\d+ms\)\s+(SELECT|INSERT|UPDATE|DELETE)\s"#).unwrap());
+static RE_CONTINUATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"↳"#).unwrap());
+
+/// Queries at least this slow stay in summary mode.
+const SUMMARY_SLOW_QUERY_MS: f64 = 100.0;
+
+/// How much of a request is shown; lucy cycles through them with `s`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    #[default]
+    Full,
+    /// Requests, parameters and queries, formatted
+    Simple,
+    /// Only the start and end of a request, its exceptions and its slow
+    /// queries
+    Summary,
+}
+
+impl Verbosity {
+    pub fn next(self) -> Self {
+        match self {
+            Verbosity::Full => Verbosity::Simple,
+            Verbosity::Simple => Verbosity::Summary,
+            Verbosity::Summary => Verbosity::Full,
+        }
+    }
+
+    /// Whether a line is shown at this verbosity.
+    pub fn shows(self, line: &str) -> bool {
+        match self {
+            Verbosity::Full => true,
+            Verbosity::Simple => is_simple_log_line(line),
+            Verbosity::Summary => is_summary_log_line(line),
+        }
+    }
+}
+
+/// The message after the last `] `, past the request id and any tags.
+pub fn core_message(line: &str) -> &str {
+    if let Some(index) = line.rfind("] ") {
+        line.split_at(index + 2).1
+    } else {
+        line
+    }
+}
+
+/// Whether simple mode shows a line with this `core_message`, other than
+/// a `Completed` line.
+pub fn is_simple_core_message(core_message: &str) -> bool {
+    RE_STARTED.is_match(core_message)
+        || RE_PROCESSING.is_match(core_message)
+        || RE_PARAMETERS.is_match(core_message)
+        || RE_SQL.is_match(core_message)
+        || RE_CONTINUATION.is_match(core_message)
+}
+
+/// Whether simple mode shows this line, without formatting it.
+pub fn is_simple_log_line(line: &str) -> bool {
+    let core_message = core_message(line);
+    RE_COMPLETED.is_match(core_message) || is_simple_core_message(core_message)
+}
+
+/// Whether summary mode shows this line.
+pub fn is_summary_log_line(line: &str) -> bool {
+    let core_message = core_message(line);
+    RE_STARTED.is_match(core_message)
+        || RE_COMPLETED.is_match(core_message)
+        || is_error_line(line)
+        || query_duration_ms(core_message).is_some_and(|ms| ms >= SUMMARY_SLOW_QUERY_MS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_summary_log_line() {
+        assert!(is_summary_log_line(r#"[req-1] Started GET "/posts" for 127.0.0.1"#));
+        assert!(is_summary_log_line("[req-1] Completed 200 OK in 12ms"));
+        assert!(is_summary_log_line("[req-1] NoMethodError (undefined method `name' for nil):"));
+        assert!(is_summary_log_line(r#"[req-1] Post Load (250.1ms)  SELECT "posts".* FROM "posts""#));
+        assert!(!is_summary_log_line(r#"[req-1] Post Load (0.5ms)  SELECT "posts".* FROM "posts""#));
+        assert!(!is_summary_log_line("[req-1] Processing by PostsController#index as HTML"));
+        assert_eq!(Verbosity::Summary.next(), Verbosity::Full);
+    }
+}
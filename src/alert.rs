@@ -0,0 +1,100 @@
+//! Drawing attention to requests that fail while lucy watches a stream: the
+//! status bar flashes, and a terminal bell or desktop notification can go off.
+
+use crate::config::AlertConfig;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the status bar stays highlighted after an alert.
+const FLASH_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Default)]
+pub struct Alerts {
+    pub config: AlertConfig,
+    flash_until: Option<Instant>,
+}
+
+impl Alerts {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            flash_until: None,
+        }
+    }
+
+    /// Flashes the status bar and sends the notifications the config asks for.
+    pub fn fire(&mut self, now: Instant, title: &str) {
+        self.flash_until = Some(now + FLASH_DURATION);
+        if self.config.bell {
+            let mut stdout = io::stdout().lock();
+            let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+        }
+        if self.config.desktop {
+            notify_desktop(title);
+        }
+    }
+
+    pub fn flashing(&self, now: Instant) -> bool {
+        self.flash_until.is_some_and(|until| now < until)
+    }
+
+    /// Ends a flash whose time is up. Returns true when it did.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.flash_until.is_some_and(|until| now >= until) {
+            self.flash_until = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// Shows `title` with `notify-send`, or `osascript` on macOS. Failures,
+/// including a missing command, are only logged.
+fn notify_desktop(title: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let quoted = title.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!("display notification \"{}\" with title \"lucy\"", quoted),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["lucy", title]);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Waited for on its own thread, so the child is reaped without blocking
+    thread::spawn(move || {
+        if let Err(e) = command.status() {
+            tracing::debug!("Desktop notification failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flash() {
+        let start = Instant::now();
+        let mut alerts = Alerts::default();
+        assert!(!alerts.flashing(start));
+
+        alerts.fire(start, "GET /posts");
+        assert!(alerts.flashing(start + Duration::from_millis(100)));
+        assert!(!alerts.tick(start + Duration::from_millis(100)));
+
+        let later = start + FLASH_DURATION;
+        assert!(!alerts.flashing(later));
+        assert!(alerts.tick(later));
+        assert!(!alerts.tick(later));
+    }
+}
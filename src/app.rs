@@ -1,296 +1,2610 @@
-use crate::app_state::{AppState, LogEntry};
-use crate::app_view::{AppView, ScrollDirection};
+use crate::alert::Alerts;
+use crate::app_state::{AppState, DisplayedEntries, GroupKind, LogEntry, LogGroup, UNASSIGNED_ID};
+use crate::archive::Archive;
+use crate::app_view::{AppView, PanelSearch, ScrollDirection, TimelineScope};
+use crate::clipboard;
+use crate::columns::{self, Column};
+use crate::command::{self, Command, Filter, SortKey};
+use crate::config::{Config, RestartAction, StatusCodes};
+use crate::db::explain::{self, Explain};
+use crate::endpoint::{self, EndpointSummary};
+use crate::export::{self, ExportOptions, RequestSerializer};
+use crate::file_index::{self, FileIndex};
+use crate::editor;
+use crate::filter;
+use crate::hooks::Hooks;
+use crate::i18n::fill;
+use crate::input::{self, InputLine, LoadProgress, Source};
+use crate::journal::Journal;
+use crate::jsonl::JsonLines;
 use crate::layout::Panel;
+use crate::log_parser;
+use crate::metrics::{RateMeter, TRAFFIC_SECONDS, TrafficMeter};
 use crate::panel_components;
-use crossterm::event::{self, Event, KeyCode};
+use crate::plain_view;
+use crate::recording::{Playback, Recorder, Record};
+use crate::sampling::Sampler;
+use crate::setup;
+use crate::simple_formatter::Verbosity;
+use crate::sql_format;
+use crate::sql_info::SqlRow;
+use crate::stats::RequestStats;
+use crate::state_file::State;
+use crate::supervisor::{self, Supervisor};
+use crate::text_measure;
+use crate::timestamp::Timestamps;
+use crate::theme::Palette;
+use crate::time_format::Precision;
+use crate::time_headers::{self, GroupBy, ListRow, Slot};
+use crate::time_window::TimeWindow;
+use crate::toast::{ToastLevel, Toasts};
+use crate::tutorial::Tutorial;
+use crate::version::UpdateNotice;
+use crate::view_model;
+use chrono::Local;
+use crossterm::event::{self, Event, EventStream, KeyCode};
+use futures::StreamExt;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const SCROLL_UNIT: usize = 1;
 const SCROLL_PAGE_SIZE: usize = 10;
 const REQUEST_SKIP_COUNT: usize = 3;
+// Rows above the detail panel's text: its border and padding
+const DETAIL_TEXT_TOP: u16 = 2;
+const LOAD_PROGRESS_WIDTH: u16 = 60;
+const LOAD_PROGRESS_HEIGHT: u16 = 3;
+const ABOUT_WIDTH: u16 = 64;
+const ABOUT_HEIGHT: u16 = 11;
+const TUTORIAL_WIDTH: u16 = 72;
+const TUTORIAL_HEIGHT: u16 = 12;
+const TOAST_MAX_WIDTH: u16 = 60;
+const TOAST_HEIGHT: u16 = 3;
+const NOTIFICATIONS_WIDTH: u16 = 72;
+const REQUEST_INFO_WIDTH: u16 = 72;
+const REQUEST_INFO_HEIGHT: u16 = 9;
+const NOTE_WIDTH: u16 = 64;
+const NOTE_HEIGHT: u16 = 7;
+const BULK_ACTIONS_WIDTH: u16 = 48;
+const BULK_ACTIONS_HEIGHT: u16 = 9;
+const COLUMN_CHOOSER_WIDTH: u16 = 56;
+// Every column, inside the border and padding
+const COLUMN_CHOOSER_HEIGHT: u16 = Column::ALL.len() as u16 + 4;
+const LINE_POPUP_WIDTH: u16 = 100;
+const EXPLAIN_WIDTH: u16 = 120;
+const DEFAULT_EXPORT_DIR: &str = "lucy-export";
+const PREVIEW_MAX_WIDTH: u16 = 80;
+// How long a request stays selected before its preview shows
+const PREVIEW_DELAY: Duration = Duration::from_millis(750);
+// Longest stretch spent ingesting a burst of lines before redrawing
+const DRAIN_BUDGET: Duration = Duration::from_millis(100);
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+// A digit this soon after Esc is Alt+digit from a terminal that split the sequence
+const ESC_ALT_WINDOW: Duration = Duration::from_millis(50);
+/// Lines read from a command while paused; later ones are dropped.
+const HELD_LINES_LIMIT: usize = 65_536;
 
 pub enum SearchTarget {
     RequestList,
     DetailLog,
+    /// Jump to a time or request id using the file index
+    Jump,
+    /// A command line (`:filter status=500`), or the number of a request
+    /// to go to (`:25`)
+    Command,
+    /// Directory to export the matching requests to
+    Export,
+    /// Span of start times to narrow the list to (`T`)
+    TimeWindow,
+    /// Note on the selected request (`n` outside a detail search)
+    Note,
+}
+
+/// Lines of the detail panel picked by dragging the mouse, as chronological
+/// entry indices.
+pub struct DragSelection {
+    anchor: usize,
+    end: usize,
+}
+
+impl DragSelection {
+    pub fn contains(&self, entry: usize) -> bool {
+        self.anchor.min(self.end) <= entry && entry <= self.anchor.max(self.end)
+    }
+}
+
+/// When the selected request was selected, for the list preview.
+struct PreviewTimer {
+    request_id: String,
+    since: Instant,
+    shown: bool,
+}
+
+/// A navigation or filter change that `.` can repeat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    /// `j` and `k`, with the count typed before them
+    Move { down: bool, steps: usize },
+    /// Ctrl+d and Ctrl+u
+    HalfPage { down: bool },
+    /// PageDown and PageUp
+    Page { down: bool },
+    /// `:filter` and the presets
+    Filter(Filter),
+    CycleKind,
+    CycleSource,
+    StarredOnly,
+    ErrorsOnly,
+}
+
+/// Actions taken so far, for `.`. Only the last one is kept.
+#[derive(Default)]
+struct ActionHistory {
+    last: Option<Action>,
+}
+
+impl ActionHistory {
+    fn record(&mut self, action: Action) {
+        self.last = Some(action);
+    }
+
+    fn last(&self) -> Option<&Action> {
+        self.last.as_ref()
+    }
+}
+
+/// An input, with what was tracked while reading it.
+struct InputState {
+    source: Source,
+    /// Byte offset of the next line read from a file
+    offset: u64,
+    /// `LoadProgress::resets` already handled
+    resets: u64,
 }
 
 pub struct App {
+    pub config: Config,
     pub state: AppState,
     pub app_view: AppView,
     pub copy_mode_enabled: bool,
-    pub simple_mode_enabled: bool,
+    /// Off with `--no-mouse` or when the terminal cannot report the mouse
+    pub mouse_enabled: bool,
+    /// Lines the detail panel shows (`s`)
+    pub verbosity: Verbosity,
     pub search_mode: Option<SearchTarget>,
     pub search_query: String,
     pub filtered_indices: Option<Vec<usize>>,
-    pub detail_search_query: String,
+    pub kind_filter: Option<GroupKind>,
+    /// Show only requests from one input (`F`)
+    pub source_filter: Option<usize>,
+    /// Show only starred requests (`B`)
+    pub starred_only: bool,
+    /// Show only failed requests (`!`)
+    pub errors_only: bool,
+    /// Show only requests started within a span of time (`T`)
+    pub time_window: Option<TimeWindow>,
+    /// Show only requests to one endpoint, picked in the endpoint view
+    pub endpoint_filter: Option<String>,
+    /// Show only requests completed with these codes (`:filter status=5xx`)
+    pub status_filter: Option<StatusCodes>,
+    /// Show only requests taking at least this many milliseconds (`:filter slow=500`)
+    pub slow_filter: Option<u64>,
+    /// Show only requests writing to the database (`:filter writes`)
+    pub writes_only: bool,
+    /// Show only likely double submits (`:filter duplicates`)
+    pub duplicates_only: bool,
+    /// Show only requests with lines from a command's stderr (`:filter stderr`)
+    pub stderr_only: bool,
+    /// Show only requests handled by this controller (`:filter controller=Posts`)
+    pub controller_filter: Option<String>,
+    /// Order of the list other than newest first (`:sort`)
+    pub sort: Option<SortKey>,
+    /// Row selected in the endpoint view (`e`), shown in place of the
+    /// request list while set
+    pub endpoint_cursor: Option<usize>,
+    /// Columns of the request list before each title
+    pub columns: Vec<Column>,
+    /// Row selected in the column chooser (`C`), which is open while set
+    pub column_cursor: Option<usize>,
+    /// Headers splitting the request list by time (`H`)
+    pub group_by: Option<GroupBy>,
+    inputs: Vec<InputState>,
+    pub file_index: Option<FileIndex>,
+    pub jump_query: String,
+    pub command_query: String,
+    pub export_query: String,
+    pub time_window_query: String,
+    /// Note being written for the selected request
+    pub note_query: String,
+    /// Format of exports and of `y`, changed with Tab in the export prompt
+    pub export_format: &'static dyn RequestSerializer,
+    /// Digits typed before a command, as in `25G`
+    pub count_prefix: String,
+    /// The first `g` of `gg` was typed
+    pub g_pending: bool,
+    /// When Esc was last pressed, for Alt+digit sent as Esc and the digit
+    esc_at: Option<Instant>,
+    /// What `.` repeats
+    history: ActionHistory,
+    /// Chronological index of the entry jumped to from the SQL panel, or under
+    /// the line cursor.
+    pub highlighted_entry: Option<usize>,
+    /// Lines dragged over in the detail panel, copied on release
+    pub drag_selection: Option<DragSelection>,
+    /// The mouse button is held down on a drag selection
+    selecting: bool,
+    /// j/k move a cursor over the detail panel's lines instead of scrolling (`v`)
+    pub line_select: bool,
+    /// Custom grouping key from `--group-key-regex`
+    pub group_key: Option<Regex>,
+    /// Reads when each line was written
+    pub timestamps: Timestamps,
+    pub journal: Option<Journal>,
+    /// Set when `check_updates` is on
+    pub update_notice: Option<UpdateNotice>,
+    /// First-run overlay, until finished or skipped
+    pub tutorial: Option<Tutorial>,
+    /// Set by `--record`
+    pub recorder: Option<Recorder>,
+    /// Set by `--archive`
+    pub archive: Option<Archive>,
+    /// The command run after `--`, whose output is the input
+    pub supervisor: Option<Arc<Supervisor>>,
+    /// Set by `--playback`, until the recording has been replayed
+    pub playback: Option<Playback>,
+    /// Set by `--output jsonl`, until stdout is closed
+    pub jsonl: Option<JsonLines>,
+    pub toasts: Toasts,
+    /// Input is left waiting in the channel (`p`), and only the view is
+    /// frozen for a command
+    pub paused: bool,
+    /// Read while paused from a command, which would block writing its
+    /// logs if nobody read them; ingested once resumed
+    held_lines: VecDeque<InputLine>,
+    /// Read while paused once `held_lines` was full
+    dropped_while_paused: usize,
+    /// Files are followed (`--follow`)
+    pub follow: bool,
+    /// Every input reached its end; the requests read stay browsable
+    pub input_ended: bool,
+    /// Files opened again with `R`, for the run loop to read from
+    resumed_input: Option<tokio::sync::mpsc::Receiver<InputLine>>,
+    /// Kept alive for as long as its lines are read
+    _resumed_reader: Option<input::Reader>,
+    /// Lines waiting to be ingested, as of the last tick
+    pub queued_lines: usize,
+    pub lines_per_second: f64,
+    pub memory_estimate: usize,
+    ingest_rate: RateMeter,
+    /// Requests and failures per second as they arrive, for the list title
+    pub traffic: TrafficMeter,
+    /// Stats of the requests a filter matched, shown instead of the totals
+    pub matching_stats: Option<RequestStats>,
+    /// EXPLAIN popup (`E`)
+    pub explain: Option<Explain>,
+    /// Opened in `$EDITOR` once the key handler returns (`o`)
+    pending_edit: Option<editor::Location>,
+    pub alerts: Alerts,
+    hooks: Hooks,
+    preview: Option<PreviewTimer>,
+    /// Something on screen changed since the last draw
+    dirty: bool,
+    /// When `[auto_clear]` last looked for requests to drop
+    last_auto_clear: Instant,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
+        let mut state = AppState::new();
+        state.sampler = config
+            .sampling
+            .rate
+            .map(|rate| Sampler::new(rate, config.sampling.slow_ms));
+        state.retention = config.retention.clone();
+        state.statuses = config.status.clone();
+        state.compaction = config.compaction.enabled;
+        state.max_pinned = config.alerts.pin;
+        let mut app_view = AppView::new();
+        app_view.plain = config.plain;
+        crate::theme::set_theme(config.theme, &config.colors);
+        let alerts = Alerts::new(config.alerts.clone());
+        let hook_options = ExportOptions {
+            redact: config.export.redact,
+            times: config.times(),
+            ..Default::default()
+        };
+        let hooks = Hooks::new(config.hooks.clone(), hook_options);
+        let columns = config.list.columns(config.status.show_codes);
+        let group_by = config.list.group_by;
+
         Self {
-            state: AppState::new(),
-            app_view: AppView::new(),
+            config,
+            state,
+            app_view,
             copy_mode_enabled: false,
-            simple_mode_enabled: false,
+            mouse_enabled: true,
+            pending_edit: None,
+            verbosity: Verbosity::Full,
             search_mode: None,
             search_query: String::new(),
             filtered_indices: None,
-            detail_search_query: String::new(),
+            kind_filter: None,
+            source_filter: None,
+            starred_only: false,
+            errors_only: false,
+            time_window: None,
+            endpoint_filter: None,
+            status_filter: None,
+            slow_filter: None,
+            writes_only: false,
+            duplicates_only: false,
+            stderr_only: false,
+            controller_filter: None,
+            sort: None,
+            endpoint_cursor: None,
+            columns,
+            column_cursor: None,
+            group_by,
+            inputs: Vec::new(),
+            file_index: None,
+            jump_query: String::new(),
+            command_query: String::new(),
+            export_query: String::new(),
+            time_window_query: String::new(),
+            note_query: String::new(),
+            export_format: export::FORMATS[0],
+            count_prefix: String::new(),
+            g_pending: false,
+            esc_at: None,
+            history: ActionHistory::default(),
+            highlighted_entry: None,
+            drag_selection: None,
+            selecting: false,
+            line_select: false,
+            group_key: None,
+            timestamps: Timestamps::default(),
+            journal: None,
+            update_notice: None,
+            tutorial: None,
+            recorder: None,
+            archive: None,
+            supervisor: None,
+            playback: None,
+            jsonl: None,
+            toasts: Toasts::default(),
+            paused: false,
+            held_lines: VecDeque::new(),
+            dropped_while_paused: 0,
+            follow: false,
+            input_ended: false,
+            resumed_input: None,
+            _resumed_reader: None,
+            queued_lines: 0,
+            lines_per_second: 0.0,
+            memory_estimate: 0,
+            ingest_rate: RateMeter::default(),
+            traffic: TrafficMeter::default(),
+            matching_stats: None,
+            explain: None,
+            alerts,
+            hooks,
+            preview: None,
+            dirty: true,
+            last_auto_clear: Instant::now(),
         }
     }
 
     pub fn render(&mut self, f: &mut ratatui::Frame) {
-        if self.copy_mode_enabled {
+        if crate::layout::too_small(f.area()) {
+            // Nothing to click or scroll until the terminal grows again
+            self.app_view.layout_info = crate::layout::LayoutInfo::new();
+            f.render_widget(panel_components::build_too_small_notice(self, f.area()), f.area());
+            return;
+        }
+        if self.app_view.plain {
+            self.app_view.layout_info = crate::layout::calculate_plain_layout(f.area());
+            plain_view::render(f, self);
+        } else if self.copy_mode_enabled {
             let focused = self.app_view.focused_panel;
             self.app_view.layout_info =
                 crate::layout::calculate_single_panel_layout(f.area(), focused);
             let region = self.app_view.layout_info.region(focused);
             match focused {
                 Panel::RequestList => {
-                    let widget = panel_components::build_list_component(self);
-                    f.render_widget(widget, region);
+                    let view = view_model::list(self);
+                    f.render_widget(panel_components::build_list_component(&view), region);
                 }
                 Panel::RequestDetail => {
-                    let widget = panel_components::build_detail_component(self);
-                    f.render_widget(widget, region);
+                    let view = view_model::detail(self);
+                    f.render_widget(panel_components::build_detail_component(&view), region);
                 }
                 Panel::SqlInfo => {
-                    let widget = panel_components::build_sql_component(self);
-                    f.render_widget(widget, region);
+                    let view = view_model::sql(self);
+                    f.render_widget(panel_components::build_sql_component(&view), region);
                 }
             }
         } else {
-            self.app_view.layout_info =
-                crate::layout::calculate_layout(f.area(), &self.app_view.panel_ratios);
+            self.app_view.layout_info = crate::layout::calculate_layout(
+                f.area(),
+                &self.app_view.panel_ratios,
+                self.app_view.timeline.is_some(),
+                self.app_view.show_stats,
+            );
+
+            let request_list_region = self.app_view.layout_info.region(Panel::RequestList);
+            let request_detail_region = self.app_view.layout_info.region(Panel::RequestDetail);
+            let sql_info_region = self.app_view.layout_info.region(Panel::SqlInfo);
+
+            let request_list = view_model::list(self);
+            f.render_widget(
+                panel_components::build_list_component(&request_list),
+                request_list_region,
+            );
+
+            let detail_panel = view_model::detail(self);
+            f.render_widget(
+                panel_components::build_detail_component(&detail_panel),
+                request_detail_region,
+            );
+
+            let sql_panel = view_model::sql(self);
+            f.render_widget(panel_components::build_sql_component(&sql_panel), sql_info_region);
+
+            if let Some(timeline_region) = self.app_view.layout_info.timeline() {
+                let timeline = view_model::timeline(self);
+                f.render_widget(
+                    panel_components::build_timeline_panel(&timeline, timeline_region.width),
+                    timeline_region,
+                );
+            }
+
+            if let Some(stats_region) = self.app_view.layout_info.stats() {
+                panel_components::render_stats_panel(f, stats_region, self);
+            }
+
+            let status_bar = view_model::status_bar(self);
+            f.render_widget(
+                panel_components::build_status_bar(&status_bar),
+                self.app_view.layout_info.status_bar(),
+            );
+
+            if let Some(gauge) = panel_components::build_load_progress(self) {
+                let area = crate::layout::bottom_right_rect(
+                    self.app_view.layout_info.status_bar().union(request_detail_region),
+                    LOAD_PROGRESS_WIDTH,
+                    LOAD_PROGRESS_HEIGHT,
+                );
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(gauge, area);
+            }
+
+            self.render_toasts(f, self.app_view.layout_info.status_bar().union(request_detail_region));
+            self.render_preview(f);
+
+            if self.app_view.show_notifications {
+                let popup = panel_components::build_notifications_popup(self);
+                let area = crate::layout::centered_rect(
+                    f.area(),
+                    NOTIFICATIONS_WIDTH,
+                    f.area().height.saturating_sub(4),
+                );
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if matches!(self.search_mode, Some(SearchTarget::Note)) {
+                let popup = panel_components::build_note_popup(self);
+                let area = crate::layout::centered_rect(f.area(), NOTE_WIDTH, NOTE_HEIGHT);
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if self.app_view.show_request_info
+                && let Some(popup) = panel_components::build_request_info_popup(self)
+            {
+                let area =
+                    crate::layout::centered_rect(f.area(), REQUEST_INFO_WIDTH, REQUEST_INFO_HEIGHT);
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if self.app_view.show_line_popup
+                && let Some((position, text)) = self.selected_line()
+            {
+                let width = LINE_POPUP_WIDTH.min(f.area().width);
+                let popup = panel_components::build_line_popup(self, position, &text);
+                // Borders and padding take two columns on each side
+                let height = popup.line_count(width.saturating_sub(4));
+                let area = crate::layout::centered_rect(
+                    f.area(),
+                    width,
+                    u16::try_from(height).unwrap_or(u16::MAX),
+                );
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if let Some(explain) = &self.explain {
+                let popup = panel_components::build_explain_popup(self, explain);
+                let area = crate::layout::centered_rect(
+                    f.area(),
+                    EXPLAIN_WIDTH,
+                    f.area().height.saturating_sub(4),
+                );
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if self.app_view.show_bulk_actions {
+                let popup = panel_components::build_bulk_actions_popup(self);
+                let area =
+                    crate::layout::centered_rect(f.area(), BULK_ACTIONS_WIDTH, BULK_ACTIONS_HEIGHT);
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if let Some(cursor) = self.column_cursor {
+                let popup = panel_components::build_column_chooser_popup(self, cursor);
+                let area = crate::layout::centered_rect(
+                    f.area(),
+                    COLUMN_CHOOSER_WIDTH,
+                    COLUMN_CHOOSER_HEIGHT,
+                );
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+
+            if self.app_view.show_about {
+                let about = panel_components::build_about_popup(self);
+                let area = crate::layout::centered_rect(f.area(), ABOUT_WIDTH, ABOUT_HEIGHT);
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(about, area);
+            }
+
+            if let Some(tutorial) = &self.tutorial {
+                let popup = panel_components::build_tutorial_popup(self, tutorial);
+                let area = crate::layout::centered_rect(f.area(), TUTORIAL_WIDTH, TUTORIAL_HEIGHT);
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(popup, area);
+            }
+        }
+    }
+
+    /// Shows the selected request's summary and first error next to its row
+    /// once it has stayed selected for `PREVIEW_DELAY`.
+    fn render_preview(&self, f: &mut ratatui::Frame) {
+        let shown = self.preview.as_ref().is_some_and(|preview| preview.shown);
+        let overlay_open = self.app_view.show_about
+            || self.app_view.show_notifications
+            || self.app_view.show_request_info
+            || self.app_view.show_bulk_actions
+            || matches!(self.search_mode, Some(SearchTarget::Note))
+            || self.column_cursor.is_some()
+            || self.tutorial.is_some();
+        if !shown
+            || overlay_open
+            || self.search_mode.is_some()
+            || self.app_view.focused_panel != Panel::RequestList
+        {
+            return;
+        }
+        let Some((preview, width)) = panel_components::build_list_preview(self) else {
+            return;
+        };
+        let width = width.min(PREVIEW_MAX_WIDTH);
+
+        let list = self.app_view.layout_info.region(Panel::RequestList);
+        let visual_index = self.filtered_position(self.state.selected_index);
+        // Time headers take rows of their own
+        let Some(row) = self
+            .list_layout(self.app_view.get_scroll_offset(Panel::RequestList))
+            .iter()
+            .position(|row| matches!(row, ListRow::Request(position) if *position == visual_index))
+        else {
+            return;
+        };
+        // Below the top border and padding of the list
+        let header_rows = if self.app_view.plain { 1 } else { 2 };
+        let y = list.y + header_rows + row as u16;
+        let height = preview.line_count(width) as u16;
+        let area = crate::layout::anchored_rect(f.area(), list.right(), y, width, height);
+        f.render_widget(ratatui::widgets::Clear, area);
+        f.render_widget(preview, area);
+    }
+
+    /// Restarts the preview delay when the selection changes. Returns true
+    /// when the preview appeared or went away.
+    fn tick_preview(&mut self, now: Instant) -> bool {
+        let selected = self.state.selected_request_id();
+        match &mut self.preview {
+            Some(preview) if Some(&preview.request_id) == selected => {
+                let due = now >= preview.since + PREVIEW_DELAY;
+                let changed = due != preview.shown;
+                preview.shown = due;
+                changed
+            }
+            _ => {
+                let was_shown = self.preview.as_ref().is_some_and(|preview| preview.shown);
+                self.preview = selected.map(|request_id| PreviewTimer {
+                    request_id: request_id.clone(),
+                    since: now,
+                    shown: false,
+                });
+                was_shown
+            }
+        }
+    }
+
+    /// Samples the ingest counters for the status bar. Returns true when
+    /// something it shows changed.
+    fn tick_metrics(&mut self, now: Instant, queued_lines: usize) -> bool {
+        self.ingest_rate.sample(now, self.state.ingest.lines);
+        let shown = |app: &Self| {
+            (
+                app.lines_per_second.round() as u64,
+                app.memory_estimate / 1024,
+                app.queued_lines,
+                app.traffic.requests(now, TRAFFIC_SECONDS),
+            )
+        };
+        let before = shown(self);
+        self.lines_per_second = self.ingest_rate.per_second();
+        self.memory_estimate = self.state.memory_estimate();
+        self.queued_lines = queued_lines;
+        shown(self) != before
+    }
+
+    /// Drops the requests finished longer ago than `[auto_clear]` allows,
+    /// once every `interval_seconds`. Returns whether any were dropped.
+    fn tick_auto_clear(&mut self, now: Instant) -> bool {
+        let auto_clear = &self.config.auto_clear;
+        let Some(minutes) = auto_clear.after_minutes else {
+            return false;
+        };
+        if now.duration_since(self.last_auto_clear) < Duration::from_secs(auto_clear.interval_seconds) {
+            return false;
+        }
+        self.last_auto_clear = now;
+        let Some(cutoff) = now.checked_sub(Duration::from_secs(minutes * 60)) else {
+            return false;
+        };
+        let cleared = self.state.clear_finished_before(cutoff);
+        if cleared == 0 {
+            return false;
+        }
+        tracing::debug!("Auto-cleared {} requests", cleared);
+        self.sync_after_removal();
+        true
+    }
+
+    /// Applies the per-class `[retention]` limits. Returns whether any
+    /// requests were dropped.
+    fn tick_retention(&mut self) -> bool {
+        if !self.state.apply_retention() {
+            return false;
+        }
+        self.sync_after_removal();
+        true
+    }
+
+    /// Stacks the visible toasts above the bottom-right corner of `area`,
+    /// newest at the bottom.
+    fn render_toasts(&self, f: &mut ratatui::Frame, mut area: ratatui::layout::Rect) {
+        if panel_components::build_load_progress(self).is_some() {
+            area.height = area.height.saturating_sub(LOAD_PROGRESS_HEIGHT);
+        }
+        for toast in self.toasts.visible().collect::<Vec<_>>().into_iter().rev() {
+            let width = (text_measure::width(&toast.message) as u16 + 4).min(TOAST_MAX_WIDTH);
+            let rect = crate::layout::bottom_right_rect(area, width, TOAST_HEIGHT);
+            if rect.height < TOAST_HEIGHT {
+                break;
+            }
+            f.render_widget(ratatui::widgets::Clear, rect);
+            f.render_widget(panel_components::build_toast(toast), rect);
+            area.height -= TOAST_HEIGHT;
+        }
+    }
+
+    pub async fn run<B: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut ratatui::Terminal<B>,
+        mut rx: tokio::sync::mpsc::Receiver<InputLine>,
+    ) -> color_eyre::Result<()>
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        let mut events = EventStream::new();
+        // Wakes up for what changes without input: toast timeouts and the update check
+        let mut tick = tokio::time::interval(TICK_INTERVAL);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut update_seen = false;
+        let mut exit_seen = false;
+
+        loop {
+            if let Some(resumed) = self.resumed_input.take() {
+                rx = resumed;
+            }
+            if !self.paused && !self.held_lines.is_empty() {
+                self.dirty = true;
+                while let Some(line) = self.held_lines.pop_front() {
+                    self.ingest_line(line.source, &line.text);
+                }
+            }
+            let now = Instant::now();
+            if self.toasts.tick(now) | self.tick_preview(now) | self.alerts.tick(now) {
+                self.dirty = true;
+            }
+            if self.dirty {
+                terminal.draw(|f| {
+                    self.render(f);
+                })?;
+                self.dirty = false;
+            }
+
+            let next_record = self.playback.as_ref().and_then(Playback::next_due);
+            tokio::select! {
+                // A command is read from even while paused, see `held_lines`
+                line = rx.recv(), if !self.input_ended
+                    && (!self.paused || self.supervisor.is_some()) => match line
+                {
+                    Some(line) if self.paused => {
+                        self.hold_line(line);
+                        while let Ok(line) = rx.try_recv() {
+                            self.hold_line(line);
+                        }
+                    }
+                    Some(line) => {
+                        self.dirty = true;
+                        self.ingest_line(line.source, &line.text);
+                        let drain_deadline = Instant::now() + DRAIN_BUDGET;
+                        while let Ok(line) = rx.try_recv() {
+                            self.ingest_line(line.source, &line.text);
+                            if Instant::now() >= drain_deadline {
+                                break;
+                            }
+                        }
+                    }
+                    None => {
+                        self.input_ended = true;
+                        self.dirty = true;
+                        self.notify_input_ended();
+                    }
+                },
+                event = events.next() => match event {
+                    // Keys pressed during playback would make it diverge
+                    Some(Ok(event)) if self.playback.is_none() || is_ctrl_c(&event) => {
+                        // Resizes included, since the next draw must fill the new size
+                        self.dirty = true;
+                        if self.handle_event(event)? {
+                            return Ok(());
+                        }
+                        if let Some(location) = self.pending_edit.take() {
+                            // Its reader would take keys meant for the editor
+                            drop(std::mem::replace(&mut events, EventStream::new()));
+                            self.edit(terminal, &location)?;
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => tracing::debug!("Event read error: {:?}", e),
+                    None => return Ok(()),
+                },
+                _ = tokio::time::sleep_until(
+                    tokio::time::Instant::from_std(next_record.unwrap_or_else(Instant::now))
+                ), if next_record.is_some() => {
+                    self.dirty = true;
+                    if self.replay_due()? {
+                        return Ok(());
+                    }
+                }
+                _ = tick.tick() => {
+                    let now = Instant::now();
+                    if self.check_file_reset()
+                        | self.tick_metrics(now, rx.len() + self.held_lines.len())
+                        | self.tick_auto_clear(now)
+                        | self.tick_retention()
+                    {
+                        self.dirty = true;
+                    }
+                    if self.explain.as_mut().is_some_and(Explain::poll) {
+                        self.dirty = true;
+                    }
+                    // The update check finishes in the background
+                    if !update_seen
+                        && self.update_notice.as_ref().is_some_and(|notice| notice.get().is_some())
+                    {
+                        update_seen = true;
+                        self.dirty = true;
+                    }
+                    if let Some(supervisor) = &self.supervisor {
+                        if supervisor.forward_signals() {
+                            return Ok(());
+                        }
+                        if !exit_seen && let Some(status) = supervisor.exit_status() {
+                            exit_seen = true;
+                            self.notify_exit(status);
+                            self.dirty = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Files each line of input without drawing anything, until the input
+    /// ends or nobody reads the JSON lines anymore (`--output jsonl`).
+    pub async fn run_headless(&mut self, mut rx: tokio::sync::mpsc::Receiver<InputLine>) {
+        // Wakes up to apply retention and pass on signals to a command run after `--`
+        let mut tick = tokio::time::interval(TICK_INTERVAL);
+        while self.jsonl.is_some() {
+            tokio::select! {
+                line = rx.recv() => match line {
+                    Some(line) => self.ingest_line(line.source, &line.text),
+                    None => return,
+                },
+                _ = tick.tick() => {
+                    self.state.apply_retention();
+                    if self.supervisor.as_deref().is_some_and(Supervisor::forward_signals) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Applies recorded lines and events whose time has come. Returns true
+    /// when one of them asks to quit.
+    fn replay_due(&mut self) -> color_eyre::Result<bool> {
+        let Some(playback) = &mut self.playback else {
+            return Ok(false);
+        };
+        for record in playback.due() {
+            let quit = match record {
+                // Recordings do not keep sources
+                Record::Line(line) => {
+                    self.ingest_line(0, &line);
+                    false
+                }
+                Record::Event(event) => self.handle_event(event)?,
+            };
+            if quit {
+                return Ok(true);
+            }
+        }
+        if self.playback.as_ref().is_some_and(Playback::is_finished) {
+            self.playback = None;
+            let strings = self.config.locale.strings();
+            self.toasts.push(ToastLevel::Info, strings.toast_playback_finished);
+        }
+        Ok(false)
+    }
+
+    /// Returns true when the event asks to quit.
+    fn handle_event(&mut self, event: Event) -> color_eyre::Result<bool> {
+        self.record(Record::Event(event.clone()));
+        // Any activity hides the preview until the selection rests again
+        if let Some(preview) = &mut self.preview
+            && matches!(event, Event::Key(_) | Event::Mouse(_))
+        {
+            preview.since = Instant::now();
+        }
+        match event {
+            Event::Key(_) if is_ctrl_c(&event) => return Ok(true),
+            Event::Key(key) if self.search_mode.is_some() => self.handle_search_key(key),
+            Event::Key(key) => self.handle_normal_key(key)?,
+            Event::Mouse(mouse_event) if self.mouse_enabled && !self.copy_mode_enabled => {
+                self.handle_mouse_event(mouse_event);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn record(&mut self, record: Record) {
+        if let Some(recorder) = &mut self.recorder
+            && let Err(e) = recorder.record(&record)
+        {
+            tracing::error!("Failed to write recording: {}", e);
+            self.recorder = None;
+            let strings = self.config.locale.strings();
+            self.toasts.push(ToastLevel::Error, fill(strings.toast_recording_failed, &[&e]));
+        }
+    }
+
+    fn parse_line(&self, line: &str) -> Option<LogEntry> {
+        match &self.group_key {
+            Some(group_key) if self.config.generic => {
+                log_parser::parse_generic(line, group_key, &self.timestamps)
+            }
+            group_key => log_parser::parse_with(line, group_key.as_ref(), &self.timestamps),
+        }
+    }
+
+    /// Parses a line read from input `source` and files it under its request.
+    pub fn ingest_line(&mut self, source: usize, line: &str) {
+        self.check_file_reset();
+        if self.recorder.is_some() {
+            self.record(Record::Line(line.to_string()));
+        }
+        if let Some(archive) = &self.archive {
+            archive.write(line);
+        }
+        let (offset, historical) = self.inputs.get_mut(source).map_or((0, false), |input| {
+            let offset = input.offset;
+            input.offset += line.len() as u64;
+            // Already in the file when it was opened
+            let historical = input.resets == 0
+                && input
+                    .source
+                    .progress
+                    .as_ref()
+                    .is_some_and(|progress| offset < progress.total_bytes);
+            (offset, historical)
+        });
+
+        if let Some(mut entry) = self.parse_line(line) {
+            entry.source = source;
+            entry.stderr = self.inputs.get(source).is_some_and(|input| input.source.stderr);
+            if let Some(index) = &mut self.file_index {
+                index.record(offset, &entry);
+            }
+            if let Some(journal) = &mut self.journal {
+                match journal.append(&entry) {
+                    Ok(journal_ref) => entry.journal_ref = Some(journal_ref),
+                    Err(e) => tracing::error!("Failed to write journal: {}", e),
+                }
+            }
+            self.add_log_entry(entry, !historical);
+        }
+    }
+
+    fn filtered_position(&self, index: usize) -> usize {
+        match &self.filtered_indices {
+            Some(indices) => indices.iter().position(|&i| i == index).unwrap_or(0),
+            None => index,
+        }
+    }
+
+    fn reset_detail_view(&mut self) {
+        self.app_view.set_scroll_offset(Panel::RequestDetail, 0);
+        self.app_view.detail_search.current = None;
+        self.highlighted_entry = None;
+        self.line_select = false;
+        self.app_view.show_line_popup = false;
+        self.drag_selection = None;
+        self.selecting = false;
+        self.open_at_first_error();
+    }
+
+    /// Opens a pinned failure at the line naming its error, highlighted.
+    fn open_at_first_error(&mut self) {
+        let Some(group) = self.state.selected_group().filter(|group| group.pinned) else {
+            return;
+        };
+        let Some(entry) = group.first_error_index() else {
+            return;
+        };
+        let Some(position) = group.displayed(self.verbosity).position(entry) else {
+            return;
+        };
+        self.highlighted_entry = Some(entry);
+        self.app_view.set_scroll_offset(Panel::RequestDetail, position);
+    }
+
+    /// The selected group's entries the detail panel shows.
+    fn displayed(&self) -> Option<DisplayedEntries<'_>> {
+        let verbosity = self.verbosity;
+        self.state
+            .selected_group()
+            .map(|group| group.displayed(verbosity))
+    }
+
+    /// Chronological indices of the selected request's displayed entries
+    /// with the detail search text in them, ignoring case.
+    pub fn detail_matches(&self) -> Vec<usize> {
+        let query = self.app_view.detail_search.query.to_lowercase();
+        let Some(displayed) = self.displayed().filter(|_| !query.is_empty()) else {
+            return Vec::new();
+        };
+        let verbosity = self.verbosity;
+        displayed
+            .from(0)
+            .filter(|(_, entry)| {
+                entry.rendered.lines(verbosity).is_some_and(|lines| {
+                    lines
+                        .iter()
+                        .any(|line| line.to_string().to_lowercase().contains(&query))
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Goes to the first match from the top of the detail panel on, as the
+    /// search text is typed.
+    fn search_detail(&mut self) {
+        let Some(displayed) = self.displayed() else {
+            return;
+        };
+        let top = self.app_view.get_scroll_offset(Panel::RequestDetail);
+        let first = displayed.index(top).unwrap_or(0);
+        let matches = self.detail_matches();
+        let found = matches.iter().find(|&&entry| entry >= first).or(matches.first());
+        self.go_to_detail_match(found.copied());
+    }
+
+    /// Goes to the match after the current one, back to the first after the
+    /// last (`n`).
+    fn next_detail_match(&mut self) {
+        let matches = self.detail_matches();
+        let next = match self.app_view.detail_search.current {
+            Some(current) => matches.iter().find(|&&entry| entry > current),
+            None => None,
+        };
+        self.go_to_detail_match(next.or(matches.first()).copied());
+    }
+
+    fn go_to_detail_match(&mut self, entry: Option<usize>) {
+        self.app_view.detail_search.current = entry;
+        let position = entry
+            .zip(self.displayed())
+            .and_then(|(entry, displayed)| displayed.position(entry));
+        if let Some(position) = position {
+            self.place_line_cursor(position);
+        }
+    }
+
+    pub fn line_select_active(&self) -> bool {
+        self.line_select && self.app_view.focused_panel == Panel::RequestDetail
+    }
+
+    fn toggle_line_select(&mut self) {
+        self.line_select = !self.line_select;
+        self.app_view.show_line_popup = false;
+        if !self.line_select {
+            self.highlighted_entry = None;
+            return;
+        }
+        let Some(displayed) = self.displayed() else {
+            return;
+        };
+        // Start on a line jumped to from the SQL panel, or the top one on screen
+        if self
+            .highlighted_entry
+            .is_none_or(|entry| displayed.position(entry).is_none())
+        {
+            let offset = self.app_view.get_scroll_offset(Panel::RequestDetail);
+            self.highlighted_entry = displayed
+                .index(offset)
+                .or_else(|| displayed.index(displayed.len().checked_sub(1)?));
+        }
+    }
+
+    /// Moves the line cursor by `amount` displayed lines, scrolling to keep it on screen.
+    fn move_line_cursor(&mut self, amount: isize) {
+        let offset = self.app_view.get_scroll_offset(Panel::RequestDetail);
+        let position = match self
+            .highlighted_entry
+            .zip(self.displayed())
+            .and_then(|(entry, displayed)| displayed.position(entry))
+        {
+            Some(position) => position.saturating_add_signed(amount),
+            None => offset,
+        };
+        self.place_line_cursor(position);
+    }
+
+    /// Puts the line cursor on the displayed line at `position`, or the last
+    /// one, and scrolls it into view.
+    fn place_line_cursor(&mut self, position: usize) {
+        let Some(displayed) = self.displayed() else {
+            return;
+        };
+        let Some(last) = displayed.len().checked_sub(1) else {
+            return;
+        };
+        let position = position.min(last);
+        let offset = self.app_view.get_scroll_offset(Panel::RequestDetail);
+        let highlighted = displayed.index(position);
+
+        let top = if position < offset {
+            position
+        } else {
+            // Wrapped lines take several rows, so count rows rather than lines,
+            // going up from the cursor to touch only the lines on screen
+            let rows = |display_position: usize| {
+                displayed
+                    .index(display_position)
+                    .map_or(1, |entry| self.detail_rows(entry))
+            };
+            let viewport_height = self.app_view.viewport_height(Panel::RequestDetail);
+            let mut top = position;
+            let mut used = rows(position);
+            while top > offset && used + rows(top - 1) <= viewport_height {
+                top -= 1;
+                used += rows(top);
+            }
+            top
+        };
+        self.highlighted_entry = highlighted;
+        self.app_view.set_scroll_offset(Panel::RequestDetail, top);
+    }
+
+    /// Rows the entry at chronological index `entry` takes in the detail panel.
+    fn detail_rows(&self, entry: usize) -> usize {
+        let Some(group) = self.state.selected_group() else {
+            return 1;
+        };
+        let Some(entry) = group.entry(entry) else {
+            return 1;
+        };
+        let wrap_width = (self.verbosity == Verbosity::Full && self.app_view.wrap_detail)
+            .then(|| self.app_view.viewport_width(Panel::RequestDetail));
+        view_model::detail_entry_lines(self, group, entry).map_or(1, |lines| {
+            lines
+                .iter()
+                .map(|line| wrap_width.map_or(1, |width| text_measure::wrapped_rows(line, width)))
+                .sum()
+        })
+    }
+
+    /// Chronological index of the entry shown at `row` of the detail panel,
+    /// counting from its first row of text.
+    fn detail_entry_at_row(&self, row: usize) -> Option<usize> {
+        let offset = self.app_view.get_scroll_offset(Panel::RequestDetail);
+        let mut bottom = 0;
+        for (entry, _) in self.displayed()?.from(offset) {
+            bottom += self.detail_rows(entry);
+            if row < bottom {
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    /// Starts selecting lines from the one under the mouse at screen row `y`.
+    fn start_drag_selection(&mut self, y: u16) {
+        let top = self.app_view.layout_info.region(Panel::RequestDetail).y + DETAIL_TEXT_TOP;
+        self.drag_selection = y
+            .checked_sub(top)
+            .and_then(|row| self.detail_entry_at_row(row as usize))
+            .map(|entry| DragSelection { anchor: entry, end: entry });
+        self.selecting = self.drag_selection.is_some();
+    }
+
+    /// Extends the selection to the line under the mouse. Dragging past the
+    /// top or bottom of the panel scrolls it.
+    fn extend_drag_selection(&mut self, y: u16) {
+        let top = self.app_view.layout_info.region(Panel::RequestDetail).y + DETAIL_TEXT_TOP;
+        let height = self.app_view.viewport_height(Panel::RequestDetail) as u16;
+        if y < top {
+            self.apply_scroll_to(Panel::RequestDetail, -1);
+        } else if y >= top + height {
+            self.apply_scroll_to(Panel::RequestDetail, 1);
+        }
+        let row = y.clamp(top, (top + height).saturating_sub(1)) - top;
+        // Below the last line, the selection ends at the last line shown
+        let entry = self.detail_entry_at_row(row as usize).or_else(|| {
+            let offset = self.app_view.get_scroll_offset(Panel::RequestDetail);
+            let displayed = self.displayed()?;
+            let last = displayed.len().checked_sub(1)?;
+            displayed.index(last).filter(|_| offset <= last)
+        });
+        if let (Some(selection), Some(entry)) = (&mut self.drag_selection, entry) {
+            selection.end = entry;
+        }
+    }
+
+    /// Copies the dragged-over lines when the mouse button is released. A
+    /// click without a drag selects nothing.
+    fn finish_drag_selection(&mut self) {
+        self.selecting = false;
+        let Some(selection) = &self.drag_selection else {
+            return;
+        };
+        if selection.anchor == selection.end {
+            self.drag_selection = None;
+            return;
+        }
+        let Some(displayed) = self.displayed() else {
+            return;
+        };
+        let lines: Vec<String> = displayed
+            .from(0)
+            .skip_while(|&(index, _)| !selection.contains(index))
+            .take_while(|&(index, _)| selection.contains(index))
+            .map(|(_, entry)| {
+                let text = log_parser::strip_ansi_for_parsing(&entry.message);
+                let text = text.trim_end_matches(['\r', '\n']);
+                self.app_view
+                    .inline_binds
+                    .then(|| sql_format::inline_line_binds(text))
+                    .flatten()
+                    .unwrap_or_else(|| text.to_string())
+            })
+            .collect();
+        let strings = self.config.locale.strings();
+        match clipboard::copy(&lines.join("\n")) {
+            Ok(()) => self
+                .toasts
+                .push(ToastLevel::Info, fill(strings.toast_copied_lines, &[&lines.len()])),
+            Err(e) => self
+                .toasts
+                .push(ToastLevel::Error, fill(strings.toast_copy_failed, &[&e])),
+        }
+    }
+
+    /// Display position and text of the line under the cursor, without ANSI colors.
+    pub fn selected_line(&self) -> Option<(usize, String)> {
+        let entry_index = self.highlighted_entry?;
+        let group = self.state.selected_group()?;
+        let entry = group.entry(entry_index)?;
+        let position = self.displayed()?.position(entry_index)?;
+        let text = log_parser::strip_ansi_for_parsing(&entry.message);
+        Some((position, text.trim_end_matches(['\r', '\n']).to_string()))
+    }
+
+    fn copy_selected_line(&mut self) {
+        let Some((position, mut text)) = self.selected_line() else {
+            return;
+        };
+        if self.app_view.inline_binds
+            && let Some(inlined) = sql_format::inline_line_binds(&text)
+        {
+            text = inlined;
+        }
+        let strings = self.config.locale.strings();
+        match clipboard::copy(&text) {
+            Ok(()) => self.toasts.push(
+                ToastLevel::Info,
+                fill(strings.toast_copied_line, &[&(position + 1)]),
+            ),
+            Err(e) => self
+                .toasts
+                .push(ToastLevel::Error, fill(strings.toast_copy_failed, &[&e])),
+        }
+    }
+
+    /// Explains the query on the highlighted line in the development database.
+    fn explain_selected_line(&mut self) {
+        let strings = self.config.locale.strings();
+        let Some(database) = &self.config.database else {
+            self.toasts
+                .push(ToastLevel::Error, strings.toast_explain_no_database.to_string());
+            return;
+        };
+        let sql = self
+            .selected_line()
+            .and_then(|(_, text)| explain::logged_statement(&text));
+        let Some(sql) = sql else {
+            self.toasts
+                .push(ToastLevel::Info, strings.toast_explain_no_query.to_string());
+            return;
+        };
+        let Some(sql) = explain::single_statement(&sql) else {
+            self.toasts
+                .push(ToastLevel::Error, strings.toast_explain_several_statements.to_string());
+            return;
+        };
+        self.explain = Some(Explain::start(database, sql.to_string(), false));
+    }
+
+    /// Opens the file and line under the line cursor, or else where the
+    /// request's exception was raised.
+    fn open_in_editor(&mut self) {
+        let location = self
+            .selected_line()
+            .and_then(|(_, text)| editor::find(&text))
+            .or_else(|| self.state.selected_group().and_then(editor::exception_location));
+        match location {
+            Some(location) => self.pending_edit = Some(location),
+            None => {
+                let strings = self.config.locale.strings();
+                self.toasts
+                    .push(ToastLevel::Info, strings.toast_editor_no_location.to_string());
+            }
+        }
+    }
+
+    fn edit<B: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut ratatui::Terminal<B>,
+        location: &editor::Location,
+    ) -> color_eyre::Result<()>
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        setup::suspend_terminal()?;
+        let result = editor::open(location);
+        setup::resume_terminal(self.mouse_enabled && !self.copy_mode_enabled)?;
+        terminal.clear()?;
+        let strings = self.config.locale.strings();
+        match result {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.toasts.push(
+                ToastLevel::Error,
+                fill(strings.toast_editor_failed, &[&status]),
+            ),
+            Err(e) => self.toasts.push(
+                ToastLevel::Error,
+                fill(strings.toast_editor_failed, &[&format!("{:#}", e)]),
+            ),
+        }
+        Ok(())
+    }
+
+    pub fn supports_analyze(&self) -> bool {
+        self.config
+            .database
+            .as_ref()
+            .is_some_and(|database| database.adapter.supports_analyze())
+    }
+
+    fn handle_explain_key(&mut self, key: event::KeyEvent) {
+        let supports_analyze = self.supports_analyze();
+        let Some(explain) = &mut self.explain else {
+            return;
+        };
+        if explain.confirming_analyze {
+            explain.confirming_analyze = false;
+            if key.code == KeyCode::Char('y')
+                && let Some(database) = &self.config.database
+            {
+                let sql = std::mem::take(&mut explain.sql);
+                self.explain = Some(Explain::start(database, sql, true));
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => explain.scroll = explain.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => explain.scroll = explain.scroll.saturating_sub(1),
+            KeyCode::Char('a') if supports_analyze => explain.confirming_analyze = true,
+            KeyCode::Esc | KeyCode::Char('E') => self.explain = None,
+            _ => {}
+        }
+    }
+
+    /// Keys of the line cursor; returns whether `key` was one of them.
+    fn handle_line_select_key(&mut self, key: event::KeyEvent, steps: usize) -> bool {
+        let steps = steps as isize;
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.move_line_cursor(steps),
+            KeyCode::Char('k') | KeyCode::Up => self.move_line_cursor(-steps),
+            KeyCode::PageDown => self.move_line_cursor(SCROLL_PAGE_SIZE as isize),
+            KeyCode::PageUp => self.move_line_cursor(-(SCROLL_PAGE_SIZE as isize)),
+            KeyCode::Enter => self.app_view.show_line_popup = !self.app_view.show_line_popup,
+            KeyCode::Char('y') => self.copy_selected_line(),
+            KeyCode::Esc if self.app_view.show_line_popup => {
+                self.app_view.show_line_popup = false;
+            }
+            KeyCode::Char('v') | KeyCode::Esc => self.toggle_line_select(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn select_request(&mut self, index: usize) {
+        if self.state.select_request(index) {
+            self.reset_detail_view();
+            let visual_index = self.filtered_position(self.state.selected_index);
+            self.scroll_list_to(visual_index);
+        }
+    }
+
+    pub fn next_request(&mut self, n: usize) {
+        if let Some(indices) = &self.filtered_indices {
+            // Navigate within the filtered list
+            let current_pos = indices
+                .iter()
+                .position(|&i| i == self.state.selected_index);
+            let new_pos = match current_pos {
+                Some(pos) => (pos + n).min(indices.len().saturating_sub(1)),
+                None => 0,
+            };
+            if let Some(&target_index) = indices.get(new_pos) {
+                self.select_request(target_index);
+            }
+        } else if self.state.next_request(n) {
+            self.reset_detail_view();
+            self.scroll_list_to(self.state.selected_index);
+        }
+    }
+
+    pub fn previous_request(&mut self, n: usize) {
+        if let Some(indices) = &self.filtered_indices {
+            // Navigate within the filtered list
+            let current_pos = indices
+                .iter()
+                .position(|&i| i == self.state.selected_index);
+            let new_pos = match current_pos {
+                Some(pos) => pos.saturating_sub(n),
+                None => 0,
+            };
+            if let Some(&target_index) = indices.get(new_pos) {
+                self.select_request(target_index);
+            }
+        } else if self.state.previous_request(n) {
+            self.reset_detail_view();
+            self.scroll_list_to(self.state.selected_index);
+        }
+    }
+
+    fn apply_scroll_to(&mut self, panel: Panel, amount: isize) {
+        let max_scroll = self.get_max_scroll(panel);
+
+        let direction = if amount < 0 {
+            ScrollDirection::Up(amount.unsigned_abs())
+        } else {
+            ScrollDirection::Down(amount as usize)
+        };
+
+        self.app_view.apply_scroll(panel, direction, max_scroll);
+    }
+
+    /// Goes to the `number`th line of the focused panel, counting from 1:
+    /// the request, endpoint or line under the cursor, or the top row of a
+    /// scrolled panel. Numbers past the end go to the last line (`gg`, `G`).
+    fn go_to_line(&mut self, number: usize) {
+        let index = number.saturating_sub(1);
+        let panel = self.app_view.focused_panel;
+        if panel == Panel::RequestList && self.endpoint_cursor.is_some() {
+            let last = self.endpoint_summaries().len().saturating_sub(1);
+            self.endpoint_cursor = Some(index.min(last));
+        } else if self.line_select_active() {
+            self.place_line_cursor(index);
+        } else if panel == Panel::RequestList {
+            self.goto_number(number);
+        } else {
+            let max_scroll = self.get_max_scroll(panel);
+            self.app_view.set_scroll_offset(panel, index.min(max_scroll));
+        }
+    }
+
+    fn get_max_scroll(&self, panel: Panel) -> usize {
+        match panel {
+            Panel::RequestDetail => self.get_max_detail_scroll(),
+            Panel::SqlInfo => self.get_max_sql_scroll(),
+            Panel::RequestList => 0,
+        }
+    }
+
+    fn get_max_request_list_scroll(&self) -> usize {
+        let total = match &self.filtered_indices {
+            Some(indices) => indices.len(),
+            None => self.state.request_ids.len(),
+        };
+        let max_scroll = total.saturating_sub(self.app_view.viewport_height(Panel::RequestList));
+        if !self.list_has_headers() || total == 0 {
+            return max_scroll;
+        }
+        self.offset_showing(max_scroll, total - 1)
+    }
+
+    /// Headers the request list shows, unless it is sorted by something
+    /// other than time.
+    pub fn list_grouping(&self) -> Option<GroupBy> {
+        self.group_by
+            .filter(|_| matches!(self.sort, None | Some(SortKey::Time)))
+    }
+
+    /// Whether the request list has rows other than requests: time headers,
+    /// or dividers where the server restarted. Neither is shown when it is
+    /// sorted by something other than time.
+    fn list_has_headers(&self) -> bool {
+        self.list_grouping().is_some()
+            || !self.state.restarts.is_empty() && matches!(self.sort, None | Some(SortKey::Time))
+    }
+
+    /// Rows of the request list on screen when scrolled to `offset`.
+    pub fn list_layout(&self, offset: usize) -> Vec<ListRow> {
+        self.list_layout_of(&self.visible_request_ids(), offset)
+    }
+
+    fn list_layout_of(&self, visible: &[(usize, &str)], offset: usize) -> Vec<ListRow> {
+        let height = self.app_view.viewport_height(Panel::RequestList);
+        if !self.list_has_headers() {
+            return (offset..visible.len().min(offset + height))
+                .map(ListRow::Request)
+                .collect();
+        }
+        let strings = self.config.locale.strings();
+        time_headers::layout(
+            self.list_grouping(),
+            self.config.times(),
+            offset,
+            height,
+            |position| {
+                let (_, request_id) = visible.get(position)?;
+                let group = self.state.logs_by_request_id.get(*request_id)?;
+                Some(Slot {
+                    time: self.config.time_zone.convert(&group.first_timestamp),
+                    restart: self.state.restart_before(group.first_timestamp),
+                })
+            },
+            |restart| {
+                let time = self.config.times().format(restart, Precision::Seconds);
+                fill(strings.restart_divider, &[&time])
+            },
+        )
+    }
+
+    /// The first offset from `offset` on that shows the request at
+    /// `position`, as headers take rows of their own.
+    fn offset_showing(&self, mut offset: usize, position: usize) -> usize {
+        let visible = self.visible_request_ids();
+        while offset < position
+            && !self
+                .list_layout_of(&visible, offset)
+                .contains(&ListRow::Request(position))
+        {
+            offset += 1;
+        }
+        offset
+    }
+
+    /// Scrolls the request list to show the request at `position` of the
+    /// visible list.
+    fn scroll_list_to(&mut self, position: usize) {
+        self.app_view
+            .adjust_scroll_for_index(Panel::RequestList, position);
+        if self.list_has_headers() {
+            let offset = self.app_view.get_scroll_offset(Panel::RequestList);
+            let offset = self.offset_showing(offset, position);
+            self.app_view.set_scroll_offset(Panel::RequestList, offset);
+        }
+    }
+
+    fn get_max_detail_scroll(&self) -> usize {
+        self.displayed()
+            .map_or(0, |displayed| displayed.len().saturating_sub(1))
+    }
+
+    fn get_max_sql_scroll(&self) -> usize {
+        self.state
+            .selected_sql_line_count()
+            .saturating_sub(self.app_view.viewport_height(Panel::SqlInfo))
+    }
+
+    /// Scrolls the detail panel to the first entry counted under `row` and highlights it.
+    fn jump_to_sql_row(&mut self, row: &SqlRow) {
+        let Some(group) = self.state.selected_group() else {
+            return;
+        };
+        let verbosity = self.verbosity;
+
+        let mut display_position = 0;
+        let mut target = None;
+        for (i, entry) in group.entries.iter().rev().enumerate() {
+            let displayed = entry.rendered.lines(verbosity).is_some();
+            if row.matches(&entry.message) && displayed {
+                target = Some((i, display_position));
+                break;
+            }
+            if displayed {
+                display_position += 1;
+            }
+        }
+
+        if let Some((entry_index, position)) = target {
+            self.highlighted_entry = Some(entry_index);
+            self.app_view.set_scroll_offset(Panel::RequestDetail, position);
+        }
+    }
+
+    /// With `notify`, a request that turns into an error gets a toast and an
+    /// alert. Lines loaded from a file are history, not news, and do not notify.
+    pub fn add_log_entry(&mut self, log_entry: LogEntry, notify: bool) {
+        let request_id = log_entry.request_id.clone();
+        let was_error = self.is_error_request(&request_id);
+        let was_finished = self.is_finished_request(&request_id);
+        let had_stderr = self.has_stderr(&request_id);
+        // Names the controller `:filter controller=` looks for
+        let handled = log_entry.message.contains("Processing by ");
+        let restarts = self.state.restarts.len();
+        let (is_new_request, evicted) = self.state.add_log_entry(log_entry);
+        if self.state.restarts.len() > restarts {
+            self.after_restart();
+        }
+        let became_error = !was_error && self.is_error_request(&request_id);
+        let became_stderr = !had_stderr && self.has_stderr(&request_id);
+        // Duration and status are only known once a request completes
+        let finished = !was_finished && self.is_finished_request(&request_id);
+        if notify && request_id != UNASSIGNED_ID {
+            let now = Instant::now();
+            if is_new_request {
+                self.traffic.record_request(now);
+            }
+            if became_error {
+                self.traffic.record_error(now);
+            }
+        }
+        let mut pinned = false;
+        if notify && became_error {
+            let strings = self.config.locale.strings();
+            let title = &self.state.logs_by_request_id[&request_id].title;
+            self.toasts.push(ToastLevel::Error, fill(strings.toast_error_request, &[title]));
+            self.alerts.fire(Instant::now(), title);
+            pinned = self.state.pin(&request_id);
+        }
+        if notify
+            && finished
+            && let Some(group) = self.state.logs_by_request_id.get(&request_id)
+        {
+            self.hooks.request_finished(&request_id, group);
+        }
+        if finished
+            && let Some(jsonl) = &mut self.jsonl
+            && let Some(group) = self.state.logs_by_request_id.get(&request_id)
+            && let Err(e) = jsonl.write(&request_id, group)
+        {
+            // Nobody is reading anymore, as after `| head`
+            tracing::debug!("Failed to write JSON lines: {}", e);
+            self.jsonl = None;
+        }
+        if (is_new_request
+            || evicted
+            || pinned
+            || became_error && self.errors_only
+            || became_stderr && self.stderr_only
+            || finished
+                && (self.sort.is_some()
+                    || self.slow_filter.is_some()
+                    || self.writes_only
+                    || self.duplicates_only)
+            || handled && self.controller_filter.is_some())
+            && self.filtered_indices.is_some()
+        {
+            // Indices shift whenever a request is added, evicted or pinned
+            self.refresh_filter();
+        }
+        if is_new_request || pinned {
+            let visual_index = self.filtered_position(self.state.selected_index);
+            self.scroll_list_to(visual_index);
+        }
+    }
+
+    fn has_stderr(&self, request_id: &str) -> bool {
+        self.state
+            .logs_by_request_id
+            .get(request_id)
+            .is_some_and(|group| group.has_stderr)
+    }
+
+    fn is_finished_request(&self, request_id: &str) -> bool {
+        self.state
+            .logs_by_request_id
+            .get(request_id)
+            .is_some_and(|group| group.finished)
+    }
+
+    fn is_error_request(&self, request_id: &str) -> bool {
+        self.state
+            .logs_by_request_id
+            .get(request_id)
+            .is_some_and(LogGroup::is_error)
+    }
+
+    pub fn set_sources(&mut self, sources: Vec<Source>) {
+        self.inputs = sources
+            .into_iter()
+            .map(|source| InputState {
+                source,
+                offset: 0,
+                resets: 0,
+            })
+            .collect();
+    }
+
+    pub fn has_multiple_sources(&self) -> bool {
+        self.inputs.len() > 1
+    }
+
+    /// Name for the source column, when lines come from more than one input.
+    pub fn source_name(&self, source: usize) -> Option<&str> {
+        if !self.has_multiple_sources() {
+            return None;
+        }
+        self.inputs
+            .get(source)
+            .map(|input| input.source.name.as_str())
+    }
+
+    /// Widest source name, for aligning the source column.
+    pub fn source_name_width(&self) -> usize {
+        (0..self.inputs.len())
+            .filter_map(|source| self.source_name(source))
+            .map(text_measure::width)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The first file still being loaded.
+    pub fn loading_progress(&self) -> Option<&LoadProgress> {
+        self.inputs
+            .iter()
+            .filter_map(|input| input.source.progress.as_deref())
+            .find(|progress| !progress.is_finished())
+    }
+
+    /// Starts a new segment once a followed file was truncated or replaced.
+    /// Requests read so far stay; offsets count from the new start.
+    fn check_file_reset(&mut self) -> bool {
+        let strings = self.config.locale.strings();
+        let mut reset = false;
+        for input in &mut self.inputs {
+            let Some(progress) = &input.source.progress else {
+                continue;
+            };
+            let resets = progress.resets();
+            if resets == input.resets {
+                continue;
+            }
+            input.resets = resets;
+            input.offset = 0;
+            self.toasts
+                .push(ToastLevel::Info, fill(strings.toast_file_reset, &[&progress.file_name]));
+            reset = true;
+        }
+        reset
+    }
+
+    fn notify_input_ended(&mut self) {
+        let strings = self.config.locale.strings();
+        let files: Vec<&str> = self
+            .inputs
+            .iter()
+            .filter_map(|input| input.source.progress.as_ref())
+            .map(|progress| progress.file_name.as_str())
+            .collect();
+        if !files.is_empty() {
+            self.toasts
+                .push(ToastLevel::Info, fill(strings.toast_file_loaded, &[&files.join(", ")]));
+        } else if self.playback.is_none() && self.supervisor.is_none() {
+            // Playback feeds its lines itself, and a command's exit has a notice of its own
+            self.toasts.push(ToastLevel::Info, strings.toast_input_ended);
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused && self.dropped_while_paused > 0 {
+            let strings = self.config.locale.strings();
+            let message = fill(strings.toast_dropped_while_paused, &[&self.dropped_while_paused]);
+            self.toasts.push(ToastLevel::Error, message);
+            self.dropped_while_paused = 0;
+        }
+    }
+
+    fn hold_line(&mut self, line: InputLine) {
+        if self.held_lines.len() < HELD_LINES_LIMIT {
+            self.held_lines.push_back(line);
+        } else {
+            self.dropped_while_paused += 1;
+        }
+    }
+
+    fn notify_exit(&mut self, status: ExitStatus) {
+        let Some(supervisor) = &self.supervisor else {
+            return;
+        };
+        let strings = self.config.locale.strings();
+        let level = if status.success() {
+            ToastLevel::Info
+        } else {
+            ToastLevel::Error
+        };
+        let message = fill(
+            strings.toast_exited,
+            &[&supervisor.command, &supervisor::exit_label(status)],
+        );
+        self.toasts.push(level, message);
+    }
+
+    /// Whether `R` can read the inputs again: files can, stdin cannot.
+    pub fn can_resume_input(&self) -> bool {
+        !self.inputs.is_empty() && self.inputs.iter().all(|input| input.source.path.is_some())
+    }
+
+    /// Opens the files again once they were read to the end, for the lines
+    /// appended since.
+    fn resume_input(&mut self) {
+        if !self.input_ended {
+            return;
+        }
+        let strings = self.config.locale.strings();
+        if !self.can_resume_input() {
+            self.toasts.push(ToastLevel::Info, strings.toast_resume_stream);
+            return;
+        }
+        let files: Vec<_> = self
+            .inputs
+            .iter()
+            .filter_map(|input| Some((input.source.path.clone()?, input.offset)))
+            .collect();
+        let (reader, rx) = match input::Reader::resume(&files) {
+            Ok(resumed) => resumed,
+            Err(e) => {
+                self.toasts
+                    .push(ToastLevel::Error, fill(strings.toast_resume_failed, &[&e]));
+                return;
+            }
+        };
+        for (input, source) in self.inputs.iter_mut().zip(reader.sources()) {
+            input.source = source;
+            input.resets = 0;
+        }
+        self._resumed_reader = Some(reader);
+        self.resumed_input = Some(rx);
+        self.input_ended = false;
+    }
+
+    fn export_options(&self) -> ExportOptions {
+        ExportOptions {
+            redact: self.config.export.redact,
+            verbosity: self.verbosity,
+            inline_binds: self.app_view.inline_binds,
+            times: self.config.times(),
+        }
+    }
+
+    /// Copies the selected request in the export format.
+    fn copy_request(&mut self) {
+        let Some(request_id) = self.state.selected_request_id() else {
+            return;
+        };
+        let Some(group) = self.state.selected_group() else {
+            return;
+        };
+        let text = export::to_string(self.export_format, request_id, group, self.export_options());
+        let strings = self.config.locale.strings();
+        match clipboard::copy(&text) {
+            Ok(()) => self
+                .toasts
+                .push(ToastLevel::Info, fill(strings.toast_copied, &[request_id])),
+            Err(e) => self
+                .toasts
+                .push(ToastLevel::Error, fill(strings.toast_copy_failed, &[&e])),
+        }
+    }
+
+    fn copy_request_id(&mut self) {
+        let Some(request_id) = self.state.selected_request_id() else {
+            return;
+        };
+        let strings = self.config.locale.strings();
+        match clipboard::copy(request_id) {
+            Ok(()) => self
+                .toasts
+                .push(ToastLevel::Info, fill(strings.toast_copied, &[request_id])),
+            Err(e) => self
+                .toasts
+                .push(ToastLevel::Error, fill(strings.toast_copy_failed, &[&e])),
+        }
+    }
+
+    /// Reads the selected group's compacted lines back from the journal.
+    fn restore_selected(&mut self) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        let Some(request_id) = self.state.selected_request_id() else {
+            return;
+        };
+        let Some(group) = self.state.logs_by_request_id.get(request_id) else {
+            return;
+        };
+        if !group.compacted {
+            return;
+        }
+
+        let mut lines = Vec::with_capacity(group.journal_refs.len());
+        for &journal_ref in &group.journal_refs {
+            match journal.read(journal_ref) {
+                Ok(line) => lines.push((journal_ref, line)),
+                Err(e) => {
+                    tracing::error!("Failed to read journal: {}", e);
+                    let strings = self.config.locale.strings();
+                    self.toasts
+                        .push(ToastLevel::Error, fill(strings.toast_restore_failed, &[&e]));
+                    return;
+                }
+            }
+        }
+
+        // Parsed the way the lines were ingested, generic or not
+        let mut entries = Vec::with_capacity(lines.len());
+        for (journal_ref, line) in lines {
+            if let Some(mut entry) = self.parse_line(&line) {
+                entry.request_id.clone_from(request_id);
+                entry.kind = group.kind;
+                entry.journal_ref = Some(journal_ref);
+                entry.source = group.source;
+                entries.push(entry);
+            }
+        }
+
+        let request_id = request_id.clone();
+        let strings = self.config.locale.strings();
+        self.toasts
+            .push(ToastLevel::Info, fill(strings.toast_restored, &[&entries.len()]));
+        self.state.restore_group(&request_id, entries);
+        self.reset_detail_view();
+    }
+
+    fn dismiss_selected(&mut self) {
+        let index = self.state.selected_index;
+        if self.state.remove_request_at(index).is_some() {
+            self.sync_after_removal();
+        }
+    }
+
+    /// Clears or archives the requests from before the restart just seen,
+    /// as `[restarts] action` asks.
+    fn after_restart(&mut self) {
+        let request_ids = match self.config.restarts.action {
+            RestartAction::Keep => return,
+            RestartAction::Clear | RestartAction::Archive => self.state.ids_before_restart(),
+        };
+        if request_ids.is_empty() {
+            return;
+        }
+        if self.config.restarts.action == RestartAction::Archive {
+            let dir = self.config.restarts.archive_dir.clone();
+            // Kept when they could not be written
+            if !self.export_requests(&request_ids, &dir) {
+                return;
+            }
+        }
+        let cleared = self.state.remove_requests(&request_ids);
+        self.sync_after_removal();
+        let strings = self.config.locale.strings();
+        self.toasts
+            .push(ToastLevel::Info, fill(strings.toast_restart_cleared, &[&cleared]));
+    }
+
+    fn clear_finished(&mut self) {
+        let cleared = self.state.clear_finished();
+        if cleared > 0 {
+            self.sync_after_removal();
+            let strings = self.config.locale.strings();
+            self.toasts.push(ToastLevel::Info, fill(strings.toast_cleared, &[&cleared]));
+        }
+    }
+
+    /// Ids of the requests bulk actions apply to, in list order: the visual
+    /// selection, or else those the filter matches. Empty without either.
+    pub fn bulk_request_ids(&self) -> Vec<String> {
+        let visible = self.visible_request_ids();
+        let range = match self.visual_range() {
+            Some(range) => range,
+            None if self.is_filtered() => 0..=visible.len().saturating_sub(1),
+            None => return Vec::new(),
+        };
+        visible
+            .get(range)
+            .unwrap_or_default()
+            .iter()
+            .map(|(_, request_id)| request_id.to_string())
+            .collect()
+    }
+
+    /// Rows of the visible list the visual selection spans, from the row of
+    /// its anchor to the selected one. An anchor that left the list leaves
+    /// only the selected row.
+    pub fn visual_range(&self) -> Option<RangeInclusive<usize>> {
+        let anchor = self.state.visual_anchor.as_ref()?;
+        let visible = self.visible_request_ids();
+        let selected = self.filtered_position(self.state.selected_index);
+        let start = visible
+            .iter()
+            .position(|(_, request_id)| request_id == anchor)
+            .unwrap_or(selected);
+        Some(start.min(selected)..=start.max(selected))
+    }
+
+    /// Starts a visual selection at the selected request, or ends it.
+    fn toggle_visual(&mut self) {
+        if self.state.visual_anchor.take().is_none() {
+            self.state.visual_anchor = self.state.selected_request_id().cloned();
+            self.app_view.focus(Panel::RequestList);
+        }
+    }
+
+    fn handle_bulk_action(&mut self, key: event::KeyEvent) {
+        self.app_view.show_bulk_actions = false;
+        match key.code {
+            KeyCode::Char('e') => {
+                self.search_mode = Some(SearchTarget::Export);
+                self.export_query = DEFAULT_EXPORT_DIR.to_string();
+            }
+            KeyCode::Char('d') => self.delete_matching(),
+            KeyCode::Char('b') => self.star_matching(),
+            KeyCode::Char('s') => {
+                self.matching_stats = Some(self.state.stats_for(&self.bulk_request_ids()));
+                self.app_view.show_stats = true;
+            }
+            KeyCode::Char('m') => self.merge_matching(),
+            _ => return,
+        }
+        // Export still needs the selection until a directory is given
+        if key.code != KeyCode::Char('e') {
+            self.state.visual_anchor = None;
+        }
+    }
+
+    /// Shows the lines of every matching request together, in the order
+    /// they were logged.
+    fn merge_matching(&mut self) {
+        let request_ids = self.bulk_request_ids();
+        let strings = self.config.locale.strings();
+        let title = fill(strings.merged_title, &[&request_ids.len()]);
+        if self.state.merge_requests(&request_ids, title).is_some() {
+            // The merged group matches none of the filters it came from
+            self.clear_filters();
+            self.update_filter();
+            self.select_request(self.state.selected_index);
+            self.app_view.focus(Panel::RequestDetail);
+        }
+    }
+
+    /// Keys of the column chooser: j/k pick a column, Space shows or hides
+    /// it and J/K move it left or right.
+    fn handle_column_key(&mut self, key: event::KeyEvent, cursor: usize) {
+        let order = columns::chooser_order(&self.columns);
+        let column = order[cursor.min(order.len() - 1)];
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.column_cursor = Some((cursor + 1).min(order.len() - 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.column_cursor = Some(cursor.saturating_sub(1)),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                columns::toggle(&mut self.columns, column);
+                self.follow_column(column);
+            }
+            KeyCode::Char(c @ ('J' | 'K')) => {
+                columns::shift(&mut self.columns, column, c == 'K');
+                self.follow_column(column);
+            }
+            KeyCode::Esc | KeyCode::Char('C') | KeyCode::Char('q') => self.column_cursor = None,
+            _ => {}
+        }
+    }
+
+    /// Keeps the chooser's cursor on `column` after it moved.
+    fn follow_column(&mut self, column: Column) {
+        self.column_cursor = columns::chooser_order(&self.columns)
+            .iter()
+            .position(|&other| other == column);
+    }
+
+    /// Writes every matching request to its own file in `dir`.
+    fn export_matching(&mut self, dir: &str) {
+        self.export_requests(&self.bulk_request_ids(), dir);
+        self.state.visual_anchor = None;
+    }
+
+    /// Returns whether the requests were written.
+    fn export_requests(&mut self, request_ids: &[String], dir: &str) -> bool {
+        let groups: Vec<_> = request_ids
+            .iter()
+            .filter_map(|request_id| {
+                self.state
+                    .logs_by_request_id
+                    .get(request_id)
+                    .map(|group| (request_id.as_str(), group))
+            })
+            .collect();
+        let strings = self.config.locale.strings();
+        let options = self.export_options();
+        match export::write_groups(Path::new(dir), self.export_format, &groups, options) {
+            Ok(count) => {
+                self.toasts
+                    .push(ToastLevel::Info, fill(strings.toast_exported, &[&count, &dir]));
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to export to {}: {}", dir, e);
+                self.toasts
+                    .push(ToastLevel::Error, fill(strings.toast_export_failed, &[&e]));
+                false
+            }
+        }
+    }
+
+    fn delete_matching(&mut self) {
+        let deleted = self.state.remove_requests(&self.bulk_request_ids());
+        if deleted > 0 {
+            self.sync_after_removal();
+            let strings = self.config.locale.strings();
+            self.toasts.push(ToastLevel::Info, fill(strings.toast_deleted, &[&deleted]));
+        }
+    }
+
+    fn star_matching(&mut self) {
+        let starred = self.state.star_requests(&self.bulk_request_ids());
+        let strings = self.config.locale.strings();
+        self.toasts.push(ToastLevel::Info, fill(strings.toast_starred, &[&starred]));
+    }
+
+    /// Keeps the filter, selection and list scroll consistent after requests were removed.
+    fn sync_after_removal(&mut self) {
+        self.refresh_filter();
+        if let Some(indices) = &self.filtered_indices
+            && !indices.contains(&self.state.selected_index)
+        {
+            // The next older visible request, or the oldest one
+            let nearest = indices
+                .iter()
+                .find(|&&i| i > self.state.selected_index)
+                .or(indices.last())
+                .copied();
+            if let Some(index) = nearest {
+                self.state.select_request(index);
+            }
+        }
+        self.reset_detail_view();
+
+        let max_scroll = self.get_max_request_list_scroll();
+        if self.app_view.get_scroll_offset(Panel::RequestList) > max_scroll {
+            self.app_view
+                .set_scroll_offset(Panel::RequestList, max_scroll);
+        }
+        let visual_index = self.filtered_position(self.state.selected_index);
+        self.scroll_list_to(visual_index);
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.app_view.focus(match self.app_view.focused_panel {
+            Panel::RequestList => Panel::RequestDetail,
+            Panel::RequestDetail => Panel::SqlInfo,
+            Panel::SqlInfo => Panel::RequestList,
+        });
+    }
 
-            let request_list_region = self.app_view.layout_info.region(Panel::RequestList);
-            let request_detail_region = self.app_view.layout_info.region(Panel::RequestDetail);
-            let sql_info_region = self.app_view.layout_info.region(Panel::SqlInfo);
+    pub fn toggle_focus_reverse(&mut self) {
+        self.app_view.focus(match self.app_view.focused_panel {
+            Panel::RequestList => Panel::SqlInfo,
+            Panel::RequestDetail => Panel::RequestList,
+            Panel::SqlInfo => Panel::RequestDetail,
+        });
+    }
+
+    pub fn jump_to_latest(&mut self) {
+        self.select_request(0);
+    }
+
+    /// Steps from all lines to simple mode, to summary mode and back.
+    fn cycle_verbosity(&mut self) {
+        self.verbosity = self.verbosity.next();
+    }
 
-            let request_list = panel_components::build_list_component(self);
-            f.render_widget(request_list, request_list_region);
+    /// Selects the request at a typed time (`HH:MM[:SS]`) or with a typed request id.
+    fn jump_to(&mut self, query: &str) {
+        let Some(index) = &self.file_index else {
+            return;
+        };
+        let target = index
+            .span()
+            .and_then(|(first, _)| file_index::parse_jump_time(query, first, self.config.time_zone))
+            .and_then(|timestamp| index.request_at_or_after(timestamp))
+            .or_else(|| index.find_request(query))
+            .cloned();
+        let Some(target) = target else {
+            return;
+        };
 
-            let detail_panel = panel_components::build_detail_component(self);
-            f.render_widget(detail_panel, request_detail_region);
+        // The group may have been renamed `<id>~1` when the server restarted
+        // and handed out the same id again; its first line tells them apart
+        let position = self.state.request_ids.iter().position(|id| {
+            id.strip_prefix(target.request_id.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('~'))
+                && self
+                    .state
+                    .logs_by_request_id
+                    .get(id)
+                    .is_some_and(|group| group.first_timestamp == target.timestamp)
+        });
+        let Some(position) = position else {
+            let strings = self.config.locale.strings();
+            let message = fill(strings.toast_jump_evicted, &[&target.request_id, &target.offset]);
+            self.toasts.push(ToastLevel::Info, message);
+            return;
+        };
 
-            let sql_panel = panel_components::build_sql_component(self);
-            f.render_widget(sql_panel, sql_info_region);
+        let hidden = self
+            .filtered_indices
+            .as_ref()
+            .is_some_and(|indices| !indices.contains(&position));
+        if hidden {
+            self.clear_filters();
+            self.update_filter();
         }
+        self.select_request(position);
     }
 
-    pub fn run<B: ratatui::backend::Backend>(
-        &mut self,
-        terminal: &mut ratatui::Terminal<B>,
-        rx: std::sync::mpsc::Receiver<String>,
-    ) -> color_eyre::Result<()>
-    where
-        B::Error: Send + Sync + 'static,
-    {
-        loop {
-            terminal.draw(|f| {
-                self.render(f);
-            })?;
+    /// Selects the `number`th visible request, counting from 1 at the top of
+    /// the list. Numbers past the end select the last one.
+    fn goto_number(&mut self, number: usize) {
+        let visible = self.visible_request_ids();
+        let target = visible
+            .get(number.saturating_sub(1))
+            .or(visible.last())
+            .map(|&(index, _)| index);
+        if let Some(index) = target {
+            self.select_request(index);
+        }
+    }
 
-            let drain_deadline =
-                std::time::Instant::now() + std::time::Duration::from_millis(100);
-            while let Ok(line) = rx.try_recv() {
-                if let Some(entry) = crate::log_parser::parse(&line) {
-                    self.add_log_entry(entry);
-                }
-                if std::time::Instant::now() >= drain_deadline {
-                    break;
-                }
+    /// Shows the next tutorial step, or closes the tutorial for good.
+    fn advance_tutorial(&mut self, skip: bool) {
+        let Some(tutorial) = &mut self.tutorial else {
+            return;
+        };
+        if !skip && tutorial.advance() {
+            if let Some(panel) = tutorial.panel() {
+                self.app_view.focus(panel);
             }
+            return;
+        }
 
-            match crossterm::event::poll(std::time::Duration::from_millis(16)) {
-                Ok(true) => {
-                    let event = match event::read() {
-                        Ok(event) => event,
-                        Err(e) => {
-                            tracing::debug!("Event read error: {:?}", e);
-                            continue;
-                        }
-                    };
-
-                    match event {
-                        Event::Key(key) if self.search_mode.is_some() => {
-                            if key.code == KeyCode::Char('c')
-                                && key.modifiers.contains(event::KeyModifiers::CONTROL)
-                            {
-                                return Ok(());
-                            }
-                            self.handle_search_key(key);
-                        }
-                        Event::Key(key) => {
-                            if key.code == KeyCode::Char('c')
-                                && key.modifiers.contains(event::KeyModifiers::CONTROL)
-                            {
-                                return Ok(());
-                            }
-                            self.handle_normal_key(key)?;
-                        }
-                        Event::Mouse(mouse_event) if !self.copy_mode_enabled => {
-                            self.handle_mouse_event(mouse_event);
-                        }
-                        _ => {}
-                    }
-                }
-                Ok(false) => {}
-                Err(e) => {
-                    tracing::debug!("Poll error: {:?}", e);
-                    continue;
-                }
-            }
+        self.tutorial = None;
+        let mut state = State::load();
+        state.tutorial_done = true;
+        if let Err(e) = state.save() {
+            tracing::warn!("Failed to save state: {}", e);
         }
     }
 
-    fn filtered_position(&self, index: usize) -> usize {
-        match &self.filtered_indices {
-            Some(indices) => indices.iter().position(|&i| i == index).unwrap_or(0),
-            None => index,
+    /// Sets the note of the selected request, removing it when `text` is
+    /// blank, and keeps the notes in the state file for the next run.
+    fn save_note(&mut self, text: &str) {
+        let Some(request_id) = self.state.selected_request_id().cloned() else {
+            return;
+        };
+        self.state.set_note(&request_id, text);
+        let mut state = State::load();
+        state.notes = self.state.notes.clone();
+        state.prune_notes(chrono::Utc::now().timestamp());
+        if let Err(e) = state.save() {
+            tracing::warn!("Failed to save state: {}", e);
         }
     }
 
-    fn select_request(&mut self, index: usize) {
-        if self.state.select_request(index) {
-            self.app_view.set_scroll_offset(Panel::RequestDetail, 0);
-            let visual_index = self.filtered_position(self.state.selected_index);
-            self.app_view
-                .adjust_scroll_for_index(Panel::RequestList, visual_index);
+    fn toggle_star(&mut self) {
+        if self.state.toggle_star_selected() == Some(false) && self.starred_only {
+            self.sync_after_removal();
         }
     }
 
-    pub fn next_request(&mut self, n: usize) {
-        if let Some(indices) = &self.filtered_indices {
-            // Navigate within the filtered list
-            let current_pos = indices
-                .iter()
-                .position(|&i| i == self.state.selected_index);
-            let new_pos = match current_pos {
-                Some(pos) => (pos + n).min(indices.len().saturating_sub(1)),
-                None => 0,
+    fn toggle_starred_only(&mut self) {
+        self.starred_only = !self.starred_only;
+        self.update_filter();
+    }
+
+    fn toggle_errors_only(&mut self) {
+        self.errors_only = !self.errors_only;
+        self.update_filter();
+    }
+
+    /// Applies the window typed at the `T` prompt. An empty one shows all
+    /// requests again.
+    fn apply_time_window(&mut self, input: &str) {
+        if input.trim().is_empty() {
+            self.time_window = None;
+        } else {
+            let reference = self
+                .state
+                .latest_timestamp()
+                .unwrap_or_else(|| Local::now().fixed_offset());
+            let Some(window) = TimeWindow::parse(input, reference, self.config.time_zone) else {
+                let strings = self.config.locale.strings();
+                self.toasts
+                    .push(ToastLevel::Error, fill(strings.toast_time_window_invalid, &[&input]));
+                return;
             };
-            if let Some(&target_index) = indices.get(new_pos) {
-                self.select_request(target_index);
-            }
-        } else if self.state.next_request(n) {
-            self.app_view.set_scroll_offset(Panel::RequestDetail, 0);
-            self.app_view
-                .adjust_scroll_for_index(Panel::RequestList, self.state.selected_index);
+            self.time_window = Some(window);
         }
+        self.update_filter();
     }
 
-    pub fn previous_request(&mut self, n: usize) {
-        if let Some(indices) = &self.filtered_indices {
-            // Navigate within the filtered list
-            let current_pos = indices
+    /// Endpoint figures over the requests the other filters let through.
+    pub fn endpoint_summaries(&self) -> Vec<EndpointSummary> {
+        endpoint::summarize(
+            self.visible_request_ids()
+                .into_iter()
+                .filter_map(|(_, request_id)| self.state.logs_by_request_id.get(request_id)),
+        )
+    }
+
+    /// Switches between the request list and the endpoint view. Coming back
+    /// from an endpoint's requests selects that endpoint again.
+    fn toggle_endpoint_view(&mut self) {
+        if self.endpoint_cursor.take().is_some() {
+            return;
+        }
+        let drilled = self.endpoint_filter.take();
+        self.update_filter();
+        let cursor = drilled.and_then(|drilled| {
+            self.endpoint_summaries()
                 .iter()
-                .position(|&i| i == self.state.selected_index);
-            let new_pos = match current_pos {
-                Some(pos) => pos.saturating_sub(n),
-                None => 0,
-            };
-            if let Some(&target_index) = indices.get(new_pos) {
-                self.select_request(target_index);
+                .position(|summary| summary.endpoint == drilled)
+        });
+        self.endpoint_cursor = Some(cursor.unwrap_or(0));
+        self.app_view.focus(Panel::RequestList);
+    }
+
+    /// Keys of the endpoint view. Returns false for the ones it leaves to the
+    /// normal handling.
+    fn handle_endpoint_key(&mut self, key: event::KeyEvent, steps: usize) -> bool {
+        let Some(cursor) = self.endpoint_cursor else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = self.endpoint_summaries().len().saturating_sub(1);
+                self.endpoint_cursor = Some((cursor + steps).min(last));
             }
-        } else if self.state.previous_request(n) {
-            self.app_view.set_scroll_offset(Panel::RequestDetail, 0);
-            self.app_view
-                .adjust_scroll_for_index(Panel::RequestList, self.state.selected_index);
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.endpoint_cursor = Some(cursor.saturating_sub(steps));
+            }
+            KeyCode::Enter => self.show_endpoint_requests(cursor),
+            KeyCode::Esc => self.endpoint_cursor = None,
+            _ => return false,
         }
+        true
     }
 
-    fn apply_scroll_to(&mut self, panel: Panel, amount: isize) {
-        let max_scroll = match panel {
-            Panel::RequestDetail => self.get_max_detail_scroll(),
-            Panel::SqlInfo => self.get_max_sql_scroll(),
-            _ => 0,
+    /// Leaves the endpoint view for the requests to the endpoint at `cursor`.
+    fn show_endpoint_requests(&mut self, cursor: usize) {
+        let Some(summary) = self.endpoint_summaries().into_iter().nth(cursor) else {
+            return;
         };
+        self.endpoint_cursor = None;
+        self.endpoint_filter = Some(summary.endpoint);
+        self.update_filter();
+        if let Some(&(index, _)) = self.visible_request_ids().first() {
+            self.select_request(index);
+        }
+    }
 
-        let direction = if amount < 0 {
-            ScrollDirection::Up(amount.unsigned_abs())
-        } else {
-            ScrollDirection::Down(amount as usize)
+    fn cycle_kind_filter(&mut self) {
+        self.kind_filter = match self.kind_filter {
+            None => Some(GroupKind::Request),
+            Some(GroupKind::Request) => Some(GroupKind::Job),
+            Some(GroupKind::Job) => Some(GroupKind::Cable),
+            Some(GroupKind::Cable | GroupKind::Generic | GroupKind::Unassigned) => None,
         };
+        self.update_filter();
+    }
 
-        self.app_view.apply_scroll(panel, direction, max_scroll);
+    fn cycle_theme(&mut self) {
+        self.set_theme(self.config.theme.next());
     }
 
-    fn get_max_request_list_scroll(&self) -> usize {
-        let total = match &self.filtered_indices {
-            Some(indices) => indices.len(),
-            None => self.state.request_ids.len(),
-        };
-        total.saturating_sub(self.app_view.viewport_height(Panel::RequestList))
+    fn set_theme(&mut self, palette: Palette) {
+        self.config.theme = palette;
+        crate::theme::set_theme(self.config.theme, &self.config.colors);
+        // Simple-mode lines were colored when they arrived
+        self.state.rerender_lines();
+        let strings = self.config.locale.strings();
+        self.toasts
+            .push(ToastLevel::Info, fill(strings.toast_theme, &[&self.config.theme.name()]));
     }
 
-    fn get_max_detail_scroll(&self) -> usize {
-        self.state.selected_entries_count().saturating_sub(1)
+    fn toggle_stats(&mut self) {
+        self.app_view.show_stats = !self.app_view.show_stats;
+        self.matching_stats = None;
     }
 
-    fn get_max_sql_scroll(&self) -> usize {
-        self.state
-            .selected_sql_line_count()
-            .saturating_sub(self.app_view.viewport_height(Panel::SqlInfo))
-            .max(0)
+    fn cycle_timeline(&mut self) {
+        self.app_view.timeline = match self.app_view.timeline {
+            None => Some(TimelineScope::Request),
+            Some(TimelineScope::Request) => Some(TimelineScope::AllRequests),
+            Some(TimelineScope::AllRequests) => None,
+        };
     }
 
-    pub fn add_log_entry(&mut self, log_entry: LogEntry) {
-        let (is_new_request, evicted) = self.state.add_log_entry(log_entry);
-        if is_new_request {
-            let visual_index = self.filtered_position(self.state.selected_index);
-            self.app_view
-                .adjust_scroll_for_index(Panel::RequestList, visual_index);
-        }
-        if evicted && self.filtered_indices.is_some() {
-            self.update_filter();
+    /// Replaces every filter with `filter`.
+    fn apply_filter(&mut self, filter: Filter) {
+        self.clear_filters();
+        self.search_query = filter.text;
+        self.kind_filter = filter.kind;
+        self.starred_only = filter.starred;
+        self.errors_only = filter.errors;
+        self.status_filter = filter.status;
+        self.slow_filter = filter.slow;
+        self.writes_only = filter.writes;
+        self.duplicates_only = filter.duplicates;
+        self.stderr_only = filter.stderr;
+        self.controller_filter = filter.controller;
+        self.endpoint_cursor = None;
+        // Shows a notice when the window does not parse
+        self.apply_time_window(filter.last.as_deref().unwrap_or_default());
+    }
+
+    /// Applies the filter preset on `digit` (Alt+1 to Alt+9).
+    fn apply_preset(&mut self, digit: char) {
+        let Some(terms) = self.config.presets.filter(digit) else {
+            return;
+        };
+        let terms: Vec<&str> = terms.split_whitespace().collect();
+        // Presets were checked when the config was loaded
+        if let Ok(filter) = command::parse_filter(&terms) {
+            self.perform(Action::Filter(filter));
         }
     }
 
-    pub fn toggle_focus(&mut self) {
-        self.app_view.focused_panel = match self.app_view.focused_panel {
-            Panel::RequestList => Panel::RequestDetail,
-            Panel::RequestDetail => Panel::SqlInfo,
-            Panel::SqlInfo => Panel::RequestList,
+    /// Runs a line typed at the `:` prompt.
+    fn run_command(&mut self, input: &str) {
+        let command = match command::parse(input) {
+            Ok(command) => command,
+            Err(reason) => {
+                let strings = self.config.locale.strings();
+                self.toasts
+                    .push(ToastLevel::Error, fill(strings.toast_command_invalid, &[&reason]));
+                return;
+            }
         };
+        match command {
+            Command::Goto(number) => self.goto_number(number),
+            Command::Filter(filter) => self.perform(Action::Filter(filter)),
+            Command::Sort(sort) => {
+                self.sort = (sort != SortKey::Time).then_some(sort);
+                self.update_filter();
+            }
+            Command::Export { format, dir } => {
+                if let Ok(format) = export::format_named(format) {
+                    self.export_format = format;
+                }
+                let request_ids: Vec<String> = self
+                    .visible_request_ids()
+                    .into_iter()
+                    .map(|(_, request_id)| request_id.to_string())
+                    .collect();
+                self.export_requests(&request_ids, &dir);
+            }
+            Command::Theme(palette) => self.set_theme(palette),
+            Command::Simple => self.cycle_verbosity(),
+            Command::Wrap => self.app_view.wrap_detail = !self.app_view.wrap_detail,
+            Command::Binds => self.app_view.inline_binds = !self.app_view.inline_binds,
+            Command::Relative => {
+                self.app_view.relative_times = !self.app_view.relative_times;
+            }
+            Command::Stats => self.toggle_stats(),
+            Command::Timeline => self.cycle_timeline(),
+            Command::Endpoints if !self.app_view.plain => self.toggle_endpoint_view(),
+            Command::Endpoints => {}
+            Command::Pause => self.toggle_pause(),
+            Command::Clear => self.clear_finished(),
+        }
     }
 
-    pub fn toggle_focus_reverse(&mut self) {
-        self.app_view.focused_panel = match self.app_view.focused_panel {
-            Panel::RequestList => Panel::SqlInfo,
-            Panel::RequestDetail => Panel::RequestList,
-            Panel::SqlInfo => Panel::RequestDetail,
+    /// Steps through the inputs one at a time, then back to all of them.
+    fn cycle_source_filter(&mut self) {
+        self.source_filter = match self.source_filter {
+            None => Some(0),
+            Some(source) if source + 1 < self.inputs.len() => Some(source + 1),
+            Some(_) => None,
         };
+        self.update_filter();
     }
 
-    pub fn jump_to_latest(&mut self) {
-        self.select_request(0);
+    fn update_filter(&mut self) {
+        self.refresh_filter();
+        if self.filtered_indices.is_some() {
+            self.app_view.set_scroll_offset(Panel::RequestList, 0);
+        }
     }
 
-    fn toggle_simple_mode(&mut self) {
-        self.simple_mode_enabled = !self.simple_mode_enabled;
+    /// Whether any filter narrows the list.
+    pub fn is_filtered(&self) -> bool {
+        !self.search_query.is_empty()
+            || self.kind_filter.is_some()
+            || self.source_filter.is_some()
+            || self.starred_only
+            || self.errors_only
+            || self.time_window.is_some()
+            || self.endpoint_filter.is_some()
+            || self.status_filter.is_some()
+            || self.slow_filter.is_some()
+            || self.writes_only
+            || self.duplicates_only
+            || self.stderr_only
+            || self.controller_filter.is_some()
     }
 
-    fn update_filter(&mut self) {
-        if self.search_query.is_empty() {
+    fn clear_filters(&mut self) {
+        self.search_query.clear();
+        self.kind_filter = None;
+        self.source_filter = None;
+        self.starred_only = false;
+        self.errors_only = false;
+        self.time_window = None;
+        self.endpoint_filter = None;
+        self.status_filter = None;
+        self.slow_filter = None;
+        self.writes_only = false;
+        self.duplicates_only = false;
+        self.stderr_only = false;
+        self.controller_filter = None;
+    }
+
+    /// Recomputes `filtered_indices` without touching the scroll position.
+    /// Only the view is narrowed or reordered; `state` is left as is.
+    fn refresh_filter(&mut self) {
+        if !self.is_filtered() && self.sort.is_none() {
             self.filtered_indices = None;
             return;
         }
-        let query_lower = self.search_query.to_lowercase();
-        let indices: Vec<usize> = self
+        let latest = self.state.latest_timestamp();
+        let mut indices: Vec<usize> = self
             .state
             .request_ids
             .iter()
             .enumerate()
             .filter(|(_, req_id)| {
-                self.state
-                    .logs_by_request_id
-                    .get(*req_id)
-                    .is_some_and(|group| group.title.to_lowercase().contains(&query_lower))
+                self.state.logs_by_request_id.get(*req_id).is_some_and(|group| {
+                    self.kind_filter.is_none_or(|kind| group.kind == kind)
+                        && self.source_filter.is_none_or(|source| group.source == source)
+                        && (group.starred || !self.starred_only)
+                        && (group.is_error() || !self.errors_only)
+                        && self
+                            .time_window
+                            .is_none_or(|window| window.contains(group.first_timestamp, latest))
+                        && self.endpoint_filter.as_ref().is_none_or(|filter| {
+                            endpoint::endpoint(group).is_some_and(|endpoint| endpoint == *filter)
+                        })
+                        && self.status_filter.is_none_or(|codes| {
+                            group.status_code.is_some_and(|code| codes.contains(code))
+                        })
+                        && self
+                            .slow_filter
+                            .is_none_or(|ms| group.duration_ms.is_some_and(|duration| duration >= ms))
+                        && (group.sql_query_info.has_writes() || !self.writes_only)
+                        && (group.duplicate || !self.duplicates_only)
+                        && (group.has_stderr || !self.stderr_only)
+                        && self
+                            .controller_filter
+                            .as_ref()
+                            .is_none_or(|controller| group.title_fields.handled_by(controller))
+                        && (self.search_query.is_empty()
+                            || filter::fuzzy_match(
+                                &group.list_title(self.config.list.title.as_ref()).text,
+                                &self.search_query,
+                            )
+                            .is_some())
+                })
             })
             .map(|(i, _)| i)
             .collect();
+        if let Some(sort) = self.sort {
+            self.sort_indices(&mut indices, sort);
+        }
         self.filtered_indices = Some(indices);
-        self.app_view.set_scroll_offset(Panel::RequestList, 0);
+    }
+
+    /// Stable, so requests that tie stay newest first.
+    fn sort_indices(&self, indices: &mut [usize], sort: SortKey) {
+        let group = |index: usize| {
+            self.state
+                .request_ids
+                .get(index)
+                .and_then(|request_id| self.state.logs_by_request_id.get(request_id))
+        };
+        match sort {
+            SortKey::Time => {}
+            // Requests still running go last
+            SortKey::Duration => indices.sort_by_key(|&index| {
+                std::cmp::Reverse(group(index).and_then(|group| group.duration_ms))
+            }),
+            SortKey::Status => indices.sort_by_key(|&index| {
+                std::cmp::Reverse(group(index).map(|group| group.status_type.severity()))
+            }),
+        }
     }
 
     pub fn visible_request_ids(&self) -> Vec<(usize, &str)> {
@@ -323,15 +2637,63 @@ impl App {
                 match target {
                     SearchTarget::RequestList => {
                         self.search_query.clear();
-                        self.filtered_indices = None;
+                        self.update_filter();
                     }
                     SearchTarget::DetailLog => {
-                        self.detail_search_query.clear();
+                        self.app_view.detail_search = PanelSearch::default();
+                    }
+                    SearchTarget::Jump => {
+                        self.jump_query.clear();
+                    }
+                    SearchTarget::Command => {
+                        self.command_query.clear();
+                    }
+                    SearchTarget::Export => {
+                        self.export_query.clear();
+                    }
+                    SearchTarget::TimeWindow => {
+                        self.time_window_query.clear();
+                    }
+                    SearchTarget::Note => {
+                        self.note_query.clear();
                     }
                 }
                 self.search_mode = None;
             }
+            KeyCode::Tab if matches!(target, SearchTarget::Export) => {
+                self.export_format = export::next_format(self.export_format);
+            }
+            KeyCode::Tab if matches!(target, SearchTarget::Command) => {
+                self.command_query = command::complete(&self.command_query).0;
+            }
             KeyCode::Enter => {
+                match target {
+                    SearchTarget::Jump => {
+                        let query = std::mem::take(&mut self.jump_query);
+                        self.jump_to(&query);
+                    }
+                    SearchTarget::Command => {
+                        let input = std::mem::take(&mut self.command_query);
+                        if !input.trim().is_empty() {
+                            self.run_command(&input);
+                        }
+                    }
+                    SearchTarget::Export => {
+                        let dir = std::mem::take(&mut self.export_query);
+                        if !dir.is_empty() {
+                            self.export_matching(&dir);
+                        }
+                    }
+                    SearchTarget::TimeWindow => {
+                        let input = std::mem::take(&mut self.time_window_query);
+                        self.apply_time_window(&input);
+                    }
+                    SearchTarget::Note => {
+                        let note = std::mem::take(&mut self.note_query);
+                        self.save_note(&note);
+                    }
+                    _ => {}
+                }
                 self.search_mode = None;
             }
             KeyCode::Backspace => match target {
@@ -340,7 +2702,23 @@ impl App {
                     self.update_filter();
                 }
                 SearchTarget::DetailLog => {
-                    self.detail_search_query.pop();
+                    self.app_view.detail_search.query.pop();
+                    self.search_detail();
+                }
+                SearchTarget::Jump => {
+                    self.jump_query.pop();
+                }
+                SearchTarget::Command => {
+                    self.command_query.pop();
+                }
+                SearchTarget::Export => {
+                    self.export_query.pop();
+                }
+                SearchTarget::TimeWindow => {
+                    self.time_window_query.pop();
+                }
+                SearchTarget::Note => {
+                    self.note_query.pop();
                 }
             },
             KeyCode::Char(c) => match target {
@@ -349,7 +2727,23 @@ impl App {
                     self.update_filter();
                 }
                 SearchTarget::DetailLog => {
-                    self.detail_search_query.push(c);
+                    self.app_view.detail_search.query.push(c);
+                    self.search_detail();
+                }
+                SearchTarget::Jump => {
+                    self.jump_query.push(c);
+                }
+                SearchTarget::Command => {
+                    self.command_query.push(c);
+                }
+                SearchTarget::Export => {
+                    self.export_query.push(c);
+                }
+                SearchTarget::TimeWindow => {
+                    self.time_window_query.push(c);
+                }
+                SearchTarget::Note => {
+                    self.note_query.push(c);
                 }
             },
             _ => {}
@@ -357,74 +2751,238 @@ impl App {
     }
 
     fn handle_normal_key(&mut self, key: event::KeyEvent) -> color_eyre::Result<()> {
+        if self.tutorial.is_some() && matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+            self.advance_tutorial(key.code == KeyCode::Esc);
+            return Ok(());
+        }
+        if self.app_view.show_bulk_actions {
+            self.handle_bulk_action(key);
+            return Ok(());
+        }
+        if let Some(cursor) = self.column_cursor {
+            self.handle_column_key(key, cursor);
+            return Ok(());
+        }
+        if self.explain.is_some() {
+            self.handle_explain_key(key);
+            return Ok(());
+        }
+        let after_esc = self.esc_at.take().is_some_and(|at| at.elapsed() < ESC_ALT_WINDOW);
+        if key.code == KeyCode::Esc {
+            self.esc_at = Some(Instant::now());
+        }
+        if let KeyCode::Char(digit @ '1'..='9') = key.code
+            && (key.modifiers.contains(event::KeyModifiers::ALT) || after_esc)
+        {
+            self.count_prefix.clear();
+            self.apply_preset(digit);
+            return Ok(());
+        }
+        if let KeyCode::Char(c @ '0'..='9') = key.code
+            && (c != '0' || !self.count_prefix.is_empty())
+        {
+            self.count_prefix.push(c);
+            return Ok(());
+        }
+        let count_text = std::mem::take(&mut self.count_prefix);
+        let count = count_text.parse::<usize>().ok();
+        // The first `g` of `gg` waits for the second, keeping the count typed before it
+        if key.code == KeyCode::Char('g') {
+            if std::mem::take(&mut self.g_pending) {
+                self.go_to_line(count.unwrap_or(1));
+            } else {
+                self.g_pending = true;
+                self.count_prefix = count_text;
+            }
+            return Ok(());
+        }
+        self.g_pending = false;
+        let steps = count.unwrap_or(1);
+        if self.line_select_active() && self.handle_line_select_key(key, steps) {
+            return Ok(());
+        }
+        if self.app_view.focused_panel == Panel::RequestList && self.handle_endpoint_key(key, steps)
+        {
+            return Ok(());
+        }
+
         match key.code {
+            // Popups close before Esc clears the filter under them
+            KeyCode::Esc if self.app_view.show_about => self.app_view.show_about = false,
+            KeyCode::Esc if self.app_view.show_notifications => {
+                self.app_view.show_notifications = false;
+            }
+            KeyCode::Esc if self.app_view.show_request_info => {
+                self.app_view.show_request_info = false;
+            }
+            KeyCode::Esc if self.state.visual_anchor.is_some() => self.state.visual_anchor = None,
             KeyCode::Char('/') => match self.app_view.focused_panel {
                 Panel::RequestList => {
                     self.search_mode = Some(SearchTarget::RequestList);
                     self.search_query.clear();
-                    self.filtered_indices = None;
+                    self.update_filter();
                 }
                 Panel::RequestDetail => {
                     self.search_mode = Some(SearchTarget::DetailLog);
-                    self.detail_search_query.clear();
+                    self.app_view.detail_search = PanelSearch::default();
                 }
                 _ => {}
             },
             KeyCode::Esc
                 if self.filtered_indices.is_some()
-                    || !self.detail_search_query.is_empty() =>
+                    || !self.app_view.detail_search.query.is_empty() =>
             {
                 self.search_query.clear();
-                self.filtered_indices = None;
-                self.detail_search_query.clear();
+                self.app_view.detail_search = PanelSearch::default();
+                self.update_filter();
+            }
+            KeyCode::Char('n')
+                if self.app_view.focused_panel == Panel::RequestDetail
+                    && !self.app_view.detail_search.query.is_empty() =>
+            {
+                self.next_detail_match();
+            }
+            // Next match above while the detail panel has a search
+            KeyCode::Char('n') => {
+                if let Some(group) = self.state.selected_group() {
+                    self.note_query = group.note.clone().unwrap_or_default();
+                    self.search_mode = Some(SearchTarget::Note);
+                }
+            }
+            KeyCode::Char('@') if self.file_index.is_some() => {
+                self.search_mode = Some(SearchTarget::Jump);
+                self.jump_query.clear();
+            }
+            KeyCode::Char(':') => {
+                self.search_mode = Some(SearchTarget::Command);
+                self.command_query.clear();
             }
+            KeyCode::Char('G') => self.go_to_line(count.unwrap_or(usize::MAX)),
             KeyCode::BackTab => self.toggle_focus_reverse(),
             KeyCode::Tab => self.toggle_focus(),
             KeyCode::Char(' ') => self.jump_to_latest(),
             KeyCode::Char('m') | KeyCode::Char('M') => self.toggle_copy_mode()?,
-            KeyCode::Char('s') | KeyCode::Char('S') => self.toggle_simple_mode(),
+            KeyCode::Char('s') | KeyCode::Char('S') => self.cycle_verbosity(),
+            KeyCode::Char('J') if !self.config.generic => self.perform(Action::CycleKind),
+            KeyCode::Char('F') if self.has_multiple_sources() => {
+                self.perform(Action::CycleSource)
+            }
+            KeyCode::Char('b') => self.toggle_star(),
+            KeyCode::Char('B') => self.perform(Action::StarredOnly),
+            KeyCode::Char('!') => self.perform(Action::ErrorsOnly),
+            KeyCode::Char('.') => {
+                if let Some(action) = self.history.last().cloned() {
+                    for _ in 0..steps {
+                        self.run_action(&action);
+                    }
+                }
+            }
+            KeyCode::Char('e') if !self.app_view.plain => self.toggle_endpoint_view(),
+            KeyCode::Char('T') => {
+                self.search_mode = Some(SearchTarget::TimeWindow);
+                self.time_window_query.clear();
+            }
+            KeyCode::Char('t') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.cycle_theme();
+            }
+            KeyCode::Char('t') => self.toggle_stats(),
+            KeyCode::Char('L') => self.cycle_timeline(),
+            KeyCode::Char('C') => self.column_cursor = Some(0),
+            KeyCode::Char('H') => {
+                self.group_by = GroupBy::cycle(self.group_by);
+                self.scroll_list_to(self.filtered_position(self.state.selected_index));
+            }
+            KeyCode::Char('A') if self.is_filtered() || self.state.visual_anchor.is_some() => {
+                self.app_view.show_bulk_actions = true;
+            }
+            KeyCode::Char('p') => self.toggle_pause(),
+            KeyCode::Char('w') => self.app_view.wrap_detail = !self.app_view.wrap_detail,
+            KeyCode::Char('P') => self.app_view.expand_params = !self.app_view.expand_params,
+            KeyCode::Char('I') => self.app_view.inline_binds = !self.app_view.inline_binds,
+            KeyCode::Char('r') => self.app_view.relative_times = !self.app_view.relative_times,
+            KeyCode::Char('v') if self.app_view.focused_panel == Panel::RequestDetail => {
+                self.toggle_line_select();
+            }
+            KeyCode::Char('V') => self.toggle_visual(),
+            KeyCode::Char('?') => self.app_view.show_about = !self.app_view.show_about,
+            KeyCode::Char('<') => self.app_view.resize_focused(false),
+            KeyCode::Char('>') => self.app_view.resize_focused(true),
+            KeyCode::Char('N') => {
+                self.app_view.show_notifications = !self.app_view.show_notifications;
+            }
+            KeyCode::Char('i') => {
+                self.app_view.show_request_info = !self.app_view.show_request_info;
+            }
+            KeyCode::Char('Y') => self.copy_request_id(),
+            KeyCode::Char('E') => self.explain_selected_line(),
+            KeyCode::Char('o') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.app_view.focus_back();
+            }
+            KeyCode::Char('o') => self.open_in_editor(),
+            KeyCode::Char('y') => self.copy_request(),
+            KeyCode::Char('x') => self.dismiss_selected(),
+            KeyCode::Char('X') => self.clear_finished(),
+            KeyCode::Char('U') => self.restore_selected(),
+            KeyCode::Char('R') => self.resume_input(),
             KeyCode::Char('d')
                 if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
             {
-                match self.app_view.focused_panel {
-                    Panel::RequestList => self.next_request(REQUEST_SKIP_COUNT),
-                    _ => self.apply_scroll_to(
-                        self.app_view.focused_panel,
-                        SCROLL_PAGE_SIZE as isize,
-                    ),
-                }
+                self.perform(Action::HalfPage { down: true });
             }
             KeyCode::Char('u')
                 if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
             {
-                match self.app_view.focused_panel {
-                    Panel::RequestList => self.previous_request(REQUEST_SKIP_COUNT),
-                    _ => self.apply_scroll_to(
-                        self.app_view.focused_panel,
-                        -(SCROLL_PAGE_SIZE as isize),
-                    ),
-                }
+                self.perform(Action::HalfPage { down: false });
             }
-            KeyCode::Char('j') | KeyCode::Down => match self.app_view.focused_panel {
-                Panel::RequestList => self.next_request(SCROLL_UNIT),
-                _ => self.apply_scroll_to(self.app_view.focused_panel, SCROLL_UNIT as isize),
-            },
-            KeyCode::Char('k') | KeyCode::Up => match self.app_view.focused_panel {
-                Panel::RequestList => self.previous_request(SCROLL_UNIT),
-                _ => self.apply_scroll_to(self.app_view.focused_panel, -(SCROLL_UNIT as isize)),
-            },
-            KeyCode::PageDown => {
-                self.apply_scroll_to(self.app_view.focused_panel, SCROLL_PAGE_SIZE as isize)
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.perform(Action::Move { down: true, steps });
             }
-            KeyCode::PageUp => self.apply_scroll_to(
-                self.app_view.focused_panel,
-                -(SCROLL_PAGE_SIZE as isize),
-            ),
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.perform(Action::Move { down: false, steps });
+            }
+            KeyCode::PageDown => self.perform(Action::Page { down: true }),
+            KeyCode::PageUp => self.perform(Action::Page { down: false }),
             _ => {}
         }
         Ok(())
     }
 
+    /// Runs `action` and keeps it for `.`.
+    fn perform(&mut self, action: Action) {
+        self.run_action(&action);
+        self.history.record(action);
+    }
+
+    /// Runs `action` on the panel focused now, which for a repeat may not be
+    /// the one it was first taken on.
+    fn run_action(&mut self, action: &Action) {
+        let focused = self.app_view.focused_panel;
+        let sign = |down: bool| if down { 1 } else { -1 };
+        match *action {
+            Action::Move { down, steps } => {
+                let lines = SCROLL_UNIT * steps;
+                match (focused, down) {
+                    (Panel::RequestList, true) => self.next_request(lines),
+                    (Panel::RequestList, false) => self.previous_request(lines),
+                    (panel, _) => self.apply_scroll_to(panel, sign(down) * lines as isize),
+                }
+            }
+            Action::HalfPage { down } => match (focused, down) {
+                (Panel::RequestList, true) => self.next_request(REQUEST_SKIP_COUNT),
+                (Panel::RequestList, false) => self.previous_request(REQUEST_SKIP_COUNT),
+                (panel, _) => self.apply_scroll_to(panel, sign(down) * SCROLL_PAGE_SIZE as isize),
+            },
+            Action::Page { down } => {
+                self.apply_scroll_to(focused, sign(down) * SCROLL_PAGE_SIZE as isize)
+            }
+            Action::Filter(ref filter) => self.apply_filter(filter.clone()),
+            Action::CycleKind => self.cycle_kind_filter(),
+            Action::CycleSource => self.cycle_source_filter(),
+            Action::StarredOnly => self.toggle_starred_only(),
+            Action::ErrorsOnly => self.toggle_errors_only(),
+        }
+    }
+
     fn handle_mouse_event(&mut self, mouse_event: event::MouseEvent) {
         let (x, y) = (mouse_event.column, mouse_event.row);
 
@@ -458,44 +3016,90 @@ impl App {
             }
 
             event::MouseEventKind::Down(event::MouseButton::Left) => {
-                if let Some(border_idx) = self.app_view.border_at_point(x) {
+                if let Some(area) = self.app_view.layout_info.timeline()
+                    && area.contains(ratatui::layout::Position::new(x, y))
+                {
+                    let view = view_model::timeline(self);
+                    if let Some(index) = panel_components::timeline_request_at(&view, area, x, y) {
+                        self.select_request(index);
+                    }
+                } else if let Some(border_idx) = self.app_view.border_at_point(x) {
                     self.app_view.dragging_border = Some(border_idx);
                 } else {
                     match self.app_view.panel_at_point(x, y) {
+                        Some(Panel::RequestList) if self.endpoint_cursor.is_some() => {
+                            self.app_view.focus(Panel::RequestList);
+                        }
                         Some(Panel::RequestList) => {
-                            self.app_view.focused_panel = Panel::RequestList;
+                            self.app_view.focus(Panel::RequestList);
                             let list_y =
                                 self.app_view.layout_info.region(Panel::RequestList).y;
-                            let row_in_list = y.saturating_sub(list_y + 2);
+                            // Border and padding, or just the heading in plain mode
+                            let header_rows = if self.app_view.plain { 1 } else { 2 };
+                            let row_in_list = y.saturating_sub(list_y + header_rows);
                             let current_offset =
                                 self.app_view.get_scroll_offset(Panel::RequestList);
-                            let clicked_visual = current_offset + row_in_list as usize;
+                            let clicked = self
+                                .list_layout(current_offset)
+                                .get(row_in_list as usize)
+                                .and_then(|row| match row {
+                                    ListRow::Request(position) => Some(*position),
+                                    ListRow::Header(_) => None,
+                                });
 
                             let visible = self.visible_request_ids();
-                            if let Some(&(original_index, _)) = visible.get(clicked_visual)
+                            if let Some(&(original_index, _)) =
+                                clicked.and_then(|position| visible.get(position))
                             {
                                 self.select_request(original_index);
                             }
                         }
+                        Some(Panel::SqlInfo) => {
+                            self.app_view.focus(Panel::SqlInfo);
+                            let sql_y = self.app_view.layout_info.region(Panel::SqlInfo).y;
+                            let row_in_panel = y.saturating_sub(sql_y + 1) as usize
+                                + self.app_view.get_scroll_offset(Panel::SqlInfo);
+                            // Plain panels are not wrapped
+                            let wrap_width = (!self.app_view.plain)
+                                .then(|| self.app_view.viewport_width(Panel::SqlInfo));
+                            let strings = self.config.locale.strings();
+                            let row = self.state.selected_group().and_then(|group| {
+                                panel_components::sql_row_at(strings, group, row_in_panel, wrap_width)
+                            });
+                            if let Some(row) = row {
+                                self.jump_to_sql_row(&row);
+                            }
+                        }
+                        Some(Panel::RequestDetail) if !self.app_view.plain => {
+                            self.app_view.focus(Panel::RequestDetail);
+                            self.start_drag_selection(y);
+                        }
                         Some(panel) => {
-                            self.app_view.focused_panel = panel;
+                            self.app_view.focus(panel);
                         }
                         _ => {}
                     }
                 }
             }
 
-            event::MouseEventKind::Drag(event::MouseButton::Left) => {
-                if self.app_view.dragging_border.is_some() {
-                    let total_width = self.app_view.layout_info.region(Panel::RequestList).width
-                        + self.app_view.layout_info.region(Panel::RequestDetail).width
-                        + self.app_view.layout_info.region(Panel::SqlInfo).width;
-                    self.app_view.apply_drag(x, total_width);
-                }
+            event::MouseEventKind::Drag(event::MouseButton::Left)
+                if self.app_view.dragging_border.is_some() =>
+            {
+                let total_width = self.app_view.layout_info.region(Panel::RequestList).width
+                    + self.app_view.layout_info.region(Panel::RequestDetail).width
+                    + self.app_view.layout_info.region(Panel::SqlInfo).width;
+                self.app_view.apply_drag(x, total_width);
+            }
+
+            event::MouseEventKind::Drag(event::MouseButton::Left) if self.selecting => {
+                self.extend_drag_selection(y);
             }
 
             event::MouseEventKind::Up(event::MouseButton::Left) => {
                 self.app_view.dragging_border = None;
+                if self.selecting {
+                    self.finish_drag_selection();
+                }
             }
 
             _ => {}
@@ -505,6 +3109,9 @@ impl App {
     pub fn toggle_copy_mode(&mut self) -> color_eyre::Result<()> {
         self.copy_mode_enabled = !self.copy_mode_enabled;
 
+        if !self.mouse_enabled {
+            return Ok(());
+        }
         let mut stdout = std::io::stdout();
         if self.copy_mode_enabled {
             crossterm::execute!(stdout, crossterm::event::DisableMouseCapture)?;
@@ -515,3 +3122,8 @@ impl App {
         Ok(())
     }
 }
+
+fn is_ctrl_c(event: &Event) -> bool {
+    matches!(event, Event::Key(key)
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL))
+}
@@ -1,5 +1,27 @@
 use crate::layout::{LayoutInfo, Panel};
 
+const MIN_RATIO: f64 = 0.10;
+const RESIZE_STEP: f64 = 0.05;
+/// Panels Ctrl+o can go back through
+const FOCUS_HISTORY_LIMIT: usize = 16;
+
+/// What the timeline strip below the panels shows (`L`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineScope {
+    /// The queries, renders and HTTP calls of the selected request
+    Request,
+    /// The requests on screen in the list, to see which ran at once
+    AllRequests,
+}
+
+/// What `/` looks for in a panel, and the match it went to last.
+#[derive(Debug, Clone, Default)]
+pub struct PanelSearch {
+    pub query: String,
+    /// Chronological index of the entry of the current match
+    pub current: Option<usize>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ScrollDirection {
     Up(usize),
@@ -8,10 +30,42 @@ pub enum ScrollDirection {
 
 pub struct AppView {
     pub focused_panel: Panel,
+    /// Panels focused before, the latest last (Ctrl+o)
+    focus_history: Vec<Panel>,
     pub scroll_offsets: [usize; 3],
+    /// Search within the selected request (`/` in the detail panel)
+    pub detail_search: PanelSearch,
     pub layout_info: LayoutInfo,
     pub panel_ratios: [f64; 3],
     pub dragging_border: Option<usize>,
+    pub show_stats: bool,
+    /// Timeline strip below the panels (`L`)
+    pub timeline: Option<TimelineScope>,
+    pub show_about: bool,
+    /// Toast history popup (`N`)
+    pub show_notifications: bool,
+    /// Details of the selected request (`i`)
+    pub show_request_info: bool,
+    /// Actions on every request the filter matches (`A`)
+    pub show_bulk_actions: bool,
+    /// Full text of the line under the detail cursor (Enter)
+    pub show_line_popup: bool,
+    /// Wrap long lines in the detail panel instead of truncating them (`w`)
+    pub wrap_detail: bool,
+    /// Show request parameters as an indented tree under their line (`P`)
+    pub expand_params: bool,
+    /// Show queries with their binds in place of the placeholders (`I`)
+    pub inline_binds: bool,
+    /// Lead detail lines with their time since the request's first line (`r`)
+    pub relative_times: bool,
+    /// Borderless screen-reader layout (`--plain`)
+    pub plain: bool,
+}
+
+impl Default for AppView {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppView {
@@ -20,10 +74,24 @@ impl AppView {
     pub fn new() -> Self {
         Self {
             focused_panel: Panel::RequestList,
+            focus_history: Vec::new(),
             scroll_offsets: [0; 3],
+            detail_search: PanelSearch::default(),
             layout_info: LayoutInfo::new(),
             panel_ratios: [0.20, 0.60, 0.20],
             dragging_border: None,
+            show_stats: false,
+            timeline: None,
+            show_about: false,
+            show_notifications: false,
+            show_request_info: false,
+            show_bulk_actions: false,
+            show_line_popup: false,
+            wrap_detail: true,
+            expand_params: false,
+            inline_binds: false,
+            relative_times: false,
+            plain: false,
         }
     }
 
@@ -46,12 +114,15 @@ impl AppView {
 
     pub fn viewport_height(&self, panel: Panel) -> usize {
         let region = self.layout_info.region(panel);
-        region.height.saturating_sub(Self::VIEW_PADDING) as usize
+        // Plain panels only spend one row on their heading
+        let padding = if self.plain { 1 } else { Self::VIEW_PADDING };
+        region.height.saturating_sub(padding) as usize
     }
 
     pub fn viewport_width(&self, panel: Panel) -> usize {
         let region = self.layout_info.region(panel);
-        region.width.saturating_sub(Self::VIEW_PADDING) as usize
+        let padding = if self.plain { 0 } else { Self::VIEW_PADDING };
+        region.width.saturating_sub(padding) as usize
     }
 
     pub fn adjust_scroll_for_index(&mut self, panel: Panel, index: usize) {
@@ -79,6 +150,10 @@ impl AppView {
     }
 
     pub fn border_at_point(&self, x: u16) -> Option<usize> {
+        // Stacked panels, as in plain mode or a narrow terminal, are not resizable
+        if self.layout_info.stacked() {
+            return None;
+        }
         let list_region = self.layout_info.region(Panel::RequestList);
         let detail_region = self.layout_info.region(Panel::RequestDetail);
 
@@ -94,9 +169,57 @@ impl AppView {
         }
     }
 
-    pub fn apply_drag(&mut self, x: u16, total_width: u16) {
-        const MIN_RATIO: f64 = 0.10;
+    /// Moves the focus to `panel`, remembering the panel it leaves.
+    pub fn focus(&mut self, panel: Panel) {
+        if panel == self.focused_panel {
+            return;
+        }
+        if self.focus_history.len() == FOCUS_HISTORY_LIMIT {
+            self.focus_history.remove(0);
+        }
+        self.focus_history.push(self.focused_panel);
+        self.focused_panel = panel;
+    }
+
+    /// Moves the focus back to the panel focused before (Ctrl+o). Each press
+    /// goes one panel further back; false once there is none left.
+    pub fn focus_back(&mut self) -> bool {
+        while let Some(panel) = self.focus_history.pop() {
+            if panel != self.focused_panel {
+                self.focused_panel = panel;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Grows or shrinks the focused panel from the keyboard (`<` / `>`). Side
+    /// panels trade width with the detail panel, which trades with both.
+    pub fn resize_focused(&mut self, grow: bool) {
+        let step = if grow { RESIZE_STEP } else { -RESIZE_STEP };
+        let mut ratios = self.panel_ratios;
+        match self.focused_panel {
+            Panel::RequestList => {
+                ratios[0] += step;
+                ratios[1] -= step;
+            }
+            Panel::SqlInfo => {
+                ratios[2] += step;
+                ratios[1] -= step;
+            }
+            Panel::RequestDetail => {
+                ratios[1] += step;
+                ratios[0] -= step / 2.0;
+                ratios[2] -= step / 2.0;
+            }
+        }
+        // Tolerates float drift after many steps
+        if ratios.iter().all(|&ratio| ratio >= MIN_RATIO - 1e-9) {
+            self.panel_ratios = ratios;
+        }
+    }
 
+    pub fn apply_drag(&mut self, x: u16, total_width: u16) {
         let Some(border_idx) = self.dragging_border else {
             return;
         };
@@ -167,4 +290,34 @@ mod tests {
         assert!(!AppView::is_in_region(15, 9, &rect)); // Top out
         assert!(!AppView::is_in_region(15, 25, &rect)); // Bottom out
     }
+
+    #[test]
+    fn test_resize_focused() {
+        let mut view = AppView::new();
+        view.resize_focused(true);
+        assert!((view.panel_ratios[0] - 0.25).abs() < 1e-9);
+        assert!((view.panel_ratios[1] - 0.55).abs() < 1e-9);
+
+        view.focused_panel = Panel::SqlInfo;
+        for _ in 0..10 {
+            view.resize_focused(false);
+        }
+        // Stops at the minimum width
+        assert!((view.panel_ratios[2] - MIN_RATIO).abs() < 1e-9);
+        assert!((view.panel_ratios.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_focus_back() {
+        let mut view = AppView::new();
+        view.focus(Panel::RequestDetail);
+        view.focus(Panel::SqlInfo);
+        view.focus(Panel::SqlInfo);
+        assert!(view.focus_back());
+        assert_eq!(view.focused_panel, Panel::RequestDetail);
+        assert!(view.focus_back());
+        assert_eq!(view.focused_panel, Panel::RequestList);
+        assert!(!view.focus_back());
+        assert_eq!(view.focused_panel, Panel::RequestList);
+    }
 }
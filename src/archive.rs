@@ -0,0 +1,148 @@
+//! Copy of every raw input line (`--archive DIR`), one file per day, so the
+//! original log can still be searched after lucy has consumed stdin.
+
+use chrono::{Local, NaiveDate};
+use color_eyre::{Result, eyre::WrapErr};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Hands lines to a writer thread, so a slow disk never holds up the screen.
+/// Dropping it writes out the lines still queued.
+pub struct Archive {
+    tx: Option<Sender<(NaiveDate, String)>>,
+    writer: Option<JoinHandle<()>>,
+    today: Box<dyn Fn() -> NaiveDate>,
+}
+
+impl Archive {
+    /// Starts archiving into `dir`, created if missing.
+    pub fn start(dir: &Path) -> Result<Self> {
+        Self::start_with(dir, || Local::now().date_naive())
+    }
+
+    fn start_with(dir: &Path, today: impl Fn() -> NaiveDate + 'static) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create archive directory {}", dir.display()))?;
+        // Opened here so that an unwritable directory fails at startup
+        let file = DailyFile::open(dir, today())?;
+        let (tx, rx) = mpsc::channel();
+        let writer = thread::spawn(move || {
+            if let Err(e) = write_loop(file, &rx) {
+                tracing::error!("Archive writer stopped: {}", e);
+            }
+        });
+        Ok(Self {
+            tx: Some(tx),
+            writer: Some(writer),
+            today: Box::new(today),
+        })
+    }
+
+    /// Queues a line as read, with or without its newline, for the file of
+    /// the day it was read.
+    pub fn write(&self, line: &str) {
+        if let Some(tx) = &self.tx {
+            // The writer only goes away after an error it has logged
+            let _ = tx.send(((self.today)(), line.to_string()));
+        }
+    }
+}
+
+impl Drop for Archive {
+    fn drop(&mut self) {
+        self.tx = None;
+        if let Some(writer) = self.writer.take()
+            && writer.join().is_err()
+        {
+            tracing::error!("Archive writer panicked");
+        }
+    }
+}
+
+/// `lucy-2024-05-01.log` in the archive directory, appended to.
+struct DailyFile {
+    dir: PathBuf,
+    date: NaiveDate,
+    writer: BufWriter<File>,
+}
+
+impl DailyFile {
+    fn open(dir: &Path, date: NaiveDate) -> Result<Self> {
+        let path = dir.join(file_name(date));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .wrap_err_with(|| format!("Failed to open archive {}", path.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            date,
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+fn file_name(date: NaiveDate) -> String {
+    format!("lucy-{}.log", date.format("%Y-%m-%d"))
+}
+
+fn write_loop(mut file: DailyFile, rx: &Receiver<(NaiveDate, String)>) -> Result<()> {
+    while let Ok(first) = rx.recv() {
+        for (date, line) in std::iter::once(first).chain(rx.try_iter()) {
+            if date != file.date {
+                file.writer.flush()?;
+                file = DailyFile::open(&file.dir, date)?;
+            }
+            write_line(&mut file.writer, &line)?;
+        }
+        // Caught up: make what was read so far visible to grep
+        file.writer.flush()?;
+    }
+    Ok(())
+}
+
+fn write_line(writer: &mut impl Write, line: &str) -> io::Result<()> {
+    writer.write_all(line.as_bytes())?;
+    if !line.ends_with('\n') {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_archive_by_day() {
+        let dir = std::env::temp_dir().join(format!("lucy-archive-{}", std::process::id()));
+        let day = Rc::new(Cell::new(1));
+        let clock = Rc::clone(&day);
+        let date = move || NaiveDate::from_ymd_opt(2024, 5, clock.get()).unwrap();
+
+        let archive = Archive::start_with(&dir, date.clone()).unwrap();
+        archive.write("[a] first\n");
+        archive.write("[a] no newline");
+        drop(archive);
+        // Same day: appended to
+        let archive = Archive::start_with(&dir, date).unwrap();
+        archive.write("[a] third\n");
+        day.set(2);
+        archive.write("[b] next day\n");
+        drop(archive);
+
+        let read = |day| {
+            let date = NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+            fs::read_to_string(dir.join(file_name(date))).unwrap()
+        };
+        assert_eq!(read(1), "[a] first\n[a] no newline\n[a] third\n");
+        assert_eq!(read(2), "[b] next day\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
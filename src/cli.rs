@@ -0,0 +1,421 @@
+use crate::input::ListenAddr;
+use crate::theme::Palette;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use std::path::PathBuf;
+
+/// Requests per second started by `lucy demo` without `--rate`.
+const DEFAULT_DEMO_RATE: f64 = 2.0;
+/// Narrowest panel `--layout` accepts, in percent of the width.
+const MIN_PANEL_PERCENT: u32 = 10;
+
+const AFTER_HELP: &str = "\
+Examples:
+  tail -f log/development.log | lucy
+  lucy -f log/development.log
+  lucy -- bin/rails server
+  lucy demo --rate 5
+  lucy --output jsonl log/test.log | jq 'select(.duration_ms > 500)'
+
+Without a file, lucy reads the log piped to it.";
+
+/// What `--output` prints in place of the viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One JSON object per finished request
+    Jsonl,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "lucy",
+    about = "Rails log viewer with request_id grouping",
+    override_usage = "lucy [OPTIONS] [FILE]...\n       lucy [OPTIONS] -- COMMAND...\n       lucy demo [--rate N] [OPTIONS]",
+    after_help = AFTER_HELP,
+    disable_version_flag = true,
+    args_override_self = true
+)]
+struct Cli {
+    /// Log files to load instead of reading stdin
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+    /// Keep reading lines appended to the files
+    #[arg(short, long)]
+    follow: bool,
+    /// Keep 1 in N fast successful requests
+    #[arg(long = "sample", value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    sample_rate: Option<u32>,
+    /// Regex whose first capture group is the key lines are grouped by
+    #[arg(long, value_name = "REGEX")]
+    group_key_regex: Option<String>,
+    /// Group logs that are not from Rails
+    #[arg(long)]
+    generic: bool,
+    /// Built-in color scheme
+    #[arg(long, value_name = "NAME", value_parser = parse_theme)]
+    theme: Option<Palette>,
+    /// Widths of the list, detail and SQL panels in percent, such as 30,50,20
+    #[arg(long, value_name = "LIST,DETAIL,SQL", value_parser = parse_layout)]
+    layout: Option<[f64; 3]>,
+    /// Most requests kept in memory before the oldest are dropped
+    #[arg(long, value_name = "N", value_parser = parse_max_requests)]
+    max_requests: Option<usize>,
+    /// Drop finished requests older than this, relative to the newest line
+    #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    max_age: Option<u64>,
+    /// Screen-reader friendly output
+    #[arg(long)]
+    plain: bool,
+    /// Keyboard only, without mouse capture
+    #[arg(long)]
+    no_mouse: bool,
+    /// Accept log streams over sockets instead of stdin (unix:PATH or tcp:HOST:PORT)
+    #[arg(long, value_name = "ADDR", value_parser = parse_listen)]
+    listen: Vec<ListenAddr>,
+    /// Save input lines and key events to replay later
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+    /// Replay a recording instead of reading input
+    #[arg(long, value_name = "PATH")]
+    playback: Option<PathBuf>,
+    /// Also append every input line to a file per day in DIR
+    #[arg(long, value_name = "DIR")]
+    archive: Option<PathBuf>,
+    /// Feed a saved log at the pace of its timestamps
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+    /// Replay speed, such as 2x or 0.5x [default: 1x]
+    #[arg(long, value_name = "N", value_parser = parse_speed)]
+    speed: Option<f64>,
+    /// Print finished requests to stdout instead of viewing them (jsonl)
+    #[arg(long, value_name = "FORMAT", value_parser = parse_output)]
+    output: Option<OutputFormat>,
+    /// Requests per second of `lucy demo` [default: 2]
+    #[arg(long, value_name = "N", value_parser = parse_rate)]
+    rate: Option<f64>,
+    /// Print version details and exit
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// Run a command, such as a dev server, and read its stdout and stderr
+    #[arg(last = true, value_name = "COMMAND")]
+    command: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Log files to load instead of reading stdin.
+    pub files: Vec<PathBuf>,
+    /// Keep reading lines appended to the files (`--follow`).
+    pub follow: bool,
+    /// Keep 1 in N fast successful requests (`--sample N`).
+    pub sample_rate: Option<u32>,
+    /// Regex defining how lines are grouped (`--group-key-regex REGEX`).
+    pub group_key_regex: Option<String>,
+    /// Screen-reader friendly output (`--plain`).
+    pub plain: bool,
+    /// Built-in color scheme (`--theme NAME`).
+    pub theme: Option<Palette>,
+    /// Panel width ratios (`--layout 30,50,20`).
+    pub layout: Option<[f64; 3]>,
+    /// Overall cap on requests kept (`--max-requests N`).
+    pub max_requests: Option<usize>,
+    /// Age limit for finished requests of every status (`--max-age MINUTES`).
+    pub max_age_minutes: Option<u64>,
+    /// Keyboard only, without mouse capture (`--no-mouse`).
+    pub no_mouse: bool,
+    /// Logs that are not from Rails (`--generic`).
+    pub generic: bool,
+    /// Print version details and exit (`--version`).
+    pub version: bool,
+    /// Requests per second of the `lucy demo` stream, when running it.
+    pub demo_rate: Option<f64>,
+    /// Accept log streams over sockets instead of stdin (`--listen unix:PATH`).
+    pub listen: Vec<ListenAddr>,
+    /// Save input lines and key events to replay later (`--record PATH`).
+    pub record: Option<PathBuf>,
+    /// Replay a recording instead of reading input (`--playback PATH`).
+    pub playback: Option<PathBuf>,
+    /// Directory keeping a copy of the raw input (`--archive DIR`).
+    pub archive: Option<PathBuf>,
+    /// Saved log to feed at its original pace (`--replay FILE`), and the
+    /// speed to feed it at (`--speed 2x`).
+    pub replay: Option<(PathBuf, f64)>,
+    /// Print finished requests instead of viewing them (`--output jsonl`).
+    pub output: Option<OutputFormat>,
+    /// Command to run and read the output of (`lucy -- bin/rails server`).
+    pub command: Vec<String>,
+}
+
+impl Args {
+    /// Parses the command line, or prints the usage or error and exits.
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1)).unwrap_or_else(|e| e.exit())
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, clap::Error> {
+        let mut args = args.into_iter().peekable();
+        // Only a leading `demo` is the subcommand; anywhere else it names a file
+        let demo = args.next_if(|arg| arg == "demo").is_some();
+        let cli = Cli::try_parse_from(std::iter::once("lucy".to_string()).chain(args))?;
+
+        let invalid = |message: &str| Cli::command().error(ErrorKind::ArgumentConflict, message);
+        if cli.rate.is_some() && !demo {
+            return Err(invalid("--rate only applies to lucy demo"));
+        }
+        if demo && !cli.files.is_empty() {
+            return Err(invalid("lucy demo does not read a file"));
+        }
+        if cli.follow && cli.files.is_empty() {
+            return Err(invalid("--follow needs a file"));
+        }
+        if !cli.listen.is_empty() && (demo || cli.playback.is_some()) {
+            return Err(invalid("--listen cannot be combined with lucy demo or --playback"));
+        }
+        if cli.speed.is_some() && cli.replay.is_none() {
+            return Err(invalid("--speed only applies to --replay"));
+        }
+        if cli.replay.is_some()
+            && (demo || !cli.files.is_empty() || !cli.listen.is_empty() || cli.playback.is_some())
+        {
+            return Err(invalid("--replay reads its own file"));
+        }
+        if !cli.command.is_empty()
+            && (demo
+                || !cli.files.is_empty()
+                || !cli.listen.is_empty()
+                || cli.replay.is_some()
+                || cli.playback.is_some())
+        {
+            return Err(invalid("a command after -- is the only input"));
+        }
+        if cli.output.is_some() && (cli.record.is_some() || cli.playback.is_some()) {
+            return Err(invalid("--output cannot be combined with --record or --playback"));
+        }
+        if cli.playback.is_some() {
+            if cli.record.is_some() {
+                return Err(invalid("--record and --playback cannot be combined"));
+            }
+            if !cli.files.is_empty() || demo {
+                return Err(invalid("--playback replays its own input"));
+            }
+            if cli.archive.is_some() {
+                return Err(invalid("--archive cannot be combined with --playback"));
+            }
+        }
+
+        Ok(Self {
+            files: cli.files,
+            follow: cli.follow,
+            sample_rate: cli.sample_rate,
+            group_key_regex: cli.group_key_regex,
+            plain: cli.plain,
+            theme: cli.theme,
+            layout: cli.layout,
+            max_requests: cli.max_requests,
+            max_age_minutes: cli.max_age,
+            no_mouse: cli.no_mouse,
+            generic: cli.generic,
+            version: cli.version,
+            demo_rate: demo.then(|| cli.rate.unwrap_or(DEFAULT_DEMO_RATE)),
+            listen: cli.listen,
+            record: cli.record,
+            playback: cli.playback,
+            archive: cli.archive,
+            replay: cli.replay.map(|path| (path, cli.speed.unwrap_or(1.0))),
+            output: cli.output,
+            command: cli.command,
+        })
+    }
+}
+
+/// Prints the usage, for when there is nothing to read.
+pub fn print_usage() {
+    println!("{}", Cli::command().render_help());
+}
+
+fn parse_theme(value: &str) -> Result<Palette, String> {
+    Palette::from_name(value).ok_or_else(|| {
+        let names: Vec<_> = Palette::ALL.iter().map(|p| p.name()).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+fn parse_layout(value: &str) -> Result<[f64; 3], String> {
+    let percents: Vec<u32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| "expected three percentages such as 30,50,20".to_string())?;
+    let [list, detail, sql] = percents[..] else {
+        return Err("expected three percentages such as 30,50,20".to_string());
+    };
+    if list + detail + sql != 100 {
+        return Err("the percentages must add up to 100".to_string());
+    }
+    if percents.iter().any(|&percent| percent < MIN_PANEL_PERCENT) {
+        return Err(format!("each panel needs at least {}%", MIN_PANEL_PERCENT));
+    }
+    Ok([list, detail, sql].map(|percent| f64::from(percent) / 100.0))
+}
+
+fn parse_output(value: &str) -> Result<OutputFormat, String> {
+    match value {
+        "jsonl" => Ok(OutputFormat::Jsonl),
+        _ => Err("expected jsonl".to_string()),
+    }
+}
+
+fn parse_max_requests(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+fn parse_listen(value: &str) -> Result<ListenAddr, String> {
+    ListenAddr::parse(value).map_err(|e| e.to_string())
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    parse_rate(value.strip_suffix('x').unwrap_or(value))
+        .map_err(|_| "expected a positive speed such as 2x or 0.5x".to_string())
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        Args::parse_from(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert!(parse(&[]).unwrap().files.is_empty());
+
+        let args = parse(&["log/development.log"]).unwrap();
+        assert_eq!(args.files, [PathBuf::from("log/development.log")]);
+
+        let args = parse(&["--sample", "10", "a.log"]).unwrap();
+        assert_eq!(args.sample_rate, Some(10));
+        assert_eq!(args.files, [PathBuf::from("a.log")]);
+        assert!(parse(&["--sample", "0"]).is_err());
+        assert!(parse(&["--sample"]).is_err());
+
+        let args = parse(&["--group-key-regex", r"request_id=(\w+)"]).unwrap();
+        assert_eq!(args.group_key_regex.as_deref(), Some(r"request_id=(\w+)"));
+        assert!(parse(&["--group-key-regex"]).is_err());
+
+        let args = parse(&["-f", "a.log"]).unwrap();
+        assert!(args.follow);
+        assert!(parse(&["--follow"]).is_err());
+        let args = parse(&["-f", "a.log", "-f", "b.log"]).unwrap();
+        assert!(args.follow);
+        assert_eq!(args.files, [PathBuf::from("a.log"), PathBuf::from("b.log")]);
+
+        let args = parse(&["--plain", "a.log"]).unwrap();
+        assert!(args.plain);
+        assert!(!parse(&[]).unwrap().plain);
+
+        assert_eq!(parse(&["--theme", "solarized"]).unwrap().theme, Some(Palette::Solarized));
+        assert_eq!(parse(&["--theme", "dark"]).unwrap().theme, Some(Palette::Default));
+        assert!(parse(&["--theme", "neon"]).is_err());
+        assert!(parse(&["--theme"]).is_err());
+
+        assert!(parse(&["--generic"]).unwrap().generic);
+        assert!(parse(&["--no-mouse"]).unwrap().no_mouse);
+        assert!(parse(&["--version"]).unwrap().version);
+        assert!(parse(&["-V"]).unwrap().version);
+
+        let args = parse(&["demo"]).unwrap();
+        assert_eq!(args.demo_rate, Some(DEFAULT_DEMO_RATE));
+        let args = parse(&["demo", "--rate", "0.5", "--plain"]).unwrap();
+        assert_eq!(args.demo_rate, Some(0.5));
+        assert!(args.plain);
+        assert!(parse(&["demo", "--rate", "0"]).is_err());
+        assert!(parse(&["demo", "a.log"]).is_err());
+        assert!(parse(&["--rate", "2"]).is_err());
+        // Only a leading `demo` is the subcommand
+        assert_eq!(
+            parse(&["--plain", "demo"]).unwrap().files,
+            [PathBuf::from("demo")]
+        );
+
+        let args = parse(&["--listen", "unix:/tmp/lucy.sock"]).unwrap();
+        assert_eq!(
+            args.listen,
+            [ListenAddr::Unix(PathBuf::from("/tmp/lucy.sock"))]
+        );
+        assert!(parse(&["--listen"]).is_err());
+        let args = parse(&[
+            "--listen",
+            "tcp:127.0.0.1:9999",
+            "--listen",
+            "unix:a.sock",
+            "a.log",
+        ]);
+        assert_eq!(args.unwrap().listen.len(), 2);
+        assert!(parse(&["demo", "--listen", "tcp:127.0.0.1:9999"]).is_err());
+
+        let args = parse(&["--record", "events.bin", "a.log"]).unwrap();
+        assert_eq!(args.record, Some(PathBuf::from("events.bin")));
+        let args = parse(&["--playback", "events.bin"]).unwrap();
+        assert_eq!(args.playback, Some(PathBuf::from("events.bin")));
+        assert!(parse(&["--record"]).is_err());
+        assert!(parse(&["--playback", "a.bin", "--record", "b.bin"]).is_err());
+        assert!(parse(&["--playback", "a.bin", "a.log"]).is_err());
+        assert!(parse(&["demo", "--playback", "a.bin"]).is_err());
+
+        let args = parse(&["--archive", "logs/"]).unwrap();
+        assert_eq!(args.archive, Some(PathBuf::from("logs/")));
+        assert!(parse(&["--playback", "a.bin", "--archive", "logs/"]).is_err());
+
+        let args = parse(&["--replay", "a.log", "--speed", "2x"]).unwrap();
+        assert_eq!(args.replay, Some((PathBuf::from("a.log"), 2.0)));
+        let args = parse(&["--replay", "a.log", "--speed", "0.5"]).unwrap();
+        assert_eq!(args.replay, Some((PathBuf::from("a.log"), 0.5)));
+        assert_eq!(parse(&["--replay", "a.log"]).unwrap().replay.unwrap().1, 1.0);
+        assert!(parse(&["--replay", "a.log", "--speed", "0x"]).is_err());
+        assert!(parse(&["--speed", "2x", "a.log"]).is_err());
+        assert!(parse(&["--replay", "a.log", "b.log"]).is_err());
+
+        let args = parse(&["--output", "jsonl", "a.log"]).unwrap();
+        assert_eq!(args.output, Some(OutputFormat::Jsonl));
+        assert!(parse(&["--output", "yaml"]).is_err());
+        assert!(parse(&["--output", "jsonl", "--record", "a.bin"]).is_err());
+
+        let args = parse(&["--plain", "--", "bin/rails", "server", "-p", "3001"]).unwrap();
+        assert_eq!(args.command, ["bin/rails", "server", "-p", "3001"]);
+        assert!(args.plain && args.files.is_empty());
+        assert!(parse(&["a.log", "--", "bin/rails", "server"]).is_err());
+        assert!(parse(&["demo", "--", "bin/rails", "server"]).is_err());
+
+        assert!(parse(&["--unknown"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_layout_and_retention() {
+        let args = parse(&["--layout", "30,50,20"]).unwrap();
+        assert_eq!(args.layout, Some([0.3, 0.5, 0.2]));
+        assert!(parse(&["--layout", "30,50"]).is_err());
+        assert!(parse(&["--layout", "30,60,20"]).is_err());
+        assert!(parse(&["--layout", "5,75,20"]).is_err());
+
+        let args = parse(&["--max-requests", "500", "--max-age", "30"]).unwrap();
+        assert_eq!(args.max_requests, Some(500));
+        assert_eq!(args.max_age_minutes, Some(30));
+        assert!(parse(&["--max-age", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+}
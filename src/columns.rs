@@ -0,0 +1,154 @@
+//! Columns of the request list before each title, set with `[list] columns`
+//! and chosen at runtime with `C`.
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    /// Start time, `HH:MM`
+    Time,
+    Duration,
+    /// HTTP status code
+    Status,
+    /// HTTP method, which the title then leaves out
+    Method,
+    /// Controller and action, such as `Posts#show`
+    Action,
+    /// Number of SQL queries
+    Sql,
+    /// Number of log lines
+    Lines,
+    /// Input the request came from, with several inputs
+    Source,
+}
+
+impl Column {
+    pub const ALL: [Column; 8] = [
+        Column::Time,
+        Column::Duration,
+        Column::Status,
+        Column::Method,
+        Column::Action,
+        Column::Sql,
+        Column::Lines,
+        Column::Source,
+    ];
+
+    /// The list's columns until configured otherwise.
+    pub const DEFAULT: [Column; 3] = [Column::Time, Column::Duration, Column::Source];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Time => "time",
+            Column::Duration => "duration",
+            Column::Status => "status",
+            Column::Method => "method",
+            Column::Action => "action",
+            Column::Sql => "sql",
+            Column::Lines => "lines",
+            Column::Source => "source",
+        }
+    }
+}
+
+/// Every column in the order the chooser lists them: the shown ones as
+/// they appear, then the hidden ones.
+pub fn chooser_order(columns: &[Column]) -> Vec<Column> {
+    let mut order = columns.to_vec();
+    order.extend(
+        Column::ALL
+            .into_iter()
+            .filter(|column| !columns.contains(column)),
+    );
+    order
+}
+
+/// Shows `column` after the others, or hides it when shown.
+pub fn toggle(columns: &mut Vec<Column>, column: Column) {
+    if let Some(position) = columns.iter().position(|&shown| shown == column) {
+        columns.remove(position);
+    } else {
+        columns.push(column);
+    }
+}
+
+/// Swaps a shown `column` with its neighbour to the left (`earlier`) or to
+/// the right. Returns whether it moved.
+pub fn shift(columns: &mut [Column], column: Column, earlier: bool) -> bool {
+    let Some(position) = columns.iter().position(|&shown| shown == column) else {
+        return false;
+    };
+    let other = if earlier {
+        position.checked_sub(1)
+    } else {
+        Some(position + 1).filter(|&other| other < columns.len())
+    };
+    match other {
+        Some(other) => {
+            columns.swap(position, other);
+            true
+        }
+        None => false,
+    }
+}
+
+/// `title` without the HTTP method it starts with, for when the method has
+/// a column of its own.
+pub fn strip_method(title: &str) -> (Option<&str>, &str) {
+    match title.split_once(' ') {
+        Some((method, rest))
+            if matches!(
+                method,
+                "GET" | "POST" | "PUT" | "PATCH" | "DELETE" | "HEAD" | "OPTIONS"
+            ) =>
+        {
+            (Some(method), rest.trim_start())
+        }
+        _ => (None, title),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_and_shift() {
+        let mut columns = Column::DEFAULT.to_vec();
+        toggle(&mut columns, Column::Sql);
+        toggle(&mut columns, Column::Source);
+        assert_eq!(columns, [Column::Time, Column::Duration, Column::Sql]);
+        assert_eq!(
+            chooser_order(&columns),
+            [
+                Column::Time,
+                Column::Duration,
+                Column::Sql,
+                Column::Status,
+                Column::Method,
+                Column::Action,
+                Column::Lines,
+                Column::Source,
+            ]
+        );
+
+        assert!(shift(&mut columns, Column::Sql, true));
+        assert_eq!(columns, [Column::Time, Column::Sql, Column::Duration]);
+        assert!(!shift(&mut columns, Column::Duration, false));
+        assert!(!shift(&mut columns, Column::Time, true));
+        // Hidden columns have no place to move from
+        assert!(!shift(&mut columns, Column::Lines, true));
+    }
+
+    #[test]
+    fn test_strip_method() {
+        assert_eq!(strip_method("GET /posts"), (Some("GET"), "/posts"));
+        assert_eq!(
+            strip_method("DELETE  /posts/1"),
+            (Some("DELETE"), "/posts/1")
+        );
+        assert_eq!(strip_method("PostsJob"), (None, "PostsJob"));
+        assert_eq!(strip_method("Gets things done"), (None, "Gets things done"));
+    }
+}
@@ -0,0 +1,367 @@
+//! The `:` command line: every filter and view setting by name, so complex
+//! filters can be typed (or pasted) in one go, such as
+//! `:filter status=5xx kind=request last=15m`.
+//!
+//! Filter presets (`[presets]`, Alt+1 to Alt+9) are written the same way.
+//!
+//! `Tab` completes command names and arguments; a bare number still selects
+//! that request, as `:25` always did.
+
+use crate::app_state::GroupKind;
+use crate::config::StatusCodes;
+use crate::export;
+use crate::theme::Palette;
+
+/// Command names, in the order completion lists them.
+pub const COMMANDS: [&str; 14] = [
+    "filter",
+    "sort",
+    "export",
+    "theme",
+    "goto",
+    "simple",
+    "wrap",
+    "binds",
+    "relative",
+    "stats",
+    "timeline",
+    "endpoints",
+    "pause",
+    "clear",
+];
+
+const FILTER_TERMS: [&str; 14] = [
+    "status=",
+    "slow=",
+    "writes",
+    "duplicates",
+    "stderr",
+    "controller=",
+    "kind=request",
+    "kind=job",
+    "kind=cable",
+    "starred",
+    "errors",
+    "last=",
+    "text=",
+    "clear",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Newest first, as the requests came in
+    Time,
+    /// Slowest first
+    Duration,
+    /// Most severe first
+    Status,
+}
+
+impl SortKey {
+    const ALL: [SortKey; 3] = [SortKey::Time, SortKey::Duration, SortKey::Status];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Time => "time",
+            SortKey::Duration => "duration",
+            SortKey::Status => "status",
+        }
+    }
+}
+
+/// What `:filter` narrows the list to. Each `:filter` replaces the previous
+/// filter as a whole; one without terms shows every request again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub status: Option<StatusCodes>,
+    pub kind: Option<GroupKind>,
+    pub starred: bool,
+    pub errors: bool,
+    /// Minimum duration in milliseconds
+    pub slow: Option<u64>,
+    /// Only requests running INSERT, UPDATE or DELETE queries
+    pub writes: bool,
+    /// Only likely double submits
+    pub duplicates: bool,
+    /// Only requests with lines from a command's stderr
+    pub stderr: bool,
+    /// Only requests handled by this controller, such as `Posts` or
+    /// `Admin::PostsController`
+    pub controller: Option<String>,
+    /// As typed at the `T` prompt, such as `15m`
+    pub last: Option<String>,
+    /// Fuzzy match on the title, as typed at the `/` prompt
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Goto(usize),
+    Filter(Filter),
+    Sort(SortKey),
+    /// Writes the requests the filter matches, in the named format
+    Export {
+        format: &'static str,
+        dir: String,
+    },
+    Theme(Palette),
+    Simple,
+    Wrap,
+    Binds,
+    Relative,
+    Stats,
+    Timeline,
+    Endpoints,
+    Pause,
+    Clear,
+}
+
+/// Reads a command line typed after `:`. The error is a short reason to
+/// show after the line.
+pub fn parse(input: &str) -> Result<Command, String> {
+    let mut words = input.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("empty command".to_string());
+    };
+    if let Ok(number) = name.parse() {
+        return Ok(Command::Goto(number));
+    }
+    let args: Vec<&str> = words.collect();
+    let no_args = |command: Command| match args[..] {
+        [] => Ok(command),
+        _ => Err(format!("{} takes no arguments", name)),
+    };
+    match name {
+        "goto" => match args[..] {
+            [number] => number
+                .parse()
+                .map(Command::Goto)
+                .map_err(|_| format!("not a number: {}", number)),
+            _ => Err("expected a request number".to_string()),
+        },
+        "filter" => parse_filter(&args).map(Command::Filter),
+        "sort" => match args[..] {
+            [] => Ok(Command::Sort(SortKey::Time)),
+            [key] => SortKey::ALL
+                .into_iter()
+                .find(|sort| sort.name() == key)
+                .map(Command::Sort)
+                .ok_or_else(|| format!("expected one of {}", sort_names().join(", "))),
+            _ => Err("expected one sort key".to_string()),
+        },
+        "export" => match args[..] {
+            [format] | [format, _] => {
+                let format = export::format_named(format)
+                    .map_err(|_| format!("expected one of {}", format_names().join(", ")))?
+                    .name();
+                let dir = args.get(1).unwrap_or(&".").to_string();
+                Ok(Command::Export { format, dir })
+            }
+            _ => Err("expected a format and a directory".to_string()),
+        },
+        "theme" => match args[..] {
+            [theme] => Palette::from_name(theme)
+                .map(Command::Theme)
+                .ok_or_else(|| format!("expected one of {}", theme_names().join(", "))),
+            _ => Err("expected a theme name".to_string()),
+        },
+        "simple" => no_args(Command::Simple),
+        "wrap" => no_args(Command::Wrap),
+        "binds" => no_args(Command::Binds),
+        "relative" => no_args(Command::Relative),
+        "stats" => no_args(Command::Stats),
+        "timeline" => no_args(Command::Timeline),
+        "endpoints" => no_args(Command::Endpoints),
+        "pause" => no_args(Command::Pause),
+        "clear" => no_args(Command::Clear),
+        _ => Err(format!("unknown command {}", name)),
+    }
+}
+
+/// Reads the terms of `:filter`, or of a preset.
+pub fn parse_filter(terms: &[&str]) -> Result<Filter, String> {
+    let mut filter = Filter::default();
+    let mut text = Vec::new();
+    for &term in terms {
+        match term.split_once('=') {
+            Some(("status", codes)) => filter.status = Some(StatusCodes::parse(codes)?),
+            Some(("kind", "request")) => filter.kind = Some(GroupKind::Request),
+            Some(("kind", "job")) => filter.kind = Some(GroupKind::Job),
+            Some(("kind", "cable")) => filter.kind = Some(GroupKind::Cable),
+            Some(("kind", kind)) => return Err(format!("unknown kind {}", kind)),
+            Some(("slow", ms)) => {
+                let ms = ms.strip_suffix("ms").unwrap_or(ms);
+                filter.slow = Some(
+                    ms.parse()
+                        .map_err(|_| format!("not a number of milliseconds: {}", ms))?,
+                );
+            }
+            Some(("controller", controller)) => filter.controller = Some(controller.to_string()),
+            Some(("last", window)) => filter.last = Some(window.to_string()),
+            Some(("text", words)) => text.push(words),
+            _ if term == "starred" => filter.starred = true,
+            _ if term == "errors" => filter.errors = true,
+            _ if term == "writes" => filter.writes = true,
+            _ if term == "duplicates" => filter.duplicates = true,
+            _ if term == "stderr" => filter.stderr = true,
+            _ if term == "clear" => {}
+            // Anything else is searched for in the titles
+            _ => text.push(term),
+        }
+    }
+    filter.text = text.join(" ");
+    Ok(filter)
+}
+
+/// Completes the word being typed as far as the candidates agree. Returns
+/// the new input and the candidates for that word.
+pub fn complete(input: &str) -> (String, Vec<&'static str>) {
+    let (done, word) = match input.rfind(' ') {
+        Some(space) => input.split_at(space + 1),
+        None => ("", input),
+    };
+    let candidates: Vec<&'static str> = candidates(done)
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+    let completed = match candidates[..] {
+        [] => word.to_string(),
+        // Done with the word, unless a value goes after the `=`
+        [only] if only.ends_with('=') => only.to_string(),
+        [only] => format!("{} ", only),
+        _ => common_prefix(&candidates).to_string(),
+    };
+    (format!("{}{}", done, completed), candidates)
+}
+
+/// What may come after the completed words `done`.
+fn candidates(done: &str) -> Vec<&'static str> {
+    let words: Vec<&str> = done.split_whitespace().collect();
+    match words[..] {
+        [] => COMMANDS.to_vec(),
+        ["filter", ..] => FILTER_TERMS.to_vec(),
+        ["sort"] => sort_names(),
+        ["export"] => format_names(),
+        ["theme"] => theme_names(),
+        _ => Vec::new(),
+    }
+}
+
+fn common_prefix<'a>(words: &[&'a str]) -> &'a str {
+    let first = words[0];
+    let len = words[1..].iter().fold(first.len(), |len, word| {
+        first[..len]
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i)
+    });
+    &first[..len]
+}
+
+fn sort_names() -> Vec<&'static str> {
+    SortKey::ALL.iter().map(|key| key.name()).collect()
+}
+
+fn format_names() -> Vec<&'static str> {
+    export::FORMATS.iter().map(|format| format.name()).collect()
+}
+
+fn theme_names() -> Vec<&'static str> {
+    Palette::ALL.iter().map(|palette| palette.name()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("25"), Ok(Command::Goto(25)));
+        assert_eq!(parse("goto 3"), Ok(Command::Goto(3)));
+        assert_eq!(
+            parse("filter status=5xx kind=job errors posts index"),
+            Ok(Command::Filter(Filter {
+                status: Some(StatusCodes {
+                    first: 500,
+                    last: 599
+                }),
+                kind: Some(GroupKind::Job),
+                errors: true,
+                text: "posts index".to_string(),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(parse("filter"), Ok(Command::Filter(Filter::default())));
+        assert_eq!(
+            parse("filter slow=500ms writes controller=Admin::Posts"),
+            Ok(Command::Filter(Filter {
+                slow: Some(500),
+                writes: true,
+                controller: Some("Admin::Posts".to_string()),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(parse("sort duration"), Ok(Command::Sort(SortKey::Duration)));
+        assert_eq!(
+            parse("export json /tmp/out"),
+            Ok(Command::Export {
+                format: "json",
+                dir: "/tmp/out".to_string()
+            })
+        );
+        assert_eq!(
+            parse("export csv"),
+            Ok(Command::Export {
+                format: "csv",
+                dir: ".".to_string()
+            })
+        );
+        assert_eq!(parse("theme light"), Ok(Command::Theme(Palette::Light)));
+        assert_eq!(parse(" wrap "), Ok(Command::Wrap));
+
+        assert!(parse("").is_err());
+        assert!(parse("frobnicate").is_err());
+        assert!(parse("filter status=abc").is_err());
+        assert!(parse("filter slow=fast").is_err());
+        assert!(parse("sort size").is_err());
+        assert!(parse("export yaml").is_err());
+        assert!(parse("wrap now").is_err());
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(complete("th"), ("theme ".to_string(), vec!["theme"]));
+        assert_eq!(
+            complete("s"),
+            ("s".to_string(), vec!["sort", "simple", "stats"])
+        );
+        assert_eq!(complete("st"), ("stats ".to_string(), vec!["stats"]));
+        assert_eq!(
+            complete("sort d"),
+            ("sort duration ".to_string(), vec!["duration"])
+        );
+        assert_eq!(
+            complete("filter errors st"),
+            ("filter errors st".to_string(), vec!["status=", "stderr", "starred"])
+        );
+        assert_eq!(
+            complete("filter errors sta"),
+            ("filter errors sta".to_string(), vec!["status=", "starred"])
+        );
+        assert_eq!(
+            complete("filter errors stat"),
+            ("filter errors status=".to_string(), vec!["status="])
+        );
+        assert_eq!(
+            complete("filter kind="),
+            ("filter kind=".to_string(), vec!["kind=request", "kind=job", "kind=cable"])
+        );
+        assert_eq!(
+            complete("theme high"),
+            ("theme high-contrast ".to_string(), vec!["high-contrast"])
+        );
+        assert_eq!(complete("x"), ("x".to_string(), vec![]));
+        assert_eq!(complete("").1.len(), COMMANDS.len());
+    }
+}
@@ -0,0 +1,607 @@
+use crate::columns::Column;
+use crate::command;
+use crate::db::Adapter;
+use crate::hooks::HookConfig;
+use crate::i18n::{Locale, Strings, fill};
+use crate::icons::IconSet;
+use crate::theme::{ColorOverrides, Palette};
+use crate::time_format::{TimeFormat, TimeFormatConfig};
+use crate::time_headers::GroupBy;
+use crate::timestamp::TimestampConfig;
+use crate::title::TitleTemplate;
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub use lucy_core::retention::{RetentionConfig, RetentionRule};
+pub use lucy_core::status::{StatusCodes, StatusConfig};
+
+/// Which zone timestamps are displayed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeZoneMode {
+    #[default]
+    Local,
+    Utc,
+    /// Keep the offset the log line was written with.
+    Original,
+}
+
+impl TimeZoneMode {
+    pub fn convert(self, timestamp: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            TimeZoneMode::Local => timestamp.with_timezone(&Local).fixed_offset(),
+            TimeZoneMode::Utc => timestamp.with_timezone(&Utc).fixed_offset(),
+            TimeZoneMode::Original => *timestamp,
+        }
+    }
+
+    pub fn label(self, strings: &Strings) -> String {
+        match self {
+            TimeZoneMode::Local => fill(strings.time_zone_local, &[&Local::now().format("%:z")]),
+            TimeZoneMode::Utc => "UTC".to_string(),
+            TimeZoneMode::Original => strings.time_zone_original.to_string(),
+        }
+    }
+}
+
+/// Opt-in sampling of fast successful requests for very high-volume streams.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    /// Keep 1 in `rate` fast successful requests. Disabled when unset.
+    pub rate: Option<u32>,
+    /// Requests at least this slow are always kept.
+    pub slow_ms: u64,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            rate: None,
+            slow_ms: 500,
+        }
+    }
+}
+
+/// `[list]`: how the request list shows each request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    /// Shown before each title, in this order.
+    pub columns: Vec<Column>,
+    /// Headers splitting the list by the minute or hour requests started in
+    pub group_by: Option<GroupBy>,
+    /// Title of each request, such as `{method} {path} → {controller}#{action}`,
+    /// in place of the tail of its `Started` line
+    pub title: Option<TitleTemplate>,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            columns: Column::DEFAULT.to_vec(),
+            group_by: None,
+            title: None,
+        }
+    }
+}
+
+impl ListConfig {
+    /// The configured columns, with the status column `status.show_codes`
+    /// asks for.
+    pub fn columns(&self, show_codes: bool) -> Vec<Column> {
+        let mut columns = self.columns.clone();
+        if show_codes && !columns.contains(&Column::Status) {
+            columns.push(Column::Status);
+        }
+        columns
+    }
+}
+
+/// Trimming of finished groups down to the lines simple mode, SQL and errors need.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CompactionConfig {
+    pub enabled: bool,
+    /// File keeping every line, so compacted groups can be restored.
+    pub journal: Option<PathBuf>,
+}
+
+/// Defaults for exporting requests (`A` then `e`, and `y`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// Name of a format in `export::FORMATS`.
+    pub format: String,
+    /// Mask passwords, tokens and email addresses.
+    pub redact: bool,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            format: "log".to_string(),
+            redact: true,
+        }
+    }
+}
+
+/// `[auto_clear]`: dropping finished requests by how long ago they
+/// finished, on the wall clock, so a session left open all day stays
+/// small even while no lines arrive to advance `[retention]` ages.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AutoClearConfig {
+    /// Off unless set
+    pub after_minutes: Option<u64>,
+    /// How often to look for requests to drop
+    pub interval_seconds: u64,
+}
+
+impl Default for AutoClearConfig {
+    fn default() -> Self {
+        Self {
+            after_minutes: None,
+            interval_seconds: 60,
+        }
+    }
+}
+
+/// What happens to the requests from before a server restart, besides the
+/// divider the list shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartAction {
+    #[default]
+    Keep,
+    /// Removed, except the starred ones
+    Clear,
+    /// Exported to `archive_dir` in the export format, then removed
+    Archive,
+}
+
+/// `[restarts]`: requests from before the server restarted.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RestartConfig {
+    pub action: RestartAction,
+    pub archive_dir: String,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            action: RestartAction::Keep,
+            archive_dir: "tmp/lucy".to_string(),
+        }
+    }
+}
+
+/// What happens when a request fails while watching a stream.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Ring the terminal bell.
+    pub bell: bool,
+    /// Send a desktop notification (`notify-send`, or `osascript` on macOS).
+    pub desktop: bool,
+    /// Keep this many of the latest failures pinned at the top of the list
+    /// until dismissed. 0 leaves failed requests in place.
+    pub pin: usize,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            bell: false,
+            desktop: false,
+            pin: 3,
+        }
+    }
+}
+
+/// `[presets]`: filters applied with Alt+1 to Alt+9, in the terms of
+/// `:filter`. Digits left out keep their default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct PresetConfig(BTreeMap<char, String>);
+
+impl PresetConfig {
+    /// Every request, failed ones, slow ones and ones writing to the database.
+    const DEFAULT: [(char, &str); 4] = [('1', ""), ('2', "errors"), ('3', "slow=500"), ('4', "writes")];
+
+    /// Terms of the preset on `digit`, if there is one.
+    pub fn filter(&self, digit: char) -> Option<&str> {
+        self.0.get(&digit).map(String::as_str).or_else(|| {
+            Self::DEFAULT
+                .iter()
+                .find(|(default, _)| *default == digit)
+                .map(|(_, filter)| *filter)
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (digit, filter) in &self.0 {
+            if !('1'..='9').contains(digit) {
+                bail!("Preset keys are the digits 1 to 9, not {:?}", digit);
+            }
+            let terms: Vec<&str> = filter.split_whitespace().collect();
+            if let Err(reason) = command::parse_filter(&terms) {
+                bail!("Invalid preset {}: {}", digit, reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Development database that `E` runs EXPLAIN against.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatabaseConfig {
+    pub adapter: Adapter,
+    /// Connection URL, or the database file for SQLite.
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub locale: Locale,
+    /// Built-in color palette.
+    pub theme: Palette,
+    /// Colors replacing those of the palette.
+    pub colors: ColorOverrides,
+    pub icons: IconSet,
+    /// Number requests in the list, for `:N` / `NG`.
+    pub request_numbers: bool,
+    /// Look for a newer release on GitHub at startup.
+    pub check_updates: bool,
+    /// Screen-reader friendly output without borders or colors.
+    pub plain: bool,
+    /// Keyboard only, leaving the mouse to the terminal for selecting text.
+    pub no_mouse: bool,
+    pub time_zone: TimeZoneMode,
+    /// 24 or 12-hour clock, and whether times show the date.
+    pub time_format: TimeFormatConfig,
+    pub sampling: SamplingConfig,
+    pub retention: RetentionConfig,
+    pub status: StatusConfig,
+    pub list: ListConfig,
+    pub compaction: CompactionConfig,
+    pub export: ExportConfig,
+    pub restarts: RestartConfig,
+    pub auto_clear: AutoClearConfig,
+    pub database: Option<DatabaseConfig>,
+    pub alerts: AlertConfig,
+    pub presets: PresetConfig,
+    /// Commands run on request events, as `[[hooks]]` tables.
+    pub hooks: Vec<HookConfig>,
+    /// Format of the timestamps lines start with, beyond the built-in ones.
+    pub timestamps: Option<TimestampConfig>,
+    /// Regex whose first capture group buckets lines into groups.
+    pub group_key_regex: Option<String>,
+    /// Make no Rails assumptions: group only by the key regex, take titles
+    /// from first lines and status from log levels.
+    pub generic: bool,
+}
+
+impl Config {
+    /// Loads the config file, falling back to defaults when it does not exist.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)
+                .wrap_err_with(|| format!("Invalid config file: {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).wrap_err_with(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.presets.validate()?;
+        Ok(config)
+    }
+
+    /// How times are displayed, in the configured zone.
+    pub fn times(&self) -> TimeFormat {
+        TimeFormat::new(self.time_zone, self.time_format)
+    }
+}
+
+/// `$LUCY_CONFIG`, otherwise `$XDG_CONFIG_HOME/lucy/config.toml` or `~/.config/lucy/config.toml`.
+pub fn config_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("LUCY_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("lucy").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::StatusType;
+    use crate::hooks::HookEvent;
+    use crate::time_format::Clock;
+
+    #[test]
+    fn test_parse_time_zone() {
+        let config = Config::parse("time_zone = \"utc\"").unwrap();
+        assert_eq!(config.time_zone, TimeZoneMode::Utc);
+
+        let config = Config::parse("time_zone = \"original\"").unwrap();
+        assert_eq!(config.time_zone, TimeZoneMode::Original);
+
+        // Missing keys fall back to defaults
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.time_zone, TimeZoneMode::Local);
+
+        assert!(Config::parse("time_zone = \"mars\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_format() {
+        let config = Config::parse("[time_format]\nclock = \"12h\"\ndate = true").unwrap();
+        assert_eq!(config.time_format.clock, Clock::H12);
+        assert!(config.time_format.date);
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.time_format.clock, Clock::H24);
+        assert!(Config::parse("[time_format]\nclock = \"13h\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Config::parse("locale = \"ja\"").unwrap().locale, Locale::Ja);
+        assert_eq!(Config::parse("").unwrap().locale, Locale::En);
+        assert!(Config::parse("locale = \"fr\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let config = Config::parse("theme = \"high-contrast\"").unwrap();
+        assert_eq!(config.theme, Palette::HighContrast);
+        assert!(config.theme.theme().status_symbols);
+
+        let config = Config::parse("theme = \"deuteranopia\"").unwrap();
+        assert_eq!(config.theme, Palette::Deuteranopia);
+
+        assert_eq!(Config::parse("").unwrap().theme, Palette::Default);
+        assert!(!Palette::Default.theme().status_symbols);
+        assert!(Config::parse("theme = \"sepia\"").is_err());
+        assert_eq!(Config::parse("theme = \"dark\"").unwrap().theme, Palette::Default);
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        let config = Config::parse("[colors]\nerror = \"#ff5f5f\"\nborder = \"8\"").unwrap();
+        let theme = config.colors.apply(Palette::Default.theme());
+        assert_eq!(theme.error, ratatui::style::Color::Rgb(0xff, 0x5f, 0x5f));
+        assert_eq!(theme.border, ratatui::style::Color::Indexed(8));
+        assert!(Config::parse("[colors]\nerror = \"reddish\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_icons() {
+        assert_eq!(Config::parse("icons = \"nerd\"").unwrap().icons, IconSet::Nerd);
+        assert_eq!(Config::parse("icons = \"ascii\"").unwrap().icons, IconSet::Ascii);
+        assert_eq!(Config::parse("").unwrap().icons, IconSet::Unicode);
+    }
+
+    #[test]
+    fn test_parse_no_mouse() {
+        assert!(!Config::parse("").unwrap().no_mouse);
+        assert!(Config::parse("no_mouse = true").unwrap().no_mouse);
+    }
+
+    #[test]
+    fn test_parse_sampling() {
+        let config = Config::parse("[sampling]\nrate = 10").unwrap();
+        assert_eq!(config.sampling.rate, Some(10));
+        assert_eq!(config.sampling.slow_ms, 500);
+
+        assert_eq!(Config::parse("").unwrap().sampling.rate, None);
+    }
+
+    #[test]
+    fn test_parse_retention() {
+        let config = Config::parse(
+            "[retention]\nmax_requests = 2000\n[retention.success]\nmax_count = 500\n[retention.error]\nforever = true",
+        )
+        .unwrap();
+        let retention = &config.retention;
+        assert_eq!(retention.max_requests, 2000);
+        assert_eq!(retention.rule(StatusType::Success).max_count, Some(500));
+        assert!(retention.rule(StatusType::Error).forever);
+        assert!(retention.has_class_limits());
+
+        assert!(!Config::parse("").unwrap().retention.has_class_limits());
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let config = Config::parse(
+            "[status]\ninfo = [\"4xx\", \"301-302\"]\nerror = [422]\nshow_codes = true",
+        )
+        .unwrap();
+        let status = &config.status;
+        assert!(status.show_codes);
+        assert_eq!(status.classify(404), StatusType::Info);
+        assert_eq!(status.classify(302), StatusType::Info);
+        assert_eq!(status.classify(422), StatusType::Error);
+        assert_eq!(status.classify(200), StatusType::Success);
+        assert_eq!(status.classify(503), StatusType::Error);
+        assert_eq!(status.classify(304), StatusType::Unknown);
+
+        assert_eq!(Config::parse("").unwrap().status.classify(404), StatusType::Warning);
+        assert!(Config::parse("[status]\ninfo = [\"4x\"]").is_err());
+        assert!(Config::parse("[status]\ninfo = [\"500-400\"]").is_err());
+    }
+
+    #[test]
+    fn test_parse_group_key_regex() {
+        let config = Config::parse(r#"group_key_regex = 'request_id=(\w+)'"#).unwrap();
+        assert_eq!(config.group_key_regex.as_deref(), Some(r"request_id=(\w+)"));
+        assert!(Config::parse("").unwrap().group_key_regex.is_none());
+    }
+
+    #[test]
+    fn test_parse_compaction() {
+        let config =
+            Config::parse("[compaction]\nenabled = true\njournal = \"/tmp/lucy.journal\"").unwrap();
+        assert!(config.compaction.enabled);
+        assert_eq!(
+            config.compaction.journal,
+            Some(PathBuf::from("/tmp/lucy.journal"))
+        );
+
+        let config = Config::parse("").unwrap();
+        assert!(!config.compaction.enabled);
+        assert!(config.compaction.journal.is_none());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let config = Config::parse("[export]\nformat = \"markdown\"\nredact = false").unwrap();
+        assert_eq!(config.export.format, "markdown");
+        assert!(!config.export.redact);
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.export.format, "log");
+        assert!(config.export.redact);
+    }
+
+    #[test]
+    fn test_parse_alerts() {
+        let config = Config::parse("[alerts]\nbell = true").unwrap();
+        assert!(config.alerts.bell);
+        assert!(!config.alerts.desktop);
+        assert!(!Config::parse("").unwrap().alerts.bell);
+    }
+
+    #[test]
+    fn test_parse_list_columns() {
+        let config = Config::parse("[list]\ncolumns = [\"method\", \"sql\", \"lines\"]").unwrap();
+        assert_eq!(config.list.columns, [Column::Method, Column::Sql, Column::Lines]);
+        assert_eq!(
+            config.list.columns(true),
+            [Column::Method, Column::Sql, Column::Lines, Column::Status]
+        );
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.list.columns(false), Column::DEFAULT);
+        assert_eq!(config.list.group_by, None);
+        assert!(Config::parse("[list]\ncolumns = [\"color\"]").is_err());
+
+        let config = Config::parse("[list]\ngroup_by = \"hour\"").unwrap();
+        assert_eq!(config.list.group_by, Some(GroupBy::Hour));
+        assert!(Config::parse("[list]\ngroup_by = \"day\"").is_err());
+
+        let config = Config::parse("[list]\ntitle = \"{controller}#{action}\"").unwrap();
+        assert_eq!(
+            config.list.title,
+            Some(TitleTemplate::parse("{controller}#{action}").unwrap())
+        );
+        assert!(Config::parse("[list]\ntitle = \"{controller}#{route}\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_auto_clear() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.auto_clear.after_minutes, None);
+        let config = Config::parse("[auto_clear]\nafter_minutes = 30").unwrap();
+        assert_eq!(config.auto_clear.after_minutes, Some(30));
+        assert_eq!(config.auto_clear.interval_seconds, 60);
+    }
+
+    #[test]
+    fn test_parse_restarts() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.restarts.action, RestartAction::Keep);
+        let config = Config::parse("[restarts]\naction = \"archive\"\narchive_dir = \"log/old\"").unwrap();
+        assert_eq!(config.restarts.action, RestartAction::Archive);
+        assert_eq!(config.restarts.archive_dir, "log/old");
+        assert!(Config::parse("[restarts]\naction = \"forget\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_presets() {
+        let config = Config::parse("[presets]\n2 = \"status=5xx\"\n5 = \"kind=job slow=1000\"").unwrap();
+        assert_eq!(config.presets.filter('1'), Some(""));
+        assert_eq!(config.presets.filter('2'), Some("status=5xx"));
+        assert_eq!(config.presets.filter('3'), Some("slow=500"));
+        assert_eq!(config.presets.filter('5'), Some("kind=job slow=1000"));
+        assert_eq!(config.presets.filter('6'), None);
+        assert_eq!(Config::parse("").unwrap().presets.filter('4'), Some("writes"));
+
+        assert!(Config::parse("[presets]\n0 = \"errors\"").is_err());
+        assert!(Config::parse("[presets]\n2 = \"status=abc\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamps() {
+        let config = Config::parse(
+            "[timestamps]\npattern = '^\\[(?P<ts>[^\\]]+)\\] '\nformat = \"%d/%b/%Y:%H:%M:%S %z\"",
+        )
+        .unwrap();
+        let timestamps = config.timestamps.unwrap();
+        assert_eq!(timestamps.pattern, r"^\[(?P<ts>[^\]]+)\] ");
+        assert!(!timestamps.strip);
+        assert!(Config::parse("").unwrap().timestamps.is_none());
+        // Both keys are needed
+        assert!(Config::parse("[timestamps]\nformat = \"%s\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        let config = Config::parse(
+            "[[hooks]]\non = \"n_plus_one\"\ncommand = \"cat > n1.json\"\n\
+             [[hooks]]\non = \"server_error\"\ncommand = \"notify\"",
+        )
+        .unwrap();
+        assert_eq!(config.hooks.len(), 2);
+        assert_eq!(config.hooks[0].on, HookEvent::NPlusOne);
+        assert_eq!(config.hooks[1].command, "notify");
+        assert!(Config::parse("").unwrap().hooks.is_empty());
+        assert!(Config::parse("[[hooks]]\non = \"startup\"\ncommand = \"true\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_database() {
+        let config = Config::parse(
+            "[database]\nadapter = \"postgres\"\nurl = \"postgres://localhost/app_development\"",
+        )
+        .unwrap();
+        let database = config.database.unwrap();
+        assert_eq!(database.adapter, Adapter::Postgresql);
+        assert_eq!(database.url, "postgres://localhost/app_development");
+
+        assert!(Config::parse("").unwrap().database.is_none());
+        assert!(Config::parse("[database]\nadapter = \"oracle\"\nurl = \"x\"").is_err());
+    }
+
+    #[test]
+    fn test_time_zone_convert() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-05-01T12:00:00+09:00").unwrap();
+
+        let utc = TimeZoneMode::Utc.convert(&timestamp);
+        assert_eq!(utc.format("%H:%M %:z").to_string(), "03:00 +00:00");
+
+        let original = TimeZoneMode::Original.convert(&timestamp);
+        assert_eq!(original.format("%H:%M %:z").to_string(), "12:00 +09:00");
+
+        // The instant never changes, only its presentation
+        assert_eq!(TimeZoneMode::Local.convert(&timestamp), timestamp);
+    }
+}
@@ -0,0 +1,142 @@
+//! `EXPLAIN` of a logged query (`E`), run in the background.
+
+use super::{Adapter, run};
+use crate::config::DatabaseConfig;
+use crate::log_parser;
+use crate::sql_format;
+use crate::sql_info;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+
+/// Plan printed by the client, or why there is none.
+type PlanResult = Arc<OnceLock<Result<String, String>>>;
+
+/// A plan being fetched or shown in the EXPLAIN popup.
+pub struct Explain {
+    /// The statement explained, binds filled in
+    pub sql: String,
+    pub analyze: bool,
+    /// Asking before running EXPLAIN ANALYZE, which executes the statement
+    pub confirming_analyze: bool,
+    /// Rows of the popup scrolled past
+    pub scroll: u16,
+    plan: PlanResult,
+    /// The plan has been reported by `poll`
+    seen: bool,
+}
+
+impl Explain {
+    /// Starts explaining `sql` on a background thread.
+    pub fn start(database: &DatabaseConfig, sql: String, analyze: bool) -> Self {
+        let plan = PlanResult::default();
+        let result = Arc::clone(&plan);
+        let database = database.clone();
+        let statements = explain_statements(database.adapter, &sql, analyze);
+        thread::spawn(move || {
+            let _ = result.set(run(&database, &statements).map_err(|e| e.to_string()));
+        });
+        Self {
+            sql,
+            analyze,
+            confirming_analyze: false,
+            scroll: 0,
+            plan,
+            seen: false,
+        }
+    }
+
+    pub fn plan(&self) -> Option<&Result<String, String>> {
+        self.plan.get()
+    }
+
+    /// Returns true once, when the plan has arrived.
+    pub fn poll(&mut self) -> bool {
+        if self.seen || self.plan.get().is_none() {
+            return false;
+        }
+        self.seen = true;
+        true
+    }
+}
+
+impl Adapter {
+    /// SQLite only has `EXPLAIN QUERY PLAN`, which does not run the statement.
+    pub fn supports_analyze(self) -> bool {
+        self != Adapter::Sqlite
+    }
+}
+
+/// The statement of a logged query, ready to run: colors and timing dropped,
+/// the logged binds in place of the placeholders.
+pub fn logged_statement(message: &str) -> Option<String> {
+    let message = log_parser::strip_ansi_for_parsing(message);
+    sql_info::statement(&message).map(sql_format::inline_binds)
+}
+
+/// `sql` without a trailing `;`, or None when it holds more than one
+/// statement. psql and mysql run every statement they are given, so a
+/// logged `SELECT 1; DELETE FROM posts` must not reach them. A `;` in a
+/// string literal is refused as well, as the clients disagree on whether a
+/// backslash escapes the quote that ends it.
+pub fn single_statement(sql: &str) -> Option<&str> {
+    let sql = sql.trim_end().trim_end_matches(';').trim_end();
+    (!sql.contains(';')).then_some(sql)
+}
+
+/// Statements to run for the plan. EXPLAIN ANALYZE executes the statement,
+/// so it runs in a transaction that is rolled back.
+fn explain_statements(adapter: Adapter, sql: &str, analyze: bool) -> Vec<String> {
+    match (adapter, analyze) {
+        (Adapter::Sqlite, _) => vec![format!("EXPLAIN QUERY PLAN {}", sql)],
+        (_, false) => vec![format!("EXPLAIN {}", sql)],
+        (Adapter::Postgresql, true) => vec![
+            "BEGIN".to_string(),
+            format!("EXPLAIN ANALYZE {}", sql),
+            "ROLLBACK".to_string(),
+        ],
+        (Adapter::Mysql, true) => vec![
+            "START TRANSACTION".to_string(),
+            format!("EXPLAIN ANALYZE {}", sql),
+            "ROLLBACK".to_string(),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logged_statement() {
+        let message = "\x1b[1m\x1b[36mUser Load (0.4ms)\x1b[0m  \x1b[1m\x1b[34mSELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1 LIMIT $2\x1b[0m  [[\"id\", 1], [\"LIMIT\", 1]]";
+        assert_eq!(
+            logged_statement(message).unwrap(),
+            r#"SELECT "users".* FROM "users" WHERE "users"."id" = 1 LIMIT 1"#
+        );
+        assert_eq!(logged_statement("Completed 200 OK in 5ms"), None);
+    }
+
+    #[test]
+    fn test_single_statement() {
+        assert_eq!(single_statement("SELECT 1;  "), Some("SELECT 1"));
+        assert_eq!(single_statement("SELECT 1; DELETE FROM posts"), None);
+        assert_eq!(single_statement("SELECT 'a\\'; DELETE FROM posts; --'"), None);
+
+        let message = "Post Load (0.2ms)  SELECT \"posts\".* FROM \"posts\"; DELETE FROM \"posts\"";
+        let sql = logged_statement(message).unwrap();
+        assert_eq!(single_statement(&sql), None);
+    }
+
+    #[test]
+    fn test_explain_statements() {
+        assert_eq!(explain_statements(Adapter::Mysql, "SELECT 1", false), ["EXPLAIN SELECT 1"]);
+        assert_eq!(
+            explain_statements(Adapter::Postgresql, "DELETE FROM posts", true),
+            ["BEGIN", "EXPLAIN ANALYZE DELETE FROM posts", "ROLLBACK"]
+        );
+        assert_eq!(
+            explain_statements(Adapter::Sqlite, "SELECT 1", false),
+            ["EXPLAIN QUERY PLAN SELECT 1"]
+        );
+    }
+}
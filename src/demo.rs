@@ -0,0 +1,430 @@
+use chrono::{SecondsFormat, Utc};
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// How often in-flight requests get a chance to write their next line
+const TICK: Duration = Duration::from_millis(25);
+// Share of 1000 requests that are followed by a background job
+const JOB_PER_MILLE: u64 = 120;
+
+/// Small xorshift generator; the demo needs variety, not quality randomness.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    fn chance(&mut self, per_mille: u64) -> bool {
+        self.below(1000) < per_mille
+    }
+}
+
+struct Route {
+    method: &'static str,
+    path: &'static str,
+    action: &'static str,
+    table: &'static str,
+}
+
+const ROUTES: [Route; 7] = [
+    Route {
+        method: "GET",
+        path: "/posts",
+        action: "PostsController#index",
+        table: "posts",
+    },
+    Route {
+        method: "GET",
+        path: "/posts/{id}",
+        action: "PostsController#show",
+        table: "posts",
+    },
+    Route {
+        method: "POST",
+        path: "/posts",
+        action: "PostsController#create",
+        table: "posts",
+    },
+    Route {
+        method: "PATCH",
+        path: "/users/{id}",
+        action: "UsersController#update",
+        table: "users",
+    },
+    Route {
+        method: "DELETE",
+        path: "/comments/{id}",
+        action: "CommentsController#destroy",
+        table: "comments",
+    },
+    Route {
+        method: "GET",
+        path: "/api/v1/orders",
+        action: "Api::V1::OrdersController#index",
+        table: "orders",
+    },
+    Route {
+        method: "GET",
+        path: "/admin/reports",
+        action: "Admin::ReportsController#index",
+        table: "reports",
+    },
+];
+
+const EXCEPTIONS: [&str; 3] = [
+    "NoMethodError (undefined method `name' for nil):",
+    "ActiveRecord::StatementInvalid (PG::QueryCanceled: ERROR:  canceling statement due to statement timeout):",
+    "ActionView::Template::Error (undefined local variable or method `current_user'):",
+];
+
+const JOBS: [&str; 3] = [
+    "SendWelcomeEmailJob",
+    "GenerateReportJob",
+    "SyncInventoryJob",
+];
+
+/// Synthesizes Rails-style log lines: requests with SQL, views, varied
+/// statuses and the occasional exception or background job.
+pub struct Generator {
+    rng: Rng,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Lines of one request from `Started` to `Completed`, plus any job it enqueues.
+    pub fn request(&mut self) -> Vec<String> {
+        let request_id = format!(
+            "{:08x}-{:04x}",
+            self.rng.next() as u32,
+            self.rng.below(0x10000)
+        );
+        let route = self.rng.pick(&ROUTES);
+        let id = self.rng.below(500) + 1;
+        let path = route.path.replace("{id}", &id.to_string());
+        let model = model_name(route.table);
+
+        let status = match self.rng.below(100) {
+            0..=5 => 500,
+            6..=11 if route.path.contains("{id}") => 404,
+            6..=11 if route.method != "GET" => 422,
+            _ if route.method == "GET" => 200,
+            _ => 302,
+        };
+
+        let mut lines = vec![
+            format!("Started {} \"{}\" for 127.0.0.1", route.method, path),
+            format!("Processing by {} as HTML", route.action),
+        ];
+        if route.path.contains("{id}") {
+            lines.push(format!("  Parameters: {{\"id\"=>\"{}\"}}", id));
+        }
+
+        let mut db_ms = 0.0;
+        let mut query = |lines: &mut Vec<String>, label: &str, sql: String, ms: f64| {
+            db_ms += ms;
+            lines.push(format!("  {} ({:.1}ms)  {}", label, ms, sql));
+        };
+
+        match route.method {
+            "GET" if route.path.contains("{id}") => {
+                let sql = format!(
+                    "SELECT \"{0}\".* FROM \"{0}\" WHERE \"{0}\".\"id\" = $1 LIMIT $2",
+                    route.table
+                );
+                query(&mut lines, &format!("{} Load", model), sql, self.ms(2));
+            }
+            "GET" => {
+                let slow = route.table == "reports" && self.rng.chance(400);
+                let ms = if slow {
+                    400.0 + self.ms(800)
+                } else {
+                    self.ms(5)
+                };
+                let sql = format!("SELECT \"{0}\".* FROM \"{0}\" LIMIT $1", route.table);
+                query(&mut lines, &format!("{} Load", model), sql, ms);
+
+                // Author lookups, sometimes one per row (N+1), sometimes cached
+                let authors = if self.rng.chance(300) { 6 } else { 1 };
+                for author in 0..authors {
+                    let cached = author > 0 && self.rng.chance(300);
+                    let label = if cached {
+                        "CACHE User Load"
+                    } else {
+                        "User Load"
+                    };
+                    let sql =
+                        "SELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1 LIMIT $2"
+                            .to_string();
+                    let ms = if cached { 0.0 } else { self.ms(1) };
+                    query(&mut lines, label, sql, ms);
+                }
+            }
+            method => {
+                let sql = match method {
+                    "POST" => format!(
+                        "INSERT INTO \"{}\" (\"created_at\", \"updated_at\") VALUES ($1, $2) RETURNING \"id\"",
+                        route.table
+                    ),
+                    "DELETE" => format!(
+                        "DELETE FROM \"{}\" WHERE \"{}\".\"id\" = $1",
+                        route.table, route.table
+                    ),
+                    _ => format!(
+                        "UPDATE \"{}\" SET \"updated_at\" = $1 WHERE \"{}\".\"id\" = $2",
+                        route.table, route.table
+                    ),
+                };
+                let verb = match method {
+                    "POST" => "Create",
+                    "DELETE" => "Destroy",
+                    _ => "Update",
+                };
+                query(&mut lines, "TRANSACTION", "BEGIN".to_string(), 0.1);
+                query(&mut lines, &format!("{} {}", model, verb), sql, self.ms(3));
+                query(&mut lines, "TRANSACTION", "COMMIT".to_string(), 0.3);
+            }
+        }
+
+        let mut view_ms = 0.0;
+        if status == 200 && route.method == "GET" {
+            let template = format!(
+                "{}/{}",
+                route.table,
+                if route.path.contains("{id}") {
+                    "show"
+                } else {
+                    "index"
+                }
+            );
+            let partial_ms = self.ms(10);
+            lines.push(format!(
+                "  Rendered collection of {}/_{}.html.erb [{} times] (Duration: {:.1}ms | Allocations: {})",
+                route.table,
+                route.table.trim_end_matches('s'),
+                self.rng.below(20) + 1,
+                partial_ms,
+                self.rng.below(3000) + 200
+            ));
+            view_ms = partial_ms + self.ms(8);
+            lines.push(format!(
+                "  Rendered {}.html.erb within layouts/application (Duration: {:.1}ms | Allocations: {})",
+                template,
+                view_ms,
+                self.rng.below(5000) + 500
+            ));
+        }
+
+        let total_ms = (db_ms + view_ms + self.ms(20)).round() as u64;
+        match status {
+            200 => lines.push(format!(
+                "Completed 200 OK in {}ms (Views: {:.1}ms | ActiveRecord: {:.1}ms)",
+                total_ms, view_ms, db_ms
+            )),
+            302 => {
+                lines.push(format!(
+                    "Redirected to http://localhost:3000/{}/{}",
+                    route.table, id
+                ));
+                lines.push(format!(
+                    "Completed 302 Found in {}ms (ActiveRecord: {:.1}ms)",
+                    total_ms, db_ms
+                ));
+            }
+            404 => {
+                lines.push(format!(
+                    "Completed 404 Not Found in {}ms (ActiveRecord: {:.1}ms)",
+                    total_ms, db_ms
+                ));
+                lines.push(format!(
+                    "ActiveRecord::RecordNotFound (Couldn't find {} with 'id'={}):",
+                    model, id
+                ));
+            }
+            422 => lines.push(format!(
+                "Completed 422 Unprocessable Entity in {}ms (ActiveRecord: {:.1}ms)",
+                total_ms, db_ms
+            )),
+            _ => {
+                lines.push(format!(
+                    "Completed 500 Internal Server Error in {}ms (ActiveRecord: {:.1}ms)",
+                    total_ms, db_ms
+                ));
+                lines.push(self.rng.pick(&EXCEPTIONS).to_string());
+                lines.push(format!(
+                    "app/controllers/{}.rb:{}:in `{}'",
+                    controller_file(route.action),
+                    self.rng.below(60) + 5,
+                    route.action.rsplit('#').next().unwrap_or("index")
+                ));
+            }
+        }
+
+        let mut lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| format!("[{}] {}", request_id, line))
+            .collect();
+        if self.rng.chance(JOB_PER_MILLE) {
+            lines.extend(self.job());
+        }
+        lines
+    }
+
+    /// ActiveJob lines of one job run.
+    fn job(&mut self) -> Vec<String> {
+        let job_class = self.rng.pick(&JOBS);
+        let job_id = format!(
+            "{:08x}-{:04x}",
+            self.rng.next() as u32,
+            self.rng.below(0x10000)
+        );
+        let tag = format!("[ActiveJob] [{}] [{}]", job_class, job_id);
+        vec![
+            format!(
+                "{} Performing {} (Job ID: {}) from Async(default)",
+                tag, job_class, job_id
+            ),
+            format!(
+                "{}   User Load ({:.1}ms)  SELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1 LIMIT $2",
+                tag,
+                self.ms(2)
+            ),
+            format!(
+                "{} Performed {} (Job ID: {}) from Async(default) in {:.1}ms",
+                tag,
+                job_class,
+                job_id,
+                self.ms(300)
+            ),
+        ]
+    }
+
+    /// Random duration below `max` milliseconds, with a tenth of a ms resolution.
+    fn ms(&mut self, max: u64) -> f64 {
+        self.rng.below(max * 10 + 1) as f64 / 10.0
+    }
+}
+
+fn model_name(table: &str) -> String {
+    let singular = table.trim_end_matches('s');
+    let mut chars = singular.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// `Admin::ReportsController#index` -> `admin/reports_controller`
+fn controller_file(action: &str) -> String {
+    let controller = action.split('#').next().unwrap_or(action);
+    controller
+        .split("::")
+        .map(|part| {
+            let mut snake = String::new();
+            for (i, c) in part.chars().enumerate() {
+                if c.is_uppercase() && i > 0 {
+                    snake.push('_');
+                }
+                snake.extend(c.to_lowercase());
+            }
+            snake
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Streams synthesized requests, starting `rate` requests per second. Lines of
+/// requests in flight are interleaved like a busy server's. Stops once `send`
+/// returns false.
+pub fn run(rate: f64, mut send: impl FnMut(String) -> bool) {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(1, |elapsed| elapsed.as_nanos() as u64);
+    let mut generator = Generator::new(seed);
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let mut next_start = Instant::now();
+    let mut in_flight: Vec<VecDeque<String>> = Vec::new();
+
+    loop {
+        if Instant::now() >= next_start {
+            in_flight.push(generator.request().into());
+            next_start += interval;
+        }
+        for lines in &mut in_flight {
+            if !generator.rng.chance(500) {
+                continue;
+            }
+            let Some(line) = lines.pop_front() else {
+                continue;
+            };
+            let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+            if !send(format!("{} {}\n", timestamp, line)) {
+                return;
+            }
+        }
+        in_flight.retain(|lines| !lines.is_empty());
+        thread::sleep(TICK.min(next_start.saturating_duration_since(Instant::now())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::{AppState, GroupKind};
+    use crate::log_parser;
+
+    #[test]
+    fn test_generated_requests_parse() {
+        let mut generator = Generator::new(42);
+        let mut state = AppState::new();
+        for _ in 0..200 {
+            for line in generator.request() {
+                let entry = log_parser::parse(&line, None).unwrap_or_else(|| panic!("{}", line));
+                state.add_log_entry(entry);
+            }
+        }
+
+        let groups: Vec<_> = state.logs_by_request_id.values().collect();
+        let requests = groups
+            .iter()
+            .filter(|group| group.kind == GroupKind::Request);
+        assert_eq!(requests.clone().count(), 200);
+        assert!(requests.clone().all(|group| group.finished));
+        assert!(groups.iter().any(|group| group.kind == GroupKind::Job));
+        assert!(
+            groups
+                .iter()
+                .any(|group| group.sql_query_info.cache_hits > 0)
+        );
+    }
+
+    #[test]
+    fn test_controller_file() {
+        assert_eq!(
+            controller_file("Admin::ReportsController#index"),
+            "admin/reports_controller"
+        );
+        assert_eq!(model_name("comments"), "Comment");
+    }
+}
@@ -0,0 +1,122 @@
+//! Opening the application's source at a line a log points to, such as the
+//! `app/models/user.rb:42:in 'save'` of a backtrace, in `$EDITOR` (`o`).
+
+use crate::app_state::LogGroup;
+use color_eyre::{Result, eyre::WrapErr};
+use regex::Regex;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::LazyLock;
+
+// app/controllers/posts_controller.rb:12:in 'show'
+static RE_LOCATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[\s'`(\[])(?P<file>(?:app|lib|config)/[\w./-]+\.\w+):(?P<line>\d+)")
+        .unwrap()
+});
+
+/// Used when `$EDITOR` is not set.
+const DEFAULT_EDITOR: &str = "vi";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Relative to the application's root, as backtraces print it
+    pub file: String,
+    pub line: u32,
+}
+
+/// The first place in the application's code that `message` names.
+pub fn find(message: &str) -> Option<Location> {
+    let caps = RE_LOCATION.captures(message)?;
+    Some(Location {
+        file: caps["file"].to_string(),
+        line: caps["line"].parse().ok()?,
+    })
+}
+
+/// The first place in the application's code that the lines of `group`
+/// name, which for an exception is where it was raised.
+pub fn exception_location(group: &LogGroup) -> Option<Location> {
+    // Entries are stored newest-first
+    group.entries.iter().rev().find_map(|entry| find(&entry.message))
+}
+
+/// `$EDITOR +42 app/models/user.rb`. `$EDITOR` may carry arguments of its
+/// own, as in `emacsclient -t`.
+pub fn command(editor: &str, location: &Location) -> Command {
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or(DEFAULT_EDITOR));
+    command
+        .args(words)
+        .arg(format!("+{}", location.line))
+        .arg(&location.file);
+    command
+}
+
+/// Runs the editor on `location` and waits for it to exit. The terminal
+/// must be handed over first (see `setup::suspend_terminal`).
+pub fn open(location: &Location) -> Result<ExitStatus> {
+    if !Path::new(&location.file).exists() {
+        return Err(io::Error::from(io::ErrorKind::NotFound))
+            .wrap_err_with(|| location.file.clone());
+    }
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    let mut command = command(&editor, location);
+    // stdin is usually the log being piped in, so the editor reads the terminal
+    if let Ok(tty) = File::open("/dev/tty") {
+        command.stdin(Stdio::from(tty));
+    }
+    command.status().wrap_err_with(|| editor.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let location = |file: &str, line| {
+            Some(Location {
+                file: file.to_string(),
+                line,
+            })
+        };
+        assert_eq!(
+            find("app/models/user.rb:42:in 'save'"),
+            location("app/models/user.rb", 42)
+        );
+        assert_eq!(
+            find("[r1]   app/controllers/posts_controller.rb:12:in `show'"),
+            location("app/controllers/posts_controller.rb", 12)
+        );
+        assert_eq!(
+            find("ActionView::Template::Error (app/views/posts/show.html.erb:3)"),
+            location("app/views/posts/show.html.erb", 3)
+        );
+        assert_eq!(find("/usr/lib/ruby/gems/3.3.0/gems/rack.rb:10:in 'call'"), None);
+        assert_eq!(find("Completed 200 OK in 5ms"), None);
+    }
+
+    #[test]
+    fn test_command() {
+        let location = Location {
+            file: "app/models/user.rb".to_string(),
+            line: 42,
+        };
+        let args = |command: &Command| {
+            std::iter::once(command.get_program())
+                .chain(command.get_args())
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            args(&command("nvim", &location)),
+            ["nvim", "+42", "app/models/user.rb"]
+        );
+        assert_eq!(
+            args(&command("emacsclient -t", &location)),
+            ["emacsclient", "-t", "+42", "app/models/user.rb"]
+        );
+    }
+}
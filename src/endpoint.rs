@@ -0,0 +1,147 @@
+//! Requests grouped by endpoint (`e`): the route with ids replaced by
+//! placeholders, so `GET /posts/12` and `GET /posts/13` count together.
+
+use crate::app_state::{GroupKind, LogGroup};
+use std::collections::HashMap;
+
+/// Figures for one endpoint over the requests shown in the list.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EndpointSummary {
+    pub endpoint: String,
+    pub count: usize,
+    pub errors: usize,
+    total_duration_ms: u64,
+    timed: usize,
+}
+
+impl EndpointSummary {
+    /// Share of the requests that failed, in percent.
+    pub fn error_rate(&self) -> usize {
+        self.errors * 100 / self.count.max(1)
+    }
+
+    pub fn mean_duration_ms(&self) -> Option<u64> {
+        (self.timed > 0).then(|| self.total_duration_ms / self.timed as u64)
+    }
+}
+
+/// Endpoint a group counts toward: the normalized route of a request, the
+/// class of a job. Other groups have none.
+pub fn endpoint(group: &LogGroup) -> Option<String> {
+    match group.kind {
+        GroupKind::Request => normalize_route(&group.title),
+        GroupKind::Job => Some(group.title.clone()),
+        GroupKind::Cable | GroupKind::Generic | GroupKind::Unassigned => None,
+    }
+}
+
+/// `GET /posts/:id` from a request title such as
+/// `GET "/posts/12?page=2" for 127.0.0.1 at 2024-05-01 12:00:00 +0900`.
+pub fn normalize_route(title: &str) -> Option<String> {
+    let mut words = title.split_whitespace();
+    let method = words.next()?;
+    let path = words.next()?.trim_matches('"');
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    if !path.starts_with('/') {
+        return None;
+    }
+    let segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| if is_id(segment) { ":id" } else { segment })
+        .collect();
+    Some(format!("{} {}", method, segments.join("/")))
+}
+
+/// Numbers, UUIDs and long hex or token-like strings.
+fn is_id(segment: &str) -> bool {
+    if segment.is_empty() {
+        return false;
+    }
+    if segment.bytes().all(|b| b.is_ascii_digit()) {
+        return true;
+    }
+    let hex_or_dash = segment.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-');
+    let digits = segment.bytes().filter(u8::is_ascii_digit).count();
+    // A UUID, a digest, or a slug-free token like `a1b2c3d4e5f6`
+    hex_or_dash && segment.len() >= 16 && digits > 0
+        || segment.len() >= 20 && digits * 4 >= segment.len()
+}
+
+/// Summaries of `groups` by endpoint, the busiest first.
+pub fn summarize<'a>(groups: impl IntoIterator<Item = &'a LogGroup>) -> Vec<EndpointSummary> {
+    let mut by_endpoint: HashMap<String, EndpointSummary> = HashMap::new();
+    for group in groups {
+        let Some(endpoint) = endpoint(group) else {
+            continue;
+        };
+        let summary = by_endpoint
+            .entry(endpoint)
+            .or_insert_with_key(|endpoint| EndpointSummary {
+                endpoint: endpoint.clone(),
+                ..Default::default()
+            });
+        summary.count += 1;
+        if group.is_error() {
+            summary.errors += 1;
+        }
+        if let Some(ms) = group.duration_ms {
+            summary.total_duration_ms += ms;
+            summary.timed += 1;
+        }
+    }
+    let mut summaries: Vec<EndpointSummary> = by_endpoint.into_values().collect();
+    summaries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.endpoint.cmp(&b.endpoint)));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::LogEntry;
+
+    fn request(path: &str, status: u16, ms: u64) -> LogGroup {
+        let mut group = LogGroup::new(LogEntry {
+            message: format!("Started GET \"{}\" for 127.0.0.1", path),
+            ..Default::default()
+        });
+        group.add_entry(LogEntry {
+            message: format!("Completed {} OK in {}ms", status, ms),
+            ..Default::default()
+        });
+        group
+    }
+
+    #[test]
+    fn test_normalize_route() {
+        assert_eq!(
+            normalize_route(r#"GET "/posts/12?page=2" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#),
+            Some("GET /posts/:id".to_string())
+        );
+        assert_eq!(
+            normalize_route(r#"PATCH "/users/3f2b8c1e-9d4a-4e5b-8c7d-1a2b3c4d5e6f/avatar""#),
+            Some("PATCH /users/:id/avatar".to_string())
+        );
+        assert_eq!(
+            normalize_route(r#"GET "/posts/hello-world""#),
+            Some("GET /posts/hello-world".to_string())
+        );
+        assert_eq!(normalize_route(r#"GET "/""#), Some("GET /".to_string()));
+        assert_eq!(normalize_route("SendEmailJob"), None);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let groups = [
+            request("/posts/1", 200, 100),
+            request("/posts/2", 500, 300),
+            request("/posts", 200, 50),
+        ];
+        let summaries = summarize(&groups);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].endpoint, "GET /posts/:id");
+        assert_eq!(summaries[0].count, 2);
+        assert_eq!(summaries[0].error_rate(), 50);
+        assert_eq!(summaries[0].mean_duration_ms(), Some(200));
+        assert_eq!(summaries[1].endpoint, "GET /posts");
+    }
+}
@@ -0,0 +1,383 @@
+//! Writing requests out as files or to the clipboard.
+//!
+//! A group is first turned into an `ExportedRequest`, which is where the
+//! options every format shares apply: lines are filtered and redacted once.
+//! A `RequestSerializer` then only lays the result out, so a new format is a
+//! module implementing it plus an entry in `FORMATS`.
+
+mod csv;
+mod html;
+mod issue;
+mod json;
+mod markdown;
+mod raw;
+mod sql;
+
+pub use json::Json;
+
+use crate::app_state::{LogGroup, StatusType};
+use crate::log_parser;
+use crate::simple_formatter::Verbosity;
+use crate::sql_format;
+use crate::time_format::TimeFormat;
+use chrono::{DateTime, FixedOffset};
+use color_eyre::{Result, eyre::eyre};
+use regex::Regex;
+use std::{borrow::Cow, fs, io, path::Path, sync::LazyLock};
+
+/// Every export format, in the order Tab cycles through them.
+pub static FORMATS: &[&dyn RequestSerializer] = &[
+    &raw::Raw,
+    &markdown::Markdown,
+    &issue::Issue,
+    &json::Json,
+    &csv::Csv,
+    &sql::Sql,
+    &html::Html,
+];
+
+const REDACTED: &str = "[FILTERED]";
+
+/// Rails params (`"password"=>"..."`), query strings and headers carrying secrets.
+static SECRET_PATTERNS: LazyLock<[(Regex, &str); 3]> = LazyLock::new(|| {
+    const KEYS: &str = r"(?:password|passwd|secret|token|api_key|access_key)";
+    [
+        (
+            Regex::new(&format!(r#"(?i)("\w*{KEYS}\w*"\s*(?:=>|:)\s*)"(?:[^"\\]|\\.)*""#)).unwrap(),
+            "${1}\"[FILTERED]\"",
+        ),
+        (
+            Regex::new(&format!(r#"(?i)\b(\w*{KEYS}\w*=)[^&\s"]+"#)).unwrap(),
+            "${1}[FILTERED]",
+        ),
+        (
+            Regex::new(r"(?i)\b(Authorization:?\s*(?:Bearer|Basic|Token)\s+)\S+").unwrap(),
+            "${1}[FILTERED]",
+        ),
+    ]
+});
+
+static EMAIL_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").unwrap());
+
+/// Lays out one request in a file format.
+pub trait RequestSerializer: Sync {
+    /// Name the format is picked by, in the config file and the export prompt.
+    fn name(&self) -> &'static str;
+    fn extension(&self) -> &'static str;
+    fn serialize(&self, request: &ExportedRequest, out: &mut String);
+}
+
+/// Applied to every request before any format sees it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    /// Mask passwords, tokens and email addresses
+    pub redact: bool,
+    /// Keep only the lines shown at this verbosity
+    pub verbosity: Verbosity,
+    /// Put the binds of queries in place of their placeholders
+    pub inline_binds: bool,
+    /// How formats read by people write times
+    pub times: TimeFormat,
+}
+
+/// A request as handed to serializers: filtered, redacted and without ANSI colors.
+pub struct ExportedRequest {
+    pub request_id: String,
+    pub title: String,
+    pub status: StatusType,
+    pub finished: bool,
+    pub started_at: DateTime<FixedOffset>,
+    /// `started_at` as the configured time format writes it, with the date
+    /// and offset
+    pub started: String,
+    pub duration_ms: Option<u64>,
+    /// Note the user attached to the request
+    pub note: Option<String>,
+    /// Oldest first
+    pub lines: Vec<ExportedLine>,
+}
+
+pub struct ExportedLine {
+    pub timestamp: DateTime<FixedOffset>,
+    pub text: String,
+}
+
+impl ExportedRequest {
+    pub fn new(request_id: &str, group: &LogGroup, options: ExportOptions) -> Self {
+        let clean = |text: &str| {
+            let inlined = options
+                .inline_binds
+                .then(|| sql_format::inline_line_binds(text))
+                .flatten();
+            let text = log_parser::strip_ansi_for_parsing(inlined.as_deref().unwrap_or(text));
+            let text = text.trim_end_matches(['\r', '\n']);
+            if options.redact {
+                redact(text).into_owned()
+            } else {
+                text.to_string()
+            }
+        };
+        let lines = group
+            .entries
+            .iter()
+            // Entries are stored newest-first
+            .rev()
+            .filter(|entry| options.verbosity.shows(&entry.message))
+            // Repeats folded into an entry are written out as the lines they were
+            .flat_map(|entry| {
+                let text = clean(&entry.message);
+                std::iter::once(entry.timestamp)
+                    .chain(entry.repeats.iter().copied())
+                    .map(move |timestamp| ExportedLine {
+                        timestamp,
+                        text: text.clone(),
+                    })
+            })
+            .collect();
+
+        Self {
+            request_id: request_id.to_string(),
+            title: clean(&group.title),
+            status: group.status_type,
+            finished: group.finished,
+            started_at: group.first_timestamp,
+            started: options.times.format_full(&group.first_timestamp),
+            duration_ms: group.duration_ms,
+            note: group.note.clone(),
+            lines,
+        }
+    }
+}
+
+/// `status` spelled out for file formats.
+pub fn status_label(status: StatusType) -> &'static str {
+    match status {
+        StatusType::Success => "success",
+        StatusType::Info => "info",
+        StatusType::Warning => "warning",
+        StatusType::Error => "error",
+        StatusType::Unknown => "unknown",
+    }
+}
+
+/// Looks up a format by name.
+pub fn format_named(name: &str) -> Result<&'static dyn RequestSerializer> {
+    FORMATS
+        .iter()
+        .copied()
+        .find(|format| format.name() == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = FORMATS.iter().map(|format| format.name()).collect();
+            eyre!("Unknown export format {:?}, expected one of: {}", name, names.join(", "))
+        })
+}
+
+/// The format after `current` in `FORMATS`, wrapping around.
+pub fn next_format(current: &dyn RequestSerializer) -> &'static dyn RequestSerializer {
+    let index = FORMATS
+        .iter()
+        .position(|format| format.name() == current.name())
+        .map_or(0, |index| (index + 1) % FORMATS.len());
+    FORMATS[index]
+}
+
+/// One request in `format`, e.g. for the clipboard.
+pub fn to_string(
+    format: &dyn RequestSerializer,
+    request_id: &str,
+    group: &LogGroup,
+    options: ExportOptions,
+) -> String {
+    let mut out = String::new();
+    format.serialize(&ExportedRequest::new(request_id, group, options), &mut out);
+    out
+}
+
+/// Writes each group to `<dir>/<request id>.<extension>`, creating `dir` if
+/// needed. Returns how many files were written.
+pub fn write_groups(
+    dir: &Path,
+    format: &dyn RequestSerializer,
+    groups: &[(&str, &LogGroup)],
+    options: ExportOptions,
+) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    for (request_id, group) in groups {
+        let contents = to_string(format, request_id, group, options);
+        let path = dir.join(format!("{}.{}", file_stem(request_id), format.extension()));
+        fs::write(path, contents)?;
+    }
+    Ok(groups.len())
+}
+
+fn redact(text: &str) -> Cow<'_, str> {
+    let mut text = Cow::Borrowed(text);
+    for (pattern, replacement) in SECRET_PATTERNS.iter() {
+        if let Cow::Owned(replaced) = pattern.replace_all(&text, *replacement) {
+            text = Cow::Owned(replaced);
+        }
+    }
+    if let Cow::Owned(replaced) = EMAIL_PATTERN.replace_all(&text, REDACTED) {
+        text = Cow::Owned(replaced);
+    }
+    text
+}
+
+/// A file name for `request_id`. Custom group keys may contain path separators.
+fn file_stem(request_id: &str) -> String {
+    let stem: String = request_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    stem.trim_start_matches('.').to_string()
+}
+
+#[cfg(test)]
+fn sample_request() -> ExportedRequest {
+    let at = |time| DateTime::parse_from_rfc3339(time).unwrap();
+    let line = |time, text: &str| ExportedLine {
+        timestamp: at(time),
+        text: text.to_string(),
+    };
+    ExportedRequest {
+        request_id: "abc-123".to_string(),
+        title: "GET \"/posts\"".to_string(),
+        status: StatusType::Success,
+        finished: true,
+        started_at: at("2024-05-01T12:00:00+09:00"),
+        started: "2024-05-01 12:00:00 +09:00".to_string(),
+        duration_ms: Some(5),
+        note: None,
+        lines: vec![
+            line("2024-05-01T12:00:00+09:00", "Started GET \"/posts\" for 127.0.0.1"),
+            line(
+                "2024-05-01T12:00:00.003+09:00",
+                "Post Load (0.5ms)  SELECT \"posts\".* FROM \"posts\"",
+            ),
+            line("2024-05-01T12:00:00.005+09:00", "Completed 200 OK in 5ms"),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(lines: &[&str]) -> LogGroup {
+        let mut entries = lines.iter().map(|line| log_parser::parse(line, None).unwrap());
+        let mut group = LogGroup::new(entries.next().unwrap());
+        entries.for_each(|entry| group.add_entry(entry));
+        group
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("3f8a1c2e-7b4d"), "3f8a1c2e-7b4d");
+        assert_eq!(file_stem("../etc/passwd"), "_etc_passwd");
+        assert_eq!(file_stem("a b"), "a_b");
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact(r#"Parameters: {"user"=>{"email"=>"a@example.com", "password"=>"hunter2"}}"#),
+            r#"Parameters: {"user"=>{"email"=>"[FILTERED]", "password"=>"[FILTERED]"}}"#
+        );
+        assert_eq!(
+            redact("Started GET \"/reset?reset_token=abc123&page=2\""),
+            "Started GET \"/reset?reset_token=[FILTERED]&page=2\""
+        );
+        assert_eq!(redact("Authorization: Bearer eyJhbGci"), "Authorization: Bearer [FILTERED]");
+        assert!(matches!(redact("Completed 200 OK"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_options_apply_to_every_format() {
+        let group = group(&[
+            "[abc-123] Started GET \"/posts?token=secret\" for 127.0.0.1",
+            "[abc-123]   Rendered posts/index.html.erb (Duration: 3.0ms)",
+            "[abc-123] Completed 200 OK in 5ms",
+        ]);
+        let options = ExportOptions {
+            redact: true,
+            verbosity: Verbosity::Simple,
+            ..Default::default()
+        };
+        for format in FORMATS {
+            let out = to_string(*format, "abc-123", &group, options);
+            assert!(!out.contains("secret"), "{}: {}", format.name(), out);
+            assert!(!out.contains("Rendered"), "{}: {}", format.name(), out);
+        }
+    }
+
+    #[test]
+    fn test_repeated_lines_are_exported_as_logged() {
+        let group = group(&[
+            "[abc-123] Started GET \"/posts\" for 127.0.0.1",
+            "[abc-123] Retrying upstream call",
+            "[abc-123] Retrying upstream call",
+            "[abc-123] Completed 200 OK in 5ms",
+        ]);
+        let exported = ExportedRequest::new("abc-123", &group, ExportOptions::default());
+        let texts: Vec<&str> = exported.lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts[1..3], ["[abc-123] Retrying upstream call"; 2]);
+        assert_eq!(texts.len(), 4);
+    }
+
+    #[test]
+    fn test_inline_binds() {
+        let group = group(&[
+            "[abc-123] Started GET \"/users/7\" for 127.0.0.1",
+            "[abc-123]   User Load (0.2ms)  SELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1  [[\"id\", 7]]",
+        ]);
+        let options = ExportOptions {
+            inline_binds: true,
+            ..Default::default()
+        };
+        let out = to_string(&sql::Sql, "abc-123", &group, options);
+        assert!(out.ends_with("WHERE \"users\".\"id\" = 7;\n"), "{}", out);
+        let out = to_string(&sql::Sql, "abc-123", &group, ExportOptions::default());
+        assert!(out.contains("= $1  [[\"id\", 7]];"), "{}", out);
+    }
+
+    #[test]
+    fn test_format_registry() {
+        assert_eq!(format_named("markdown").unwrap().name(), "markdown");
+        assert!(format_named("pdf").is_err());
+        let mut names: Vec<_> = FORMATS.iter().map(|format| format.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), FORMATS.len());
+        assert_eq!(next_format(FORMATS[FORMATS.len() - 1]).name(), FORMATS[0].name());
+    }
+
+    #[test]
+    fn test_write_groups() {
+        let lines = [
+            "[abc-123] Started GET \"/posts\" for 127.0.0.1",
+            "[abc-123] Completed 200 OK in 5ms",
+        ];
+        let group = group(&lines);
+
+        let dir = std::env::temp_dir().join(format!("lucy-export-test-{}", std::process::id()));
+        let written = write_groups(
+            &dir.join("nested"),
+            &raw::Raw,
+            &[("abc-123", &group)],
+            ExportOptions::default(),
+        )
+        .unwrap();
+        let contents = fs::read_to_string(dir.join("nested/abc-123.log")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(written, 1);
+        assert_eq!(contents, format!("{}\n{}\n", lines[0], lines[1]));
+    }
+}
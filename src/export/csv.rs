@@ -0,0 +1,54 @@
+use super::{ExportedRequest, RequestSerializer};
+
+/// One row per line, for spreadsheets.
+pub struct Csv;
+
+impl RequestSerializer for Csv {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        out.push_str("timestamp,request_id,line\n");
+        for line in &request.lines {
+            out.push_str(&format!(
+                "{},{},{}\n",
+                line.timestamp.to_rfc3339(),
+                field(&request.request_id),
+                field(&line.text)
+            ));
+        }
+    }
+}
+
+/// Quotes a field holding separators or quotes, doubling the quotes (RFC 4180).
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_request;
+
+    #[test]
+    fn test_csv() {
+        let mut out = String::new();
+        Csv.serialize(&sample_request(), &mut out);
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[1],
+            r#"2024-05-01T12:00:00+09:00,abc-123,"Started GET ""/posts"" for 127.0.0.1""#
+        );
+        assert_eq!(rows[3], "2024-05-01T12:00:00.005+09:00,abc-123,Completed 200 OK in 5ms");
+    }
+}
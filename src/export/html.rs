@@ -0,0 +1,66 @@
+use super::{ExportedRequest, RequestSerializer, status_label};
+
+/// A standalone page, for sharing with people who do not read logs in a terminal.
+pub struct Html;
+
+impl RequestSerializer for Html {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        let title = escape(&request.title);
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n</head>\n<body>\n", title));
+        out.push_str(&format!("<h1>{}</h1>\n<dl>\n", title));
+        let mut facts = vec![
+            ("Request ID", escape(&request.request_id)),
+            ("Started", request.started.clone()),
+        ];
+        if let Some(ms) = request.duration_ms {
+            facts.push(("Duration", format!("{}ms", ms)));
+        }
+        facts.push(("Status", status_label(request.status).to_string()));
+        if let Some(note) = &request.note {
+            facts.push(("Note", escape(note)));
+        }
+        for (label, value) in facts {
+            out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", label, value));
+        }
+        out.push_str("</dl>\n<pre>\n");
+        for line in &request.lines {
+            out.push_str(&escape(&line.text));
+            out.push('\n');
+        }
+        out.push_str("</pre>\n</body>\n</html>\n");
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_request;
+
+    #[test]
+    fn test_html() {
+        let mut request = sample_request();
+        request.lines[0].text = "<script>alert(1)</script>".to_string();
+        let mut out = String::new();
+        Html.serialize(&request, &mut out);
+        assert!(out.contains("<h1>GET &quot;/posts&quot;</h1>"));
+        assert!(out.contains("<dt>Duration</dt><dd>5ms</dd>"));
+        assert!(out.contains("&lt;script&gt;alert(1)&lt;/script&gt;\n"));
+        assert!(!out.contains("<script>"));
+    }
+}
@@ -0,0 +1,183 @@
+use super::markdown::fence;
+use super::{ExportedRequest, RequestSerializer, status_label};
+use crate::log_parser::{self, RE_COMPLETED, strip_tags};
+use crate::params_formatter::{self, INDENT};
+use crate::sql_info;
+use regex::Regex;
+use std::sync::LazyLock;
+
+// app/controllers/posts_controller.rb:12:in 'show'
+// /usr/local/bundle/gems/actionpack-7.1.3/lib/action_controller/metal/basic_implicit_render.rb:6:in `send_action'
+static RE_FRAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\S+\.\w+:\d+:in ").unwrap());
+
+/// A report of one request for a GitHub issue: a summary table, then its
+/// params, its queries with their timings and the backtrace of its error.
+pub struct Issue;
+
+impl RequestSerializer for Issue {
+    fn name(&self) -> &'static str {
+        "issue"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        let messages: Vec<&str> = request
+            .lines
+            .iter()
+            .map(|line| strip_tags(&line.text))
+            .collect();
+        let queries: Vec<(f64, &str)> = messages
+            .iter()
+            .filter_map(|message| {
+                Some((
+                    sql_info::query_duration_ms(message)?,
+                    sql_info::statement(message)?,
+                ))
+            })
+            .collect();
+
+        out.push_str(&format!("### {}\n\n", request.title));
+        out.push_str("| | |\n| --- | --- |\n");
+        let mut row = |name: &str, value: String| {
+            out.push_str(&format!("| {} | {} |\n", name, value.replace('|', "\\|")));
+        };
+        row("Request ID", code(&request.request_id));
+        let status_code = messages
+            .iter()
+            .find_map(|message| RE_COMPLETED.captures(message))
+            .map(|caps| format!("{} ", &caps["status"]));
+        row(
+            "Status",
+            format!(
+                "{}{}",
+                status_code.unwrap_or_default(),
+                status_label(request.status)
+            ),
+        );
+        row("Started", request.started.clone());
+        if let Some(ms) = request.duration_ms {
+            row("Duration", format!("{}ms", ms));
+        }
+        if !request.finished {
+            row("Finished", "no".to_string());
+        }
+        if let Some(note) = &request.note {
+            row("Note", note.clone());
+        }
+        let query_ms = queries.iter().fold(0.0, |total, (ms, _)| total + ms);
+        row("Queries", format!("{} ({:.1}ms)", queries.len(), query_ms));
+
+        if let Some(params) = messages
+            .iter()
+            .find_map(|message| params_formatter::parse(message))
+        {
+            let lines: Vec<String> = params_formatter::tree_lines(&params)
+                .iter()
+                .map(|line| {
+                    let text = line.to_string().replace(INDENT, "  ");
+                    text.strip_prefix("  ").unwrap_or(&text).to_string()
+                })
+                .collect();
+            block(out, "Parameters", lines.iter().map(String::as_str));
+        }
+
+        if !queries.is_empty() {
+            out.push_str("\n#### SQL\n\n");
+            for (i, (ms, statement)) in queries.iter().enumerate() {
+                out.push_str(&format!("{}. {:.1}ms {}\n", i + 1, ms, code(statement)));
+            }
+        }
+
+        // The error, followed by the frames logged after it
+        let backtrace: Vec<&str> = messages
+            .iter()
+            .skip_while(|message| !log_parser::is_error_line(message))
+            .filter(|message| log_parser::is_error_line(message) || RE_FRAME.is_match(message))
+            .copied()
+            .collect();
+        if !backtrace.is_empty() {
+            block(out, "Backtrace", backtrace.iter().copied());
+        }
+    }
+}
+
+/// A heading and `lines` in a fenced block.
+fn block<'a>(out: &mut String, heading: &str, lines: impl Iterator<Item = &'a str> + Clone) {
+    let fence = fence(lines.clone());
+    out.push_str(&format!("\n#### {}\n\n{}\n", heading, fence));
+    for line in lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&fence);
+    out.push('\n');
+}
+
+/// `text` as inline code, delimited by more backticks than it contains.
+fn code(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    if longest_run == 0 {
+        return format!("`{}`", text);
+    }
+    let delimiter = "`".repeat(longest_run + 1);
+    format!("{} {} {}", delimiter, text, delimiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{ExportedLine, sample_request};
+
+    #[test]
+    fn test_issue() {
+        let mut request = sample_request();
+        let at = request.started_at;
+        let completed = request.lines.pop().unwrap();
+        for text in [
+            r#"Parameters: {"post"=>{"title"=>"Hello"}, "id"=>"3"}"#,
+            "NoMethodError (undefined method `name' for nil):",
+            "app/controllers/posts_controller.rb:12:in 'show'",
+            "app/controllers/application_controller.rb:5:in 'around'",
+        ] {
+            request.lines.push(ExportedLine {
+                timestamp: at,
+                text: format!("[abc-123] {}", text),
+            });
+        }
+        request.lines.push(completed);
+
+        let mut out = String::new();
+        Issue.serialize(&request, &mut out);
+        assert!(
+            out.starts_with(
+                "### GET \"/posts\"\n\n| | |\n| --- | --- |\n| Request ID | `abc-123` |\n"
+            ),
+            "{}",
+            out
+        );
+        assert!(out.contains("| Status | 200 success |\n"), "{}", out);
+        assert!(out.contains("| Queries | 1 (0.5ms) |\n"), "{}", out);
+        assert!(
+            out.contains("#### Parameters\n\n```\npost:\n  title: \"Hello\"\nid: \"3\"\n```\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("#### SQL\n\n1. 0.5ms `SELECT \"posts\".* FROM \"posts\"`\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.ends_with(
+                "#### Backtrace\n\n```\nNoMethodError (undefined method `name' for nil):\napp/controllers/posts_controller.rb:12:in 'show'\napp/controllers/application_controller.rb:5:in 'around'\n```\n"
+            ),
+            "{}",
+            out
+        );
+
+        assert_eq!(code("a`b"), "`` a`b ``");
+    }
+}
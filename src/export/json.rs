@@ -0,0 +1,66 @@
+use super::{ExportedRequest, RequestSerializer, status_label};
+use serde_json::json;
+
+/// One JSON object per request, for scripts and other tools.
+pub struct Json;
+
+impl RequestSerializer for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        let lines: Vec<_> = request
+            .lines
+            .iter()
+            .map(|line| {
+                json!({
+                    "timestamp": line.timestamp.to_rfc3339(),
+                    "text": line.text,
+                })
+            })
+            .collect();
+        let value = json!({
+            "request_id": request.request_id,
+            "title": request.title,
+            "status": status_label(request.status),
+            "finished": request.finished,
+            "started_at": request.started_at.to_rfc3339(),
+            "duration_ms": request.duration_ms,
+            "note": request.note,
+            "lines": lines,
+        });
+        // Serializing a `Value` cannot fail
+        out.push_str(&serde_json::to_string_pretty(&value).unwrap_or_default());
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_request;
+
+    #[test]
+    fn test_json() {
+        let mut out = String::new();
+        Json.serialize(&sample_request(), &mut out);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["request_id"], "abc-123");
+        assert_eq!(value["duration_ms"], 5);
+        assert_eq!(value["lines"][2]["text"], "Completed 200 OK in 5ms");
+        assert_eq!(value["lines"][2]["timestamp"], "2024-05-01T12:00:00.005+09:00");
+        assert!(value["note"].is_null());
+
+        let mut request = sample_request();
+        request.note = Some("same as #123".to_string());
+        out.clear();
+        Json.serialize(&request, &mut out);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["note"], "same as #123");
+    }
+}
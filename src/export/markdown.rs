@@ -0,0 +1,68 @@
+use super::{ExportedRequest, RequestSerializer, status_label};
+
+/// A heading, a list of facts and the lines in a fenced block, for pasting
+/// into issues and pull requests.
+pub struct Markdown;
+
+impl RequestSerializer for Markdown {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        out.push_str(&format!("### {}\n\n", request.title));
+        out.push_str(&format!("- Request ID: `{}`\n", request.request_id));
+        out.push_str(&format!("- Started: {}\n", request.started));
+        if let Some(ms) = request.duration_ms {
+            out.push_str(&format!("- Duration: {}ms\n", ms));
+        }
+        out.push_str(&format!("- Status: {}\n", status_label(request.status)));
+        if let Some(note) = &request.note {
+            out.push_str(&format!("- Note: {}\n", note));
+        }
+        out.push('\n');
+
+        let fence = fence(request.lines.iter().map(|line| line.text.as_str()));
+        out.push_str(&format!("{}log\n", fence));
+        for line in &request.lines {
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+        out.push_str(&fence);
+        out.push('\n');
+    }
+}
+
+/// A backtick fence longer than any run of backticks in the lines.
+pub(super) fn fence<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    let longest_run = lines
+        .into_iter()
+        .flat_map(|line| line.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat((longest_run + 1).max(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_request;
+
+    #[test]
+    fn test_markdown() {
+        let mut request = sample_request();
+        let mut out = String::new();
+        Markdown.serialize(&request, &mut out);
+        assert!(out.starts_with("### GET \"/posts\"\n\n- Request ID: `abc-123`\n"));
+        assert!(out.contains("- Duration: 5ms\n- Status: success\n\n```log\nStarted GET"));
+        assert!(out.ends_with("Completed 200 OK in 5ms\n```\n"));
+
+        request.lines[0].text = "````".to_string();
+        assert_eq!(fence(request.lines.iter().map(|line| line.text.as_str())), "`````");
+    }
+}
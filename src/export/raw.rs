@@ -0,0 +1,21 @@
+use super::{ExportedRequest, RequestSerializer};
+
+/// The log lines as they were written.
+pub struct Raw;
+
+impl RequestSerializer for Raw {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn extension(&self) -> &'static str {
+        "log"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        for line in &request.lines {
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+    }
+}
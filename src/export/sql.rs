@@ -0,0 +1,44 @@
+use super::{ExportedRequest, RequestSerializer};
+use crate::sql_info;
+
+/// The request's queries as a script, to replay or `EXPLAIN` them.
+pub struct Sql;
+
+impl RequestSerializer for Sql {
+    fn name(&self) -> &'static str {
+        "sql"
+    }
+
+    fn extension(&self) -> &'static str {
+        "sql"
+    }
+
+    fn serialize(&self, request: &ExportedRequest, out: &mut String) {
+        out.push_str(&format!("-- {} {}\n", request.request_id, request.title));
+        if let Some(note) = &request.note {
+            out.push_str(&format!("-- {}\n", note));
+        }
+        for line in &request.lines {
+            if let Some(statement) = sql_info::statement(&line.text) {
+                out.push_str(statement.trim_end_matches(';'));
+                out.push_str(";\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_request;
+
+    #[test]
+    fn test_sql() {
+        let mut out = String::new();
+        Sql.serialize(&sample_request(), &mut out);
+        assert_eq!(
+            out,
+            "-- abc-123 GET \"/posts\"\nSELECT \"posts\".* FROM \"posts\";\n"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::app_state::LogEntry;
+use crate::config::TimeZoneMode;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, NaiveTime, TimeZone};
+use std::collections::HashMap;
+
+/// Where a request first appears in a loaded file.
+#[derive(Debug, Clone)]
+pub struct IndexedRequest {
+    pub request_id: String,
+    pub offset: u64,
+    pub timestamp: DateTime<FixedOffset>,
+}
+
+/// Time/offset index built while a file is loaded, in file order.
+#[derive(Debug, Default)]
+pub struct FileIndex {
+    requests: Vec<IndexedRequest>,
+    by_id: HashMap<String, usize>,
+    first_timestamp: Option<DateTime<FixedOffset>>,
+    last_timestamp: Option<DateTime<FixedOffset>>,
+}
+
+impl FileIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an entry read from byte `offset` of the file.
+    pub fn record(&mut self, offset: u64, entry: &LogEntry) {
+        let timestamp = entry.timestamp;
+        if self.first_timestamp.is_none_or(|first| timestamp < first) {
+            self.first_timestamp = Some(timestamp);
+        }
+        if self.last_timestamp.is_none_or(|last| timestamp > last) {
+            self.last_timestamp = Some(timestamp);
+        }
+
+        if entry.request_id.is_empty() || self.by_id.contains_key(&entry.request_id) {
+            return;
+        }
+        self.by_id
+            .insert(entry.request_id.clone(), self.requests.len());
+        self.requests.push(IndexedRequest {
+            request_id: entry.request_id.clone(),
+            offset,
+            timestamp,
+        });
+    }
+
+    pub fn span(&self) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        Some((self.first_timestamp?, self.last_timestamp?))
+    }
+
+    pub fn get(&self, request_id: &str) -> Option<&IndexedRequest> {
+        self.by_id.get(request_id).map(|&i| &self.requests[i])
+    }
+
+    /// Exact request id, or the first request whose id starts with `prefix`.
+    pub fn find_request(&self, prefix: &str) -> Option<&IndexedRequest> {
+        if prefix.is_empty() {
+            return None;
+        }
+        self.get(prefix).or_else(|| {
+            self.requests
+                .iter()
+                .find(|request| request.request_id.starts_with(prefix))
+        })
+    }
+
+    /// First request started at or after `timestamp`. Files are assumed to be
+    /// written in chronological order.
+    pub fn request_at_or_after(&self, timestamp: DateTime<FixedOffset>) -> Option<&IndexedRequest> {
+        let pos = self
+            .requests
+            .partition_point(|request| request.timestamp < timestamp);
+        self.requests.get(pos)
+    }
+}
+
+/// Parses `HH:MM[:SS]` or `YYYY-MM-DD HH:MM[:SS]` as typed by the user, in the
+/// zone timestamps are displayed in. A bare time is taken on the date of `reference`.
+pub fn parse_jump_time(
+    input: &str,
+    reference: DateTime<FixedOffset>,
+    zone: TimeZoneMode,
+) -> Option<DateTime<FixedOffset>> {
+    let reference = zone.convert(&reference);
+    let input = input.trim().replacen('T', " ", 1);
+
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&input, format).ok())
+        .or_else(|| {
+            ["%H:%M:%S", "%H:%M"]
+                .iter()
+                .find_map(|format| NaiveTime::parse_from_str(&input, format).ok())
+                .map(|time| reference.date_naive().and_time(time))
+        })?;
+
+    reference.offset().from_local_datetime(&naive).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(request_id: &str, timestamp: &str) -> LogEntry {
+        LogEntry {
+            request_id: request_id.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn index() -> FileIndex {
+        let mut index = FileIndex::new();
+        index.record(0, &entry("aaa-1", "2024-05-01T12:00:00Z"));
+        index.record(40, &entry("aaa-1", "2024-05-01T12:00:01Z"));
+        index.record(80, &entry("", "2024-05-01T12:03:00Z"));
+        index.record(120, &entry("bbb-2", "2024-05-01T12:05:00Z"));
+        index.record(160, &entry("ccc-3", "2024-05-01T12:10:00Z"));
+        index
+    }
+
+    #[test]
+    fn test_span() {
+        let (first, last) = index().span().unwrap();
+        assert_eq!(first.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        assert_eq!(last.to_rfc3339(), "2024-05-01T12:10:00+00:00");
+        assert!(FileIndex::new().span().is_none());
+    }
+
+    #[test]
+    fn test_find_request() {
+        let index = index();
+        assert_eq!(index.find_request("bbb-2").unwrap().offset, 120);
+        assert_eq!(index.find_request("ccc").unwrap().request_id, "ccc-3");
+        assert!(index.find_request("zzz").is_none());
+        assert!(index.find_request("").is_none());
+    }
+
+    #[test]
+    fn test_request_at_or_after() {
+        let index = index();
+        let at = |ts: &str| DateTime::parse_from_rfc3339(ts).unwrap();
+
+        let found = index
+            .request_at_or_after(at("2024-05-01T12:01:00Z"))
+            .unwrap();
+        assert_eq!(found.request_id, "bbb-2");
+
+        let found = index
+            .request_at_or_after(at("2024-05-01T12:05:00Z"))
+            .unwrap();
+        assert_eq!(found.request_id, "bbb-2");
+
+        assert!(
+            index
+                .request_at_or_after(at("2024-05-01T13:00:00Z"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_jump_time() {
+        let reference = DateTime::parse_from_rfc3339("2024-05-01T23:30:00Z").unwrap();
+
+        let parsed = parse_jump_time("12:05", reference, TimeZoneMode::Utc).unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-05-01T12:05:00+00:00");
+
+        let parsed = parse_jump_time("2024-04-30 08:00:30", reference, TimeZoneMode::Utc).unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-04-30T08:00:30+00:00");
+
+        // Typed in the original zone of the log
+        let reference = DateTime::parse_from_rfc3339("2024-05-01T09:00:00+09:00").unwrap();
+        let parsed = parse_jump_time("10:00", reference, TimeZoneMode::Original).unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-05-01T10:00:00+09:00");
+
+        assert!(parse_jump_time("req-123", reference, TimeZoneMode::Utc).is_none());
+    }
+}
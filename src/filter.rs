@@ -0,0 +1,45 @@
+/// Matches `query` against `text` as a case-insensitive subsequence, so
+/// `gpost` finds `GET "/posts"`. Whitespace in the query is ignored.
+///
+/// Returns the char indices of `text` that matched, for highlighting.
+pub fn fuzzy_match(text: &str, query: &str) -> Option<Vec<usize>> {
+    let mut query_chars = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut positions = Vec::new();
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            positions.push(i);
+            query_chars.next();
+        }
+    }
+
+    query_chars.peek().is_none().then_some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        let title = "GET \"/posts/1\" for 127.0.0.1";
+        assert_eq!(fuzzy_match(title, "gpost"), Some(vec![0, 6, 7, 8, 9]));
+        assert_eq!(fuzzy_match(title, "GET posts").map(|p| p.len()), Some(8));
+        assert_eq!(fuzzy_match(title, ""), Some(vec![]));
+
+        assert!(fuzzy_match(title, "post get").is_none());
+        assert!(fuzzy_match(title, "users").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_match_non_ascii() {
+        assert_eq!(fuzzy_match("GET \"/café\"", "fé"), Some(vec![8, 9]));
+    }
+}
@@ -0,0 +1,125 @@
+//! Drawing the app off screen, into a `TestBackend` buffer, so benchmarks and
+//! tests can time frames and check what they show without a terminal.
+
+use crate::app::App;
+use crate::config::Config;
+use crate::demo::Generator;
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+
+/// Line padding generated requests to the requested length, numbered so
+/// that consecutive ones are not folded together as repeats.
+fn padding_line(tag: &str, number: usize) -> String {
+    format!(
+        "{}]   Rendered posts/_post.html.erb (Duration: 0.2ms | Allocations: {})",
+        tag,
+        84 + number
+    )
+}
+
+pub struct Headless {
+    terminal: Terminal<TestBackend>,
+}
+
+impl Headless {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            terminal: Terminal::new(TestBackend::new(width, height))
+                .expect("TestBackend cannot fail"),
+        }
+    }
+
+    /// Draws one frame of `app` and returns what it put on screen.
+    pub fn draw(&mut self, app: &mut App) -> &Buffer {
+        self.terminal
+            .draw(|f| app.render(f))
+            .expect("TestBackend cannot fail");
+        self.terminal.backend().buffer()
+    }
+}
+
+/// An app with the default config holding `requests` generated requests,
+/// each padded with rendered partials to at least `lines_per_request` lines.
+/// None are evicted, however many there are.
+pub fn populated_app(requests: usize, lines_per_request: usize) -> App {
+    let mut config = Config::default();
+    config.retention.max_requests = config.retention.max_requests.max(requests);
+    let mut app = App::new(config);
+    let mut generator = Generator::new(42);
+    for _ in 0..requests {
+        let mut lines = generator.request();
+        let Some((tag, _)) = lines[0].split_once("] ") else {
+            continue;
+        };
+        // Jobs the request enqueues follow its `Completed` line
+        let completed = lines
+            .iter()
+            .position(|line| line.starts_with(tag) && line.contains("] Completed "))
+            .unwrap_or(lines.len() - 1);
+        let missing = lines_per_request.saturating_sub(completed + 1);
+        let padding: Vec<String> = (0..missing).map(|i| padding_line(tag, i)).collect();
+        lines.splice(completed..completed, padding);
+        for line in &lines {
+            app.ingest_line(0, line);
+        }
+    }
+    app
+}
+
+/// The buffer's rows, one per line, for assertions on what a frame shows.
+pub fn buffer_text(buffer: &Buffer) -> String {
+    let width = buffer.area.width as usize;
+    buffer
+        .content
+        .chunks(width.max(1))
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::GroupKind;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_draw() {
+        let mut app = populated_app(20, 30);
+        let requests: Vec<_> = app
+            .state
+            .logs_by_request_id
+            .values()
+            .filter(|group| group.kind == GroupKind::Request)
+            .collect();
+        assert_eq!(requests.len(), 20);
+        assert!(
+            requests
+                .iter()
+                .all(|group| group.line_count() >= 30 && group.finished)
+        );
+
+        let mut screen = Headless::new(120, 30);
+        let text = buffer_text(screen.draw(&mut app));
+        assert_eq!(text.lines().count(), 30);
+        assert!(text.contains("SELECT"), "{}", text);
+    }
+
+    /// Frame time with 10k requests and 1M lines. Run in release mode with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_frame_time() {
+        const FRAMES: u32 = 50;
+        const BUDGET: Duration = Duration::from_millis(16);
+
+        let mut app = populated_app(10_000, 100);
+        let mut screen = Headless::new(200, 60);
+        screen.draw(&mut app);
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            screen.draw(&mut app);
+        }
+        let frame = start.elapsed() / FRAMES;
+        assert!(frame < BUDGET, "{:?} per frame", frame);
+    }
+}
@@ -0,0 +1,220 @@
+//! Shell commands the config runs when something happens to a request, such
+//! as posting failures to a chat or opening an editor at the exception:
+//!
+//! ```toml
+//! [[hooks]]
+//! on = "server_error"
+//! command = "curl -s -X POST -H 'Content-Type: application/json' -d @- $WEBHOOK_URL"
+//! ```
+//!
+//! The command gets the request as JSON on stdin, in the `json` export
+//! format, and what the event is about in `LUCY_*` environment variables.
+
+use crate::app_state::LogGroup;
+use crate::editor;
+use crate::export::{self, ExportOptions};
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    /// Any request finishing
+    Completed,
+    /// A request finishing with a 5xx status
+    ServerError,
+    /// A finished request that queried a table over and over
+    NPlusOne,
+}
+
+impl HookEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Completed => "completed",
+            HookEvent::ServerError => "server_error",
+            HookEvent::NPlusOne => "n_plus_one",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HookConfig {
+    pub on: HookEvent,
+    /// Run with `sh -c`.
+    pub command: String,
+}
+
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<HookConfig>,
+    options: ExportOptions,
+}
+
+impl Hooks {
+    pub fn new(hooks: Vec<HookConfig>, options: ExportOptions) -> Self {
+        Self { hooks, options }
+    }
+
+    /// Runs the hooks for each event that `group` finishing raises.
+    pub fn request_finished(&self, request_id: &str, group: &LogGroup) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let events = events(group);
+        let mut json = None;
+        for hook in self.hooks.iter().filter(|hook| events.contains(&hook.on)) {
+            let json = json.get_or_insert_with(|| {
+                export::to_string(&export::Json, request_id, group, self.options)
+            });
+            let mut command = shell(&hook.command);
+            command.envs(environment(hook.on, request_id, group));
+            spawn(command, json.clone());
+        }
+    }
+}
+
+/// The events a finished request raises.
+pub fn events(group: &LogGroup) -> Vec<HookEvent> {
+    let mut events = vec![HookEvent::Completed];
+    if group.status_code.is_some_and(|code| code >= 500) {
+        events.push(HookEvent::ServerError);
+    }
+    if !n_plus_one_tables(group).is_empty() {
+        events.push(HookEvent::NPlusOne);
+    }
+    events
+}
+
+fn n_plus_one_tables(group: &LogGroup) -> Vec<&str> {
+    let sql_info = &group.sql_query_info;
+    sql_info
+        .sorted_tables()
+        .into_iter()
+        .map(|(table, _)| table.as_str())
+        .filter(|table| sql_info.is_n_plus_one(table))
+        .collect()
+}
+
+fn environment(
+    event: HookEvent,
+    request_id: &str,
+    group: &LogGroup,
+) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("LUCY_EVENT", event.name().to_string()),
+        ("LUCY_REQUEST_ID", request_id.to_string()),
+        ("LUCY_TITLE", group.title.trim_end().to_string()),
+    ];
+    if let Some(code) = group.status_code {
+        env.push(("LUCY_STATUS", code.to_string()));
+    }
+    if let Some(ms) = group.duration_ms {
+        env.push(("LUCY_DURATION_MS", ms.to_string()));
+    }
+    if let Some(location) = editor::exception_location(group) {
+        env.push(("LUCY_FILE", location.file));
+        env.push(("LUCY_LINE", location.line.to_string()));
+    }
+    let tables = n_plus_one_tables(group);
+    if !tables.is_empty() {
+        env.push(("LUCY_TABLES", tables.join(",")));
+    }
+    env
+}
+
+fn shell(command_line: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(command_line);
+    command
+}
+
+/// Runs `command` with `input` on stdin. Its output would garble the
+/// screen and is discarded; failures are only logged.
+fn spawn(mut command: Command, input: String) {
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Waited for on its own thread, so a slow hook never holds up the screen
+    thread::spawn(move || {
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::warn!("Hook failed to start: {}", e);
+                return;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take()
+            && let Err(e) = stdin.write_all(input.as_bytes())
+            // A hook may well not read its input
+            && e.kind() != io::ErrorKind::BrokenPipe
+        {
+            tracing::warn!("Hook input failed: {}", e);
+        }
+        match child.wait() {
+            Ok(status) if !status.success() => tracing::warn!("Hook exited with {}", status),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Hook failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::LogEntry;
+
+    fn request(lines: &[&str]) -> LogGroup {
+        let mut group = LogGroup::new(LogEntry {
+            message: "Started GET \"/posts/1\" for 127.0.0.1".to_string(),
+            ..Default::default()
+        });
+        for line in lines {
+            group.add_entry(LogEntry {
+                message: line.to_string(),
+                ..Default::default()
+            });
+        }
+        group
+    }
+
+    #[test]
+    fn test_events() {
+        let group = request(&["Completed 200 OK in 5ms"]);
+        assert_eq!(events(&group), [HookEvent::Completed]);
+
+        let group = request(&[
+            "NoMethodError (undefined method 'title' for nil):",
+            "app/controllers/posts_controller.rb:12:in 'show'",
+            "app/controllers/application_controller.rb:3:in 'around'",
+            "Completed 500 Internal Server Error in 9ms",
+        ]);
+        assert_eq!(
+            events(&group),
+            [HookEvent::Completed, HookEvent::ServerError]
+        );
+        let env = environment(HookEvent::ServerError, "r1", &group);
+        assert!(env.contains(&("LUCY_FILE", "app/controllers/posts_controller.rb".to_string())));
+        assert!(env.contains(&("LUCY_LINE", "12".to_string())));
+
+        let select = r#"User Load (0.2ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1"#;
+        let mut lines = vec![select; 5];
+        lines.push("Completed 200 OK in 30ms");
+        let group = request(&lines);
+        assert_eq!(events(&group), [HookEvent::Completed, HookEvent::NPlusOne]);
+        assert_eq!(n_plus_one_tables(&group), ["users"]);
+        let env = environment(HookEvent::NPlusOne, "r1", &group);
+        assert!(env.contains(&("LUCY_TABLES", "users".to_string())));
+        assert!(!env.iter().any(|(name, _)| *name == "LUCY_FILE"));
+    }
+}
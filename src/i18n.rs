@@ -0,0 +1,651 @@
+use serde::Deserialize;
+use std::fmt::Display;
+
+/// Language of the UI, set with `locale` in the config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Locale::En => &EN,
+            Locale::Ja => &JA,
+        }
+    }
+}
+
+/// User-facing strings. `{}` placeholders are filled in order by [`fill`].
+pub struct Strings {
+    pub requests: &'static str,
+    pub jobs: &'static str,
+    pub cable: &'static str,
+    /// Title of the group of lines without a request id, with its line count
+    pub unassigned: &'static str,
+    pub source_filter: &'static str,
+    pub logs: &'static str,
+    pub waiting_for_logs: &'static str,
+    pub jump_prompt: &'static str,
+    pub toast_jump_evicted: &'static str,
+    pub time_window_prompt: &'static str,
+    pub compacted: &'static str,
+    pub compacted_restorable: &'static str,
+    /// Current and total matches of the detail search
+    pub search_matches: &'static str,
+    pub help: &'static str,
+    pub help_simple: &'static str,
+    pub help_summary: &'static str,
+    pub help_copy: &'static str,
+    pub help_line_select: &'static str,
+    pub help_visual: &'static str,
+    pub help_endpoints: &'static str,
+    pub line_popup_title: &'static str,
+    pub explain_title: &'static str,
+    pub explain_title_no_analyze: &'static str,
+    pub explain_running: &'static str,
+    pub explain_confirm: &'static str,
+    pub toast_explain_no_database: &'static str,
+    pub toast_explain_no_query: &'static str,
+    pub toast_explain_several_statements: &'static str,
+    pub toast_time_window_invalid: &'static str,
+    pub toast_command_invalid: &'static str,
+    pub toast_editor_no_location: &'static str,
+    pub toast_editor_failed: &'static str,
+    pub help_no_mouse: &'static str,
+    /// Current and needed size, each as `{}x{}`
+    pub terminal_too_small: &'static str,
+    /// Divider in the request list, with the time of the restart
+    pub restart_divider: &'static str,
+    pub toast_restart_cleared: &'static str,
+    pub help_sources: &'static str,
+    pub help_bulk: &'static str,
+    pub time_zone: &'static str,
+    pub time_zone_local: &'static str,
+    pub time_zone_original: &'static str,
+    pub span: &'static str,
+    pub offset: &'static str,
+    pub jump: &'static str,
+    pub sampling: &'static str,
+    pub badge_paused: &'static str,
+    pub badge_follow: &'static str,
+    pub badge_input_ended: &'static str,
+    pub badge_exited: &'static str,
+    pub badge_end_of_file: &'static str,
+    pub ingest_rate: &'static str,
+    pub ingest_requests: &'static str,
+    pub ingest_orphans: &'static str,
+    pub ingest_dropped: &'static str,
+    pub stats_title: &'static str,
+    pub stats_title_matching: &'static str,
+    pub timeline_title: &'static str,
+    pub timeline_no_timestamps: &'static str,
+    pub timeline_empty: &'static str,
+    pub timeline_all_title: &'static str,
+    pub timeline_all_no_timestamps: &'static str,
+    pub status_other: &'static str,
+    pub duration: &'static str,
+    pub request_rate: &'static str,
+    pub loading: &'static str,
+    pub loaded: &'static str,
+    pub plain_requests: &'static str,
+    pub plain_detail: &'static str,
+    pub plain_sql: &'static str,
+    pub plain_focused: &'static str,
+    pub status_running: &'static str,
+    pub status_success: &'static str,
+    pub status_info: &'static str,
+    pub status_warning: &'static str,
+    pub status_error: &'static str,
+    pub status_unknown: &'static str,
+    pub status_warning_level: &'static str,
+    pub status_error_level: &'static str,
+    pub views: &'static str,
+    pub http_calls: &'static str,
+    pub cache_operations: &'static str,
+    pub memory: &'static str,
+    pub memory_allocations: &'static str,
+    pub memory_process: &'static str,
+    pub memory_delta: &'static str,
+    pub memory_instantiated: &'static str,
+    pub cache_hit_ratio: &'static str,
+    pub starred: &'static str,
+    pub errors: &'static str,
+    pub time_window_last: &'static str,
+    pub sorted_by: &'static str,
+    pub traffic_rate: &'static str,
+    pub traffic_errors: &'static str,
+    pub endpoints_title: &'static str,
+    pub starred_marker: &'static str,
+    pub pinned_marker: &'static str,
+    pub tutorial_title: &'static str,
+    pub tutorial_footer: &'static str,
+    /// One text per `Tutorial` step
+    pub tutorial_steps: &'static [&'static str],
+    pub picker_title: &'static str,
+    pub picker_hint: &'static str,
+    pub picker_footer: &'static str,
+    pub about_title: &'static str,
+    pub update_available: &'static str,
+    pub update_check_off: &'static str,
+    pub update_none: &'static str,
+    pub gap_note: &'static str,
+    pub gap_note_no_sql: &'static str,
+    pub notifications_title: &'static str,
+    pub request_info_title: &'static str,
+    pub note_title: &'static str,
+    pub note_hint: &'static str,
+    pub note_marker: &'static str,
+    pub duplicate_marker: &'static str,
+    pub stderr_marker: &'static str,
+    pub request_info_id: &'static str,
+    pub request_info_started: &'static str,
+    pub request_info_ended: &'static str,
+    pub request_info_duration: &'static str,
+    pub request_info_lines: &'static str,
+    pub bulk_title: &'static str,
+    pub bulk_title_selected: &'static str,
+    pub bulk_export: &'static str,
+    pub bulk_delete: &'static str,
+    pub bulk_star: &'static str,
+    pub bulk_stats: &'static str,
+    pub bulk_merge: &'static str,
+    pub merged_title: &'static str,
+    pub columns_title: &'static str,
+    pub column_time: &'static str,
+    pub column_duration: &'static str,
+    pub column_status: &'static str,
+    pub column_method: &'static str,
+    pub column_action: &'static str,
+    pub column_sql: &'static str,
+    pub column_lines: &'static str,
+    pub column_source: &'static str,
+    pub export_prompt: &'static str,
+    pub notifications_empty: &'static str,
+    pub toast_error_request: &'static str,
+    pub toast_input_ended: &'static str,
+    pub toast_exited: &'static str,
+    pub toast_dropped_while_paused: &'static str,
+    pub toast_file_loaded: &'static str,
+    pub toast_resume_stream: &'static str,
+    pub toast_resume_failed: &'static str,
+    pub toast_file_reset: &'static str,
+    pub toast_cleared: &'static str,
+    pub toast_restored: &'static str,
+    pub toast_restore_failed: &'static str,
+    pub toast_recording_failed: &'static str,
+    pub toast_playback_finished: &'static str,
+    pub toast_copied: &'static str,
+    pub toast_copied_line: &'static str,
+    pub toast_copied_lines: &'static str,
+    pub toast_copy_failed: &'static str,
+    pub toast_exported: &'static str,
+    pub toast_export_failed: &'static str,
+    pub toast_deleted: &'static str,
+    pub toast_starred: &'static str,
+    pub toast_theme: &'static str,
+}
+
+pub const EN: Strings = Strings {
+    requests: "Requests",
+    jobs: "Jobs",
+    cable: "WebSockets",
+    unassigned: "Lines without a request id ({})",
+    source_filter: "from {}",
+    logs: "Logs",
+    waiting_for_logs: "Waiting for logs...",
+    jump_prompt: " @{}_ (HH:MM[:SS] or request id) ",
+    toast_jump_evicted: "{} is no longer held; it starts at byte {} of the file",
+    time_window_prompt: " T:{}_ (15m, 2h or HH:MM-HH:MM) ",
+    compacted: "[compacted] ",
+    compacted_restorable: "[compacted, U: restore] ",
+    search_matches: "[match {}/{}] ",
+    help: " j/k | Ctrl+d/u | Tab/Shift+Tab | Ctrl+o: back | Ctrl+c | m: copy | s: simple/summary | /: search | :: command | J: jobs | b/B: star | n: note | !: errors | Alt+1-9: presets | .: repeat | T: time | e: endpoints | t: stats | L: timeline | w: wrap | C: columns | H: time headers | P: params | I: binds | r: relative time | o: open file | v: select line | x/X: dismiss | N: notices | i: info | Ctrl+t: theme | p: pause | R: read on | U: restore",
+    help_simple: " SIMPLE MODE ('s': summary) | j/k | Tab/Shift+Tab | Ctrl+c | m: copy | /: search",
+    help_summary: " SUMMARY MODE ('s': all lines) | j/k | Tab/Shift+Tab | Ctrl+c | m: copy | /: search",
+    help_copy: " COPY MODE [{}] (Tab: switch panel | m: exit) ",
+    help_line_select: " LINE SELECT | j/k: move | Enter: full line | y: copy line | E: explain | o: open file | v/Esc: exit ",
+    help_visual: " VISUAL [{} selected] | j/k: extend | A: act on selection | V/Esc: exit ",
+    help_endpoints: " ENDPOINTS | j/k: move | Enter: show requests | e/Esc: back to requests ",
+    line_popup_title: " Line {} (y: copy | Esc: close) ",
+    explain_title: " {} (a: ANALYZE | j/k: scroll | Esc: close) ",
+    explain_title_no_analyze: " {} (j/k: scroll | Esc: close) ",
+    explain_running: "Running...",
+    explain_confirm: "EXPLAIN ANALYZE executes the query, in a transaction that is rolled back. Press y to run it.",
+    toast_explain_no_database: "Set [database] in the config file to run EXPLAIN",
+    toast_explain_no_query: "Select a SQL line first (v, or the SQL panel)",
+    toast_explain_several_statements: "Only a single statement can be explained",
+    toast_time_window_invalid: "Not a time window: {}",
+    toast_command_invalid: "Command failed: {}",
+    toast_editor_no_location: "No file:line to open in this request",
+    toast_editor_failed: "Could not open the editor: {}",
+    help_no_mouse: " | </>: resize | mouse off",
+    terminal_too_small: "Terminal too small ({}x{}), lucy needs {}x{}",
+    restart_divider: "server restarted {}",
+    toast_restart_cleared: "Server restarted: cleared {} earlier requests",
+    help_sources: " | F: source",
+    help_bulk: " | A: act on matches",
+    time_zone: "TZ: {}",
+    time_zone_local: "Local ({})",
+    time_zone_original: "Original",
+    span: "Span: {} - {} ({})",
+    offset: "Offset: {}",
+    jump: "@: jump",
+    sampling: " SAMPLING 1/{} ({} dropped) ",
+    badge_paused: " PAUSED ({} queued) ",
+    badge_follow: " FOLLOW ",
+    badge_input_ended: " INPUT ENDED ",
+    badge_exited: " EXITED {} ",
+    badge_end_of_file: " END OF FILE (R: read on) ",
+    ingest_rate: "{} lines/s",
+    ingest_requests: "{} requests",
+    ingest_orphans: "{} without id",
+    ingest_dropped: "{} lines dropped",
+    stats_title: "[{} finished] Stats (t: close) ",
+    stats_title_matching: "[{} finished, matching filter] Stats (t: close) ",
+    timeline_title: "[{} steps in {}ms] Timeline (L: all requests) ",
+    timeline_no_timestamps: "No timing: the lines of this request share one timestamp",
+    timeline_empty: "No queries, renders or HTTP calls from the top of the detail panel on",
+    timeline_all_title: "[{} requests in {}ms] Timeline of the requests on screen (L: close) ",
+    timeline_all_no_timestamps: "No timing: the requests on screen share one timestamp",
+    status_other: "other: {}",
+    duration: "Duration",
+    request_rate: "req/min (last 60m, now {})",
+    loading: " Loading {} ",
+    loaded: "{} / {}  {} requests",
+    plain_requests: "Requests {}-{} of {}",
+    plain_detail: "Detail: {} ({} lines)",
+    plain_sql: "SQL: {} queries",
+    plain_focused: " (focused)",
+    status_running: "running",
+    status_success: "ok",
+    status_info: "info",
+    status_warning: "client error",
+    status_error: "server error",
+    status_unknown: "done",
+    status_warning_level: "warning",
+    status_error_level: "error",
+    views: "Views",
+    http_calls: "External HTTP",
+    cache_operations: "Cache",
+    memory: "Memory",
+    memory_allocations: "Allocations: {}",
+    memory_process: "Process: {}",
+    memory_delta: "Delta: {}",
+    memory_instantiated: "Records: {}",
+    cache_hit_ratio: "{}% hit",
+    starred: "Starred",
+    errors: "Errors",
+    time_window_last: "Last {}",
+    sorted_by: "by {}",
+    traffic_rate: "{}/s",
+    traffic_errors: "{}% err",
+    endpoints_title: "[{}] Endpoints",
+    starred_marker: "starred",
+    pinned_marker: "pinned",
+    tutorial_title: " Welcome to lucy ({}/{}) ",
+    tutorial_footer: " Enter: next | Esc: skip ",
+    tutorial_steps: &[
+        "Lucy groups Rails log lines by request_id, so each request reads on its own.\n\nThe requests here are a built-in sample. To see your app, pipe its log in:\n  tail -f log/development.log | lucy",
+        "Requests, newest first, colored by status.\n\nj/k moves, Space jumps to the latest, / filters as you type and b stars a request.",
+        "Every line of the selected request.\n\nTab moves between panels. Here, / searches, s hides framework noise and w toggles wrapping.",
+        "SQL queries by type and table. N+1? marks tables queried over and over; click a row to jump to its lines.",
+        "m selects text for copying, t shows stats, x/X dismiss requests and V shows the version.\n\nCtrl+c quits. That's it!",
+    ],
+    picker_title: " lucy ",
+    picker_hint: "Nothing was piped in. Tail one of this app's logs?",
+    picker_footer: "↑/↓: select | Enter: tail | Esc: quit",
+    about_title: " About (?: close) ",
+    update_available: "v{} available",
+    update_check_off: "Update check off (set check_updates = true)",
+    update_none: "No newer release found",
+    gap_note: "  [+{}ms: {} queries, {}ms SQL ({}%)]",
+    gap_note_no_sql: "  [+{}ms, no SQL]",
+    notifications_title: " Notifications (N: close) ",
+    request_info_title: " Request (i: close | Y: copy id | y: copy lines) ",
+    note_title: " Note (Enter: save | Esc: cancel) ",
+    note_hint: "Kept for the next run. Leave it empty to remove the note.",
+    note_marker: "noted",
+    duplicate_marker: "duplicate",
+    stderr_marker: "stderr",
+    request_info_id: "Request ID",
+    request_info_started: "Started",
+    request_info_ended: "Last line",
+    request_info_duration: "Duration",
+    request_info_lines: "Lines",
+    bulk_title: " {} matching requests (Esc: close) ",
+    bulk_title_selected: " {} selected requests (Esc: close) ",
+    bulk_export: "Export each to a directory",
+    bulk_delete: "Delete all",
+    bulk_star: "Star all",
+    bulk_stats: "Statistics of these requests",
+    bulk_merge: "Merge into one view",
+    merged_title: "Merged {} requests",
+    columns_title: " Columns (Space: show/hide | J/K: move | Esc: close) ",
+    column_time: "Start time",
+    column_duration: "Duration",
+    column_status: "Status code",
+    column_method: "HTTP method",
+    column_action: "Controller#action",
+    column_sql: "SQL queries",
+    column_lines: "Log lines",
+    column_source: "Input, with several",
+    export_prompt: " Export as {} to: {}_ (Tab: format) ",
+    notifications_empty: "No notifications yet",
+    toast_error_request: "Error: {}",
+    toast_input_ended: "Input stream ended",
+    toast_exited: "{} exited with {}",
+    toast_dropped_while_paused: "{} lines were dropped while paused",
+    toast_file_loaded: "Loaded {}",
+    toast_resume_stream: "Piped input cannot be opened again; pipe into a new lucy",
+    toast_resume_failed: "Failed to open the input again: {}",
+    toast_file_reset: "{} was truncated or replaced, following it from the start",
+    toast_cleared: "Cleared {} finished requests",
+    toast_restored: "Restored {} lines",
+    toast_restore_failed: "Could not restore: {}",
+    toast_recording_failed: "Recording stopped: {}",
+    toast_playback_finished: "Playback finished",
+    toast_copied: "Copied {}",
+    toast_copied_line: "Copied line {}",
+    toast_copied_lines: "Copied {} lines",
+    toast_copy_failed: "Copy failed: {}",
+    toast_exported: "Exported {} requests to {}",
+    toast_export_failed: "Export failed: {}",
+    toast_deleted: "Deleted {} requests",
+    toast_starred: "Starred {} requests",
+    toast_theme: "Theme: {}",
+};
+
+pub const JA: Strings = Strings {
+    requests: "リクエスト",
+    jobs: "ジョブ",
+    cable: "WebSocket",
+    unassigned: "リクエスト ID のない行 ({})",
+    source_filter: "{} から",
+    logs: "ログ",
+    waiting_for_logs: "ログを待っています...",
+    jump_prompt: " @{}_ (HH:MM[:SS] またはリクエストID) ",
+    toast_jump_evicted: "{} はもう保持されていません (ファイルの {} バイト目から)",
+    time_window_prompt: " T:{}_ (15m, 2h または HH:MM-HH:MM) ",
+    compacted: "[圧縮済み] ",
+    compacted_restorable: "[圧縮済み, U: 復元] ",
+    search_matches: "[一致 {}/{}] ",
+    help: " j/k | Ctrl+d/u | Tab/Shift+Tab | Ctrl+o: 戻る | Ctrl+c | m: コピー | s: シンプル/要約 | /: 検索 | :: コマンド | J: ジョブ | b/B: スター | n: メモ | !: エラー | Alt+1-9: プリセット | .: 繰り返し | T: 時間 | e: エンドポイント | t: 統計 | L: タイムライン | w: 折り返し | C: 列 | H: 時刻見出し | P: パラメータ | I: バインド展開 | r: 相対時刻 | o: ファイルを開く | v: 行選択 | x/X: 削除 | N: 通知 | i: 詳細 | Ctrl+t: テーマ | p: 一時停止 | R: 続きを読む | U: 復元",
+    help_simple: " シンプルモード ('s': 要約) | j/k | Tab/Shift+Tab | Ctrl+c | m: コピー | /: 検索",
+    help_summary: " 要約モード ('s': 全行) | j/k | Tab/Shift+Tab | Ctrl+c | m: コピー | /: 検索",
+    help_copy: " コピーモード [{}] (Tab: パネル切替 | m: 終了) ",
+    help_line_select: " 行選択 | j/k: 移動 | Enter: 全文 | y: 行をコピー | E: EXPLAIN | o: ファイルを開く | v/Esc: 終了 ",
+    help_visual: " 範囲選択 [{} 件] | j/k: 広げる | A: 選択分を一括操作 | V/Esc: 終了 ",
+    help_endpoints: " エンドポイント | j/k: 移動 | Enter: リクエストを表示 | e/Esc: リクエスト一覧へ ",
+    line_popup_title: " {} 行目 (y: コピー | Esc: 閉じる) ",
+    explain_title: " {} (a: ANALYZE | j/k: スクロール | Esc: 閉じる) ",
+    explain_title_no_analyze: " {} (j/k: スクロール | Esc: 閉じる) ",
+    explain_running: "実行中...",
+    explain_confirm: "EXPLAIN ANALYZE はクエリを実行します (トランザクションはロールバックされます)。y で実行します。",
+    toast_explain_no_database: "EXPLAIN を実行するには設定ファイルに [database] を追加してください",
+    toast_explain_no_query: "先に SQL の行を選択してください (v または SQL パネル)",
+    toast_explain_several_statements: "EXPLAIN できるのは 1 つの文だけです",
+    toast_time_window_invalid: "時間範囲として読めません: {}",
+    toast_command_invalid: "コマンドを実行できません: {}",
+    toast_editor_no_location: "このリクエストには開けるファイルの位置がありません",
+    toast_editor_failed: "エディタを開けません: {}",
+    help_no_mouse: " | </>: 幅調整 | マウス無効",
+    terminal_too_small: "端末が小さすぎます ({}x{})。{}x{} 以上が必要です",
+    restart_divider: "サーバー再起動 {}",
+    toast_restart_cleared: "サーバーが再起動しました: それ以前のリクエスト {} 件を削除しました",
+    help_sources: " | F: 入力元",
+    help_bulk: " | A: 一致分を一括操作",
+    time_zone: "TZ: {}",
+    time_zone_local: "ローカル ({})",
+    time_zone_original: "ログのまま",
+    span: "期間: {} - {} ({})",
+    offset: "オフセット: {}",
+    jump: "@: ジャンプ",
+    sampling: " サンプリング 1/{} ({} 件破棄) ",
+    badge_paused: " 一時停止中 (待機 {} 行) ",
+    badge_follow: " 追跡中 ",
+    badge_input_ended: " 入力終了 ",
+    badge_exited: " 終了 {} ",
+    badge_end_of_file: " ファイル終端 (R: 続きを読む) ",
+    ingest_rate: "{} 行/秒",
+    ingest_requests: "{} リクエスト",
+    ingest_orphans: "ID なし {} 行",
+    ingest_dropped: "破棄 {} 行",
+    stats_title: "[完了 {} 件] 統計 (t: 閉じる) ",
+    stats_title_matching: "[フィルタに一致する完了 {} 件] 統計 (t: 閉じる) ",
+    timeline_title: "[{} 件 / {}ms] タイムライン (L: 全リクエスト) ",
+    timeline_no_timestamps: "タイミングなし: このリクエストの行はすべて同じ時刻です",
+    timeline_empty: "詳細パネルの先頭行以降にクエリ・描画・HTTP 呼び出しはありません",
+    timeline_all_title: "[{} 件 / {}ms] 表示中のリクエストのタイムライン (L: 閉じる) ",
+    timeline_all_no_timestamps: "タイミングなし: 表示中のリクエストはすべて同じ時刻です",
+    status_other: "その他: {}",
+    duration: "所要時間",
+    request_rate: "リクエスト/分 (直近60分, 現在 {})",
+    loading: " {} を読み込み中 ",
+    loaded: "{} / {}  {} リクエスト",
+    plain_requests: "リクエスト {}-{} / {} 件",
+    plain_detail: "詳細: {} ({} 行)",
+    plain_sql: "SQL: {} 件のクエリ",
+    plain_focused: " (フォーカス中)",
+    status_running: "実行中",
+    status_success: "成功",
+    status_info: "情報",
+    status_warning: "クライアントエラー",
+    status_error: "サーバーエラー",
+    status_unknown: "完了",
+    status_warning_level: "警告",
+    status_error_level: "エラー",
+    views: "ビュー",
+    http_calls: "外部 HTTP",
+    cache_operations: "キャッシュ",
+    memory: "メモリ",
+    memory_allocations: "アロケーション: {}",
+    memory_process: "プロセス: {}",
+    memory_delta: "増減: {}",
+    memory_instantiated: "レコード: {}",
+    cache_hit_ratio: "ヒット率 {}%",
+    starred: "スター付き",
+    errors: "エラー",
+    time_window_last: "直近 {}",
+    sorted_by: "{}順",
+    traffic_rate: "{}/秒",
+    traffic_errors: "エラー {}%",
+    endpoints_title: "[{}] エンドポイント",
+    starred_marker: "スター",
+    pinned_marker: "ピン留め",
+    tutorial_title: " lucy へようこそ ({}/{}) ",
+    tutorial_footer: " Enter: 次へ | Esc: スキップ ",
+    tutorial_steps: &[
+        "lucy は Rails のログを request_id ごとにまとめて、リクエスト単位で読めるようにします。\n\nここに表示しているのは組み込みのサンプルです。アプリのログはパイプで渡します:\n  tail -f log/development.log | lucy",
+        "リクエスト一覧です。新しい順に並び、ステータスで色分けされます。\n\nj/k で移動、Space で最新へ、/ で絞り込み、b でスターを付けます。",
+        "選択中のリクエストの全行です。\n\nTab でパネルを移動します。ここでは / で検索、s でノイズを隠し、w で折り返しを切り替えます。",
+        "SQL クエリを種類とテーブルごとに数えます。N+1? は何度もクエリされたテーブルの印です。行をクリックすると該当行へ移動します。",
+        "m でコピー用の選択、t で統計、x/X でリクエストを削除、V でバージョン情報を表示します。\n\nCtrl+c で終了します。以上です!",
+    ],
+    picker_title: " lucy ",
+    picker_hint: "入力がありません。このアプリのログを追跡しますか?",
+    picker_footer: "↑/↓: 選択 | Enter: 追跡 | Esc: 終了",
+    about_title: " バージョン情報 (?: 閉じる) ",
+    update_available: "v{} が利用可能",
+    update_check_off: "更新確認はオフです (check_updates = true で有効)",
+    update_none: "新しいリリースはありません",
+    gap_note: "  [+{}ms: クエリ {} 件, SQL {}ms ({}%)]",
+    gap_note_no_sql: "  [+{}ms, SQL なし]",
+    notifications_title: " 通知 (N: 閉じる) ",
+    request_info_title: " リクエスト情報 (i: 閉じる | Y: IDをコピー | y: 行をコピー) ",
+    note_title: " メモ (Enter: 保存 | Esc: 取消) ",
+    note_hint: "次回も残ります。空にするとメモを削除します。",
+    note_marker: "メモあり",
+    duplicate_marker: "重複",
+    stderr_marker: "標準エラー",
+    request_info_id: "リクエストID",
+    request_info_started: "開始",
+    request_info_ended: "最終行",
+    request_info_duration: "所要時間",
+    request_info_lines: "行数",
+    bulk_title: " 一致するリクエスト {} 件 (Esc: 閉じる) ",
+    bulk_title_selected: " 選択したリクエスト {} 件 (Esc: 閉じる) ",
+    bulk_export: "1 件ずつディレクトリに書き出す",
+    bulk_delete: "すべて削除",
+    bulk_star: "すべてにスター",
+    bulk_stats: "これらの統計",
+    bulk_merge: "1 つの表示にまとめる",
+    merged_title: "{} 件のリクエストをまとめたもの",
+    columns_title: " 列 (Space: 表示切替 | J/K: 移動 | Esc: 閉じる) ",
+    column_time: "開始時刻",
+    column_duration: "所要時間",
+    column_status: "ステータスコード",
+    column_method: "HTTP メソッド",
+    column_action: "コントローラ#アクション",
+    column_sql: "SQL クエリ数",
+    column_lines: "ログ行数",
+    column_source: "入力元 (複数の時)",
+    export_prompt: " {} 形式で書き出し: {}_ (Tab: 形式) ",
+    notifications_empty: "通知はまだありません",
+    toast_error_request: "エラー: {}",
+    toast_input_ended: "入力が終了しました",
+    toast_exited: "{} が終了しました ({})",
+    toast_dropped_while_paused: "一時停止中に {} 行を破棄しました",
+    toast_file_loaded: "{} を読み込みました",
+    toast_resume_stream: "パイプ入力は開き直せません。新しい lucy にパイプしてください",
+    toast_resume_failed: "入力を開き直せませんでした: {}",
+    toast_file_reset: "{} が切り詰め・置き換えられたため、先頭から追跡します",
+    toast_cleared: "完了済みリクエストを {} 件削除しました",
+    toast_restored: "{} 行を復元しました",
+    toast_restore_failed: "復元できませんでした: {}",
+    toast_recording_failed: "記録を停止しました: {}",
+    toast_playback_finished: "再生が終了しました",
+    toast_copied: "{} をコピーしました",
+    toast_copied_line: "{} 行目をコピーしました",
+    toast_copied_lines: "{} 行をコピーしました",
+    toast_copy_failed: "コピーに失敗しました: {}",
+    toast_exported: "{} 件のリクエストを {} に書き出しました",
+    toast_export_failed: "書き出しに失敗しました: {}",
+    toast_deleted: "{} 件のリクエストを削除しました",
+    toast_starred: "{} 件のリクエストにスターを付けました",
+    toast_theme: "テーマ: {}",
+};
+
+/// Replaces each `{}` in `template` with the next value.
+pub fn fill(template: &str, values: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut values = values.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        result.push_str(first);
+    }
+    for part in parts {
+        if let Some(value) = values.next() {
+            result.push_str(&value.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(EN.span, &[&"12:00", &"13:00", &"1h 0m"]),
+            "Span: 12:00 - 13:00 (1h 0m)"
+        );
+        assert_eq!(
+            fill(JA.sampling, &[&10, &3]),
+            " サンプリング 1/10 (3 件破棄) "
+        );
+        assert_eq!(fill(EN.jobs, &[]), "Jobs");
+    }
+
+    #[test]
+    fn test_placeholders_match_between_locales() {
+        let templates = |s: &Strings| {
+            [
+                s.jump_prompt,
+                s.toast_jump_evicted,
+                s.time_window_prompt,
+                s.time_window_last,
+                s.sorted_by,
+                s.traffic_rate,
+                s.traffic_errors,
+                s.endpoints_title,
+                s.toast_time_window_invalid,
+                s.toast_command_invalid,
+                s.toast_editor_failed,
+                s.terminal_too_small,
+                s.restart_divider,
+                s.toast_restart_cleared,
+                s.search_matches,
+                s.memory_allocations,
+                s.memory_process,
+                s.memory_delta,
+                s.memory_instantiated,
+                s.unassigned,
+                s.help_copy,
+                s.source_filter,
+                s.time_zone,
+                s.time_zone_local,
+                s.span,
+                s.offset,
+                s.sampling,
+                s.badge_paused,
+                s.ingest_rate,
+                s.ingest_requests,
+                s.ingest_orphans,
+                s.ingest_dropped,
+                s.stats_title,
+                s.stats_title_matching,
+                s.timeline_title,
+                s.timeline_all_title,
+                s.bulk_title,
+                s.bulk_title_selected,
+                s.merged_title,
+                s.help_visual,
+                s.export_prompt,
+                s.status_other,
+                s.request_rate,
+                s.loading,
+                s.loaded,
+                s.plain_requests,
+                s.plain_detail,
+                s.plain_sql,
+                s.cache_hit_ratio,
+                s.update_available,
+                s.tutorial_title,
+                s.gap_note,
+                s.gap_note_no_sql,
+                s.toast_error_request,
+                s.toast_file_loaded,
+                s.toast_resume_failed,
+                s.toast_file_reset,
+                s.toast_cleared,
+                s.toast_restored,
+                s.toast_restore_failed,
+                s.toast_recording_failed,
+                s.toast_copied,
+                s.toast_copied_line,
+                s.toast_copied_lines,
+                s.badge_exited,
+                s.toast_exited,
+                s.toast_dropped_while_paused,
+                s.line_popup_title,
+                s.explain_title,
+                s.explain_title_no_analyze,
+                s.toast_copy_failed,
+                s.toast_exported,
+                s.toast_export_failed,
+                s.toast_deleted,
+                s.toast_starred,
+                s.toast_theme,
+            ]
+            .map(|template| template.matches("{}").count())
+        };
+        assert_eq!(templates(&EN), templates(&JA));
+    }
+}
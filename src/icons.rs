@@ -0,0 +1,142 @@
+use crate::app_state::StatusType;
+use serde::Deserialize;
+
+/// Glyphs for methods, statuses and jobs, set with `icons` in the config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSet {
+    #[default]
+    Unicode,
+    /// Nerd Font glyphs, for terminals with a patched font.
+    Nerd,
+    /// Plain ASCII for terminals without Unicode fonts.
+    Ascii,
+}
+
+impl IconSet {
+    pub fn job(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "⚙",
+            IconSet::Nerd => "\u{f085}",
+            IconSet::Ascii => "J",
+        }
+    }
+
+    /// An ActionCable connection.
+    pub fn cable(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "⇄",
+            IconSet::Nerd => "\u{f1e6}",
+            IconSet::Ascii => "~",
+        }
+    }
+
+    pub fn star(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "★",
+            IconSet::Nerd => "\u{f005}",
+            IconSet::Ascii => "*",
+        }
+    }
+
+    /// A request with a note attached.
+    pub fn note(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "✎",
+            IconSet::Nerd => "\u{f040}",
+            IconSet::Ascii => "#",
+        }
+    }
+
+    /// A request likely sent twice, such as a double-clicked submit button.
+    pub fn duplicate(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "⧉",
+            IconSet::Nerd => "\u{f0c5}",
+            IconSet::Ascii => "2",
+        }
+    }
+
+    /// The gutter of a line read from a command's stderr.
+    pub fn stderr(self) -> &'static str {
+        match self {
+            IconSet::Unicode | IconSet::Nerd => "┃",
+            IconSet::Ascii => "|",
+        }
+    }
+
+    /// A failed request pinned at the top of the list.
+    pub fn pin(self) -> &'static str {
+        match self {
+            IconSet::Unicode => "⚑",
+            IconSet::Nerd => "\u{f08d}",
+            IconSet::Ascii => "^",
+        }
+    }
+
+    /// Shape that tells the status apart without color.
+    pub fn status(self, status_type: StatusType) -> Option<&'static str> {
+        let icon = match (self, status_type) {
+            (_, StatusType::Unknown) => return None,
+            (IconSet::Unicode, StatusType::Success) => "✓",
+            (IconSet::Unicode | IconSet::Ascii, StatusType::Info) => "i",
+            (IconSet::Unicode, StatusType::Warning) => "!",
+            (IconSet::Unicode, StatusType::Error) => "✗",
+            (IconSet::Nerd, StatusType::Success) => "\u{f00c}",
+            (IconSet::Nerd, StatusType::Info) => "\u{f05a}",
+            (IconSet::Nerd, StatusType::Warning) => "\u{f071}",
+            (IconSet::Nerd, StatusType::Error) => "\u{f057}",
+            (IconSet::Ascii, StatusType::Success) => "+",
+            (IconSet::Ascii, StatusType::Warning) => "!",
+            (IconSet::Ascii, StatusType::Error) => "x",
+        };
+        Some(icon)
+    }
+
+    /// Only Nerd Font icons are distinct enough to show next to every request;
+    /// the others appear when the palette asks for status symbols.
+    pub fn always_show_status(self) -> bool {
+        self == IconSet::Nerd
+    }
+
+    /// Icon for an HTTP method. The method name is already in the title, so
+    /// this is only drawn with Nerd Font glyphs.
+    pub fn method(self, method: &str) -> Option<&'static str> {
+        if self != IconSet::Nerd {
+            return None;
+        }
+        let icon = match method {
+            "GET" | "HEAD" => "\u{f063}",
+            "POST" => "\u{f067}",
+            "PUT" | "PATCH" => "\u{f040}",
+            "DELETE" => "\u{f1f8}",
+            _ => return None,
+        };
+        Some(icon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_icons_are_ascii() {
+        let status_icons = [StatusType::Success, StatusType::Info, StatusType::Warning, StatusType::Error]
+            .map(|status_type| IconSet::Ascii.status(status_type).unwrap());
+        assert!(status_icons.iter().all(|icon| icon.is_ascii()));
+        assert!(IconSet::Ascii.job().is_ascii());
+        assert!(IconSet::Ascii.cable().is_ascii());
+        assert!(IconSet::Ascii.star().is_ascii());
+        assert!(IconSet::Ascii.pin().is_ascii());
+        assert_eq!(IconSet::Ascii.method("GET"), None);
+    }
+
+    #[test]
+    fn test_status_icons() {
+        assert_eq!(IconSet::Unicode.status(StatusType::Success), Some("✓"));
+        assert_eq!(IconSet::Nerd.status(StatusType::Unknown), None);
+        assert!(IconSet::Nerd.method("DELETE").is_some());
+        assert_eq!(IconSet::Nerd.method("CONNECT"), None);
+    }
+}
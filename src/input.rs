@@ -1,37 +1,446 @@
-use std::io::{self, BufRead, BufReader, Stdin};
-use std::sync::mpsc::{self, Receiver, Sender};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use crate::supervisor::Supervisor;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::net::TcpListener;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Receiver, Sender};
 
+// Bounded so that bytes read stays close to bytes actually parsed by the app
+const FILE_CHANNEL_CAPACITY: usize = 4096;
+// Roomy enough that a burst on a live stream does not stall the writer
+const STREAM_CHANNEL_CAPACITY: usize = 64 * 1024;
+// How often `--follow` looks for appended lines, truncation and replacement
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reads lines from one or more inputs into a single channel.
+#[derive(Default)]
 pub struct Reader {
-    _reader_thread: JoinHandle<()>,
+    _reader_threads: Vec<JoinHandle<()>>,
+    sources: Vec<Source>,
+    /// Unix sockets created by `--listen`, removed on drop
+    socket_paths: Vec<PathBuf>,
+    /// The command run after `--`, stopped once nothing holds it
+    supervisor: Option<Arc<Supervisor>>,
+}
+
+/// A line and the input it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLine {
+    /// Index into `Reader::sources`
+    pub source: usize,
+    pub text: String,
+}
+
+/// One input of a `Reader`.
+#[derive(Clone)]
+pub struct Source {
+    /// Short name for the source column, such as the file name
+    pub name: String,
+    /// Set for files
+    pub progress: Option<Arc<LoadProgress>>,
+    /// Set for files, to read them again once the input ended
+    pub path: Option<PathBuf>,
+    /// A command's stderr, whose lines are marked apart
+    pub stderr: bool,
+}
+
+/// Where `--listen` accepts log streams from other processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl ListenAddr {
+    /// Parses `unix:/tmp/lucy.sock` or `tcp:127.0.0.1:9999`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once(':') {
+            Some(("unix", path)) if !path.is_empty() => Ok(Self::Unix(PathBuf::from(path))),
+            Some(("tcp", addr)) if !addr.is_empty() => Ok(Self::Tcp(addr.to_string())),
+            _ => bail!(
+                "--listen expects unix:PATH or tcp:HOST:PORT, got '{}'",
+                spec
+            ),
+        }
+    }
+
+    /// The socket's file name or the TCP address.
+    fn name(&self) -> String {
+        match self {
+            Self::Unix(path) => file_name(path),
+            Self::Tcp(addr) => addr.clone(),
+        }
+    }
+}
+
+/// Progress of loading a file, shared between the reader thread and the UI.
+pub struct LoadProgress {
+    pub file_name: String,
+    pub total_bytes: u64,
+    bytes_read: AtomicU64,
+    finished: AtomicBool,
+    resets: AtomicU64,
+}
+
+impl LoadProgress {
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Times a followed file was truncated or replaced and read from its start again.
+    pub fn resets(&self) -> u64 {
+        self.resets.load(Ordering::Relaxed)
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        (self.bytes_read() as f64 / self.total_bytes as f64).clamp(0.0, 1.0)
+    }
 }
 
 impl Reader {
-    pub fn new() -> (Self, Receiver<String>) {
-        let (tx, rx) = mpsc::channel::<String>();
+    pub fn new() -> (Self, Receiver<InputLine>) {
+        Self::spawn_single("stdin", |send| {
+            let reader = BufReader::with_capacity(32 * 1024, io::stdin());
+            process_input(reader, send, None);
+        })
+    }
 
+    /// Feeds built-in lines instead of stdin.
+    pub fn sample(text: &'static str) -> (Self, Receiver<InputLine>) {
+        Self::spawn_single("sample", move |send| {
+            process_input(text.as_bytes(), send, None)
+        })
+    }
+
+    /// Feeds synthesized requests from `lucy demo`.
+    pub fn demo(rate: f64) -> (Self, Receiver<InputLine>) {
+        Self::spawn_single("demo", move |send| {
+            crate::demo::run(rate, |line| send(line).is_ok());
+        })
+    }
+
+    /// Feeds a saved log at the pace of its timestamps (`--replay`).
+    pub fn replay(path: &Path, speed: f64) -> Result<(Self, Receiver<InputLine>)> {
+        let file = File::open(path)
+            .wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+        Ok(Self::spawn_single(&file_name(path), move |send| {
+            crate::replay::run(BufReader::new(file), speed, |line| send(line).is_ok());
+        }))
+    }
+
+    fn spawn_single<F>(name: &str, read: F) -> (Self, Receiver<InputLine>)
+    where
+        F: FnOnce(&dyn Fn(String) -> SendResult) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let reader_thread = thread::spawn(move || read(&sender(&tx, 0)));
+        let reader = Self {
+            _reader_threads: vec![reader_thread],
+            sources: vec![Source {
+                name: name.to_string(),
+                progress: None,
+                path: None,
+                stderr: false,
+            }],
+            socket_paths: Vec::new(),
+            supervisor: None,
+        };
+        (reader, rx)
+    }
+
+    /// Runs `command` (`lucy -- bin/rails server`) and reads its stdout and
+    /// stderr as two sources, so either can be told apart in the list.
+    pub fn command(command: &[String]) -> Result<(Self, Receiver<InputLine>)> {
+        let (supervisor, stdout, stderr) = Supervisor::spawn(command)?;
+        let (tx, rx) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let mut reader = Self::default();
+        reader.supervisor = Some(Arc::new(supervisor));
+        let pipes: [(&str, Box<dyn io::Read + Send>, bool); 2] = [
+            ("stdout", Box::new(stdout), false),
+            ("stderr", Box::new(stderr), true),
+        ];
+        for (name, pipe, stderr) in pipes {
+            let send = sender(&tx, reader.sources.len());
+            reader
+                ._reader_threads
+                .push(thread::spawn(move || process_input(BufReader::new(pipe), send, None)));
+            reader.sources.push(Source {
+                name: name.to_string(),
+                progress: None,
+                path: None,
+                stderr,
+            });
+        }
+        Ok((reader, rx))
+    }
+
+    /// The command run after `--`, if any.
+    pub fn supervisor(&self) -> Option<Arc<Supervisor>> {
+        self.supervisor.clone()
+    }
+
+    /// Reads every file and socket at once, tagging each line with the
+    /// index of its source. With `follow`, files keep being read as they grow.
+    pub fn open(
+        files: &[PathBuf],
+        follow: bool,
+        listen: &[ListenAddr],
+    ) -> Result<(Self, Receiver<InputLine>)> {
+        let capacity = if listen.is_empty() {
+            FILE_CHANNEL_CAPACITY
+        } else {
+            STREAM_CHANNEL_CAPACITY
+        };
+        let (tx, rx) = mpsc::channel(capacity);
+        let mut reader = Self::default();
+        for path in files {
+            reader.add_file(path, 0, follow, &tx)?;
+        }
+        for addr in listen {
+            reader.add_listener(addr, &tx)?;
+        }
+        Ok((reader, rx))
+    }
+
+    /// Reads files again from the given byte offsets, for the lines appended
+    /// since they were read to the end. A file now shorter than its offset was
+    /// truncated and is read from its start, counted as a reset.
+    pub fn resume(files: &[(PathBuf, u64)]) -> Result<(Self, Receiver<InputLine>)> {
+        let (tx, rx) = mpsc::channel(FILE_CHANNEL_CAPACITY);
+        let mut reader = Self::default();
+        for (path, offset) in files {
+            reader.add_file(path, *offset, false, &tx)?;
+        }
+        Ok((reader, rx))
+    }
+
+    fn add_file(
+        &mut self,
+        path: &Path,
+        offset: u64,
+        follow: bool,
+        tx: &Sender<InputLine>,
+    ) -> Result<()> {
+        let mut file =
+            File::open(path).wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+        let total_bytes = file.metadata()?.len();
+        let truncated = offset > total_bytes;
+        let start = if truncated { 0 } else { offset };
+        file.seek(SeekFrom::Start(start))?;
+
+        let progress = Arc::new(LoadProgress {
+            file_name: file_name(path),
+            total_bytes,
+            bytes_read: AtomicU64::new(start),
+            finished: AtomicBool::new(false),
+            resets: AtomicU64::new(u64::from(truncated)),
+        });
+
+        let send = sender(tx, self.sources.len());
+        let thread_progress = Arc::clone(&progress);
+        let thread_path = path.to_path_buf();
         let reader_thread = thread::spawn(move || {
-            let stdin = io::stdin();
-            process_input(stdin, tx);
+            let reader = BufReader::with_capacity(256 * 1024, file);
+            if follow {
+                follow_file(&thread_path, reader, &thread_progress, send);
+            } else {
+                process_input(reader, send, Some(&thread_progress));
+                thread_progress.finished.store(true, Ordering::Relaxed);
+            }
+        });
+
+        self._reader_threads.push(reader_thread);
+        self.sources.push(Source {
+            name: progress.file_name.clone(),
+            progress: Some(progress),
+            path: Some(path.to_path_buf()),
+            stderr: false,
+        });
+        Ok(())
+    }
+
+    /// Accepts any number of connections and reads lines from all of them,
+    /// so several processes can stream into one lucy.
+    fn add_listener(&mut self, addr: &ListenAddr, tx: &Sender<InputLine>) -> Result<()> {
+        let source = self.sources.len();
+        let tx = tx.clone();
+        let reader_thread = match addr {
+            ListenAddr::Unix(path) => {
+                if UnixStream::connect(path).is_ok() {
+                    bail!("{} is already in use", path.display());
+                }
+                // Left behind by a previous run, and would make bind fail
+                if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)
+                    .wrap_err_with(|| format!("Failed to listen on {}", path.display()))?;
+                self.socket_paths.push(path.clone());
+                thread::spawn(move || accept_loop(listener.incoming(), tx, source))
+            }
+            ListenAddr::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)
+                    .wrap_err_with(|| format!("Failed to listen on {}", addr))?;
+                thread::spawn(move || accept_loop(listener.incoming(), tx, source))
+            }
+        };
+
+        self._reader_threads.push(reader_thread);
+        self.sources.push(Source {
+            name: addr.name(),
+            progress: None,
+            path: None,
+            stderr: false,
         });
+        Ok(())
+    }
+
+    pub fn sources(&self) -> Vec<Source> {
+        self.sources.clone()
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        for path in &self.socket_paths {
+            if let Err(e) = fs::remove_file(path) {
+                tracing::debug!("Failed to remove socket {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+type SendResult = Result<(), mpsc::error::SendError<InputLine>>;
+
+/// Sends lines to the app as coming from `source`.
+fn sender(tx: &Sender<InputLine>, source: usize) -> impl Fn(String) -> SendResult + use<> {
+    let tx = tx.clone();
+    move |text| tx.blocking_send(InputLine { source, text })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
 
-        (Self { _reader_thread: reader_thread }, rx)
+/// Reads each accepted connection on its own thread; lines from different
+/// connections interleave whole.
+fn accept_loop<S>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    tx: Sender<InputLine>,
+    source: usize,
+) where
+    S: io::Read + Send + 'static,
+{
+    for stream in incoming {
+        match stream {
+            Ok(stream) => {
+                let send = sender(&tx, source);
+                thread::spawn(move || process_input(BufReader::new(stream), send, None));
+            }
+            Err(e) => tracing::debug!("Failed to accept connection: {}", e),
+        }
     }
 }
 
-fn process_input(input: Stdin, tx: Sender<String>) {
-    let mut reader = BufReader::with_capacity(32 * 1024, input);
+/// Reads `path` like `tail -F`: lines as they are appended, and the whole
+/// file again once it was truncated (`rails log:clear`) or replaced.
+fn follow_file(
+    path: &Path,
+    mut reader: BufReader<File>,
+    progress: &LoadProgress,
+    send: impl Fn(String) -> SendResult,
+) {
+    let mut inode = reader
+        .get_ref()
+        .metadata()
+        .map(|meta| meta.ino())
+        .unwrap_or(0);
+    let mut position = 0;
+    let mut line = String::with_capacity(1024);
+
+    loop {
+        match reader.read_line(&mut line) {
+            Ok(n) => position += n as u64,
+            Err(e) => {
+                tracing::debug!("Input reader error: {}", e);
+                return;
+            }
+        }
+        if line.ends_with('\n') {
+            progress
+                .bytes_read
+                .fetch_add(line.len() as u64, Ordering::Relaxed);
+            if send(std::mem::take(&mut line)).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        // At the end, possibly in the middle of a line still being written
+        progress.finished.store(true, Ordering::Relaxed);
+        thread::sleep(FOLLOW_INTERVAL);
+        // Missing for a moment while it is being replaced
+        let Ok(meta) = fs::metadata(path) else {
+            continue;
+        };
+        if meta.ino() != inode {
+            let Ok(file) = File::open(path) else {
+                continue;
+            };
+            reader = BufReader::with_capacity(256 * 1024, file);
+            inode = meta.ino();
+        } else if meta.len() < position {
+            if let Err(e) = reader.seek(SeekFrom::Start(0)) {
+                tracing::debug!("Failed to rewind {}: {}", path.display(), e);
+                return;
+            }
+        } else {
+            continue;
+        }
+        position = 0;
+        line.clear();
+        progress.resets.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn process_input<R, F>(mut reader: R, send: F, progress: Option<&LoadProgress>)
+where
+    R: BufRead,
+    F: Fn(String) -> SendResult,
+{
     let mut buffer = String::with_capacity(1024);
 
     loop {
         buffer.clear();
         match reader.read_line(&mut buffer) {
             Ok(0) => break,
-            Ok(_) => {
-                if let Err(e) = tx.send(buffer.clone()) {
+            Ok(n) => {
+                if let Err(e) = send(buffer.clone()) {
                     tracing::debug!("Failed to send message to channel: {}", e);
                     break;
                 }
+                if let Some(progress) = progress {
+                    progress.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+                }
             }
             Err(e) => {
                 tracing::debug!("Input reader error: {}", e);
@@ -42,3 +451,122 @@ fn process_input(input: Stdin, tx: Sender<String>) {
 
     tracing::debug!("Input reader thread terminated");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_listen_addr() {
+        assert_eq!(
+            ListenAddr::parse("unix:/tmp/lucy.sock").unwrap(),
+            ListenAddr::Unix(PathBuf::from("/tmp/lucy.sock"))
+        );
+        assert_eq!(
+            ListenAddr::parse("tcp:127.0.0.1:9999").unwrap(),
+            ListenAddr::Tcp("127.0.0.1:9999".to_string())
+        );
+        assert!(ListenAddr::parse("unix:").is_err());
+        assert!(ListenAddr::parse("/tmp/lucy.sock").is_err());
+        assert!(ListenAddr::parse("udp:127.0.0.1:9999").is_err());
+    }
+
+    #[test]
+    fn test_follow_truncated_file() {
+        let path = std::env::temp_dir().join(format!("lucy-follow-{}.log", std::process::id()));
+        fs::write(&path, "[a] first\n").unwrap();
+        let (reader, mut rx) = Reader::open(std::slice::from_ref(&path), true, &[]).unwrap();
+        assert_eq!(rx.blocking_recv().unwrap().text, "[a] first\n");
+
+        // Lines are sent once complete
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"[a] sec").unwrap();
+        thread::sleep(FOLLOW_INTERVAL * 2);
+        file.write_all(b"ond\n").unwrap();
+        assert_eq!(rx.blocking_recv().unwrap().text, "[a] second\n");
+
+        fs::write(&path, "[b] new\n").unwrap();
+        assert_eq!(rx.blocking_recv().unwrap().text, "[b] new\n");
+        let progress = reader.sources()[0].progress.clone().unwrap();
+        assert_eq!(progress.resets(), 1);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_file() {
+        let path = std::env::temp_dir().join(format!("lucy-resume-{}.log", std::process::id()));
+        fs::write(&path, "[a] first\n[a] second\n").unwrap();
+        let offset = "[a] first\n".len() as u64;
+
+        let (reader, mut rx) = Reader::resume(&[(path.clone(), offset)]).unwrap();
+        assert_eq!(rx.blocking_recv().unwrap().text, "[a] second\n");
+        assert!(rx.blocking_recv().is_none());
+        assert_eq!(reader.sources()[0].path.as_deref(), Some(path.as_path()));
+
+        // Shorter than the offset: truncated, so read from the start
+        fs::write(&path, "[b]\n").unwrap();
+        let (reader, mut rx) = Reader::resume(&[(path.clone(), offset)]).unwrap();
+        assert_eq!(rx.blocking_recv().unwrap().text, "[b]\n");
+        assert_eq!(reader.sources()[0].progress.as_ref().unwrap().resets(), 1);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_lines_are_tagged_with_their_source() {
+        let dir = std::env::temp_dir();
+        let paths = ["web", "jobs"]
+            .map(|name| dir.join(format!("lucy-{}-{}.log", name, std::process::id())));
+        fs::write(&paths[0], "[a] web 1\n[a] web 2\n").unwrap();
+        fs::write(&paths[1], "[b] job 1\n").unwrap();
+
+        let (reader, mut rx) = Reader::open(&paths, false, &[]).unwrap();
+        let names: Vec<String> = reader.sources().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, [file_name(&paths[0]), file_name(&paths[1])]);
+
+        let mut lines = Vec::new();
+        while let Some(line) = rx.blocking_recv() {
+            lines.push((line.source, line.text));
+        }
+        lines.sort();
+        assert_eq!(
+            lines,
+            [
+                (0, "[a] web 1\n".to_string()),
+                (0, "[a] web 2\n".to_string()),
+                (1, "[b] job 1\n".to_string()),
+            ]
+        );
+
+        for path in paths {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_listen_on_unix_socket() {
+        let path = std::env::temp_dir().join(format!("lucy-listen-{}.sock", std::process::id()));
+        let addr = ListenAddr::Unix(path.clone());
+        let (reader, mut rx) = Reader::open(&[], false, std::slice::from_ref(&addr)).unwrap();
+        assert!(Reader::open(&[], false, &[addr]).is_err());
+
+        for line in ["[web] Started GET \"/\"\n", "[job] Performing HardJob\n"] {
+            let mut client = UnixStream::connect(&path).unwrap();
+            client.write_all(line.as_bytes()).unwrap();
+        }
+        let mut lines = vec![
+            rx.blocking_recv().unwrap().text,
+            rx.blocking_recv().unwrap().text,
+        ];
+        lines.sort();
+        assert_eq!(
+            lines,
+            ["[job] Performing HardJob\n", "[web] Started GET \"/\"\n"]
+        );
+
+        drop(reader);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,220 @@
+//! Request and response bodies that middleware logs as JSON, such as
+//! `Request body: {"post":{"title":"hi"}}` or `response_body=[...]`.
+//!
+//! Like `Parameters:` lines, a body shows on one highlighted row, and
+//! pretty-printed below it when expanded (`P`). Values under keys that look
+//! like secrets are masked.
+
+use crate::params_formatter::{INDENT, MASK, is_secret};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use regex::Regex;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+// A label naming a body, then `:` or `=` and the JSON up to the end of the line
+static RE_BODY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?P<label>\b[\w -]*?(?:body|payload|json))\s*[:=]\s*(?P<json>[\[{].*)$")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    /// `Request body`, `response_payload`, as logged
+    pub label: String,
+    value: Value,
+}
+
+/// The JSON object or array a line logs as a body, with secrets masked.
+/// None for other lines, and for bodies cut short or not valid JSON.
+pub fn parse(message: &str) -> Option<Body> {
+    let caps = RE_BODY.captures(message.trim_end())?;
+    let mut value: Value = serde_json::from_str(&caps["json"]).ok()?;
+    mask_secrets(&mut value);
+    let label = caps["label"].trim().to_string();
+    Some(Body { label, value })
+}
+
+/// The line with its body highlighted on one row, marked as expandable.
+pub fn summary_line(body: &Body, expanded: bool) -> Line<'static> {
+    let marker = if expanded { "▾ " } else { "▸ " };
+    let mut spans = vec![
+        Span::styled(marker, punctuation()),
+        Span::raw(format!("{}: ", body.label)),
+    ];
+    inline(&body.value, &mut spans);
+    Line::from(spans)
+}
+
+/// The body pretty-printed, one row per value, indented under the line.
+pub fn pretty_lines(body: &Body) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    pretty(&body.value, 1, Vec::new(), "", &mut lines);
+    lines
+}
+
+fn pretty(
+    value: &Value,
+    depth: usize,
+    label: Vec<Span<'static>>,
+    comma: &'static str,
+    lines: &mut Vec<Line<'static>>,
+) {
+    let mut spans = vec![Span::raw(INDENT.repeat(depth))];
+    spans.extend(label);
+    let closing = |bracket: &'static str| {
+        Line::from(vec![
+            Span::raw(INDENT.repeat(depth)),
+            Span::styled(format!("{}{}", bracket, comma), punctuation()),
+        ])
+    };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            spans.push(Span::styled("{", punctuation()));
+            lines.push(Line::from(spans));
+            for (i, (key, value)) in map.iter().enumerate() {
+                let comma = if i + 1 < map.len() { "," } else { "" };
+                pretty(value, depth + 1, key_spans(key), comma, lines);
+            }
+            lines.push(closing("}"));
+        }
+        Value::Array(items) if !items.is_empty() => {
+            spans.push(Span::styled("[", punctuation()));
+            lines.push(Line::from(spans));
+            for (i, item) in items.iter().enumerate() {
+                let comma = if i + 1 < items.len() { "," } else { "" };
+                pretty(item, depth + 1, Vec::new(), comma, lines);
+            }
+            lines.push(closing("]"));
+        }
+        value => {
+            inline(value, &mut spans);
+            spans.push(Span::styled(comma, punctuation()));
+            lines.push(Line::from(spans));
+        }
+    }
+}
+
+fn key_spans(key: &str) -> Vec<Span<'static>> {
+    vec![
+        Span::styled(format!("\"{}\"", key), Style::default().fg(Color::Cyan)),
+        Span::styled(": ", punctuation()),
+    ]
+}
+
+/// `value` as compact JSON.
+fn inline(value: &Value, spans: &mut Vec<Span<'static>>) {
+    match value {
+        Value::String(text) if text == MASK => {
+            spans.push(Span::styled(MASK, Style::default().fg(Color::DarkGray)));
+        }
+        Value::String(_) => spans.push(Span::styled(
+            value.to_string(),
+            Style::default().fg(Color::Green),
+        )),
+        Value::Number(_) | Value::Bool(_) | Value::Null => spans.push(Span::styled(
+            value.to_string(),
+            Style::default().fg(Color::Magenta),
+        )),
+        Value::Array(items) => {
+            spans.push(Span::styled("[", punctuation()));
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(",", punctuation()));
+                }
+                inline(item, spans);
+            }
+            spans.push(Span::styled("]", punctuation()));
+        }
+        Value::Object(map) => {
+            spans.push(Span::styled("{", punctuation()));
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(",", punctuation()));
+                }
+                spans.push(Span::styled(
+                    format!("\"{}\"", key),
+                    Style::default().fg(Color::Cyan),
+                ));
+                spans.push(Span::styled(":", punctuation()));
+                inline(value, spans);
+            }
+            spans.push(Span::styled("}", punctuation()));
+        }
+    }
+}
+
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret(key) {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    mask_secrets(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+fn punctuation() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let body = parse(r#"[r1] Request body: {"post":{"title":"hi","password":"x"}}"#).unwrap();
+        assert_eq!(body.label, "Request body");
+        assert_eq!(
+            text(&summary_line(&body, false)),
+            r#"▸ Request body: {"post":{"title":"hi","password":[FILTERED]}}"#
+        );
+
+        let body = parse("response_payload=[1, 2] ").unwrap();
+        assert_eq!(body.label, "response_payload");
+
+        // Cut short, not JSON, or not labelled as a body
+        assert_eq!(parse(r#"Response body: {"items":[1,2"#), None);
+        assert_eq!(parse(r#"Response body: {"a"=>1}"#), None);
+        assert_eq!(
+            parse(r#"User Load (0.2ms)  SELECT "users".* WHERE "id" = $1  [["id", 1]]"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pretty_lines() {
+        let body = parse(r#"Response body: {"id":1,"tags":["a"],"meta":{},"ok":true}"#).unwrap();
+        let lines: Vec<String> = pretty_lines(&body)
+            .iter()
+            .map(|line| text(line).replace(INDENT, "  "))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "  {",
+                r#"    "id": 1,"#,
+                r#"    "tags": ["#,
+                r#"      "a""#,
+                "    ],",
+                r#"    "meta": {},"#,
+                r#"    "ok": true"#,
+                "  }",
+            ]
+        );
+    }
+}
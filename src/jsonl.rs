@@ -0,0 +1,102 @@
+//! `--output jsonl`: lucy without its screen, writing one JSON object per
+//! finished request to stdout, so CI jobs and scripts can use the same
+//! grouping and SQL counting as the viewer.
+
+use crate::app_state::{GroupKind, LogGroup};
+use crate::export::status_label;
+use crate::sql_info::QueryType;
+use serde_json::{Value, json};
+use std::io::{self, Write};
+
+pub struct JsonLines {
+    out: Box<dyn Write>,
+}
+
+impl JsonLines {
+    pub fn new(out: Box<dyn Write>) -> Self {
+        Self { out }
+    }
+
+    /// Writes `group` as one line.
+    pub fn write(&mut self, request_id: &str, group: &LogGroup) -> io::Result<()> {
+        // Serializing a `Value` cannot fail
+        let line = serde_json::to_string(&record(request_id, group)).unwrap_or_default();
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()
+    }
+}
+
+/// The object written for a finished request.
+pub fn record(request_id: &str, group: &LogGroup) -> Value {
+    let sql = &group.sql_query_info;
+    let tables: serde_json::Map<String, Value> = sql
+        .sorted_tables()
+        .into_iter()
+        .map(|(table, count)| (table.clone(), json!(count)))
+        .collect();
+    let n_plus_one: Vec<&str> = sql
+        .sorted_tables()
+        .into_iter()
+        .map(|(table, _)| table.as_str())
+        .filter(|table| sql.is_n_plus_one(table))
+        .collect();
+    let kind = match group.kind {
+        GroupKind::Request => "request",
+        GroupKind::Job => "job",
+        GroupKind::Cable => "cable",
+        GroupKind::Generic | GroupKind::Unassigned => "generic",
+    };
+    json!({
+        "request_id": request_id,
+        "kind": kind,
+        "title": group.title.trim_end(),
+        "status": status_label(group.status_type),
+        "status_code": group.status_code,
+        "started_at": group.first_timestamp.to_rfc3339(),
+        "duration_ms": group.duration_ms,
+        "lines": group.entries.len(),
+        "sql": {
+            "queries": sql.total_queries(),
+            "select": sql.query_count(QueryType::Select),
+            "insert": sql.query_count(QueryType::Insert),
+            "update": sql.query_count(QueryType::Update),
+            "delete": sql.query_count(QueryType::Delete),
+            "cache_hits": sql.cache_hits,
+            "tables": tables,
+            "n_plus_one": n_plus_one,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_parser;
+
+    #[test]
+    fn test_record() {
+        let lines = [
+            "2024-05-01T03:00:00Z [req-1] Started GET \"/posts\" for 127.0.0.1",
+            "2024-05-01T03:00:00.002Z [req-1]   Post Load (0.5ms)  SELECT \"posts\".* FROM \"posts\"",
+            "2024-05-01T03:00:00.003Z [req-1]   CACHE Post Load (0.0ms)  SELECT \"posts\".* FROM \"posts\"",
+            "2024-05-01T03:00:00.004Z [req-1]   Post Update (0.8ms)  UPDATE \"posts\" SET \"views\" = 2",
+            "2024-05-01T03:00:00.010Z [req-1] Completed 200 OK in 10ms",
+        ];
+        let mut entries = lines.iter().map(|line| log_parser::parse(line, None).unwrap());
+        let mut group = LogGroup::new(entries.next().unwrap());
+        entries.for_each(|entry| group.add_entry(entry));
+
+        let value = record("req-1", &group);
+        assert_eq!(value["kind"], "request");
+        assert_eq!(value["status"], "success");
+        assert_eq!(value["status_code"], 200);
+        assert_eq!(value["duration_ms"], 10);
+        assert_eq!(value["started_at"], "2024-05-01T03:00:00+00:00");
+        assert_eq!(value["lines"], 5);
+        assert_eq!(value["sql"]["queries"], 2);
+        assert_eq!(value["sql"]["update"], 1);
+        assert_eq!(value["sql"]["cache_hits"], 1);
+        assert_eq!(value["sql"]["tables"]["posts"], 2);
+        assert_eq!(value["sql"]["n_plus_one"], json!([]));
+    }
+}
@@ -24,6 +24,11 @@ impl Panel {
 #[derive(Debug, Clone)]
 pub struct LayoutInfo {
     regions: [Rect; 3],
+    status_bar: Rect,
+    stats: Option<Rect>,
+    timeline: Option<Rect>,
+    /// Panels on top of each other rather than side by side
+    stacked: bool,
 }
 
 impl Default for LayoutInfo {
@@ -36,6 +41,10 @@ impl LayoutInfo {
     pub fn new() -> Self {
         Self {
             regions: [Rect::default(); 3],
+            status_bar: Rect::default(),
+            stats: None,
+            timeline: None,
+            stacked: false,
         }
     }
 
@@ -47,24 +56,186 @@ impl LayoutInfo {
     pub fn region(&self, panel: Panel) -> Rect {
         self.regions[panel.index()]
     }
+
+    pub fn with_status_bar(mut self, rect: Rect) -> Self {
+        self.status_bar = rect;
+        self
+    }
+
+    pub fn status_bar(&self) -> Rect {
+        self.status_bar
+    }
+
+    pub fn with_stats(mut self, rect: Rect) -> Self {
+        self.stats = Some(rect);
+        self
+    }
+
+    pub fn stats(&self) -> Option<Rect> {
+        self.stats
+    }
+
+    pub fn with_timeline(mut self, rect: Rect) -> Self {
+        self.timeline = Some(rect);
+        self
+    }
+
+    pub fn timeline(&self) -> Option<Rect> {
+        self.timeline
+    }
+
+    pub fn stacked(&self) -> bool {
+        self.stacked
+    }
+}
+
+const STATS_PANEL_HEIGHT: u16 = 10;
+const TIMELINE_PANEL_HEIGHT: u16 = 12;
+const PLAIN_SQL_HEIGHT: u16 = 9;
+/// Status line and help line
+const PLAIN_STATUS_HEIGHT: u16 = 2;
+/// Narrower than this, the panels are stacked rather than side by side
+pub const STACK_WIDTH: u16 = 80;
+/// Rows the panels keep before the timeline and stats panels are left out
+const MIN_PANELS_HEIGHT: u16 = 12;
+/// Smallest terminal the panels are drawn in; smaller ones get a notice
+pub const MIN_WIDTH: u16 = 30;
+pub const MIN_HEIGHT: u16 = 10;
+
+/// Whether `area` is too small to draw the panels in.
+pub fn too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
 }
 
-pub fn calculate_layout(area: Rect, ratios: &[f64; 3]) -> LayoutInfo {
+/// The three panels side by side, or stacked when `area` is narrower than
+/// `STACK_WIDTH`. The timeline and stats panels only take their rows when
+/// that leaves the panels `MIN_PANELS_HEIGHT`.
+pub fn calculate_layout(
+    area: Rect,
+    ratios: &[f64; 3],
+    show_timeline: bool,
+    show_stats: bool,
+) -> LayoutInfo {
     use ratatui::layout::{Constraint, Direction, Layout};
 
-    let top_chunks = Layout::default()
-        .direction(Direction::Horizontal)
+    let mut spare = area.height.saturating_sub(MIN_PANELS_HEIGHT + 1);
+    let mut take = |show: bool, height: u16| {
+        let fits = show && height <= spare;
+        if fits {
+            spare -= height;
+        }
+        fits
+    };
+    let show_timeline = take(show_timeline, TIMELINE_PANEL_HEIGHT);
+    let show_stats = take(show_stats, STATS_PANEL_HEIGHT);
+    let timeline_height = if show_timeline { TIMELINE_PANEL_HEIGHT } else { 0 };
+    let stats_height = if show_stats { STATS_PANEL_HEIGHT } else { 0 };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage((ratios[0] * 100.0) as u16),
-            Constraint::Percentage((ratios[1] * 100.0) as u16),
-            Constraint::Percentage((ratios[2] * 100.0) as u16),
+            Constraint::Min(0),
+            Constraint::Length(timeline_height),
+            Constraint::Length(stats_height),
+            Constraint::Length(1),
         ])
         .split(area);
 
-    LayoutInfo::new()
+    let stacked = area.width < STACK_WIDTH;
+    let top_chunks = if stacked {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(30),
+                Constraint::Min(0),
+                Constraint::Length(PLAIN_SQL_HEIGHT),
+            ])
+            .split(rows[0])
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage((ratios[0] * 100.0) as u16),
+                Constraint::Percentage((ratios[1] * 100.0) as u16),
+                Constraint::Percentage((ratios[2] * 100.0) as u16),
+            ])
+            .split(rows[0])
+    };
+
+    let mut layout = LayoutInfo::new()
         .with_region(Panel::RequestList, top_chunks[0])
         .with_region(Panel::RequestDetail, top_chunks[1])
         .with_region(Panel::SqlInfo, top_chunks[2])
+        .with_status_bar(rows[3]);
+    layout.stacked = stacked;
+    if show_timeline {
+        layout = layout.with_timeline(rows[1]);
+    }
+    if show_stats {
+        layout = layout.with_stats(rows[2]);
+    }
+    layout
+}
+
+/// Panels stacked top to bottom for `--plain`, so they read in order.
+pub fn calculate_plain_layout(area: Rect) -> LayoutInfo {
+    use ratatui::layout::{Constraint, Direction, Layout};
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Min(0),
+            Constraint::Length(PLAIN_SQL_HEIGHT),
+            Constraint::Length(PLAIN_STATUS_HEIGHT),
+        ])
+        .split(area);
+
+    let mut layout = LayoutInfo::new()
+        .with_region(Panel::RequestList, rows[0])
+        .with_region(Panel::RequestDetail, rows[1])
+        .with_region(Panel::SqlInfo, rows[2])
+        .with_status_bar(rows[3]);
+    layout.stacked = true;
+    layout
+}
+
+/// Area of at most `width` x `height` anchored to the bottom-right corner of `area`,
+/// leaving the last row (status bar) uncovered.
+pub fn bottom_right_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let available_height = area.height.saturating_sub(1);
+    let width = width.min(area.width);
+    let height = height.min(available_height);
+    Rect::new(
+        area.x + area.width - width,
+        area.y + available_height - height,
+        width,
+        height,
+    )
+}
+
+/// Area of at most `width` x `height` with its top-left corner at `(x, y)`,
+/// moved up and left as needed to stay inside `area`.
+pub fn anchored_rect(area: Rect, x: u16, y: u16, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        x.clamp(area.x, area.right() - width),
+        y.clamp(area.y, area.bottom() - height),
+        width,
+        height,
+    )
+}
+
+/// Area of at most `width` x `height` in the middle of `area`, for popups.
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
 
 pub fn calculate_single_panel_layout(area: Rect, panel: Panel) -> LayoutInfo {
@@ -84,11 +255,41 @@ mod tests {
         assert_eq!(layout.region(Panel::RequestList), rect);
     }
 
+    #[test]
+    fn test_centered_rect() {
+        let area = Rect::new(0, 0, 100, 40);
+        assert_eq!(centered_rect(area, 60, 10), Rect::new(20, 15, 60, 10));
+        // Clamped to small terminals
+        assert_eq!(centered_rect(Rect::new(0, 0, 30, 5), 60, 10), Rect::new(0, 0, 30, 5));
+    }
+
+    #[test]
+    fn test_calculate_plain_layout() {
+        let area = Rect::new(0, 0, 80, 40);
+        let layout = calculate_plain_layout(area);
+
+        let list = layout.region(Panel::RequestList);
+        let detail = layout.region(Panel::RequestDetail);
+        let sql = layout.region(Panel::SqlInfo);
+        let status = layout.status_bar();
+
+        // Full width, stacked in reading order without gaps
+        for region in [list, detail, sql, status] {
+            assert_eq!(region.width, 80);
+        }
+        assert_eq!(list.y, 0);
+        assert_eq!(detail.y, list.bottom());
+        assert_eq!(sql.y, detail.bottom());
+        assert_eq!(status.y, sql.bottom());
+        assert_eq!(status.bottom(), 40);
+        assert_eq!(sql.height, PLAIN_SQL_HEIGHT);
+    }
+
     #[test]
     fn test_calculate_layout() {
         let area = Rect::new(0, 0, 100, 100);
         let ratios = [0.20, 0.60, 0.20];
-        let layout = calculate_layout(area, &ratios);
+        let layout = calculate_layout(area, &ratios, false, false);
 
         // Check all panels exist
         for panel in Panel::all().iter() {
@@ -110,5 +311,82 @@ mod tests {
 
         // RequestList should be to the left of RequestDetail
         assert!(request_list.x < request_detail.x);
+
+        // Status bar takes the last row below the panels
+        let status_bar = layout.status_bar();
+        assert_eq!(status_bar.height, 1);
+        assert_eq!(status_bar.y, request_list.y + request_list.height);
+        assert!(layout.stats().is_none());
+        assert!(layout.timeline().is_none());
+    }
+
+    #[test]
+    fn test_calculate_layout_with_stats() {
+        let area = Rect::new(0, 0, 100, 40);
+        let layout = calculate_layout(area, &[0.20, 0.60, 0.20], false, true);
+
+        let stats = layout.stats().unwrap();
+        let request_list = layout.region(Panel::RequestList);
+        assert_eq!(stats.y, request_list.y + request_list.height);
+        assert_eq!(stats.width, area.width);
+        assert_eq!(layout.status_bar().y, stats.y + stats.height);
+    }
+
+    #[test]
+    fn test_calculate_layout_with_timeline() {
+        let area = Rect::new(0, 0, 100, 40);
+        let layout = calculate_layout(area, &[0.20, 0.60, 0.20], true, true);
+
+        // Panels, timeline, stats and status bar from top to bottom
+        let timeline = layout.timeline().unwrap();
+        let stats = layout.stats().unwrap();
+        let request_list = layout.region(Panel::RequestList);
+        assert_eq!(timeline.y, request_list.bottom());
+        assert_eq!(timeline.height, TIMELINE_PANEL_HEIGHT);
+        assert_eq!(stats.y, timeline.bottom());
+        assert_eq!(layout.status_bar().y, stats.bottom());
+    }
+
+    #[test]
+    fn test_calculate_layout_narrow() {
+        let area = Rect::new(0, 0, 60, 30);
+        let layout = calculate_layout(area, &[0.20, 0.60, 0.20], true, true);
+        assert!(layout.stacked());
+
+        let list = layout.region(Panel::RequestList);
+        let detail = layout.region(Panel::RequestDetail);
+        let sql = layout.region(Panel::SqlInfo);
+        for region in [list, detail, sql] {
+            assert_eq!(region.width, 60);
+        }
+        assert_eq!(detail.y, list.bottom());
+        assert_eq!(sql.y, detail.bottom());
+        // The timeline fits; the stats panel would leave too few rows
+        assert_eq!(layout.timeline().unwrap().y, sql.bottom());
+        assert!(layout.stats().is_none());
+        assert!(sql.bottom() >= MIN_PANELS_HEIGHT);
+
+        assert!(!too_small(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(too_small(Rect::new(0, 0, MIN_WIDTH - 1, 40)));
+        assert!(too_small(Rect::new(0, 0, 100, MIN_HEIGHT - 1)));
+    }
+
+    #[test]
+    fn test_bottom_right_rect() {
+        let area = Rect::new(10, 0, 100, 40);
+        let rect = bottom_right_rect(area, 60, 3);
+        assert_eq!(rect, Rect::new(50, 36, 60, 3));
+
+        // Never larger than the area itself
+        let rect = bottom_right_rect(Rect::new(0, 0, 20, 3), 60, 3);
+        assert_eq!(rect, Rect::new(0, 0, 20, 2));
+    }
+
+    #[test]
+    fn test_anchored_rect() {
+        let area = Rect::new(0, 0, 100, 40);
+        assert_eq!(anchored_rect(area, 30, 10, 50, 4), Rect::new(30, 10, 50, 4));
+        // Pushed back inside near the bottom-right corner
+        assert_eq!(anchored_rect(area, 70, 38, 50, 4), Rect::new(50, 36, 50, 4));
     }
 }
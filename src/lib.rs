@@ -0,0 +1,57 @@
+//! lucy's modules, built as a library so that benchmarks can drive the app
+//! without a terminal. The `lucy` binary is `main.rs`. Parsing, grouping and
+//! the per-line analyzers live in the `lucy-core` crate and are re-exported
+//! here.
+
+pub mod alert;
+pub mod app;
+pub mod app_state;
+pub mod app_view;
+pub mod archive;
+pub mod cli;
+pub mod clipboard;
+pub mod columns;
+pub mod command;
+pub mod config;
+pub mod db;
+pub mod demo;
+pub mod editor;
+pub mod endpoint;
+pub mod export;
+pub mod file_index;
+pub mod filter;
+pub mod headless;
+pub mod hooks;
+pub mod i18n;
+pub mod icons;
+pub mod input;
+pub mod jsonl;
+pub mod json_view;
+pub mod layout;
+pub mod log_parser;
+pub mod metrics;
+pub mod panel_components;
+pub mod params_formatter;
+pub mod plain_view;
+pub mod recording;
+pub mod replay;
+pub mod setup;
+pub mod simple_formatter;
+pub mod sql_format;
+pub mod state_file;
+pub mod supervisor;
+pub mod text_measure;
+pub mod theme;
+pub mod time_format;
+pub mod time_headers;
+pub mod time_window;
+pub mod toast;
+pub mod tutorial;
+pub mod version;
+pub mod view_model;
+pub mod waterfall;
+
+pub use lucy_core::{
+    cable, cache_info, http_info, journal, memory_info, render_info, sampling, sql_info, stats,
+    timeline, timestamp, title,
+};
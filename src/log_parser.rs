@@ -1,122 +1,30 @@
+//! `lucy_core::log_parser`, with lines parsed into the `LogEntry` the app
+//! keeps in its groups.
+
 use crate::app_state::LogEntry;
-use chrono::Local;
+use crate::timestamp::Timestamps;
+use lucy_core::log_parser as core;
 use regex::Regex;
-use std::sync::LazyLock;
-
-static ANSI_ESCAPE_PATTERN: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;]*[mK]").expect("Invalid ANSI escape sequence regex"));
-
-pub static RE_COMPLETED: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"Completed (?P<status>\d+) .+ in (?P<duration>\d+)ms").unwrap()
-});
-
-pub fn parse(line: &str) -> Option<LogEntry> {
-    let trimmed = line.trim_start();
-    if trimmed.is_empty() {
-        return None;
-    }
-
-    let request_id = if trimmed.starts_with('[') {
-        let cleaned = if line.contains("\x1b[") {
-            strip_ansi_for_parsing(line)
-        } else {
-            line.to_string()
-        };
-        extract_request_id(&cleaned).unwrap_or_default()
-    } else {
-        String::new()
-    };
 
-    Some(LogEntry {
-        request_id,
-        timestamp: Local::now(),
-        message: line.to_string(),
-    })
-}
+pub use lucy_core::log_parser::*;
 
-pub fn strip_ansi_for_parsing(text: &str) -> String {
-    if !text.contains("\x1b[") {
-        return text.to_string();
-    }
-    ANSI_ESCAPE_PATTERN.replace_all(text, "").to_string()
+/// Lines matching `group_key` are grouped by its capture instead of the
+/// built-in request and job id detection.
+pub fn parse(line: &str, group_key: Option<&Regex>) -> Option<LogEntry> {
+    core::parse(line, group_key).map(LogEntry::from)
 }
 
-fn extract_request_id(line: &str) -> Option<String> {
-    if !line.starts_with('[') {
-        return None;
-    }
-
-    let end = line.find(']')?;
-    let request_id = line[1..end].trim();
-
-    if !request_id.is_empty() {
-        Some(request_id.to_string())
-    } else {
-        None
-    }
+/// `parse`, reading when the line was written with `timestamps`.
+pub fn parse_with(
+    line: &str,
+    group_key: Option<&Regex>,
+    timestamps: &Timestamps,
+) -> Option<LogEntry> {
+    core::parse_with(line, group_key, timestamps).map(LogEntry::from)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_strip_ansi_for_parsing() {
-        // Test with no ANSI codes
-        let text = "This is plain text";
-        assert_eq!(strip_ansi_for_parsing(text), text);
-
-        // Test with ANSI codes
-        let text_with_ansi = "\x1b[31mThis is red text\x1b[0m";
-        assert_eq!(strip_ansi_for_parsing(text_with_ansi), "This is red text");
-
-        // Test with multiple ANSI codes
-        let complex_ansi = "\x1b[1m\x1b[32mBold green\x1b[0m and \x1b[36mcyan\x1b[0m";
-        assert_eq!(strip_ansi_for_parsing(complex_ansi), "Bold green and cyan");
-    }
-
-    #[test]
-    fn test_extract_request_id() {
-        // Valid request ID
-        let line = "[abc-123] Some log message";
-        assert_eq!(extract_request_id(line), Some("abc-123".to_string()));
-
-        // No request ID
-        let line_without_id = "Some log message";
-        assert_eq!(extract_request_id(line_without_id), None);
-
-        // Empty brackets
-        let empty_brackets = "[] Some log message";
-        assert_eq!(extract_request_id(empty_brackets), None);
-
-        // Only whitespace in brackets
-        let whitespace_brackets = "[   ] Some log message";
-        assert_eq!(extract_request_id(whitespace_brackets), None);
-    }
-
-    #[test]
-    fn test_parse() {
-        // Normal log line with request ID
-        let line = "[req-123] Started GET /test";
-        let entry = parse(line).unwrap();
-        assert_eq!(entry.request_id, "req-123");
-        assert_eq!(entry.message, line);
-
-        // Log line with ANSI codes
-        let ansi_line = "\x1b[32m[req-456]\x1b[0m Processing data";
-        let entry = parse(ansi_line).unwrap();
-        // The ANSI codes affect how the request ID is extracted
-        // So we won't assert on the exact request ID here
-        assert_eq!(entry.message, ansi_line);
-
-        // Empty line
-        assert!(parse("").is_none());
-        assert!(parse("   ").is_none());
-
-        // Line without request ID
-        let no_id_line = "Log message without request ID";
-        let entry = parse(no_id_line).unwrap();
-        assert_eq!(entry.request_id, "");
-        assert_eq!(entry.message, no_id_line);
-    }
+/// Parses a line for `--generic`, where nothing Rails specific is assumed:
+/// lines are grouped only by `group_key`.
+pub fn parse_generic(line: &str, group_key: &Regex, timestamps: &Timestamps) -> Option<LogEntry> {
+    core::parse_generic(line, group_key, timestamps).map(LogEntry::from)
 }
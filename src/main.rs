@@ -1,16 +1,10 @@
-mod app;
-mod app_state;
-mod app_view;
-mod input;
-mod layout;
-mod log_parser;
-mod panel_components;
-mod setup;
-mod simple_formatter;
-mod sql_info;
-mod theme;
-
 use color_eyre::Result;
+use lucy::{
+    app, archive, cli, config, demo, export, file_index, input, journal, jsonl, log_parser,
+    recording, setup, state_file, timestamp, tutorial, version,
+};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 
 struct TerminalGuard<B: ratatui::backend::Backend>
 where
@@ -43,15 +37,179 @@ where
     }
 }
 
-fn main() -> Result<()> {
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
     setup::initialize()?;
+    let mut args = cli::Args::parse();
+    if args.version {
+        println!("{}", version::about_lines().join("\n"));
+        return Ok(());
+    }
+    let mut config = config::Config::load()?;
+    if let Some(rate) = args.sample_rate {
+        config.sampling.rate = Some(rate);
+    }
+    if args.plain {
+        config.plain = true;
+    }
+    if let Some(palette) = args.theme {
+        config.theme = palette;
+    }
+    if args.generic {
+        config.generic = true;
+    }
+    if args.no_mouse {
+        config.no_mouse = true;
+    }
+    if let Some(max_requests) = args.max_requests {
+        config.retention.max_requests = max_requests;
+    }
+    if let Some(minutes) = args.max_age_minutes {
+        let retention = &mut config.retention;
+        for rule in [
+            &mut retention.success,
+            &mut retention.warning,
+            &mut retention.error,
+            &mut retention.info,
+            &mut retention.unknown,
+        ] {
+            rule.max_age_minutes = Some(minutes);
+        }
+    }
+    if let Some(pattern) = args.group_key_regex {
+        config.group_key_regex = Some(pattern);
+    }
+    let group_key_regex = match config.group_key_regex.as_deref() {
+        None if config.generic => Some(log_parser::DEFAULT_GENERIC_GROUP_KEY),
+        pattern => pattern,
+    };
+    let group_key = group_key_regex
+        .map(log_parser::compile_group_key)
+        .transpose()?;
+
+    let export_format = export::format_named(&config.export.format)?;
+    let timestamps = timestamp::Timestamps::new(config.timestamps.as_ref())?;
+
+    let journal = config
+        .compaction
+        .journal
+        .as_deref()
+        .map(journal::Journal::create)
+        .transpose()?;
 
-    let (_input_reader, rx) = input::Reader::new();
-    let terminal = setup::initialize_terminal()?;
+    let headless = args.output.is_some();
+    // Plain mode has no overlays to show the tutorial in
+    let saved_state = state_file::State::load();
+    let first_run = !config.plain && !headless && !saved_state.tutorial_done;
+    let playback = args
+        .playback
+        .as_deref()
+        .map(recording::Playback::load)
+        .transpose()?;
+    let (input_reader, rx) = match args.demo_rate {
+        _ if !args.command.is_empty() => input::Reader::command(&args.command)?,
+        _ if !args.files.is_empty() || !args.listen.is_empty() => {
+            input::Reader::open(&args.files, args.follow, &args.listen)?
+        }
+        _ if let Some((path, speed)) = &args.replay => input::Reader::replay(path, *speed)?,
+        Some(rate) => {
+            // `lucy demo > demo.log` records the stream instead of viewing it
+            if !headless && !io::stdout().is_terminal() {
+                let mut stdout = io::stdout().lock();
+                demo::run(rate, |line| stdout.write_all(line.as_bytes()).is_ok());
+                return Ok(());
+            }
+            input::Reader::demo(rate)
+        }
+        // Lines come from the recording
+        None if playback.is_some() => input::Reader::sample(""),
+        None if first_run && io::stdin().is_terminal() => {
+            input::Reader::sample(tutorial::SAMPLE_LOG)
+        }
+        // Nothing piped in and nothing to open: an empty screen would only
+        // wait, so offer to tail the logs of a Rails app run from here
+        None if io::stdin().is_terminal() => {
+            let logs = setup::find_rails_logs(Path::new("."));
+            if logs.is_empty() {
+                cli::print_usage();
+                return Ok(());
+            }
+            // Plain and headless runs never take over the screen, so list
+            // the logs for the user to pass back instead of asking
+            if config.plain || headless {
+                for log in &logs {
+                    println!("{}", log.display());
+                }
+                return Ok(());
+            }
+            let Some(path) = setup::pick_log(&logs, config.locale.strings())? else {
+                return Ok(());
+            };
+            args.files = vec![path];
+            args.follow = true;
+            input::Reader::open(&args.files, args.follow, &args.listen)?
+        }
+        None => input::Reader::new(),
+    };
+    let archive = args
+        .archive
+        .as_deref()
+        .map(archive::Archive::start)
+        .transpose()?;
+
+    let mut app = app::App::new(config);
+    app.state.notes = saved_state.notes;
+    app.set_sources(input_reader.sources());
+    app.group_key = group_key;
+    app.timestamps = timestamps;
+    app.journal = journal;
+    app.export_format = export_format;
+    app.follow = args.follow;
+    app.archive = archive;
+    app.supervisor = input_reader.supervisor();
+    if let Some(cli::OutputFormat::Jsonl) = args.output {
+        app.jsonl = Some(jsonl::JsonLines::new(Box::new(io::stdout())));
+        app.run_headless(rx).await;
+        return Ok(());
+    }
+
+    let (terminal, mouse_enabled) =
+        setup::initialize_terminal(!app.config.no_mouse && setup::mouse_supported())?;
     let mut guard = TerminalGuard::new(terminal);
+    let size = guard.terminal().size()?;
+    let recorder = args
+        .record
+        .as_deref()
+        .map(|path| recording::Recorder::create(path, (size.width, size.height)))
+        .transpose()?;
+    if let Some(playback) = &playback
+        && playback.size != (size.width, size.height)
+    {
+        tracing::warn!(
+            "Recording was made in a {}x{} terminal, replaying in {}x{}",
+            playback.size.0,
+            playback.size.1,
+            size.width,
+            size.height
+        );
+    }
 
-    let mut app = app::App::new();
-    app.run(guard.terminal(), rx)?;
+    if !args.files.is_empty() {
+        app.file_index = Some(file_index::FileIndex::new());
+    }
+    if let Some(ratios) = args.layout {
+        app.app_view.panel_ratios = ratios;
+    }
+    app.mouse_enabled = mouse_enabled;
+    app.recorder = recorder;
+    app.playback = playback;
+    if first_run {
+        app.tutorial = Some(tutorial::Tutorial::new());
+    }
+    if app.config.check_updates {
+        app.update_notice = Some(version::spawn_update_check());
+    }
+    app.run(guard.terminal(), rx).await?;
 
     Ok(())
 }
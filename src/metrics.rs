@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Span the line rate is averaged over.
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Seconds of traffic `TrafficMeter` keeps.
+pub const TRAFFIC_SECONDS: usize = 60;
+
+/// Complete seconds the current request rate is averaged over.
+const TRAFFIC_RATE_SECONDS: u64 = 5;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub use lucy_core::app_state::IngestCounters;
+
+/// Rate of a growing total, from samples taken as time passes.
+#[derive(Debug, Default)]
+pub struct RateMeter {
+    /// (when, total), oldest first, spanning at most `RATE_WINDOW`
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RateMeter {
+    pub fn sample(&mut self, now: Instant, total: u64) {
+        self.samples.push_back((now, total));
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|&(at, _)| now.duration_since(at) >= RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Average growth per second across the samples held.
+    pub fn per_second(&self) -> f64 {
+        let (Some(&(first_at, first)), Some(&(last_at, last))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return 0.0;
+        };
+        let elapsed = last_at.duration_since(first_at).as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        last.saturating_sub(first) as f64 / elapsed
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TrafficSecond {
+    /// Seconds since the meter's origin
+    second: u64,
+    requests: u64,
+    errors: u64,
+}
+
+/// Requests that arrived and failed in each of the last `TRAFFIC_SECONDS`
+/// seconds, in a ring indexed by the second.
+#[derive(Debug)]
+pub struct TrafficMeter {
+    origin: Instant,
+    ring: [TrafficSecond; TRAFFIC_SECONDS],
+}
+
+impl Default for TrafficMeter {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl TrafficMeter {
+    pub fn new(origin: Instant) -> Self {
+        Self {
+            origin,
+            ring: [TrafficSecond::default(); TRAFFIC_SECONDS],
+        }
+    }
+
+    pub fn record_request(&mut self, now: Instant) {
+        self.slot(now).requests += 1;
+    }
+
+    pub fn record_error(&mut self, now: Instant) {
+        self.slot(now).errors += 1;
+    }
+
+    fn second(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.origin).as_secs()
+    }
+
+    fn slot(&mut self, now: Instant) -> &mut TrafficSecond {
+        let second = self.second(now);
+        let slot = &mut self.ring[second as usize % TRAFFIC_SECONDS];
+        if slot.second != second {
+            *slot = TrafficSecond {
+                second,
+                ..TrafficSecond::default()
+            };
+        }
+        slot
+    }
+
+    /// The second `seconds_ago` before the current one, if still held.
+    fn at(&self, now: Instant, seconds_ago: u64) -> TrafficSecond {
+        let Some(second) = self.second(now).checked_sub(seconds_ago) else {
+            return TrafficSecond::default();
+        };
+        let slot = self.ring[second as usize % TRAFFIC_SECONDS];
+        if slot.second == second {
+            slot
+        } else {
+            TrafficSecond::default()
+        }
+    }
+
+    /// Requests per second over the last `seconds` seconds, oldest first,
+    /// ending with the current one.
+    pub fn requests(&self, now: Instant, seconds: usize) -> Vec<u64> {
+        (0..seconds.min(TRAFFIC_SECONDS) as u64)
+            .rev()
+            .map(|seconds_ago| self.at(now, seconds_ago).requests)
+            .collect()
+    }
+
+    /// Requests per second, averaged over the last few complete seconds.
+    pub fn per_second(&self, now: Instant) -> f64 {
+        let requests: u64 = (1..=TRAFFIC_RATE_SECONDS)
+            .map(|seconds_ago| self.at(now, seconds_ago).requests)
+            .sum();
+        requests as f64 / TRAFFIC_RATE_SECONDS as f64
+    }
+
+    /// Share of the requests held that failed, in percent. None without
+    /// requests.
+    pub fn error_percent(&self, now: Instant) -> Option<f64> {
+        let (requests, errors) = (0..TRAFFIC_SECONDS as u64)
+            .map(|seconds_ago| self.at(now, seconds_ago))
+            .fold((0, 0), |(requests, errors), second| {
+                (requests + second.requests, errors + second.errors)
+            });
+        (requests > 0).then(|| errors as f64 * 100.0 / requests as f64)
+    }
+}
+
+/// `values` as a line of block characters, scaled to the largest.
+pub fn sparkline(values: &[u64]) -> String {
+    let max = values.iter().copied().max().unwrap_or_default();
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            _ => {
+                let level = (value * SPARK_LEVELS.len() as u64).div_ceil(max.max(1)) as usize;
+                SPARK_LEVELS[level.clamp(1, SPARK_LEVELS.len()) - 1]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_meter() {
+        let start = Instant::now();
+        let mut meter = RateMeter::default();
+        assert_eq!(meter.per_second(), 0.0);
+
+        meter.sample(start, 0);
+        assert_eq!(meter.per_second(), 0.0);
+        meter.sample(start + Duration::from_secs(1), 100);
+        meter.sample(start + Duration::from_secs(2), 300);
+        assert_eq!(meter.per_second(), 150.0);
+
+        // Samples older than the window stop counting
+        meter.sample(start + Duration::from_secs(7), 300);
+        meter.sample(start + Duration::from_secs(8), 310);
+        assert_eq!(meter.per_second(), 10.0 / 6.0);
+    }
+
+    #[test]
+    fn test_traffic_meter() {
+        let start = Instant::now();
+        let mut meter = TrafficMeter::new(start);
+        assert_eq!(meter.error_percent(start), None);
+
+        for _ in 0..4 {
+            meter.record_request(start);
+        }
+        meter.record_error(start);
+        let later = start + Duration::from_millis(2500);
+        meter.record_request(later);
+        assert_eq!(meter.requests(later, 4), [0, 4, 0, 1]);
+        assert_eq!(meter.per_second(later), 4.0 / 5.0);
+        assert_eq!(meter.error_percent(later), Some(20.0));
+
+        // A second the ring comes back around to starts afresh
+        let lap = start + Duration::from_secs(TRAFFIC_SECONDS as u64);
+        meter.record_request(lap);
+        assert_eq!(meter.requests(lap, 3), [0, 0, 1]);
+        assert_eq!(meter.error_percent(lap), Some(0.0));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 2, 4, 8]), " ▁▂▄█");
+        assert_eq!(sparkline(&[0, 0]), "  ");
+        assert_eq!(sparkline(&[3, 3]), "██");
+    }
+}
@@ -1,119 +1,200 @@
 use crate::app::App;
-use crate::app_state::StatusType;
-use crate::layout::Panel;
-use crate::log_parser::strip_ansi_for_parsing;
-use crate::simple_formatter::{format_simple_log_line, parse_ansi_colors};
-use crate::sql_info::{QueryType, SqlQueryInfo};
-use crate::theme::{ColorExt, THEME};
+use crate::app_state::{GroupKind, LogGroup, StatusType};
+use crate::db::explain::Explain;
+use crate::i18n::{Strings, fill};
+use crate::json_view;
+use crate::layout;
+use crate::params_formatter;
+use crate::cache_info::{self, CommandTiming};
+use crate::columns::{self, Column};
+use crate::http_info::HttpInfo;
+use crate::memory_info::MemoryInfo;
+use crate::render_info::RenderInfo;
+use crate::simple_formatter::{Verbosity, format_full_log_line, format_simple_log_line};
+use crate::sql_format;
+use crate::sql_info::{self, QueryType, SqlQueryInfo, SqlRow};
+use crate::text_measure;
+use crate::theme::{ColorExt, StatusColor, source_color, theme};
+use crate::time_format::Precision;
+use crate::timeline::GapNote;
+use crate::toast::{Toast, ToastLevel};
+use crate::tutorial::Tutorial;
+use crate::version;
+use crate::view_model::{
+    DetailView, EndpointListView, ListView, PanelChrome, RequestListView, RequestRow, SqlView,
+    StatusBarView, RequestBar, TimelineRows, TimelineView,
+};
+use crate::waterfall::StepKind;
+use chrono::TimeDelta;
+use std::borrow::Cow;
 use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, List, ListItem, Padding, Paragraph, Wrap},
+    widgets::{
+        BarChart, Block, BorderType, Borders, Gauge, List, ListItem, Padding, Paragraph,
+        Sparkline, Wrap,
+    },
 };
 
-const INDEX_OFFSET: usize = 1;
-
-pub fn build_list_component(app: &App) -> List<'_> {
-    let visible_requests = app.visible_request_ids();
-    let total_visible = visible_requests.len();
-
-    let mut items = Vec::with_capacity(total_visible);
-
-    let viewport_height = app.app_view.viewport_height(Panel::RequestList);
-    let current_offset = app.app_view.get_scroll_offset(Panel::RequestList);
-    let visible_count = viewport_height.min(total_visible.saturating_sub(current_offset));
-
-    for &(original_index, request_id) in visible_requests
-        .iter()
-        .skip(current_offset)
-        .take(visible_count)
-    {
-        let Some(group) = app.state.logs_by_request_id.get(request_id) else {
-            continue;
-        };
-        let time_str = group.first_timestamp.format("%H:%M").to_string();
-
-        let finished = group.finished;
-
-        let status_color = if finished {
-            group.status_type.to_color()
-        } else {
-            THEME.default
-        };
-
-        let duration_str = match group.duration_ms {
-            Some(ms) => format!("{:>4}ms ", ms),
-            None => " ---ms ".to_string(),
-        };
-        let duration_color = match group.duration_ms {
-            Some(ms) if ms >= 3000 => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            Some(ms) if ms >= 500 => Style::default().fg(Color::Yellow),
-            _ => Style::default().fg(Color::Cyan),
-        };
-
-        let content = Line::from(vec![
-            Span::raw(format!("{} ", time_str)),
-            Span::styled(duration_str, duration_color),
-            Span::styled(group.title.as_str(), status_color),
-        ]);
-
-        let style = if original_index == app.state.selected_index {
-            status_color.style_with_modifier(Modifier::BOLD | Modifier::UNDERLINED)
-        } else if finished {
-            THEME.default.style().fg(status_color)
-        } else {
-            THEME.default.style()
-        };
-
-        items.push(ListItem::new(content).style(style));
+pub fn build_list_component<'a>(view: &'a ListView) -> List<'a> {
+    match view {
+        ListView::Requests(view) => build_request_list(view),
+        ListView::Endpoints(view) => build_endpoint_list(view),
     }
+}
 
-    let border_style = match app.app_view.focused_panel {
-        Panel::RequestList => THEME.active_border,
-        _ => THEME.border,
-    };
-
-    let total_requests = app.state.log_group_count();
-    let scroll_info = if total_visible == 0 {
-        "0/0".to_string()
-    } else if app.filtered_indices.is_some() {
-        format!("{}/{}", total_visible, total_requests)
-    } else {
-        let start_idx = current_offset + INDEX_OFFSET;
-        let end_idx = (start_idx + visible_count - INDEX_OFFSET).min(total_visible);
-        format!("{}-{}/{}", start_idx, end_idx, total_requests)
-    };
-
-    let is_list_search = matches!(app.search_mode, Some(crate::app::SearchTarget::RequestList));
-    let title_text = if is_list_search || app.filtered_indices.is_some() {
-        format!("[{}] /{}", scroll_info, app.search_query)
-    } else {
-        format!("[{}]", scroll_info)
-    };
-
-    let title_style = match app.app_view.focused_panel {
-        Panel::RequestList => THEME.default.style_with_modifier(Modifier::BOLD),
-        _ => THEME.default.style(),
-    };
+fn build_request_list<'a>(view: &'a RequestListView) -> List<'a> {
+    let icons = view.icons;
+    let items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .flat_map(|row| {
+            let header = row.header.as_ref().map(|label| {
+                ListItem::new(format!("── {} ──", label)).style(Style::default().fg(Color::DarkGray))
+            });
+            let status_color = if row.finished {
+                row.status.to_color()
+            } else {
+                theme().default
+            };
+
+            let (method, title) = if view.columns.contains(&Column::Method) {
+                columns::strip_method(&row.title)
+            } else {
+                (None, row.title.as_str())
+            };
+
+            let mut spans = Vec::new();
+            if let Some(width) = view.number_width {
+                spans.push(Span::styled(
+                    format!("{:>width$} ", row.number, width = width),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            for &column in view.columns {
+                spans.extend(column_span(view, row, column, method, status_color));
+            }
+            if row.kind == GroupKind::Job {
+                spans.push(Span::styled(
+                    format!("{} ", icons.job()),
+                    Style::default().fg(Color::Magenta),
+                ));
+            } else if row.kind == GroupKind::Cable {
+                spans.push(Span::styled(
+                    format!("{} ", icons.cable()),
+                    Style::default().fg(Color::Blue),
+                ));
+            } else if let Some(icon) = title
+                .split_whitespace()
+                .next()
+                .and_then(|method| icons.method(method))
+            {
+                spans.push(Span::styled(format!("{} ", icon), Style::default().fg(Color::Cyan)));
+            }
+            if row.starred {
+                spans.push(Span::styled(
+                    format!("{} ", icons.star()),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            if row.pinned {
+                spans.push(Span::styled(format!("{} ", icons.pin()), theme().error));
+            }
+            if row.noted {
+                spans.push(Span::styled(
+                    format!("{} ", icons.note()),
+                    Style::default().fg(Color::LightBlue),
+                ));
+            }
+            if row.duplicate {
+                spans.push(Span::styled(
+                    format!("{} ", icons.duplicate()),
+                    Style::default().fg(Color::LightMagenta),
+                ));
+            }
+            if row.finished && (theme().status_symbols || icons.always_show_status()) {
+                let icon = icons.status(row.status).unwrap_or(" ");
+                spans.push(Span::styled(format!("{} ", icon), status_color));
+            }
+            if row.kind == GroupKind::Unassigned {
+                spans.push(Span::styled(
+                    fill(view.strings.unassigned, &[&row.line_count]),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            } else {
+                // Match positions count from the start of the full title
+                let skipped = row.title.chars().count() - title.chars().count();
+                let mut matched: Vec<usize> = row
+                    .matched
+                    .iter()
+                    .filter_map(|&position| position.checked_sub(skipped))
+                    .collect();
+                let used: usize = spans.iter().map(Span::width).sum();
+                let excess = (used + text_measure::width(title)).saturating_sub(view.width);
+                let skipped_bytes = row.title.len() - title.len();
+                let elided = row
+                    .title_path
+                    .as_ref()
+                    .filter(|_| excess > 0)
+                    .and_then(|path| {
+                        let path = path.start.checked_sub(skipped_bytes)?..path.end - skipped_bytes;
+                        text_measure::elide(title, path, excess)
+                    });
+                let title = match elided {
+                    Some((elided, cut)) => {
+                        matched = matched
+                            .into_iter()
+                            .filter(|position| !cut.contains(position))
+                            .map(|position| {
+                                if position < cut.start {
+                                    position
+                                } else {
+                                    position + 1 - cut.len()
+                                }
+                            })
+                            .collect();
+                        Cow::Owned(elided)
+                    }
+                    None => Cow::Borrowed(title),
+                };
+                if matched.is_empty() {
+                    spans.push(Span::styled(title, status_color));
+                } else {
+                    spans.extend(highlight_positions(&title, &matched, status_color.style()));
+                }
+            }
+            let content = Line::from(spans);
+
+            let style = if row.selected {
+                status_color.style_with_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else if row.marked {
+                status_color.style_with_modifier(Modifier::REVERSED)
+            } else if row.finished {
+                theme().default.style().fg(status_color)
+            } else {
+                theme().default.style()
+            };
+            header.into_iter().chain([ListItem::new(content).style(style)])
+        })
+        .collect();
 
-    let borders = if app.copy_mode_enabled {
-        Borders::TOP | Borders::BOTTOM
+    let title_style = if view.chrome.focused {
+        theme().default.style_with_modifier(Modifier::BOLD)
     } else {
-        Borders::ALL
+        theme().default.style()
     };
-
     let mut block = Block::default()
-        .borders(borders)
+        .borders(panel_borders(view.chrome))
         .border_type(BorderType::Rounded)
-        .border_style(border_style)
+        .border_style(panel_border_color(view.chrome))
         .padding(Padding::new(1, 1, 1, 1))
-        .title(Span::styled(title_text, title_style));
-
-    if is_list_search {
-        let search_display = format!(" /{}_ ", app.search_query);
+        .title(Span::styled(view.title.as_str(), title_style));
+    if let Some(prompt) = &view.prompt {
         block = block.title_bottom(
             Line::from(Span::styled(
-                search_display,
+                prompt.as_str(),
                 Style::default().fg(Color::Yellow),
             ))
             .alignment(ratatui::layout::Alignment::Left),
@@ -123,304 +204,1220 @@ pub fn build_list_component(app: &App) -> List<'_> {
     List::new(items).block(block)
 }
 
-pub fn build_detail_component(app: &App) -> Paragraph<'_> {
-    let (title_span, log_text, total_entries) = build_detail_content(app);
+/// Requests by endpoint with their count, error rate and mean duration, in
+/// place of the request list.
+fn build_endpoint_list(view: &EndpointListView) -> List<'_> {
+    let items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(row, summary)| {
+            let rate_style = if summary.errors > 0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let duration = match summary.mean_duration_ms() {
+                Some(ms) => format!(" {:>5}ms", ms),
+                None => "   ---ms".to_string(),
+            };
+            let content = Line::from(vec![
+                Span::raw(text_measure::fit(&summary.endpoint, view.endpoint_width)),
+                Span::raw(format!(" {:>4}", summary.count)),
+                Span::styled(format!(" {:>3}%", summary.error_rate()), rate_style),
+                Span::styled(duration, Style::default().fg(Color::Cyan)),
+            ]);
+            let style = if row == view.cursor {
+                theme().default.style_with_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else {
+                theme().default.style()
+            };
+            ListItem::new(content).style(style)
+        })
+        .collect();
 
-    let border_style = match app.app_view.focused_panel {
-        Panel::RequestDetail => THEME.active_border,
-        _ => THEME.border,
+    let title_style = if view.chrome.focused {
+        theme().default.style_with_modifier(Modifier::BOLD)
+    } else {
+        theme().default.style()
     };
+    let block = Block::default()
+        .borders(panel_borders(view.chrome))
+        .border_type(BorderType::Rounded)
+        .border_style(panel_border_color(view.chrome))
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(Span::styled(view.title.as_str(), title_style));
+    List::new(items).block(block)
+}
 
-    let scroll_info = build_detail_scroll_info(app, total_entries);
-    let title_text = format!("[{}] {} ", scroll_info, title_span);
-    let status = app
-        .state
-        .selected_group()
-        .map_or(StatusType::Unknown, |g| g.status_type);
-    let title_style = status.to_color().style_with_modifier(Modifier::BOLD);
-
-    let borders = if app.copy_mode_enabled {
+fn panel_borders(chrome: PanelChrome) -> Borders {
+    if chrome.copy_mode {
         Borders::TOP | Borders::BOTTOM
     } else {
         Borders::ALL
+    }
+}
+
+fn panel_border_color(chrome: PanelChrome) -> Color {
+    if chrome.focused {
+        theme().active_border
+    } else {
+        theme().border
+    }
+}
+
+/// Splits `text` into spans, emphasizing the chars at `positions`.
+/// The cell of `column` in `row`, padded so the columns line up.
+fn column_span(
+    view: &RequestListView,
+    row: &RequestRow,
+    column: Column,
+    method: Option<&str>,
+    status_color: Color,
+) -> Option<Span<'static>> {
+    let span = match column {
+        Column::Time => Span::raw(format!("{} ", row.time)),
+        Column::Duration => {
+            let style = match row.duration_ms {
+                Some(ms) if ms >= 3000 => {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                }
+                Some(ms) if ms >= 500 => Style::default().fg(Color::Yellow),
+                _ => Style::default().fg(Color::Cyan),
+            };
+            match row.duration_ms {
+                Some(ms) => Span::styled(format!("{:>4}ms ", ms), style),
+                None => Span::styled(" ---ms ", style),
+            }
+        }
+        Column::Status => {
+            let code = row.status_code.map_or("---".to_string(), |code| code.to_string());
+            Span::styled(format!("{} ", code), status_color)
+        }
+        Column::Method => Span::styled(
+            format!("{:<6} ", method.unwrap_or_default()),
+            Style::default().fg(Color::Cyan),
+        ),
+        Column::Action => Span::styled(
+            format!(
+                "{} ",
+                text_measure::fit(row.action.as_deref().unwrap_or_default(), view.action_width)
+            ),
+            Style::default().fg(Color::Magenta),
+        ),
+        Column::Sql => Span::styled(
+            format!("{:>3}q ", row.sql_count),
+            Style::default().fg(Color::Green),
+        ),
+        Column::Lines => Span::styled(
+            format!("{:>4}ln ", row.line_count),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Column::Source => {
+            let (name, source) = row.source?;
+            Span::styled(
+                format!("{} ", text_measure::fit(name, view.source_width)),
+                source_color(source).style(),
+            )
+        }
     };
+    Some(span)
+}
+
+fn highlight_positions(text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    let matched_style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+    let mut positions = positions.iter().peekable();
+
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.next_if(|&&p| p == i).is_some();
+        if matched != current_matched && !current.is_empty() {
+            let style = if current_matched { matched_style } else { style };
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        current_matched = matched;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        let style = if current_matched { matched_style } else { style };
+        spans.push(Span::styled(current, style));
+    }
+    spans
+}
 
-    let bottom_line = build_detail_bottom_bar(app);
+pub fn build_detail_component(view: &DetailView) -> Paragraph<'_> {
+    let mut title_text = view.title.clone();
+    let title_style = view.status.to_color().style_with_modifier(Modifier::BOLD);
+    if (theme().status_symbols || view.icons.always_show_status())
+        && let Some(icon) = view.icons.status(view.status)
+    {
+        title_text.insert_str(0, &format!("{} ", icon));
+    }
+
+    let mut text = Text::default();
+    for entry in &view.entries {
+        for line in &entry.lines {
+            text.push_line(highlight_entry(line.clone(), entry.highlighted));
+        }
+    }
 
     let block = Block::default()
         .padding(Padding::new(1, 1, 1, 1))
         .title_alignment(ratatui::layout::Alignment::Left)
         .title(Span::styled(title_text, title_style))
-        .title_bottom(bottom_line)
-        .borders(borders)
-        .border_style(border_style);
+        .title_bottom(build_detail_bottom_bar(view))
+        .borders(panel_borders(view.chrome))
+        .border_style(panel_border_color(view.chrome));
 
-    let paragraph = Paragraph::new(log_text);
-    if app.simple_mode_enabled {
-        paragraph.block(block)
+    let paragraph = Paragraph::new(text).block(block);
+    if view.wrap {
+        paragraph.wrap(text_measure::WRAP)
     } else {
-        paragraph.block(block).wrap(Wrap { trim: true })
+        paragraph
     }
 }
 
-fn build_detail_title(app: &App, group: &crate::app_state::LogGroup) -> Span<'static> {
-    let entry = group.entries.iter().find(|entry| {
-        let msg = &entry.message;
-        msg.contains("Started GET")
-            || msg.contains("Started POST")
-            || msg.contains("Started PUT")
-            || msg.contains("Started PATCH")
-            || msg.contains("Started DELETE")
-            || msg.contains("Started HEAD")
-            || msg.contains("Started OPTIONS")
-            || msg.contains("Started TRACE")
-    });
-
-    let Some(entry) = entry else {
-        return Span::raw("");
+/// The summary row of a line logging parameters or a JSON body, and the
+/// tree or pretty-printed body under it when `expanded`.
+pub fn structured_lines(
+    message: &str,
+    expanded: bool,
+) -> Option<(Line<'static>, Vec<Line<'static>>)> {
+    if let Some(params) = params_formatter::parse(message) {
+        let tree = if expanded {
+            params_formatter::tree_lines(&params)
+        } else {
+            Vec::new()
+        };
+        return Some((params_formatter::summary_line(&params, expanded), tree));
+    }
+    let body = json_view::parse(message)?;
+    let pretty = if expanded {
+        json_view::pretty_lines(&body)
+    } else {
+        Vec::new()
     };
+    Some((json_view::summary_line(&body, expanded), pretty))
+}
 
-    let msg = strip_ansi_for_parsing(&entry.message);
-    let method = msg
-        .split_whitespace()
-        .skip_while(|&s| s != "Started")
-        .nth(1)
-        .unwrap_or("");
-
-    let url = msg
-        .find(" \"")
-        .and_then(|start| {
-            msg[start + 2..]
-                .find('"')
-                .map(|end| &msg[start + 2..start + 2 + end])
-        })
-        .unwrap_or("");
-
-    let view_width = app.app_view.viewport_width(Panel::RequestDetail);
-    let text = format!("{} {}", method, url)
-        .chars()
-        .take(view_width.saturating_sub(10))
-        .collect::<String>();
-    Span::raw(text)
+/// An entry's lines as the detail panel shows them, with the binds of its
+/// query inlined when `inline_binds` is on. None when `verbosity` hides it.
+pub fn detail_lines(
+    log: &crate::app_state::LogEntry,
+    verbosity: Verbosity,
+    inline_binds: bool,
+) -> Option<Vec<Line<'static>>> {
+    let shown = log.rendered.lines(verbosity)?;
+    let inlined = inline_binds
+        .then(|| sql_format::inline_line_binds(&log.message))
+        .flatten();
+    let Some(message) = inlined else {
+        return Some(shown.to_vec());
+    };
+    match verbosity {
+        Verbosity::Full => Some(format_full_log_line(&message)),
+        Verbosity::Simple | Verbosity::Summary => format_simple_log_line(&message)
+            .or_else(|| Some(format_full_log_line(&message))),
+    }
 }
 
-fn build_detail_log_line(
+/// The rows an entry takes in the detail panel: its lines, followed by the
+/// parameters tree or the body when it logs either and `expand_params` is on.
+pub fn build_detail_log_lines(
+    strings: &Strings,
     log: &crate::app_state::LogEntry,
     sql_info: &SqlQueryInfo,
     detail_query: &str,
-    simple_mode: bool,
-) -> Option<Line<'static>> {
-    if simple_mode {
-        format_simple_log_line(&log.message)
-            .map(|line| highlight_n_plus_one_tables(line, sql_info))
-            .map(|line| highlight_search_matches(line, detail_query))
-    } else {
-        let message = if let Some(after_id) = log.message.find(']') {
-            log.message[(after_id + 1)..].trim().to_string()
-        } else {
-            log.message.clone()
-        };
-        let spans = parse_ansi_colors(&message);
-        let line = Line::from(spans);
-        let line = highlight_n_plus_one_tables(line, sql_info);
-        Some(highlight_search_matches(line, detail_query))
+    verbosity: Verbosity,
+    expand_params: bool,
+    inline_binds: bool,
+) -> Option<Vec<Line<'static>>> {
+    let detail = detail_lines(log, verbosity, inline_binds)?;
+    let (detail, expanded) = match structured_lines(&log.message, expand_params) {
+        Some((summary, expanded)) => (vec![summary], expanded),
+        None => (detail, Vec::new()),
+    };
+    let mut lines: Vec<Line<'static>> = detail
+        .into_iter()
+        .map(|line| {
+            highlight_search_matches(highlight_n_plus_one_tables(line, sql_info), detail_query)
+        })
+        .collect();
+    if let Some(note) = &log.gap_note
+        && let Some(last) = lines.last_mut()
+    {
+        last.push_span(Span::styled(
+            gap_note_text(strings, note),
+            Style::default().fg(Color::DarkGray),
+        ));
     }
+    if log.count() > 1
+        && let Some(last) = lines.last_mut()
+    {
+        last.push_span(Span::styled(
+            format!("  ×{}", log.count()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    lines.extend(
+        expanded
+            .into_iter()
+            .map(|line| highlight_search_matches(line, detail_query)),
+    );
+    Some(lines)
 }
 
-fn build_detail_content(app: &App) -> (Span<'static>, Text<'static>, usize) {
-    let Some(group) = app.state.selected_group() else {
-        return (Span::raw("Logs"), Text::from("Waiting for logs..."), 0);
-    };
-
-    let title_span = build_detail_title(app, group);
-    let sql_info = &group.sql_query_info;
-    let detail_query = &app.detail_search_query;
-    let simple_mode = app.simple_mode_enabled;
+/// How long after its request's first line an entry was logged, e.g.
+/// `+12ms`, or `+1.25s` from a second on.
+pub fn relative_time(offset: TimeDelta) -> String {
+    let ms = offset.num_milliseconds().max(0);
+    if ms < 1000 {
+        format!("+{}ms", ms)
+    } else {
+        format!("+{:.2}s", ms as f64 / 1000.0)
+    }
+}
 
-    let viewport_height = app.app_view.viewport_height(Panel::RequestDetail);
-    let scroll_offset = app.app_view.get_scroll_offset(Panel::RequestDetail);
+/// Leads `line` with `relative_time`, padded so the lines of a request
+/// keep their text in one column (`r`).
+pub fn prefix_relative_time(line: &mut Line<'static>, offset: TimeDelta) {
+    line.spans.insert(
+        0,
+        Span::styled(
+            format!("{:<8} ", relative_time(offset)),
+            Style::default().fg(Color::DarkGray),
+        ),
+    );
+}
 
-    // Entries are stored newest-first (push_front), so reverse for display
-    let mut text = Text::default();
-    let total = if simple_mode {
-        // Collect filtered lines once in chronological order
-        let all_lines: Vec<Line<'static>> = group
-            .entries
-            .iter()
-            .rev()
-            .filter_map(|log| format_simple_log_line(&log.message))
-            .collect();
-        let total = all_lines.len();
-        let start_idx = scroll_offset.min(total.saturating_sub(1));
-        let visible_count = viewport_height.min(total.saturating_sub(start_idx));
-
-        for line in all_lines.into_iter().skip(start_idx).take(visible_count) {
-            let line = highlight_n_plus_one_tables(line, sql_info);
-            let line = highlight_search_matches(line, detail_query);
-            text.extend(Text::from(line));
-        }
-        total
-    } else {
-        let total = group.entries.len();
-        let start_idx = scroll_offset.min(total.saturating_sub(1));
-        let visible_count = viewport_height.min(total.saturating_sub(start_idx));
-
-        for i in 0..visible_count {
-            let idx = total.saturating_sub(1).saturating_sub(start_idx + i);
-            if let Some(log) = group.entries.get(idx)
-                && let Some(line) = build_detail_log_line(log, sql_info, detail_query, false)
-            {
-                text.extend(Text::from(line));
+/// Leads each line of an entry read from a command's stderr with `gutter`,
+/// and colors the text its own highlighting left plain.
+pub fn mark_stderr(lines: &mut [Line<'static>], gutter: &str) {
+    let color = theme().error;
+    for line in lines {
+        for span in &mut line.spans {
+            if span.style.fg.is_none() {
+                span.style = span.style.fg(color);
             }
         }
-        total
-    };
-
-    (title_span, text, total)
+        line.spans.insert(0, Span::styled(format!("{} ", gutter), Style::default().fg(color)));
+    }
 }
 
-fn build_detail_scroll_info(app: &App, total_entries: usize) -> String {
-    if total_entries == 0 {
-        "0/0".to_string()
-    } else {
-        let scroll_offset = app.app_view.get_scroll_offset(Panel::RequestDetail);
-        let start_idx = (scroll_offset + INDEX_OFFSET).min(total_entries);
-        format!("{}-*/{}", start_idx.max(1), total_entries)
+/// Where the time before a line went, e.g. `[+800ms: 3 queries, 620ms SQL (78%)]`.
+pub fn gap_note_text(strings: &Strings, note: &GapNote) -> String {
+    if note.queries == 0 {
+        return fill(strings.gap_note_no_sql, &[&note.gap_ms]);
     }
+    fill(
+        strings.gap_note,
+        &[
+            &note.gap_ms,
+            &note.queries,
+            &format!("{:.0}", note.sql_ms),
+            &format!("{:.0}", note.sql_percent()),
+        ],
+    )
 }
 
-fn build_detail_bottom_bar(app: &App) -> Line<'static> {
-    let is_detail_search =
-        matches!(app.search_mode, Some(crate::app::SearchTarget::DetailLog));
-    let has_detail_query = !app.detail_search_query.is_empty();
-
-    if is_detail_search || has_detail_query {
-        let search_display = if is_detail_search {
-            format!(" /{}_ ", app.detail_search_query)
+fn build_detail_bottom_bar(view: &DetailView) -> Line<'_> {
+    if view.searching || !view.search_query.is_empty() {
+        let search_display = if view.searching {
+            format!(" /{}_ ", view.search_query)
         } else {
-            format!(" /{} ", app.detail_search_query)
+            format!(" /{} ", view.search_query)
         };
         Line::from(vec![
             Span::styled(search_display, Style::default().fg(Color::Yellow)),
             Span::styled(
-                format!("  {}", help_text(app)),
+                format!("  {}", view.help),
                 Style::default().fg(Color::DarkGray),
             ),
         ])
         .alignment(ratatui::layout::Alignment::Left)
     } else {
         Line::from(vec![Span::styled(
-            help_text(app),
+            view.help.as_str(),
             Style::default().fg(Color::DarkGray),
         )])
         .alignment(ratatui::layout::Alignment::Right)
     }
 }
 
-fn help_text(app: &App) -> String {
-    if app.copy_mode_enabled {
-        let panel_name = match app.app_view.focused_panel {
-            Panel::RequestList => "RequestList",
-            Panel::RequestDetail => "RequestDetail",
-            Panel::SqlInfo => "SqlInfo",
-        };
-        return format!(" COPY MODE [{}] (Tab: switch panel | m: exit) ", panel_name);
+/// Drawn in place of the panels while the terminal is smaller than
+/// `layout::MIN_WIDTH` x `layout::MIN_HEIGHT`.
+pub fn build_too_small_notice(app: &App, area: Rect) -> Paragraph<'static> {
+    let strings = app.config.locale.strings();
+    let text = fill(
+        strings.terminal_too_small,
+        &[&area.width, &area.height, &layout::MIN_WIDTH, &layout::MIN_HEIGHT],
+    );
+    // Roughly in the middle, leaving room for the text to wrap
+    let mut lines = vec![Line::raw(""); usize::from(area.height.saturating_sub(2) / 2)];
+    lines.push(Line::styled(text, Style::default().fg(Color::Yellow)));
+    Paragraph::new(lines)
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(text_measure::WRAP)
+}
+
+pub fn build_status_bar(view: &StatusBarView) -> Paragraph<'_> {
+    let mut spans = Vec::new();
+    for badge in &view.badges {
+        spans.push(Span::styled(
+            badge.as_str(),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(badge) = &view.sampling {
+        spans.push(Span::styled(
+            badge.as_str(),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
     }
-    if app.simple_mode_enabled {
-        " SIMPLE MODE (press 's' to exit) | j/k | Tab/Shift+Tab | Ctrl+c | m: copy | /: search"
-            .to_string()
+    spans.push(Span::styled(
+        format!(" {} ", view.segments.join(" │ ")),
+        Style::default().fg(Color::DarkGray),
+    ));
+    let paragraph = Paragraph::new(Line::from(spans));
+    if view.flashing {
+        paragraph.style(Style::default().bg(theme().error))
     } else {
-        " j/k | Ctrl+d/u | Tab/Shift+Tab | Ctrl+c | m: copy | s: simple | /: search".to_string()
+        paragraph
     }
 }
 
-pub fn build_sql_component(app: &App) -> Paragraph<'_> {
-    let border_style = match app.app_view.focused_panel {
-        Panel::SqlInfo => THEME.active_border,
-        _ => THEME.border,
+/// Aggregates over all finished requests: summary, duration histogram and request rate.
+pub fn render_stats_panel(f: &mut Frame, area: Rect, app: &App) {
+    let stats = app.matching_stats.as_ref().unwrap_or(&app.state.stats);
+    let strings = app.config.locale.strings();
+    let title = if app.matching_stats.is_some() {
+        strings.stats_title_matching
+    } else {
+        strings.stats_title
     };
 
-    let mut text = Text::default();
-    if let Some(group) = app.state.selected_group() {
-        let sql_info = &group.sql_query_info;
-
-        text.extend(Text::from(Line::from("")));
-
-        text.extend(Text::from(Line::from(vec![
-            Span::styled("SELECT: ", Style::default().fg(Color::Green)),
-            Span::raw(sql_info.query_count(QueryType::Select).to_string()),
-        ])));
-
-        text.extend(Text::from(Line::from(vec![
-            Span::styled("INSERT: ", Style::default().fg(Color::Yellow)),
-            Span::raw(sql_info.query_count(QueryType::Insert).to_string()),
-        ])));
-
-        text.extend(Text::from(Line::from(vec![
-            Span::styled("UPDATE: ", Style::default().fg(Color::Magenta)),
-            Span::raw(sql_info.query_count(QueryType::Update).to_string()),
-        ])));
-
-        text.extend(Text::from(Line::from(vec![
-            Span::styled("DELETE: ", Style::default().fg(Color::Red)),
-            Span::raw(sql_info.query_count(QueryType::Delete).to_string()),
-        ])));
-
-        if !sql_info.table_counts.is_empty() {
-            text.extend(Text::from(Line::from("")));
-            for (table, count) in sql_info.sorted_tables() {
-                let mut spans = vec![
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().border)
+        .title(Span::styled(
+            fill(title, &[&stats.total()]),
+            theme().default.style_with_modifier(Modifier::BOLD),
+        ));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(24),
+            Constraint::Min(30),
+            Constraint::Percentage(40),
+        ])
+        .split(inner);
+
+    let format_ms = |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{}ms", ms));
+    let summary = Text::from(vec![
+        Line::from(vec![
+            Span::styled("2xx: ", theme().success.style()),
+            Span::raw(stats.count(StatusType::Success).to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("4xx: ", theme().warning.style()),
+            Span::raw(stats.count(StatusType::Warning).to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("5xx: ", theme().error.style()),
+            Span::raw(stats.count(StatusType::Error).to_string()),
+        ]),
+        Line::from(fill(
+            strings.status_other,
+            &[&stats.count(StatusType::Unknown)],
+        )),
+        // Only with codes mapped to info, in place of the spacer
+        match stats.count(StatusType::Info) {
+            0 => Line::from(""),
+            info => Line::from(vec![
+                Span::styled("info: ", theme().info.style()),
+                Span::raw(info.to_string()),
+            ]),
+        },
+        Line::from(format!("p50: {}", format_ms(stats.percentile(0.50)))),
+        Line::from(format!("p95: {}", format_ms(stats.percentile(0.95)))),
+        Line::from(format!("max: {}", format_ms(stats.max_duration()))),
+    ]);
+    f.render_widget(
+        Paragraph::new(summary).block(Block::default().padding(Padding::horizontal(1))),
+        columns[0],
+    );
+
+    let histogram = stats.histogram();
+    let bar_chart = BarChart::default()
+        .block(Block::default().title(strings.duration))
+        .data(histogram.as_slice())
+        .bar_width(4)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(bar_chart, columns[1]);
+
+    let rate = stats.requests_per_minute();
+    let current_rate = rate.last().copied().unwrap_or(0);
+    let sparkline = Sparkline::default()
+        .block(Block::default().title(fill(strings.request_rate, &[&current_rate])))
+        .data(&rate)
+        .style(Style::default().fg(Color::Green));
+    f.render_widget(sparkline, columns[2]);
+}
+
+// Cells for the longest step labels, and for durations such as `1234.5ms`
+const TIMELINE_LABEL_MAX_WIDTH: usize = 32;
+const TIMELINE_DURATION_WIDTH: usize = 9;
+// Border and padding on either side
+const TIMELINE_FRAME_WIDTH: u16 = 4;
+
+/// Cells of the axis in a timeline panel `width` cells wide, when it spans
+/// the panel as it does for the requests on screen.
+pub fn timeline_axis_width(width: u16) -> usize {
+    width.saturating_sub(TIMELINE_FRAME_WIDTH) as usize
+}
+
+/// The timeline strip, `width` cells wide including the borders. Steps of a
+/// request get a row each with their label and duration before the bar;
+/// requests are bars in lanes across the whole width.
+pub fn build_timeline_panel(view: &TimelineView, width: u16) -> Paragraph<'_> {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().border)
+        .padding(Padding::horizontal(1))
+        .title(Span::styled(
+            view.title.as_str(),
+            theme().default.style_with_modifier(Modifier::BOLD),
+        ));
+    if let Some(note) = view.note {
+        return Paragraph::new(Line::styled(note, Style::default().fg(Color::DarkGray)))
+            .block(block);
+    }
+
+    let inner_width = timeline_axis_width(width);
+    let label_width = match view.rows {
+        TimelineRows::Steps { .. } => {
+            (inner_width / 3).min(TIMELINE_LABEL_MAX_WIDTH) + TIMELINE_DURATION_WIDTH + 1
+        }
+        TimelineRows::Requests(_) => 0,
+    };
+    let axis_width = inner_width.saturating_sub(label_width);
+    let (axis_start, axis_end) = &view.axis;
+    let mut lines = vec![Line::styled(
+        format!(
+            "{}{}{:>width$}",
+            " ".repeat(label_width),
+            axis_start,
+            axis_end,
+            width = axis_width.saturating_sub(text_measure::width(axis_start)),
+        ),
+        Style::default().fg(Color::DarkGray),
+    )];
+
+    match &view.rows {
+        TimelineRows::Steps { total_ms, steps } => {
+            let cell = |ms: f64| ms / total_ms.max(f64::MIN_POSITIVE) * axis_width as f64;
+            for step in steps {
+                let color = match step.kind {
+                    StepKind::Sql => Color::Green,
+                    StepKind::Render => Color::Magenta,
+                    StepKind::Http => Color::Yellow,
+                };
+                let start = (cell(step.start_ms) as usize).min(axis_width.saturating_sub(1));
+                let end = (cell(step.start_ms + step.duration_ms).ceil() as usize)
+                    .clamp(start + 1, axis_width.max(start + 1));
+                lines.push(Line::from(vec![
                     Span::styled(
-                        format!("{}: ", table),
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
+                        text_measure::fit(&step.label, label_width - TIMELINE_DURATION_WIDTH - 1),
+                        Style::default().fg(color),
                     ),
-                    Span::raw(count.to_string()),
-                ];
-                if sql_info.is_n_plus_one(table) {
-                    spans.push(Span::styled(
-                        " N+1?",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ));
+                    Span::styled(
+                        format!(
+                            "{:>width$} ",
+                            format!("{:.1}ms", step.duration_ms),
+                            width = TIMELINE_DURATION_WIDTH
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(" ".repeat(start)),
+                    Span::styled("█".repeat(end - start), Style::default().fg(color)),
+                ]));
+            }
+        }
+        TimelineRows::Requests(bars) => {
+            let lanes = bars.iter().map(|bar| bar.lane + 1).max().unwrap_or(0);
+            for lane in 0..lanes {
+                let mut lane_bars: Vec<&RequestBar> =
+                    bars.iter().filter(|bar| bar.lane == lane).collect();
+                lane_bars.sort_by_key(|bar| bar.cells.start);
+                let mut spans = Vec::new();
+                let mut used = 0;
+                for bar in lane_bars {
+                    let color = match bar.status {
+                        StatusType::Success => theme().success,
+                        StatusType::Info => theme().info,
+                        StatusType::Warning => theme().warning,
+                        StatusType::Error => theme().error,
+                        StatusType::Unknown => Color::DarkGray,
+                    };
+                    let mut style = Style::default().fg(color);
+                    if !bar.selected {
+                        style = style.add_modifier(Modifier::DIM);
+                    }
+                    spans.push(Span::raw(" ".repeat(bar.cells.start - used)));
+                    spans.push(Span::styled("█".repeat(bar.cells.len()), style));
+                    used = bar.cells.end;
                 }
-                text.extend(Text::from(Line::from(spans)));
+                lines.push(Line::from(spans));
             }
         }
     }
+    Paragraph::new(lines).block(block)
+}
+
+/// List index of the request whose bar in the timeline panel drawn in `area`
+/// is at `(x, y)`.
+pub fn timeline_request_at(view: &TimelineView, area: Rect, x: u16, y: u16) -> Option<usize> {
+    let TimelineRows::Requests(bars) = &view.rows else {
+        return None;
+    };
+    // Below the border and the axis, right of the border and padding
+    let lane = y.checked_sub(area.y + 2)? as usize;
+    let cell = x.checked_sub(area.x + TIMELINE_FRAME_WIDTH / 2)? as usize;
+    bars.iter()
+        .find(|bar| bar.lane == lane && bar.cells.contains(&cell))
+        .map(|bar| bar.index)
+}
+
+pub fn build_about_popup(app: &App) -> Paragraph<'_> {
+    let strings = app.config.locale.strings();
+    let mut lines: Vec<Line> = version::about_lines().into_iter().map(Line::from).collect();
+    lines.push(Line::from(""));
+    lines.push(match &app.update_notice {
+        None => Line::from(strings.update_check_off),
+        Some(notice) => match notice.get() {
+            Some(latest) => Line::styled(
+                fill(strings.update_available, &[latest]),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            None => Line::from(strings.update_none),
+        },
+    });
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(strings.about_title);
+    Paragraph::new(lines).block(block)
+}
+
+/// The statement explained and its plan, or how fetching it went.
+pub fn build_explain_popup<'a>(app: &App, explain: &'a Explain) -> Paragraph<'a> {
+    let strings = app.config.locale.strings();
+    let mut lines: Vec<Line> = sql_format::format(&explain.sql)
+        .lines()
+        .map(|line| Line::styled(line.to_string(), Style::default().fg(Color::DarkGray)))
+        .collect();
+    lines.push(Line::from(""));
+    if explain.confirming_analyze {
+        lines.push(Line::styled(
+            strings.explain_confirm,
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(""));
+    }
+    match explain.plan() {
+        None => lines.push(Line::from(strings.explain_running)),
+        Some(Ok(plan)) => lines.extend(plan.lines().map(Line::from)),
+        Some(Err(error)) => lines.extend(
+            error
+                .lines()
+                .map(|line| Line::styled(line, theme().error.style())),
+        ),
+    }
 
-    let scroll_info = if let Some(group) = app.state.selected_group() {
-        let total_queries = group.sql_query_info.total_queries();
-        if total_queries == 0 {
-            "0/0".to_string()
-        } else {
-            total_queries.to_string()
+    let statement = if explain.analyze {
+        "EXPLAIN ANALYZE"
+    } else {
+        "EXPLAIN"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(fill(
+            if app.supports_analyze() {
+                strings.explain_title
+            } else {
+                strings.explain_title_no_analyze
+            },
+            &[&statement],
+        ));
+    Paragraph::new(lines).block(block).scroll((explain.scroll, 0))
+}
+
+/// The full text of the line under the detail cursor, wrapped. A query is
+/// laid out by `sql_format`, below the rest of the line (`Post Load (0.4ms)`).
+pub fn build_line_popup(app: &App, position: usize, text: &str) -> Paragraph<'static> {
+    let strings = app.config.locale.strings();
+    let text = match sql_info::statement(text) {
+        Some(statement) => {
+            let sql = sql_format::format(&sql_format::inline_binds(statement));
+            match text[..text.find(statement).unwrap_or(0)].trim() {
+                "" => sql,
+                prefix => format!("{}\n\n{}", prefix, sql),
+            }
         }
+        None => text.to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(fill(strings.line_popup_title, &[&(position + 1)]));
+    // Untrimmed, to keep the indentation of queries
+    Paragraph::new(text).block(block).wrap(Wrap { trim: false })
+}
+
+/// The selected request's summary line and first error, for the preview
+/// next to the request list, with the width the lines need.
+pub fn build_list_preview(app: &App) -> Option<(Paragraph<'_>, u16)> {
+    let group = app.state.selected_group()?;
+    let mut lines = Vec::new();
+    if let Some(error) = group.first_error_entry() {
+        let style = theme().error;
+        lines.extend(error.rendered.full.iter().map(|line| line.clone().style(style)));
+    }
+    if let Some(summary) = group.summary_entry() {
+        let style = group.status_type.to_color();
+        lines.extend(summary.rendered.full.iter().map(|line| line.clone().style(style)));
+    }
+    let content_width = lines.iter().map(Line::width).max()?;
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().border);
+    let paragraph = Paragraph::new(lines).block(block).wrap(text_measure::WRAP);
+    Some((paragraph, content_width as u16 + 2))
+}
+
+/// Id, times, duration and line count of the selected request.
+pub fn build_request_info_popup(app: &App) -> Option<Paragraph<'_>> {
+    let request_id = app.state.selected_request_id()?;
+    let group = app.state.selected_group()?;
+    let strings = app.config.locale.strings();
+    let times = app.config.times();
+    let format_time = |timestamp| times.format_dated(timestamp, Precision::Millis);
+
+    // Entries are stored newest-first
+    let last_timestamp = group.entries.front().map_or(group.first_timestamp, |e| e.timestamp);
+    let mut ended = format_time(&last_timestamp);
+    if !group.finished {
+        ended.push_str(&format!(" ({})", strings.status_running));
+    }
+    let duration_ms = group.duration_ms.unwrap_or_else(|| {
+        (last_timestamp - group.first_timestamp)
+            .num_milliseconds()
+            .max(0) as u64
+    });
+    let rows = [
+        (strings.request_info_id, request_id.clone()),
+        (strings.request_info_started, format_time(&group.first_timestamp)),
+        (strings.request_info_ended, ended),
+        (strings.request_info_duration, format!("{}ms", duration_ms)),
+        (
+            strings.request_info_lines,
+            group.entries.len().max(group.journal_refs.len()).to_string(),
+        ),
+    ];
+    let label_width = rows
+        .iter()
+        .map(|(label, _)| text_measure::width(label))
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{}  ", text_measure::fit(label, label_width)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(strings.request_info_title);
+    Some(Paragraph::new(lines).block(block))
+}
+
+/// The note being written for the selected request (`n`).
+pub fn build_note_popup(app: &App) -> Paragraph<'_> {
+    let strings = app.config.locale.strings();
+    let lines = vec![
+        Line::from(format!("{}_", app.note_query)),
+        Line::raw(""),
+        Line::styled(strings.note_hint, Style::default().fg(Color::DarkGray)),
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(strings.note_title);
+    Paragraph::new(lines).block(block).wrap(text_measure::WRAP)
+}
+
+/// The actions `A` offers for every request the filter matches.
+pub fn build_bulk_actions_popup(app: &App) -> Paragraph<'_> {
+    let strings = app.config.locale.strings();
+    let title = if app.state.visual_anchor.is_some() {
+        strings.bulk_title_selected
     } else {
-        "0/0".to_string()
+        strings.bulk_title
     };
+    let lines: Vec<Line> = [
+        ('e', strings.bulk_export),
+        ('d', strings.bulk_delete),
+        ('b', strings.bulk_star),
+        ('s', strings.bulk_stats),
+        ('m', strings.bulk_merge),
+    ]
+    .into_iter()
+    .map(|(key, label)| {
+        Line::from(vec![
+            Span::styled(format!("{}  ", key), Style::default().fg(Color::Yellow)),
+            Span::raw(label),
+        ])
+    })
+    .collect();
 
-    let borders = if app.copy_mode_enabled {
-        Borders::TOP | Borders::BOTTOM
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(fill(title, &[&app.bulk_request_ids().len()]));
+    Paragraph::new(lines).block(block)
+}
+
+/// Every column of the request list, shown ones first, for `C`.
+pub fn build_column_chooser_popup(app: &App, cursor: usize) -> Paragraph<'_> {
+    let strings = app.config.locale.strings();
+    let lines: Vec<Line> = columns::chooser_order(&app.columns)
+        .into_iter()
+        .enumerate()
+        .map(|(row, column)| {
+            let shown = app.columns.contains(&column);
+            let label = match column {
+                Column::Time => strings.column_time,
+                Column::Duration => strings.column_duration,
+                Column::Status => strings.column_status,
+                Column::Method => strings.column_method,
+                Column::Action => strings.column_action,
+                Column::Sql => strings.column_sql,
+                Column::Lines => strings.column_lines,
+                Column::Source => strings.column_source,
+            };
+            let style = if row == cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else if shown {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(vec![
+                Span::styled(
+                    if shown { "[x] " } else { "[ ] " },
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(format!("{:<9} {}", column.name(), label), style),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::new(1, 1, 1, 1))
+        .title(strings.columns_title);
+    Paragraph::new(lines).block(block)
+}
+
+pub fn build_toast(toast: &Toast) -> Paragraph<'_> {
+    let color = match toast.level {
+        ToastLevel::Info => theme().active_border,
+        ToastLevel::Error => theme().error,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(color);
+    Paragraph::new(Line::styled(toast.message.as_str(), Style::default().fg(color))).block(block)
+}
+
+pub fn build_notifications_popup(app: &App) -> Paragraph<'_> {
+    let strings = app.config.locale.strings();
+    let times = app.config.times();
+    let mut lines: Vec<Line> = app
+        .toasts
+        .history()
+        .map(|toast| {
+            let style = match toast.level {
+                ToastLevel::Info => Style::default(),
+                ToastLevel::Error => Style::default().fg(theme().error),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{} ",
+                        times.format(&toast.created_at.fixed_offset(), Precision::Seconds)
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(toast.message.as_str(), style),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(strings.notifications_empty));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .padding(Padding::horizontal(1))
+        .title(strings.notifications_title);
+    Paragraph::new(lines).block(block)
+}
+
+pub fn build_tutorial_popup<'a>(app: &'a App, tutorial: &Tutorial) -> Paragraph<'a> {
+    let strings = app.config.locale.strings();
+    let text = strings.tutorial_steps.get(tutorial.step).copied().unwrap_or_default();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .padding(Padding::new(1, 1, 1, 0))
+        .title(fill(
+            strings.tutorial_title,
+            &[&(tutorial.step + 1), &Tutorial::step_count()],
+        ))
+        .title_bottom(Line::from(strings.tutorial_footer).alignment(ratatui::layout::Alignment::Right));
+    Paragraph::new(text).block(block).wrap(Wrap { trim: false })
+}
+
+pub fn build_load_progress(app: &App) -> Option<Gauge<'_>> {
+    let progress = app.loading_progress()?;
+
+    let strings = app.config.locale.strings();
+    let label = fill(
+        strings.loaded,
+        &[
+            &format_bytes(progress.bytes_read()),
+            &format_bytes(progress.total_bytes),
+            &app.state.log_group_count(),
+        ],
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme().active_border)
+        .title(fill(strings.loading, &[&progress.file_name]));
+
+    Some(
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::default().fg(Color::Cyan).bg(Color::DarkGray))
+            .ratio(progress.ratio())
+            .label(label),
+    )
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
     } else {
-        Borders::ALL
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Cache hit count with its share of SELECT lookups, e.g. `3 (75% hit)`.
+pub fn cache_summary(strings: &Strings, sql_info: &SqlQueryInfo) -> String {
+    match sql_info.cache_hit_ratio() {
+        Some(ratio) => format!(
+            "{} ({})",
+            sql_info.cache_hits,
+            fill(strings.cache_hit_ratio, &[&format!("{:.0}", ratio * 100.0)])
+        ),
+        None => "0".to_string(),
+    }
+}
+
+/// The lines of the SQL panel, each with the row a click on it jumps to.
+pub fn sql_lines(strings: &Strings, group: &LogGroup) -> Vec<(Line<'static>, Option<SqlRow>)> {
+    let mut lines = Vec::new();
+    let sql_info = &group.sql_query_info;
+    lines.push((Line::from(""), None));
+
+    for query_type in QueryType::DISPLAY_ORDER {
+        let color = match query_type {
+            QueryType::Select => Color::Green,
+            QueryType::Insert => Color::Yellow,
+            QueryType::Update => Color::Magenta,
+            QueryType::Delete => Color::Red,
+        };
+        let line = Line::from(vec![
+            Span::styled(format!("{}: ", query_type.label()), Style::default().fg(color)),
+            Span::raw(sql_info.query_count(query_type).to_string()),
+        ]);
+        lines.push((line, Some(SqlRow::QueryType(query_type))));
+    }
+    let line = Line::from(vec![
+        Span::styled("CACHE: ", Style::default().fg(Color::DarkGray)),
+        Span::raw(cache_summary(strings, sql_info)),
+    ]);
+    lines.push((line, Some(SqlRow::Cache)));
+
+    if !sql_info.table_counts.is_empty() {
+        lines.push((Line::from(""), None));
+        for (table, count) in sql_info.sorted_tables() {
+            let mut spans = vec![
+                Span::styled(
+                    format!("{}: ", table),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(count.to_string()),
+            ];
+            if sql_info.is_n_plus_one(table) {
+                spans.push(Span::styled(
+                    " N+1?",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            lines.push((Line::from(spans), Some(SqlRow::Table(table.to_string()))));
+        }
+    }
+
+    if !group.render_info.is_empty() {
+        lines.push((Line::from(""), None));
+        lines.push((Line::from(Span::styled(
+            format!("{}:", strings.views),
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )), None));
+        for row in view_rows(&group.render_info) {
+            lines.push((Line::from(row), None));
+        }
+    }
+
+    let sections = [
+        (
+            strings.http_calls,
+            group.http_info.total_calls(),
+            http_rows(&group.http_info),
+            Color::Magenta,
+        ),
+        (
+            strings.cache_operations,
+            group.cache_info.cache_count(),
+            timing_rows(&cache_info::by_total_time(&group.cache_info.cache)),
+            Color::Cyan,
+        ),
+        (
+            "Redis",
+            group.cache_info.redis_count(),
+            timing_rows(&cache_info::by_total_time(&group.cache_info.redis)),
+            Color::Red,
+        ),
+    ];
+    for (heading, count, rows, color) in sections {
+        if rows.is_empty() {
+            continue;
+        }
+        lines.push((Line::from(""), None));
+        lines.push((Line::from(Span::styled(
+            format!("{} ({}):", heading, count),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )), None));
+        for row in rows {
+            lines.push((Line::from(row), None));
+        }
+    }
+
+    if !group.memory_info.is_empty() {
+        lines.push((Line::from(""), None));
+        lines.push((Line::from(Span::styled(
+            format!("{}:", strings.memory),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )), None));
+        for row in memory_rows(strings, &group.memory_info) {
+            lines.push((Line::from(row), None));
+        }
+    }
+lines
+}
+
+/// Which row line `row` of the SQL panel shows, counting the rows wrapped
+/// lines take when `wrap_width` is set.
+pub fn sql_row_at(
+    strings: &Strings,
+    group: &LogGroup,
+    row: usize,
+    wrap_width: Option<usize>,
+) -> Option<SqlRow> {
+    let mut top = 0;
+    for (line, target) in sql_lines(strings, group) {
+        top += wrap_width.map_or(1, |width| text_measure::wrapped_rows(&line, width));
+        if row < top {
+            return target;
+        }
+    }
+    None
+}
+
+pub fn build_sql_component<'a>(view: &SqlView<'a>) -> Paragraph<'a> {
+    let text = view.request.map_or_else(Text::default, |group| {
+        Text::from(
+            sql_lines(view.strings, group)
+                .into_iter()
+                .map(|(line, _)| line)
+                .collect::<Vec<_>>(),
+        )
+    });
+
+    let scroll_info = match view.request {
+        Some(group) if group.sql_query_info.total_queries() > 0 => {
+            group.sql_query_info.total_queries().to_string()
+        }
+        _ => "0/0".to_string(),
     };
 
     let title_text = format!("[{}] ", scroll_info);
     let block = Block::default()
-        .borders(borders)
-        .border_style(border_style)
+        .borders(panel_borders(view.chrome))
+        .border_style(panel_border_color(view.chrome))
         .padding(Padding::new(1, 1, 0, 0))
         .title(title_text);
 
-    let sql_scroll_offset = app.app_view.get_scroll_offset(Panel::SqlInfo);
-
     Paragraph::new(text)
         .block(block)
-        .wrap(Wrap { trim: true })
-        .scroll((sql_scroll_offset as u16, 0))
+        .wrap(text_measure::WRAP)
+        .scroll((view.scroll as u16, 0))
+}
+
+/// `total ×count template` rows of the Views section, slowest first.
+pub fn view_rows(render_info: &RenderInfo) -> Vec<String> {
+    render_info
+        .by_total_time()
+        .into_iter()
+        .map(|(template, timing)| {
+            format!(
+                "{:.1}ms ×{} {}",
+                timing.total_ms, timing.count, template
+            )
+        })
+        .collect()
+}
+
+/// `total ×count host` rows of the External HTTP section, slowest first.
+/// Hosts whose client does not log times show only the count.
+pub fn http_rows(http_info: &HttpInfo) -> Vec<String> {
+    http_info
+        .by_total_time()
+        .into_iter()
+        .map(|(host, timing)| timed_row(host, timing.count, timing.timed, timing.total_ms))
+        .collect()
+}
+
+/// `total ×count name` rows of the Cache and Redis sections.
+pub fn timing_rows(commands: &[(&String, &CommandTiming)]) -> Vec<String> {
+    commands
+        .iter()
+        .map(|(name, timing)| timed_row(name, timing.count, timing.timed, timing.total_ms))
+        .collect()
+}
+
+/// Rows of the Memory section, for the figures the request logged.
+pub fn memory_rows(strings: &Strings, memory_info: &MemoryInfo) -> Vec<String> {
+    let mut rows = Vec::new();
+    if let Some(allocations) = memory_info.allocations {
+        let value = match memory_info.allocated_bytes {
+            Some(bytes) => format!("{} ({})", allocations, format_bytes(bytes)),
+            None => allocations.to_string(),
+        };
+        rows.push(fill(strings.memory_allocations, &[&value]));
+    }
+    if let Some(ms) = memory_info.gc_ms {
+        rows.push(format!("GC: {:.1}ms", ms));
+    }
+    if let Some(bytes) = memory_info.memory_bytes {
+        let value = match memory_info.pid {
+            Some(pid) => format!("{} (PID {})", format_bytes(bytes), pid),
+            None => format_bytes(bytes),
+        };
+        rows.push(fill(strings.memory_process, &[&value]));
+    }
+    if let Some(delta) = memory_info.delta_bytes {
+        let sign = if delta < 0 { "-" } else { "+" };
+        let value = format!("{}{}", sign, format_bytes(delta.unsigned_abs()));
+        rows.push(fill(strings.memory_delta, &[&value]));
+    }
+    if let Some(count) = memory_info.instantiated {
+        rows.push(fill(strings.memory_instantiated, &[&count]));
+    }
+    rows
+}
+
+fn timed_row(name: &str, count: usize, timed: usize, total_ms: f64) -> String {
+    if timed == 0 {
+        format!("×{} {}", count, name)
+    } else {
+        format!("{:.1}ms ×{} {}", total_ms, count, name)
+    }
 }
 
 fn highlight_n_plus_one_tables<'a>(line: Line<'a>, sql_info: &SqlQueryInfo) -> Line<'a> {
@@ -483,6 +1480,15 @@ fn highlight_n_plus_one_tables<'a>(line: Line<'a>, sql_info: &SqlQueryInfo) -> L
     Line::from(new_spans)
 }
 
+/// Marks the entry jumped to from the SQL panel. `position` is chronological.
+fn highlight_entry(line: Line<'static>, highlighted: bool) -> Line<'static> {
+    if highlighted {
+        line.patch_style(Style::default().bg(Color::DarkGray))
+    } else {
+        line
+    }
+}
+
 fn highlight_search_matches<'a>(line: Line<'a>, query: &str) -> Line<'a> {
     if query.is_empty() {
         return line;
@@ -533,3 +1539,335 @@ fn highlight_search_matches<'a>(line: Line<'a>, query: &str) -> Line<'a> {
 
     Line::from(new_spans)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint;
+    use crate::headless::buffer_text;
+    use crate::i18n::Locale;
+    use crate::icons::IconSet;
+    use crate::log_parser;
+    use crate::view_model::{DetailEntry, RequestRow};
+    use crate::waterfall::Step;
+    use ratatui::{buffer::Buffer, widgets::Widget};
+    use std::path::Path;
+    use std::{env, fs};
+
+    /// Compares what `widget` draws in a `width`×`height` area with
+    /// `src/snapshots/<name>.txt`. Run with `UPDATE_SNAPSHOTS=1` to rewrite
+    /// the file after an intended change.
+    fn assert_snapshot(name: &str, widget: impl Widget, width: u16, height: u16) {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+        widget.render(buffer.area, &mut buffer);
+        let actual: String = buffer_text(&buffer)
+            .lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect();
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.txt", name));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!("No snapshot at {}, run with UPDATE_SNAPSHOTS=1", path.display())
+        });
+        assert_eq!(actual, expected, "{} differs from its snapshot", name);
+    }
+
+    fn group(lines: &[&str]) -> LogGroup {
+        let mut entries = lines.iter().map(|line| log_parser::parse(line, None).unwrap());
+        let mut group = LogGroup::new(entries.next().unwrap());
+        entries.for_each(|entry| group.add_entry(entry));
+        group
+    }
+
+    fn row(number: usize, title: &str, duration_ms: Option<u64>, status: StatusType) -> RequestRow<'_> {
+        RequestRow {
+            number,
+            time: "12:00".to_string(),
+            duration_ms,
+            source: None,
+            kind: GroupKind::Request,
+            title: title.to_string(),
+            title_path: None,
+            action: None,
+            line_count: 5,
+            sql_count: 2,
+            status,
+            status_code: None,
+            finished: duration_ms.is_some(),
+            starred: false,
+            noted: false,
+            duplicate: false,
+            pinned: false,
+            matched: Vec::new(),
+            selected: false,
+            marked: false,
+            header: None,
+        }
+    }
+
+    #[test]
+    fn test_request_list_snapshot() {
+        let mut rows = vec![
+            row(1, r#"GET "/posts" for 127.0.0.1"#, Some(12), StatusType::Success),
+            row(2, r#"POST "/posts" for 127.0.0.1"#, Some(3400), StatusType::Error),
+            row(3, r#"GET "/posts/1" for 127.0.0.1"#, None, StatusType::Unknown),
+            row(4, "", None, StatusType::Unknown),
+        ];
+        rows[0].selected = true;
+        rows[1].starred = true;
+        rows[1].pinned = true;
+        rows[2].noted = true;
+        rows[1].matched = vec![0, 1];
+        rows[3].kind = GroupKind::Unassigned;
+        let view = ListView::Requests(RequestListView {
+            chrome: PanelChrome {
+                focused: true,
+                copy_mode: false,
+            },
+            title: "[1-4/4] /po".to_string(),
+            prompt: Some(" /po_ ".to_string()),
+            rows,
+            number_width: Some(1),
+            source_width: 0,
+            action_width: 0,
+            width: 44,
+            columns: &Column::DEFAULT,
+            icons: IconSet::Ascii,
+            strings: Locale::En.strings(),
+        });
+        assert_snapshot("request_list", build_list_component(&view), 48, 9);
+    }
+
+    #[test]
+    fn test_request_list_columns_snapshot() {
+        let mut rows = vec![
+            row(1, r#"GET "/posts" for 127.0.0.1"#, Some(12), StatusType::Success),
+            row(2, "PostsJob", Some(40), StatusType::Success),
+        ];
+        rows[0].status_code = Some(200);
+        rows[0].action = Some("Posts#index".to_string());
+        rows[0].matched = vec![4, 5];
+        rows[1].kind = GroupKind::Job;
+        let view = ListView::Requests(RequestListView {
+            chrome: PanelChrome {
+                focused: true,
+                copy_mode: false,
+            },
+            title: "[1-2/2]".to_string(),
+            prompt: None,
+            rows,
+            number_width: None,
+            source_width: 0,
+            action_width: 11,
+            width: 44,
+            columns: &[Column::Status, Column::Method, Column::Action, Column::Sql, Column::Lines],
+            icons: IconSet::Ascii,
+            strings: Locale::En.strings(),
+        });
+        assert_snapshot("request_list_columns", build_list_component(&view), 48, 6);
+    }
+
+    #[test]
+    fn test_request_list_title_snapshot() {
+        let title = "GET /admin/posts/42/comments?page=3 → Admin::CommentsController#index";
+        let mut rows = vec![row(1, title, Some(12), StatusType::Success)];
+        rows[0].title_path = Some(4..35);
+        // `index`, which the path is shortened around
+        rows[0].matched = vec![64, 65];
+        let view = ListView::Requests(RequestListView {
+            chrome: PanelChrome {
+                focused: true,
+                copy_mode: false,
+            },
+            title: "[1/1]".to_string(),
+            prompt: None,
+            rows,
+            number_width: None,
+            source_width: 0,
+            action_width: 0,
+            width: 54,
+            columns: &[Column::Method, Column::Duration],
+            icons: IconSet::Ascii,
+            strings: Locale::En.strings(),
+        });
+        assert_snapshot("request_list_title", build_list_component(&view), 58, 5);
+    }
+
+    #[test]
+    fn test_endpoint_list_snapshot() {
+        let request = |path: &str, completed: &str| {
+            group(&[
+                &format!(r#"[{0}] Started GET "{0}" for 127.0.0.1"#, path),
+                &format!("[{}] Completed {}", path, completed),
+            ])
+        };
+        let groups = [
+            request("/posts/1", "200 OK in 12ms"),
+            request("/posts/2", "500 Internal Server Error in 30ms"),
+            request("/comments", "200 OK in 4ms"),
+        ];
+        let view = ListView::Endpoints(EndpointListView {
+            chrome: PanelChrome::default(),
+            title: "[2] Endpoints".to_string(),
+            rows: endpoint::summarize(&groups),
+            cursor: 1,
+            endpoint_width: 20,
+        });
+        assert_snapshot("endpoint_list", build_list_component(&view), 42, 6);
+    }
+
+    #[test]
+    fn test_detail_snapshot() {
+        let view = DetailView {
+            chrome: PanelChrome::default(),
+            title: "[1-3/3] GET /posts ".to_string(),
+            status: StatusType::Success,
+            icons: IconSet::Ascii,
+            entries: vec![
+                DetailEntry {
+                    lines: vec![Line::from(r#"Started GET "/posts" for 127.0.0.1"#)],
+                    highlighted: false,
+                },
+                DetailEntry {
+                    lines: vec![Line::from(
+                        r#"Post Load (0.5ms)  SELECT "posts".* FROM "posts" WHERE "posts"."published" = TRUE"#,
+                    )],
+                    highlighted: true,
+                },
+                DetailEntry {
+                    lines: vec![Line::from("Completed 200 OK in 5ms")],
+                    highlighted: false,
+                },
+            ],
+            wrap: true,
+            search_query: "posts".to_string(),
+            searching: false,
+            help: "j/k".to_string(),
+        };
+        assert_snapshot("detail", build_detail_component(&view), 50, 9);
+    }
+
+    #[test]
+    fn test_sql_snapshot() {
+        let group = group(&[
+            r#"[req-1] Started GET "/posts" for 127.0.0.1"#,
+            r#"[req-1]   Post Load (0.5ms)  SELECT "posts".* FROM "posts""#,
+            r#"[req-1]   User Load (0.2ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1"#,
+            r#"[req-1]   Rendered posts/index.html.erb (Duration: 2.1ms | Allocations: 120)"#,
+            "[req-1] Completed 200 OK in 5ms",
+        ]);
+        let view = SqlView {
+            chrome: PanelChrome::default(),
+            request: Some(&group),
+            scroll: 0,
+            strings: Locale::En.strings(),
+        };
+        assert_snapshot("sql", build_sql_component(&view), 36, 16);
+    }
+
+    #[test]
+    fn test_timeline_snapshot() {
+        let step = |kind, label: &str, start_ms, duration_ms| Step {
+            kind,
+            label: label.to_string(),
+            start_ms,
+            duration_ms,
+            entry: 0,
+        };
+        let view = TimelineView {
+            title: "[3 steps in 100ms] Timeline (L: all requests) ".to_string(),
+            axis: ("0ms".to_string(), "100ms".to_string()),
+            rows: TimelineRows::Steps {
+                total_ms: 100.0,
+                steps: vec![
+                    step(StepKind::Sql, "Post Load", 6.0, 4.0),
+                    step(StepKind::Http, "GET https://api.example.com/feed", 20.0, 50.0),
+                    step(StepKind::Render, "posts/index.html.erb", 74.5, 15.5),
+                ],
+            },
+            note: None,
+        };
+        assert_snapshot("timeline", build_timeline_panel(&view, 80), 80, 6);
+    }
+
+    #[test]
+    fn test_all_requests_timeline_snapshot() {
+        let bar = |index, lane, cells, status| RequestBar {
+            index,
+            lane,
+            cells,
+            status,
+            selected: index == 1,
+        };
+        let view = TimelineView {
+            title: "[3 requests in 100ms] Timeline of the requests on screen (L: close) ".to_string(),
+            axis: ("12:00:00.000".to_string(), "12:00:00.100".to_string()),
+            rows: TimelineRows::Requests(vec![
+                bar(0, 0, 0..30, StatusType::Success),
+                bar(1, 1, 10..60, StatusType::Error),
+                bar(2, 0, 40..76, StatusType::Warning),
+            ]),
+            note: None,
+        };
+        let area = Rect::new(0, 0, 80, 5);
+        assert_snapshot("timeline_all_requests", build_timeline_panel(&view, 80), 80, 5);
+        assert_eq!(timeline_request_at(&view, area, 2, 2), Some(0));
+        assert_eq!(timeline_request_at(&view, area, 50, 3), Some(1));
+        assert_eq!(timeline_request_at(&view, area, 35, 2), None);
+        assert_eq!(timeline_request_at(&view, area, 50, 1), None);
+    }
+
+    #[test]
+    fn test_status_bar_snapshot() {
+        let view = StatusBarView {
+            badges: vec![" PAUSED (3) ".to_string()],
+            sampling: None,
+            segments: vec!["120 lines/s".to_string(), "14 requests".to_string()],
+            flashing: false,
+        };
+        assert_snapshot("status_bar", build_status_bar(&view), 60, 1);
+    }
+
+    #[test]
+    fn test_sql_row_at() {
+        let mut group = group(&["[req-1] Started GET \"/\" for 127.0.0.1"]);
+        group.sql_query_info = sql_info::parse_sql_from_logs(&[
+            "SQL (0.5ms) SELECT * FROM users WHERE id = 1",
+            "SQL (0.8ms) INSERT INTO order_line_item_adjustments (name) VALUES ('Test')",
+        ]);
+        let strings = Locale::En.strings();
+        let row_at = |row, width| sql_row_at(strings, &group, row, width);
+
+        assert_eq!(row_at(0, None), None);
+        assert_eq!(row_at(1, None), Some(SqlRow::QueryType(QueryType::Select)));
+        assert_eq!(row_at(4, None), Some(SqlRow::QueryType(QueryType::Delete)));
+        assert_eq!(row_at(5, None), Some(SqlRow::Cache));
+        assert_eq!(row_at(6, None), None);
+        let adjustments = SqlRow::Table("order_line_item_adjustments".to_string());
+        assert_eq!(row_at(7, None), Some(adjustments.clone()));
+        assert_eq!(row_at(8, None), Some(SqlRow::Table("users".to_string())));
+        assert_eq!(row_at(9, None), None);
+
+        // The long table name wraps onto a second row, pushing `users` down
+        assert_eq!(row_at(8, Some(20)), Some(adjustments));
+        assert_eq!(row_at(9, Some(20)), Some(SqlRow::Table("users".to_string())));
+    }
+
+    #[test]
+    fn test_relative_time() {
+        assert_eq!(relative_time(TimeDelta::milliseconds(12)), "+12ms");
+        assert_eq!(relative_time(TimeDelta::milliseconds(1250)), "+1.25s");
+        // Lines logged out of order never count back
+        assert_eq!(relative_time(TimeDelta::milliseconds(-5)), "+0ms");
+
+        let mut line = Line::from("Completed 200 OK");
+        prefix_relative_time(&mut line, TimeDelta::milliseconds(40));
+        assert_eq!(line.to_string(), "+40ms    Completed 200 OK");
+    }
+}
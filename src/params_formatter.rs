@@ -0,0 +1,412 @@
+//! Reading the `Parameters: {...}` line Rails logs for a request.
+//!
+//! The hash is parsed so the detail panel can show it inline with syntax
+//! highlighting, or as an indented tree (`P`). Values under keys that look
+//! like secrets are masked, in case the app's `filter_parameters` missed them.
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+/// Non-breaking spaces, which wrapping panels do not trim.
+pub const INDENT: &str = "\u{a0}\u{a0}";
+pub const MASK: &str = "[FILTERED]";
+/// Hashes nested deeper than this are not parsed.
+const MAX_DEPTH: usize = 32;
+
+/// Keys whose values are masked, matched anywhere in the key ignoring case,
+/// the same keys exports redact.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "access_key",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Param {
+    /// A quoted string, as logged without its quotes
+    String(String),
+    /// nil, a number, a symbol or an object's inspect output, as logged
+    Literal(String),
+    Array(Vec<Param>),
+    Hash(Vec<(String, Param)>),
+    /// A value masked because of its key
+    Masked,
+}
+
+/// The parameters of a `Parameters: {...}` line, with secrets masked. None
+/// for other lines, and for hashes cut short or not in inspect format.
+pub fn parse(message: &str) -> Option<Param> {
+    let start = message.find("Parameters: {")? + "Parameters: ".len();
+    let mut parser = Parser {
+        rest: &message[start..],
+    };
+    let params = parser.value(0)?;
+    parser.rest.trim().is_empty().then_some(params)
+}
+
+/// The line with its hash highlighted on one row, marked as expandable.
+pub fn summary_line(params: &Param, expanded: bool) -> Line<'static> {
+    let marker = if expanded { "▾ " } else { "▸ " };
+    let mut spans = vec![
+        Span::styled(marker, punctuation()),
+        Span::raw("Parameters: "),
+    ];
+    inline(params, &mut spans);
+    Line::from(spans)
+}
+
+/// One row per key or array item, nested values indented under their key.
+pub fn tree_lines(params: &Param) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    match params {
+        Param::Hash(entries) => entries
+            .iter()
+            .for_each(|(key, value)| tree(value, 1, key_spans(key), &mut lines)),
+        Param::Array(items) => items
+            .iter()
+            .for_each(|item| tree(item, 1, item_spans(), &mut lines)),
+        value => tree(value, 1, Vec::new(), &mut lines),
+    }
+    lines
+}
+
+fn tree(value: &Param, depth: usize, label: Vec<Span<'static>>, lines: &mut Vec<Line<'static>>) {
+    let keyed = label.last().is_some_and(|span| span.content == ":");
+    let mut spans = vec![Span::raw(INDENT.repeat(depth))];
+    spans.extend(label);
+    match value {
+        Param::Hash(entries) if !entries.is_empty() => {
+            lines.push(Line::from(spans));
+            for (key, value) in entries {
+                tree(value, depth + 1, key_spans(key), lines);
+            }
+        }
+        Param::Array(items) if !items.is_empty() => {
+            lines.push(Line::from(spans));
+            for item in items {
+                tree(item, depth + 1, item_spans(), lines);
+            }
+        }
+        value => {
+            if keyed {
+                spans.push(Span::raw(" "));
+            }
+            inline(value, &mut spans);
+            lines.push(Line::from(spans));
+        }
+    }
+}
+
+fn key_spans(key: &str) -> Vec<Span<'static>> {
+    vec![
+        Span::styled(key.to_string(), Style::default().fg(Color::Cyan)),
+        Span::styled(":", punctuation()),
+    ]
+}
+
+fn item_spans() -> Vec<Span<'static>> {
+    vec![Span::styled("- ", punctuation())]
+}
+
+/// `value` in inspect format, as Rails logs it.
+fn inline(value: &Param, spans: &mut Vec<Span<'static>>) {
+    match value {
+        Param::String(text) => spans.push(Span::styled(
+            format!("\"{}\"", text),
+            Style::default().fg(Color::Green),
+        )),
+        Param::Literal(text) => {
+            spans.push(Span::styled(text.clone(), Style::default().fg(Color::Magenta)));
+        }
+        Param::Masked => spans.push(Span::styled(MASK, Style::default().fg(Color::DarkGray))),
+        Param::Array(items) => {
+            spans.push(Span::styled("[", punctuation()));
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(", ", punctuation()));
+                }
+                inline(item, spans);
+            }
+            spans.push(Span::styled("]", punctuation()));
+        }
+        Param::Hash(entries) => {
+            spans.push(Span::styled("{", punctuation()));
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(", ", punctuation()));
+                }
+                spans.push(Span::styled(
+                    format!("\"{}\"", key),
+                    Style::default().fg(Color::Cyan),
+                ));
+                spans.push(Span::styled("=>", punctuation()));
+                inline(value, spans);
+            }
+            spans.push(Span::styled("}", punctuation()));
+        }
+    }
+}
+
+fn punctuation() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+pub fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEYS.iter().any(|secret| key.contains(secret))
+}
+
+/// Reads Ruby's inspect format: `{"a"=>"b"}`, and `{"a" => "b"}` or
+/// `{a: "b"}` as Ruby 3.4 prints hashes.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, token: &str) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Param> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if self.eat("{") {
+            self.hash(depth)
+        } else if self.eat("[") {
+            self.array(depth)
+        } else if self.rest.starts_with('"') {
+            self.string().map(Param::String)
+        } else {
+            self.literal().map(Param::Literal)
+        }
+    }
+
+    fn hash(&mut self, depth: usize) -> Option<Param> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Some(Param::Hash(entries));
+        }
+        loop {
+            self.rest = self.rest.trim_start();
+            let key = if self.rest.starts_with('"') {
+                self.string()?
+            } else {
+                self.eat(":");
+                self.word()?
+            };
+            if !self.eat("=>") && !self.eat(":") {
+                return None;
+            }
+            let value = self.value(depth + 1)?;
+            let value = if is_secret(&key) { Param::Masked } else { value };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Some(Param::Hash(entries));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Param> {
+        let mut items = Vec::new();
+        if self.eat("]") {
+            return Some(Param::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            if self.eat("]") {
+                return Some(Param::Array(items));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+
+    /// The contents of a quoted string, escapes kept as they are.
+    fn string(&mut self) -> Option<String> {
+        let end = quoted_end(self.rest)?;
+        let text = self.rest[1..end].to_string();
+        self.rest = &self.rest[end + 1..];
+        Some(text)
+    }
+
+    fn word(&mut self) -> Option<String> {
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '?' | '!')))
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        (!word.is_empty()).then(|| word.to_string())
+    }
+
+    /// Up to the next `,`, `}` or `]`, or an object such as
+    /// `#<ActionDispatch::Http::UploadedFile ...>` to its closing `>`.
+    fn literal(&mut self) -> Option<String> {
+        self.rest = self.rest.trim_start();
+        let end = if self.rest.starts_with("#<") {
+            object_end(self.rest)? + 1
+        } else {
+            self.rest.find([',', '}', ']']).unwrap_or(self.rest.len())
+        };
+        let (literal, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        let literal = literal.trim();
+        (!literal.is_empty()).then(|| literal.to_string())
+    }
+}
+
+/// Byte index of the quote closing the string `text` starts with.
+fn quoted_end(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Byte index of the `>` closing the object `text` starts with, skipping
+/// objects and strings inside it.
+fn object_end(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("#<") {
+            depth += 1;
+            i += 2;
+            continue;
+        }
+        match rest.chars().next()? {
+            '"' => i += quoted_end(rest)?,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += rest.chars().next()?.len_utf8();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let params = parse(
+            r#"[abc]   Parameters: {"post"=>{"title"=>"Say \"hi\"", "tags"=>["a", "b"]}, "page"=>nil, "id"=>"3"}"#,
+        );
+        assert_eq!(
+            params,
+            Some(Param::Hash(vec![
+                (
+                    "post".to_string(),
+                    Param::Hash(vec![
+                        ("title".to_string(), Param::String(r#"Say \"hi\""#.to_string())),
+                        (
+                            "tags".to_string(),
+                            Param::Array(vec![
+                                Param::String("a".to_string()),
+                                Param::String("b".to_string()),
+                            ])
+                        ),
+                    ])
+                ),
+                ("page".to_string(), Param::Literal("nil".to_string())),
+                ("id".to_string(), Param::String("3".to_string())),
+            ]))
+        );
+
+        // Ruby 3.4 inspect format
+        assert_eq!(
+            parse(r#"Parameters: {"id" => "3", page: 2}"#),
+            Some(Param::Hash(vec![
+                ("id".to_string(), Param::String("3".to_string())),
+                ("page".to_string(), Param::Literal("2".to_string())),
+            ]))
+        );
+
+        let upload = parse(
+            r#"Parameters: {"file"=>#<ActionDispatch::Http::UploadedFile:0x0001 @tempfile=#<Tempfile:/tmp/a>, @original_filename="a>b.png">, "x"=>{}}"#,
+        )
+        .unwrap();
+        let Param::Hash(entries) = upload else {
+            panic!("not a hash");
+        };
+        assert_eq!(
+            entries[0].1,
+            Param::Literal(
+                r#"#<ActionDispatch::Http::UploadedFile:0x0001 @tempfile=#<Tempfile:/tmp/a>, @original_filename="a>b.png">"#
+                    .to_string()
+            )
+        );
+        assert_eq!(entries[1].1, Param::Hash(Vec::new()));
+
+        assert_eq!(parse(r#"Parameters: {"post"=>{"title"=>"Hel"#), None);
+        assert_eq!(parse("Completed 200 OK in 5ms"), None);
+    }
+
+    #[test]
+    fn test_mask_secrets() {
+        let params = parse(
+            r#"Parameters: {"user"=>{"email"=>"a@example.com", "password"=>"hunter2", "API_TOKEN"=>{"v"=>"x"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            text(&summary_line(&params, false)),
+            r#"▸ Parameters: {"user"=>{"email"=>"a@example.com", "password"=>[FILTERED], "API_TOKEN"=>[FILTERED]}}"#
+        );
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let params = parse(
+            r#"Parameters: {"post"=>{"title"=>"Hello", "tags"=>["a", "b"]}, "empty"=>[], "id"=>"3"}"#,
+        )
+        .unwrap();
+        let lines: Vec<String> = tree_lines(&params)
+            .iter()
+            .map(|line| text(line).replace(INDENT, "  "))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "  post:",
+                "    title: \"Hello\"",
+                "    tags:",
+                "      - \"a\"",
+                "      - \"b\"",
+                "  empty: []",
+                "  id: \"3\"",
+            ]
+        );
+    }
+}
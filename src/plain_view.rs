@@ -0,0 +1,335 @@
+use crate::app::App;
+use crate::app_state::{GroupKind, LogGroup, StatusType};
+use crate::i18n::{Strings, fill};
+use crate::layout::Panel;
+use crate::log_parser::strip_ansi_for_parsing;
+use crate::cache_info;
+use crate::columns::{self, Column};
+use crate::panel_components::{
+    build_detail_log_lines, cache_summary, http_rows, memory_rows, prefix_relative_time,
+    timing_rows, view_rows,
+};
+use crate::view_model::{
+    detail_match_label, detail_title, help_text, input_badges, sampling_badge, status_segments,
+};
+use crate::sql_info::QueryType;
+use crate::time_format::Precision;
+use crate::time_headers::ListRow;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::{Line, Text},
+    widgets::Paragraph,
+};
+
+/// Renders every panel as unstyled, labeled text for screen readers and
+/// braille terminals: no borders, colors or box-drawing characters.
+pub fn render(f: &mut Frame, app: &App) {
+    let layout = &app.app_view.layout_info;
+    render_text(f, layout.region(Panel::RequestList), build_list_text(app));
+    render_text(
+        f,
+        layout.region(Panel::RequestDetail),
+        build_detail_text(app),
+    );
+    render_text(f, layout.region(Panel::SqlInfo), build_sql_text(app));
+    render_text(f, layout.status_bar(), build_status_text(app));
+}
+
+fn render_text(f: &mut Frame, area: Rect, lines: Vec<String>) {
+    let text = Text::from(lines.into_iter().map(Line::raw).collect::<Vec<_>>());
+    f.render_widget(Paragraph::new(text), area);
+}
+
+fn heading(app: &App, panel: Panel, label: String) -> String {
+    if app.app_view.focused_panel == panel {
+        format!("{}{}:", label, app.config.locale.strings().plain_focused)
+    } else {
+        format!("{}:", label)
+    }
+}
+
+fn status_word(strings: &Strings, group: &LogGroup) -> &'static str {
+    if !group.finished {
+        return strings.status_running;
+    }
+    let generic = group.kind == GroupKind::Generic;
+    match group.status_type {
+        StatusType::Success => strings.status_success,
+        StatusType::Info => strings.status_info,
+        StatusType::Warning if generic => strings.status_warning_level,
+        StatusType::Error if generic => strings.status_error_level,
+        StatusType::Warning => strings.status_warning,
+        StatusType::Error => strings.status_error,
+        StatusType::Unknown => strings.status_unknown,
+    }
+}
+
+fn build_list_text(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let visible_requests = app.visible_request_ids();
+    let offset = app.app_view.get_scroll_offset(Panel::RequestList);
+    let layout = app.list_layout(offset);
+    let shown = layout
+        .iter()
+        .filter(|row| matches!(row, ListRow::Request(_)))
+        .count();
+
+    let mut lines = vec![heading(
+        app,
+        Panel::RequestList,
+        fill(
+            strings.plain_requests,
+            &[
+                &(offset + 1).min(visible_requests.len()),
+                &(offset + shown),
+                &visible_requests.len(),
+            ],
+        ),
+    )];
+
+    let marked = app.visual_range();
+    for list_row in layout {
+        let row = match list_row {
+            ListRow::Header(label) => {
+                lines.push(format!("-- {} --", label));
+                continue;
+            }
+            ListRow::Request(row) => row,
+        };
+        let Some(&(index, request_id)) = visible_requests.get(row) else {
+            continue;
+        };
+        let Some(group) = app.state.logs_by_request_id.get(request_id) else {
+            continue;
+        };
+        let marker = if index == app.state.selected_index {
+            ">"
+        } else if marked.as_ref().is_some_and(|marked| marked.contains(&row)) {
+            "+"
+        } else {
+            " "
+        };
+        let number = if app.config.request_numbers {
+            format!("{} ", row + 1)
+        } else {
+            String::new()
+        };
+        let mut starred = String::new();
+        if group.starred {
+            starred.push_str(&format!("{} ", strings.starred_marker));
+        }
+        if group.pinned {
+            starred.push_str(&format!("{} ", strings.pinned_marker));
+        }
+        if group.note.is_some() {
+            starred.push_str(&format!("{} ", strings.note_marker));
+        }
+        if group.duplicate {
+            starred.push_str(&format!("{} ", strings.duplicate_marker));
+        }
+        let title = strip_ansi_for_parsing(&group.list_title(app.config.list.title.as_ref()).text);
+        let (method, title) = if app.columns.contains(&Column::Method) {
+            columns::strip_method(&title)
+        } else {
+            (None, title.as_str())
+        };
+        let mut cells = String::new();
+        for column in &app.columns {
+            let cell = match column {
+                Column::Time => app.config.times().format(&group.first_timestamp, Precision::Minutes),
+                Column::Duration => group
+                    .duration_ms
+                    .map_or("-".to_string(), |ms| format!("{}ms", ms)),
+                Column::Status => group
+                    .status_code
+                    .map_or("-".to_string(), |code| code.to_string()),
+                Column::Method => method.unwrap_or("-").to_string(),
+                Column::Action => group
+                    .title_fields
+                    .controller_action()
+                    .unwrap_or("-".to_string()),
+                Column::Sql => format!("{}q", group.sql_query_info.total_queries()),
+                Column::Lines => format!("{}ln", group.entries.len()),
+                Column::Source => match app.source_name(group.source) {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                },
+            };
+            cells.push_str(&cell);
+            cells.push(' ');
+        }
+        lines.push(format!(
+            "{} {}{}{}{} {}",
+            marker,
+            number,
+            starred,
+            cells,
+            status_word(strings, group),
+            if group.kind == GroupKind::Unassigned {
+                fill(strings.unassigned, &[&group.entries.len()])
+            } else {
+                title.to_string()
+            }
+        ));
+    }
+    lines
+}
+
+fn build_detail_text(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let Some(group) = app.state.selected_group() else {
+        return vec![
+            heading(app, Panel::RequestDetail, strings.logs.to_string()),
+            strings.waiting_for_logs.to_string(),
+        ];
+    };
+
+    let entries: Vec<Vec<String>> = group
+        .entries
+        .iter()
+        .rev()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let mut lines = build_detail_log_lines(
+                strings,
+                entry,
+                &group.sql_query_info,
+                &app.app_view.detail_search.query,
+                app.verbosity,
+                app.app_view.expand_params,
+                app.app_view.inline_binds,
+            )?;
+            if entry.stderr {
+                lines[0].spans.insert(0, format!("{} ", strings.stderr_marker).into());
+            }
+            if app.app_view.relative_times {
+                prefix_relative_time(&mut lines[0], entry.timestamp - group.first_timestamp);
+            }
+            // Marks the line under the cursor, as highlighting is not shown
+            let marker = if app.highlighted_entry == Some(i) { "> " } else { "" };
+            Some(
+                lines
+                    .iter()
+                    .map(|line| {
+                        std::iter::once(marker)
+                            .chain(line.spans.iter().map(|span| span.content.as_ref()))
+                            .collect()
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let mut title = detail_title(app, group);
+    if group.compacted {
+        title.push(' ');
+        title.push_str(strings.compacted.trim_end());
+    }
+    if let Some(matches) = detail_match_label(app) {
+        title.push(' ');
+        title.push_str(matches.trim_end());
+    }
+    let mut lines = vec![heading(
+        app,
+        Panel::RequestDetail,
+        fill(strings.plain_detail, &[&title, &entries.len()]),
+    )];
+
+    let offset = app.app_view.get_scroll_offset(Panel::RequestDetail);
+    let viewport_height = app.app_view.viewport_height(Panel::RequestDetail);
+    lines.extend(entries.into_iter().skip(offset).flatten().take(viewport_height));
+    lines
+}
+
+/// Same line layout as the SQL panel, so `panel_components::sql_row_at` still applies.
+fn build_sql_text(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let Some(group) = app.state.selected_group() else {
+        return vec![heading(app, Panel::SqlInfo, fill(strings.plain_sql, &[&0]))];
+    };
+    let sql_info = &group.sql_query_info;
+
+    let mut body = vec![String::new()];
+    for query_type in QueryType::DISPLAY_ORDER {
+        body.push(format!(
+            "{}: {}",
+            query_type.label(),
+            sql_info.query_count(query_type)
+        ));
+    }
+    body.push(format!("CACHE: {}", cache_summary(strings, sql_info)));
+    if !sql_info.table_counts.is_empty() {
+        body.push(String::new());
+        for (table, count) in sql_info.sorted_tables() {
+            let warning = if sql_info.is_n_plus_one(table) {
+                " N+1?"
+            } else {
+                ""
+            };
+            body.push(format!("{}: {}{}", table, count, warning));
+        }
+    }
+    if !group.render_info.is_empty() {
+        body.push(String::new());
+        body.push(format!("{}:", strings.views));
+        body.extend(view_rows(&group.render_info));
+    }
+    let cache = &group.cache_info;
+    let sections = [
+        (strings.http_calls, group.http_info.total_calls(), http_rows(&group.http_info)),
+        (
+            strings.cache_operations,
+            cache.cache_count(),
+            timing_rows(&cache_info::by_total_time(&cache.cache)),
+        ),
+        (
+            "Redis",
+            cache.redis_count(),
+            timing_rows(&cache_info::by_total_time(&cache.redis)),
+        ),
+    ];
+    for (heading, count, rows) in sections {
+        if !rows.is_empty() {
+            body.push(String::new());
+            body.push(format!("{} ({}):", heading, count));
+            body.extend(rows);
+        }
+    }
+    if !group.memory_info.is_empty() {
+        body.push(String::new());
+        body.push(format!("{}:", strings.memory));
+        body.extend(memory_rows(strings, &group.memory_info));
+    }
+
+    let mut lines = vec![heading(
+        app,
+        Panel::SqlInfo,
+        fill(strings.plain_sql, &[&sql_info.total_queries()]),
+    )];
+    let offset = app.app_view.get_scroll_offset(Panel::SqlInfo);
+    lines.extend(body.into_iter().skip(offset));
+    lines
+}
+
+fn build_status_text(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let mut segments: Vec<String> = input_badges(app)
+        .iter()
+        .map(|badge| badge.trim().to_string())
+        .collect();
+    if let Some(badge) = sampling_badge(app) {
+        segments.push(badge.trim().to_string());
+    }
+    segments.extend(status_segments(app));
+    if let Some(progress) = app.loading_progress() {
+        segments.push(format!(
+            "{} {:.0}%",
+            fill(strings.loading, &[&progress.file_name]).trim(),
+            progress.ratio() * 100.0
+        ));
+    }
+    segments.extend(app.toasts.visible().map(|toast| toast.message.clone()));
+
+    vec![segments.join(" | "), help_text(app).trim().to_string()]
+}
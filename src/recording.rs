@@ -0,0 +1,336 @@
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 8] = b"LUCYREC\x01";
+
+const TAG_LINE: u8 = 0;
+const TAG_KEY: u8 = 1;
+const TAG_MOUSE: u8 = 2;
+
+// Keys without a payload, in encoding order after Char (0) and F (1)
+const NAMED_KEYS: [KeyCode; 16] = [
+    KeyCode::Backspace,
+    KeyCode::Enter,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Tab,
+    KeyCode::BackTab,
+    KeyCode::Delete,
+    KeyCode::Insert,
+    KeyCode::Esc,
+    KeyCode::Null,
+];
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Something the app processed, in the order it processed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    Line(String),
+    Event(Event),
+}
+
+/// Writes input lines and key/mouse events with their timing (`--record`).
+///
+/// The file starts with a magic header and the terminal size, followed by
+/// records of `[u32 ms since start][u8 tag][payload]`, all little endian.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, (columns, rows): (u16, u16)) -> Result<Self> {
+        let file = File::create(path)
+            .wrap_err_with(|| format!("Failed to create recording {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&columns.to_le_bytes())?;
+        writer.write_all(&rows.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Events that cannot be replayed, such as resizes, are skipped.
+    pub fn record(&mut self, record: &Record) -> io::Result<()> {
+        let Some(payload) = encode(record) else {
+            return Ok(());
+        };
+        let elapsed_ms = u32::try_from(self.start.elapsed().as_millis()).unwrap_or(u32::MAX);
+        self.writer.write_all(&elapsed_ms.to_le_bytes())?;
+        self.writer.write_all(&payload)?;
+        // Flushed as we go so the file is complete even after a crash
+        self.writer.flush()
+    }
+}
+
+/// Replays a recording with its original timing (`--playback`).
+pub struct Playback {
+    records: VecDeque<(Duration, Record)>,
+    start: Instant,
+    /// Terminal size the recording was made in
+    pub size: (u16, u16),
+}
+
+impl Playback {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .wrap_err_with(|| format!("Failed to read recording {}", path.display()))?;
+        Self::parse(&bytes).wrap_err_with(|| format!("Invalid recording {}", path.display()))
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            bail!("Not a lucy recording");
+        }
+        let size = (reader.u16()?, reader.u16()?);
+
+        let mut records = VecDeque::new();
+        while !reader.is_empty() {
+            let elapsed = Duration::from_millis(reader.u32()?.into());
+            records.push_back((elapsed, decode(&mut reader)?));
+        }
+        Ok(Self {
+            records,
+            start: Instant::now(),
+            size,
+        })
+    }
+
+    /// Records whose time has come, oldest first.
+    pub fn due(&mut self) -> Vec<Record> {
+        let elapsed = self.start.elapsed();
+        let count = self
+            .records
+            .iter()
+            .take_while(|(at, _)| *at <= elapsed)
+            .count();
+        self.records
+            .drain(..count)
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// When the next record is due, if any are left.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.records.front().map(|(at, _)| self.start + *at)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+fn encode(record: &Record) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    match record {
+        Record::Line(line) => {
+            out.push(TAG_LINE);
+            out.extend_from_slice(&(line.len() as u32).to_le_bytes());
+            out.extend_from_slice(line.as_bytes());
+        }
+        Record::Event(Event::Key(key)) => {
+            let (code, value) = match key.code {
+                KeyCode::Char(c) => (0, c as u32),
+                KeyCode::F(n) => (1, n.into()),
+                named => (2 + NAMED_KEYS.iter().position(|k| *k == named)? as u8, 0),
+            };
+            let kind = match key.kind {
+                KeyEventKind::Press => 0,
+                KeyEventKind::Repeat => 1,
+                KeyEventKind::Release => 2,
+            };
+            out.extend_from_slice(&[TAG_KEY, code]);
+            out.extend_from_slice(&value.to_le_bytes());
+            out.extend_from_slice(&[key.modifiers.bits(), kind]);
+        }
+        Record::Event(Event::Mouse(mouse)) => {
+            let button_index =
+                |button| MOUSE_BUTTONS.iter().position(|b| *b == button).unwrap_or(0) as u8;
+            let (kind, button) = match mouse.kind {
+                MouseEventKind::Down(button) => (0, button_index(button)),
+                MouseEventKind::Up(button) => (1, button_index(button)),
+                MouseEventKind::Drag(button) => (2, button_index(button)),
+                MouseEventKind::Moved => (3, 0),
+                MouseEventKind::ScrollDown => (4, 0),
+                MouseEventKind::ScrollUp => (5, 0),
+                MouseEventKind::ScrollLeft => (6, 0),
+                MouseEventKind::ScrollRight => (7, 0),
+            };
+            out.extend_from_slice(&[TAG_MOUSE, kind, button]);
+            out.extend_from_slice(&mouse.column.to_le_bytes());
+            out.extend_from_slice(&mouse.row.to_le_bytes());
+            out.push(mouse.modifiers.bits());
+        }
+        Record::Event(_) => return None,
+    }
+    Some(out)
+}
+
+fn decode(reader: &mut ByteReader) -> Result<Record> {
+    match reader.u8()? {
+        TAG_LINE => {
+            let len = reader.u32()? as usize;
+            let line = String::from_utf8(reader.take(len)?.to_vec())?;
+            Ok(Record::Line(line))
+        }
+        TAG_KEY => {
+            let code = reader.u8()?;
+            let value = reader.u32()?;
+            let code = match code {
+                0 => KeyCode::Char(char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER)),
+                1 => KeyCode::F(value as u8),
+                named => *NAMED_KEYS
+                    .get(named as usize - 2)
+                    .ok_or_else(|| color_eyre::eyre::eyre!("Unknown key code {}", named))?,
+            };
+            let modifiers = KeyModifiers::from_bits_truncate(reader.u8()?);
+            let kind = match reader.u8()? {
+                1 => KeyEventKind::Repeat,
+                2 => KeyEventKind::Release,
+                _ => KeyEventKind::Press,
+            };
+            Ok(Record::Event(Event::Key(KeyEvent::new_with_kind(
+                code, modifiers, kind,
+            ))))
+        }
+        TAG_MOUSE => {
+            let kind = reader.u8()?;
+            let button = MOUSE_BUTTONS
+                .get(reader.u8()? as usize)
+                .copied()
+                .unwrap_or(MouseButton::Left);
+            let kind = match kind {
+                0 => MouseEventKind::Down(button),
+                1 => MouseEventKind::Up(button),
+                2 => MouseEventKind::Drag(button),
+                3 => MouseEventKind::Moved,
+                4 => MouseEventKind::ScrollDown,
+                5 => MouseEventKind::ScrollUp,
+                6 => MouseEventKind::ScrollLeft,
+                7 => MouseEventKind::ScrollRight,
+                other => bail!("Unknown mouse event {}", other),
+            };
+            Ok(Record::Event(Event::Mouse(MouseEvent {
+                kind,
+                column: reader.u16()?,
+                row: reader.u16()?,
+                modifiers: KeyModifiers::from_bits_truncate(reader.u8()?),
+            })))
+        }
+        tag => bail!("Unknown record tag {}", tag),
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let Some(slice) = self.bytes.get(self.position..self.position + len) else {
+            bail!("Recording ends early at byte {}", self.position);
+        };
+        self.position += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("lucy-recording-{}.bin", std::process::id()));
+        let records = [
+            Record::Line("[req-1] Started GET \"/\"\n".to_string()),
+            Record::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('j'),
+                KeyModifiers::NONE,
+            ))),
+            Record::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL,
+            ))),
+            Record::Event(Event::Key(KeyEvent::new(
+                KeyCode::BackTab,
+                KeyModifiers::SHIFT,
+            ))),
+            Record::Event(Event::Key(KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE))),
+            Record::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Drag(MouseButton::Left),
+                column: 42,
+                row: 7,
+                modifiers: KeyModifiers::ALT,
+            })),
+        ];
+
+        let mut recorder = Recorder::create(&path, (160, 40)).unwrap();
+        for record in &records {
+            recorder.record(record).unwrap();
+        }
+        // Not replayable, so not recorded
+        recorder
+            .record(&Record::Event(Event::Resize(80, 24)))
+            .unwrap();
+        drop(recorder);
+
+        let mut playback = Playback::load(&path).unwrap();
+        assert_eq!(playback.size, (160, 40));
+        assert!(playback.next_due().is_some());
+        assert_eq!(playback.due(), records);
+        assert!(playback.is_finished());
+        assert_eq!(playback.next_due(), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_recordings() {
+        assert!(Playback::parse(b"not a recording").is_err());
+
+        let mut truncated = MAGIC.to_vec();
+        truncated.extend_from_slice(&[80, 0, 24, 0, 0, 0, 0, 0, TAG_LINE, 9, 0, 0, 0, b'x']);
+        assert!(Playback::parse(&truncated).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! `--replay FILE`: a saved log fed back at the pace it was written, to see
+//! how lucy copes with real bursts and lulls.
+
+use crate::timestamp::Timestamps;
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+use std::io::BufRead;
+use std::sync::LazyLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Started GET "/posts" for 127.0.0.1 at 2024-05-01 12:00:00 +0900
+static RE_STARTED_AT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bStarted \w+ .* at (?P<ts>\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} [+-]\d{4})").unwrap()
+});
+
+/// Longest wait between two lines, whatever the gap in the file, so idle
+/// hours in a development log don't stall the replay.
+pub const MAX_PAUSE: Duration = Duration::from_secs(5);
+
+/// When a line was written: its own timestamp, or the time a Rails
+/// request says it started. Other lines follow the previous one at once.
+pub fn line_time(line: &str) -> Option<DateTime<FixedOffset>> {
+    if let Some((timestamp, _)) = Timestamps::default().extract(line) {
+        return Some(timestamp);
+    }
+    let caps = RE_STARTED_AT.captures(line)?;
+    DateTime::parse_from_str(&caps["ts"], "%Y-%m-%d %H:%M:%S %z").ok()
+}
+
+/// How long to wait before a line written `gap` after the previous timed
+/// one, at `speed` times the original pace.
+pub fn pause(gap: chrono::TimeDelta, speed: f64) -> Duration {
+    let gap = gap.to_std().unwrap_or_default();
+    gap.div_f64(speed).min(MAX_PAUSE)
+}
+
+/// Sends each line of `reader` once it is due, until the input ends or
+/// `send` returns false.
+pub fn run(mut reader: impl BufRead, speed: f64, mut send: impl FnMut(String) -> bool) {
+    let mut due = Instant::now();
+    let mut last_time: Option<DateTime<FixedOffset>> = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                tracing::debug!("Replay reader error: {}", e);
+                break;
+            }
+        }
+        if let Some(time) = line_time(&line) {
+            // Lines of concurrent requests can be slightly out of order
+            if let Some(last) = last_time.filter(|&last| time > last) {
+                due += pause(time - last, speed);
+            }
+            last_time = last_time.max(Some(time));
+        }
+        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+        if !send(std::mem::take(&mut line)) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_line_time() {
+        let time = line_time("2024-05-01T12:00:01.500Z [r1] Completed 200 OK in 5ms").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-05-01T12:00:01.500+00:00");
+        let time =
+            line_time(r#"[r1] Started GET "/posts" for 127.0.0.1 at 2024-05-01 12:00:00 +0900"#)
+                .unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-05-01T12:00:00+09:00");
+        assert_eq!(line_time("[r1] Completed 200 OK in 5ms"), None);
+    }
+
+    #[test]
+    fn test_pause() {
+        assert_eq!(
+            pause(TimeDelta::milliseconds(300), 2.0),
+            Duration::from_millis(150)
+        );
+        assert_eq!(pause(TimeDelta::hours(2), 1.0), MAX_PAUSE);
+        assert_eq!(pause(TimeDelta::milliseconds(-10), 1.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_run() {
+        let log = "2024-05-01T12:00:00.000Z [a] one\n\
+                   [a] untimed\n\
+                   2024-05-01T12:00:00.100Z [b] two\n\
+                   2024-05-01T12:00:00.050Z [a] late\n";
+        let start = Instant::now();
+        let mut sent = Vec::new();
+        run(log.as_bytes(), 2.0, |line| {
+            sent.push((line, start.elapsed()));
+            true
+        });
+        let lines: Vec<&str> = sent.iter().map(|(line, _)| line.trim_end()).collect();
+        assert_eq!(
+            lines,
+            [
+                "2024-05-01T12:00:00.000Z [a] one",
+                "[a] untimed",
+                "2024-05-01T12:00:00.100Z [b] two",
+                "2024-05-01T12:00:00.050Z [a] late"
+            ]
+        );
+        assert!(sent[1].1 < Duration::from_millis(40));
+        assert!(sent[2].1 >= Duration::from_millis(50));
+        assert!(sent[3].1 - sent[2].1 < Duration::from_millis(40));
+    }
+}
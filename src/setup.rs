@@ -1,6 +1,20 @@
+use crate::i18n::Strings;
+use crate::layout;
+use crate::panel_components::format_bytes;
 use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
 use std::io;
 use std::panic;
+use std::path::{Path, PathBuf};
+
+/// Logs a Rails app writes, offered when lucy starts with no input.
+const RAILS_LOGS: [&str; 2] = ["log/development.log", "log/test.log"];
 
 pub fn initialize() -> Result<()> {
     color_eyre::install()?;
@@ -91,18 +105,173 @@ fn setup_panic_handler() {
     }));
 }
 
-pub fn initialize_terminal()
--> Result<ratatui::Terminal<ratatui::backend::CrosstermBackend<io::Stdout>>> {
+/// Whether mouse capture is worth trying in this terminal. Consoles without
+/// xterm mouse reporting would show the escape codes as garbage, and GNU
+/// screen garbles the extended coordinates while taking over selection.
+pub fn mouse_supported() -> bool {
+    mouse_supported_by(
+        std::env::var("TERM").ok().as_deref(),
+        std::env::var_os("STY").is_some(),
+    )
+}
+
+fn mouse_supported_by(term: Option<&str>, in_gnu_screen: bool) -> bool {
+    match term {
+        None | Some("" | "dumb" | "linux" | "cons25") => false,
+        Some(term) if term.starts_with("vt") => false,
+        _ => !in_gnu_screen,
+    }
+}
+
+/// Sets up the terminal, capturing the mouse when asked to. Returns whether
+/// mouse capture is actually on.
+pub fn initialize_terminal(
+    mouse: bool,
+) -> Result<(
+    ratatui::Terminal<ratatui::backend::CrosstermBackend<io::Stdout>>,
+    bool,
+)> {
     let mut stdout = io::stdout();
     crossterm::execute!(
         stdout,
         crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-        crossterm::terminal::EnterAlternateScreen,
-        crossterm::event::EnableMouseCapture
+        crossterm::terminal::EnterAlternateScreen
     )?;
+    let mouse = mouse
+        && crossterm::execute!(stdout, crossterm::event::EnableMouseCapture)
+            .inspect_err(|e| tracing::warn!("Mouse capture unavailable: {}", e))
+            .is_ok();
     crossterm::terminal::enable_raw_mode()?;
 
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
     let terminal = ratatui::Terminal::new(backend)?;
-    Ok(terminal)
+    Ok((terminal, mouse))
+}
+
+/// Hands the terminal back to the shell for a program run from lucy, such
+/// as an editor, leaving the log on the alternate screen.
+pub fn suspend_terminal() -> Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        io::stdout(),
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::event::DisableMouseCapture,
+        crossterm::cursor::Show
+    )?;
+    Ok(())
+}
+
+/// Takes the terminal back after `suspend_terminal`. The caller redraws
+/// the whole screen.
+pub fn resume_terminal(mouse: bool) -> Result<()> {
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    if mouse {
+        crossterm::execute!(stdout, crossterm::event::EnableMouseCapture)?;
+    }
+    crossterm::terminal::enable_raw_mode()?;
+    Ok(())
+}
+
+/// The Rails logs under `dir`, in the order of `RAILS_LOGS`.
+pub fn find_rails_logs(dir: &Path) -> Vec<PathBuf> {
+    RAILS_LOGS
+        .iter()
+        .map(|log| dir.join(log))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Asks which of `logs` to tail, on a screen of its own before the app
+/// starts. None when the user leaves without picking one.
+pub fn pick_log(logs: &[PathBuf], strings: &Strings) -> Result<Option<PathBuf>> {
+    let (mut terminal, _) = initialize_terminal(false)?;
+    let picked = run_picker(&mut terminal, logs, strings);
+    suspend_terminal()?;
+    picked
+}
+
+fn run_picker<B>(
+    terminal: &mut ratatui::Terminal<B>,
+    logs: &[PathBuf],
+    strings: &Strings,
+) -> Result<Option<PathBuf>>
+where
+    B: ratatui::backend::Backend,
+    B::Error: Send + Sync + 'static,
+{
+    let mut selected = 0;
+    loop {
+        terminal.draw(|f| render_picker(f, logs, selected, strings))?;
+        let Event::Key(key) = crossterm::event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(logs.len() - 1),
+            KeyCode::Enter => return Ok(logs.get(selected).cloned()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn render_picker(f: &mut Frame, logs: &[PathBuf], selected: usize, strings: &Strings) {
+    let mut lines = vec![Line::raw(strings.picker_hint), Line::raw("")];
+    for (i, path) in logs.iter().enumerate() {
+        let size = std::fs::metadata(path).map_or(0, |meta| meta.len());
+        let text = format!(" {} ({}) ", path.display(), format_bytes(size));
+        let style = if i == selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::styled(text, style));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(strings.picker_footer, Style::default().fg(Color::DarkGray)));
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let area = layout::centered_rect(f.area(), width, lines.len() as u16 + 2);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(strings.picker_title);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_rails_logs() {
+        let dir = std::env::temp_dir().join(format!("lucy-rails-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("log")).unwrap();
+        assert!(find_rails_logs(&dir).is_empty());
+
+        for log in ["log/test.log", "log/development.log", "log/other.log"] {
+            std::fs::write(dir.join(log), "").unwrap();
+        }
+        assert_eq!(
+            find_rails_logs(&dir),
+            [dir.join("log/development.log"), dir.join("log/test.log")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_mouse_supported_by() {
+        assert!(mouse_supported_by(Some("xterm-256color"), false));
+        assert!(mouse_supported_by(Some("tmux-256color"), false));
+        assert!(!mouse_supported_by(Some("screen"), true));
+        assert!(!mouse_supported_by(Some("linux"), false));
+        assert!(!mouse_supported_by(Some("vt100"), false));
+        assert!(!mouse_supported_by(Some("dumb"), false));
+        assert!(!mouse_supported_by(None, false));
+    }
 }
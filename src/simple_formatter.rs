@@ -1,63 +1,48 @@
 use ansi_to_tui::IntoText;
-use ratatui::text::{Line, Span};
-use regex::Regex;
-use std::sync::LazyLock;
+use ratatui::text::Line;
 
-use crate::theme::{ANSI_RESET, ColorExt, THEME};
-
-static RE_STARTED: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"Started (?P<method>[A-Z]+) "(?P<path>[^"]+)""#).unwrap());
-static RE_PROCESSING: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r#"Processing by (?P<controller>[\w:]+)#(?P<action>\w+) as (?P<format>\w+)"#)
-        .unwrap()
-});
-static RE_PARAMETERS: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"Parameters: \{(?P<params>.*)\}"#).unwrap());
-static RE_SQL: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"\(\d+\.\d+ms\)\s+(SELECT|INSERT|UPDATE|DELETE)\s"#).unwrap());
 use crate::log_parser::RE_COMPLETED;
-static RE_CONTINUATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"↳"#).unwrap());
+use crate::theme::{ANSI_RESET, ColorExt, theme};
+use lucy_core::verbosity::{core_message, is_simple_core_message};
 
-pub fn format_simple_log_line(line: &str) -> Option<Line<'static>> {
-    let core_message = if let Some(index) = line.rfind("] ") {
-        line.split_at(index + 2).1
-    } else {
-        line
-    };
+pub use lucy_core::verbosity::{Verbosity, is_simple_log_line, is_summary_log_line};
+
+/// The lines simple mode shows for a log line, more than one when its
+/// message runs over several. None when simple mode hides it.
+pub fn format_simple_log_line(line: &str) -> Option<Vec<Line<'static>>> {
+    let core_message = core_message(line);
 
     if let Some(captures) = RE_COMPLETED.captures(core_message) {
         let status = captures.name("status").unwrap().as_str();
         let colored_message = match status.chars().next().unwrap() {
-            '2' => format!("{}{}{}", THEME.success.ansi(), core_message, ANSI_RESET),
-            '4' => format!("{}{}{}", THEME.warning.ansi(), core_message, ANSI_RESET),
-            '5' => format!("{}{}{}", THEME.error.ansi(), core_message, ANSI_RESET),
+            '2' => format!("{}{}{}", theme().success.ansi(), core_message, ANSI_RESET),
+            '4' => format!("{}{}{}", theme().warning.ansi(), core_message, ANSI_RESET),
+            '5' => format!("{}{}{}", theme().error.ansi(), core_message, ANSI_RESET),
             _ => core_message.to_string(),
         };
-        Some(Line::from(parse_ansi_colors(&colored_message)))
-    } else if RE_STARTED.is_match(core_message)
-        || RE_PROCESSING.is_match(core_message)
-        || RE_PARAMETERS.is_match(core_message)
-        || RE_SQL.is_match(core_message)
-        || RE_CONTINUATION.is_match(core_message)
-    {
-        Some(Line::from(parse_ansi_colors(core_message)))
+        Some(parse_ansi_colors(&colored_message))
+    } else if is_simple_core_message(core_message) {
+        Some(parse_ansi_colors(core_message))
     } else {
         None
     }
 }
 
-pub fn parse_ansi_colors(text: &str) -> Vec<Span<'static>> {
+/// The line as the detail panel shows it outside simple mode, without the request id tag.
+pub fn format_full_log_line(line: &str) -> Vec<Line<'static>> {
+    let message = match line.find(']') {
+        Some(after_id) => line[(after_id + 1)..].trim(),
+        None => line,
+    };
+    parse_ansi_colors(message)
+}
+
+/// Every line of `text`, with the colors its escape codes set. A color
+/// left open at the end of a line goes on into the next.
+pub fn parse_ansi_colors(text: &str) -> Vec<Line<'static>> {
     match text.into_text() {
-        Ok(mut parsed_text) => {
-            if !parsed_text.lines.is_empty() {
-                parsed_text.lines.remove(0).spans
-            } else {
-                vec![Span::raw(text.to_string())]
-            }
-        }
-        Err(_) => {
-            vec![Span::raw(text.to_string())]
-        }
+        Ok(parsed_text) if !parsed_text.lines.is_empty() => parsed_text.lines,
+        _ => vec![Line::raw(text.to_string())],
     }
 }
 
@@ -68,14 +53,32 @@ mod tests {
     #[test]
     fn test_parse_ansi_colors() {
         let plain_text = "Hello, world!";
-        let spans = parse_ansi_colors(plain_text);
-        assert_eq!(spans.len(), 1);
-        assert_eq!(spans[0].content, "Hello, world!");
+        let lines = parse_ansi_colors(plain_text);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "Hello, world!");
 
         let colored_text = "\\x1b[31mRed text\\x1b[0m";
-        let spans = parse_ansi_colors(colored_text);
-        assert!(!spans.is_empty());
-        assert!(spans.iter().any(|span| span.content.contains("Red text")));
+        let lines = parse_ansi_colors(colored_text);
+        assert!(!lines.is_empty());
+        assert!(lines[0].spans.iter().any(|span| span.content.contains("Red text")));
+    }
+
+    #[test]
+    fn test_parse_ansi_colors_multiline() {
+        let lines = parse_ansi_colors("\x1b[31mfirst\nsecond\x1b[0m\nthird");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].to_string(), "second");
+        assert_eq!(lines[1].spans[0].style.fg, Some(ratatui::style::Color::Red));
+        assert_eq!(lines[2].to_string(), "third");
+        assert_ne!(lines[2].spans[0].style.fg, Some(ratatui::style::Color::Red));
+    }
+
+    #[test]
+    fn test_format_full_log_line() {
+        let lines = format_full_log_line("[req-1]   User Load (0.4ms)");
+        assert_eq!(lines[0].spans[0].content, "User Load (0.4ms)");
+        let lines = format_full_log_line("no request id");
+        assert_eq!(lines[0].spans[0].content, "no request id");
     }
 
     // Add tests for format_simple_log_line if needed
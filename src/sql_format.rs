@@ -0,0 +1,358 @@
+//! Laying out a logged SQL statement for reading.
+//!
+//! Rails prints each query on one line with placeholders and its binds after
+//! it. `inline_binds` puts the values back in, and `format` starts each clause
+//! on its own line, indenting conditions, select lists and subqueries.
+
+use crate::sql_info;
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+const INDENT: &str = "  ";
+
+/// One bind of the list Rails logs after a statement: `["name", value]`.
+static RE_BIND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\[(?:"(?:[^"\\]|\\.)*"|nil),\s*("(?:[^"\\]|\\.)*"|[^\[\],]+)\]"#).unwrap()
+});
+
+static RE_NUMBERED_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$(\d+)").unwrap());
+
+/// Written in upper case.
+const KEYWORDS: &[&str] = &[
+    "ALL", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CONFLICT", "CROSS", "DELETE", "DESC",
+    "DISTINCT", "DO", "ELSE", "END", "EXISTS", "FALSE", "FROM", "FULL", "GROUP", "HAVING",
+    "ILIKE", "IN", "INNER", "INSERT", "INTO", "IS", "JOIN", "LEFT", "LIKE", "LIMIT", "NOT",
+    "NOTHING", "NULL", "OFFSET", "ON", "OR", "ORDER", "OUTER", "RETURNING", "RIGHT", "SELECT",
+    "SET", "THEN", "TRUE", "UNION", "UPDATE", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+/// Keywords starting a clause on a new line.
+const CLAUSES: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "OFFSET", "VALUES", "SET",
+    "RETURNING", "UNION", "INSERT", "UPDATE", "DELETE", "WITH",
+];
+
+/// Keywords that may open a join (`LEFT OUTER JOIN`).
+const JOIN_PREFIXES: &[&str] = &["INNER", "LEFT", "RIGHT", "FULL", "CROSS"];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    /// String literal, quoted identifier or comment, kept as written
+    Verbatim(&'a str),
+    Punct(char),
+}
+
+/// `SELECT ... $1  [["id", 1]]` with the logged binds in place of the
+/// placeholders. Placeholders without a bind are left as they are.
+pub fn inline_binds(statement: &str) -> String {
+    let (sql, binds) = split_binds(statement);
+    if binds.is_empty() {
+        return sql.to_string();
+    }
+    // PostgreSQL numbers its placeholders, MySQL and SQLite use `?`
+    if RE_NUMBERED_PLACEHOLDER.is_match(sql) {
+        return RE_NUMBERED_PLACEHOLDER
+            .replace_all(sql, |caps: &Captures| {
+                caps[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| binds.get(n.checked_sub(1)?))
+                    .map_or_else(|| caps[0].to_string(), Clone::clone)
+            })
+            .into_owned();
+    }
+
+    let mut out = String::with_capacity(sql.len());
+    let mut binds = binds.iter();
+    let mut in_string = false;
+    for c in sql.chars() {
+        match c {
+            '\'' => {
+                in_string = !in_string;
+                out.push(c);
+            }
+            '?' if !in_string => match binds.next() {
+                Some(bind) => out.push_str(bind),
+                None => out.push(c),
+            },
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `sql` with each clause on its own line and keywords in upper case.
+pub fn format(sql: &str) -> String {
+    let tokens = tokenize(sql);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    // Per subquery level: the clause being written and the indent of its lines
+    let mut clauses: Vec<(&str, usize)> = vec![("", 0)];
+    // Open parentheses, true for those holding a subquery
+    let mut parens: Vec<bool> = Vec::new();
+    let mut between = false;
+
+    let break_line = |lines: &mut Vec<String>, line: &mut String, indent: usize| {
+        if !line.trim().is_empty() {
+            lines.push(std::mem::take(line));
+        }
+        *line = INDENT.repeat(indent);
+    };
+
+    for (i, (space, token)) in tokens.iter().enumerate() {
+        let level = clauses.len() - 1;
+        let (clause, indent) = clauses[level];
+        let next_word = tokens[i + 1..].iter().find_map(|(_, token)| match token {
+            Token::Word(word) => Some(word.to_ascii_uppercase()),
+            _ => None,
+        });
+        let mut text = match token {
+            Token::Word(word) | Token::Verbatim(word) => word.to_string(),
+            Token::Punct(c) => c.to_string(),
+        };
+        let mut space = *space;
+
+        match token {
+            Token::Word(word) => {
+                let upper = word.to_ascii_uppercase();
+                if KEYWORDS.contains(&upper.as_str()) {
+                    text.clone_from(&upper);
+                }
+                let starts_join = match upper.as_str() {
+                    "JOIN" => {
+                        let previous = previous_word(&tokens[..i]);
+                        previous != "OUTER" && !JOIN_PREFIXES.contains(&previous.as_str())
+                    }
+                    prefix => {
+                        JOIN_PREFIXES.contains(&prefix)
+                            && matches!(next_word.as_deref(), Some("JOIN" | "OUTER"))
+                    }
+                };
+                let new_clause = if starts_join {
+                    Some("JOIN")
+                } else {
+                    CLAUSES.iter().copied().find(|clause| *clause == upper)
+                };
+                if let Some(new_clause) = new_clause {
+                    break_line(&mut lines, &mut line, indent);
+                    space = false;
+                    clauses[level].0 = new_clause;
+                } else if matches!(upper.as_str(), "AND" | "OR")
+                    && !between
+                    && parens.last().is_none_or(|&subquery| subquery)
+                    && matches!(clause, "WHERE" | "HAVING" | "JOIN")
+                {
+                    break_line(&mut lines, &mut line, indent + 1);
+                    space = false;
+                }
+                between = match upper.as_str() {
+                    "BETWEEN" => true,
+                    "AND" => false,
+                    _ => between,
+                };
+            }
+            Token::Punct('(') => {
+                let subquery = matches!(tokens.get(i + 1),
+                    Some((_, Token::Word(word))) if word.eq_ignore_ascii_case("SELECT"));
+                parens.push(subquery);
+                if subquery {
+                    // One deeper than the line the parenthesis opens on
+                    let line_indent = (line.len() - line.trim_start().len()) / INDENT.len();
+                    clauses.push(("", line_indent + 1));
+                }
+            }
+            // Every closing parenthesis pops, a subquery's also ends its level
+            Token::Punct(')') if parens.pop() == Some(true) => {
+                clauses.pop();
+                break_line(&mut lines, &mut line, indent.saturating_sub(1));
+                space = false;
+            }
+            _ => {}
+        }
+
+        if space && !line.trim().is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&text);
+
+        if *token == Token::Punct(',')
+            && parens.last().is_none_or(|&subquery| subquery)
+            && matches!(clause, "SELECT" | "SET")
+        {
+            break_line(&mut lines, &mut line, indent + 1);
+        }
+    }
+    break_line(&mut lines, &mut line, 0);
+    lines.join("\n")
+}
+
+fn previous_word(tokens: &[(bool, Token)]) -> String {
+    tokens
+        .iter()
+        .rev()
+        .find_map(|(_, token)| match token {
+            Token::Word(word) => Some(word.to_ascii_uppercase()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Splits `sql` into tokens, each with whether whitespace came before it.
+fn tokenize(sql: &str) -> Vec<(bool, Token<'_>)> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    let mut space = false;
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        let token = match c {
+            '\'' | '"' | '`' => {
+                // A doubled quote inside the literal is an escaped one
+                while let Some((i, next)) = chars.next() {
+                    end = i + next.len_utf8();
+                    if next == c {
+                        if chars.peek().is_some_and(|&(_, after)| after == c) {
+                            chars.next();
+                            end += c.len_utf8();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                Token::Verbatim(&sql[start..end])
+            }
+            '/' if chars.peek().is_some_and(|&(_, next)| next == '*') => {
+                let close = sql[start..].find("*/").map_or(sql.len(), |i| start + i + 2);
+                while chars.peek().is_some_and(|&(i, _)| i < close) {
+                    chars.next();
+                }
+                Token::Verbatim(&sql[start..close])
+            }
+            c if c.is_alphanumeric() || matches!(c, '_' | '$') => {
+                while let Some(&(i, next)) = chars.peek() {
+                    if !(next.is_alphanumeric() || matches!(next, '_' | '$' | '.')) {
+                        break;
+                    }
+                    end = i + next.len_utf8();
+                    chars.next();
+                }
+                Token::Word(&sql[start..end])
+            }
+            c => Token::Punct(c),
+        };
+        tokens.push((space, token));
+        space = false;
+    }
+    tokens
+}
+
+/// The log line with the binds of its query inlined, keeping the rest of
+/// it (`User Load (0.2ms)`). None for lines without a query or binds.
+pub fn inline_line_binds(message: &str) -> Option<String> {
+    let statement = sql_info::statement(message)?;
+    if split_binds(statement).1.is_empty() {
+        return None;
+    }
+    let start = message.find(statement)?;
+    Some(format!(
+        "{}{}{}",
+        &message[..start],
+        inline_binds(statement),
+        &message[start + statement.len()..]
+    ))
+}
+
+/// Splits `SELECT ... $1  [["id", 1]]` into the statement and its bind
+/// values as SQL literals.
+fn split_binds(statement: &str) -> (&str, Vec<String>) {
+    let Some(start) = statement.rfind("  [[").filter(|_| statement.ends_with("]]")) else {
+        return (statement, Vec::new());
+    };
+    let binds = RE_BIND
+        .captures_iter(&statement[start..])
+        .map(|caps| sql_literal(&caps[1]))
+        .collect();
+    (statement[..start].trim_end(), binds)
+}
+
+/// A bind value as Ruby inspects it (`"text"`, `1`, `nil`) turned into SQL.
+fn sql_literal(value: &str) -> String {
+    let value = value.trim();
+    if let Some(text) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        let text = text.replace("\\\"", "\"").replace("\\\\", "\\");
+        return format!("'{}'", text.replace('\'', "''"));
+    }
+    match value {
+        "nil" => "NULL".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_binds() {
+        assert_eq!(
+            inline_binds(r#"SELECT "users".* FROM "users" WHERE "users"."id" = $1 AND "users"."name" = $2 LIMIT $3  [["id", 1], ["name", "O'Brien \"Bob\""], ["LIMIT", 1]]"#),
+            r#"SELECT "users".* FROM "users" WHERE "users"."id" = 1 AND "users"."name" = 'O''Brien "Bob"' LIMIT 1"#
+        );
+        assert_eq!(
+            inline_binds("SELECT `posts`.* FROM `posts` WHERE `posts`.`title` = '?' AND `posts`.`deleted_at` = ? LIMIT ?  [[\"deleted_at\", nil], [\"LIMIT\", 1]]"),
+            "SELECT `posts`.* FROM `posts` WHERE `posts`.`title` = '?' AND `posts`.`deleted_at` = NULL LIMIT 1"
+        );
+        assert_eq!(inline_binds("SELECT COUNT(*) FROM posts"), "SELECT COUNT(*) FROM posts");
+    }
+
+    #[test]
+    fn test_inline_line_binds() {
+        assert_eq!(
+            inline_line_binds("[r1]   \x1b[1m\x1b[36mUser Load (0.2ms)\x1b[0m  \x1b[1m\x1b[34mSELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = $1 LIMIT $2\x1b[0m  [[\"id\", 7], [\"LIMIT\", 1]]\n"),
+            Some("[r1]   \x1b[1m\x1b[36mUser Load (0.2ms)\x1b[0m  \x1b[1m\x1b[34mSELECT \"users\".* FROM \"users\" WHERE \"users\".\"id\" = 7 LIMIT 1\x1b[0m\n".to_string())
+        );
+        assert_eq!(inline_line_binds("[r1]   Post Load (0.4ms)  SELECT \"posts\".* FROM \"posts\""), None);
+        assert_eq!(inline_line_binds("[r1] Completed 200 OK in 5ms"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            format(r#"select "posts"."id", "posts"."title" from "posts" left outer join "users" on "users"."id" = "posts"."user_id" and "users"."active" = true where "posts"."id" in (select "post_id" from "comments" where "spam" = false) and "created_at" between '2024-01-01' and '2024-02-01' order by "id" desc limit 10"#),
+            [
+                r#"SELECT "posts"."id","#,
+                r#"  "posts"."title""#,
+                r#"FROM "posts""#,
+                r#"LEFT OUTER JOIN "users" ON "users"."id" = "posts"."user_id""#,
+                r#"  AND "users"."active" = TRUE"#,
+                r#"WHERE "posts"."id" IN ("#,
+                r#"  SELECT "post_id""#,
+                r#"  FROM "comments""#,
+                r#"  WHERE "spam" = FALSE"#,
+                r#")"#,
+                r#"  AND "created_at" BETWEEN '2024-01-01' AND '2024-02-01'"#,
+                r#"ORDER BY "id" DESC"#,
+                r#"LIMIT 10"#,
+            ]
+            .join("\n")
+        );
+        // Subqueries are indented from the line they open on
+        assert_eq!(
+            format("SELECT 1 FROM a WHERE x = 1 AND y IN (SELECT y FROM b)"),
+            "SELECT 1\nFROM a\nWHERE x = 1\n  AND y IN (\n    SELECT y\n    FROM b\n  )"
+        );
+        assert_eq!(
+            format("INSERT INTO \"posts\" (\"title\", \"body\") VALUES ('it''s, fine', COALESCE(NULL, 'x')) RETURNING \"id\""),
+            "INSERT INTO \"posts\" (\"title\", \"body\")\nVALUES ('it''s, fine', COALESCE(NULL, 'x'))\nRETURNING \"id\""
+        );
+        assert_eq!(
+            format("UPDATE \"posts\" SET \"title\" = 'a', \"updated_at\" = '2024' /*action='update'*/"),
+            "UPDATE \"posts\"\nSET \"title\" = 'a',\n  \"updated_at\" = '2024' /*action='update'*/"
+        );
+    }
+}
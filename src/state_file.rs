@@ -0,0 +1,95 @@
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub use lucy_core::app_state::Note;
+
+/// What lucy remembers between runs, as opposed to the user-edited config.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// The first-run tutorial was finished or skipped.
+    pub tutorial_done: bool,
+    /// Notes attached to requests (`n`), by request id.
+    pub notes: BTreeMap<String, Note>,
+}
+
+/// Notes older than this are dropped the next time one is saved.
+const NOTE_MAX_AGE_DAYS: i64 = 90;
+
+impl State {
+    /// Loads the state file; a missing or unreadable file is a first run.
+    pub fn load() -> Self {
+        state_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Drops the notes written more than `NOTE_MAX_AGE_DAYS` before `now`,
+    /// in seconds since the epoch.
+    pub fn prune_notes(&mut self, now: i64) {
+        let cutoff = now - NOTE_MAX_AGE_DAYS * 24 * 60 * 60;
+        self.notes.retain(|_, note| note.written >= cutoff);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = state_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// `$XDG_STATE_HOME/lucy/state.toml` or `~/.local/state/lucy/state.toml`.
+fn state_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })?;
+    Some(base.join("lucy").join("state.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let note = Note {
+            text: "flaky".to_string(),
+            written: 1_714_564_800,
+            started: "2024-05-01T12:00:00+09:00".to_string(),
+        };
+        let state = State {
+            tutorial_done: true,
+            notes: BTreeMap::from([("req-1".to_string(), note)]),
+        };
+        let content = toml::to_string(&state).unwrap();
+        assert_eq!(toml::from_str::<State>(&content).unwrap(), state);
+        assert_eq!(toml::from_str::<State>("").unwrap(), State::default());
+    }
+
+    #[test]
+    fn test_prune_notes() {
+        let day = 24 * 60 * 60;
+        let now = 1_714_564_800;
+        let mut state = State::default();
+        for (request_id, days_ago) in [("recent", 1), ("old", NOTE_MAX_AGE_DAYS + 1)] {
+            let note = Note {
+                written: now - days_ago * day,
+                ..Default::default()
+            };
+            state.notes.insert(request_id.to_string(), note);
+        }
+        state.prune_notes(now);
+        assert_eq!(state.notes.keys().collect::<Vec<_>>(), ["recent"]);
+    }
+}
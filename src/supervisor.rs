@@ -0,0 +1,171 @@
+//! Running the command given after `--`, such as `bin/rails server`, so lucy
+//! reads its output directly instead of through a pipe. The command gets a
+//! process group of its own, so keys pressed in lucy never reach it; signals
+//! sent to lucy are passed on, and it is stopped as Ctrl+C would stop it when
+//! lucy quits.
+
+use color_eyre::{
+    Result,
+    eyre::{OptionExt, WrapErr},
+};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{ChildStderr, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the command gets to stop after SIGINT before it is killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const SHUTDOWN_POLL: Duration = Duration::from_millis(50);
+
+/// Signal lucy caught and has yet to pass on, 0 for none.
+static CAUGHT_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn catch_signal(signal: libc::c_int) {
+    CAUGHT_SIGNAL.store(signal, Ordering::Relaxed);
+}
+
+pub struct Supervisor {
+    /// The command as typed, for the status bar
+    pub command: String,
+    pid: i32,
+    /// Set by the thread waiting on the command once it exits
+    exit: Arc<OnceLock<ExitStatus>>,
+}
+
+impl Supervisor {
+    /// Starts `command`, returning its stdout and stderr to read.
+    pub fn spawn(command: &[String]) -> Result<(Self, ChildStdout, ChildStderr)> {
+        let (program, args) = command.split_first().ok_or_eyre("No command to run")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .wrap_err_with(|| format!("Failed to run {}", program))?;
+        let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+            unreachable!("both are piped");
+        };
+
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+            // Caught rather than ending lucy, for `forward_signals`
+            unsafe {
+                libc::signal(signal, catch_signal as *const () as libc::sighandler_t);
+            }
+        }
+        let pid = child.id() as i32;
+        let exit = Arc::new(OnceLock::new());
+        let thread_exit = Arc::clone(&exit);
+        thread::spawn(move || match child.wait() {
+            Ok(status) => {
+                let _ = thread_exit.set(status);
+            }
+            Err(e) => tracing::warn!("Failed to wait for the command: {}", e),
+        });
+
+        let supervisor = Self {
+            command: command.join(" "),
+            pid,
+            exit,
+        };
+        Ok((supervisor, stdout, stderr))
+    }
+
+    /// None while the command runs.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit.get().copied()
+    }
+
+    /// Sends `signal` to the command and whatever it started.
+    pub fn signal(&self, signal: i32) {
+        if self.exit_status().is_some() {
+            return;
+        }
+        // Negative: the whole process group the command leads
+        unsafe {
+            libc::kill(-self.pid, signal);
+        }
+    }
+
+    /// Passes on the SIGINT, SIGTERM or SIGHUP lucy caught since the last
+    /// call, if any. True when lucy should quit as well: SIGTERM and SIGHUP
+    /// end both, while SIGINT, as from Ctrl+C in the terminal, only stops
+    /// the command and lucy shows how it exited.
+    pub fn forward_signals(&self) -> bool {
+        match CAUGHT_SIGNAL.swap(0, Ordering::Relaxed) {
+            0 => false,
+            signal => {
+                self.signal(signal);
+                signal != libc::SIGINT
+            }
+        }
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        if self.exit_status().is_some() {
+            return;
+        }
+        self.signal(libc::SIGINT);
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while self.exit_status().is_none() {
+            if Instant::now() >= deadline {
+                tracing::warn!("{} did not stop, killing it", self.command);
+                self.signal(libc::SIGKILL);
+                return;
+            }
+            thread::sleep(SHUTDOWN_POLL);
+        }
+    }
+}
+
+/// `0`, `1`, or `SIGKILL` for a command killed by a signal.
+pub fn exit_label(status: ExitStatus) -> String {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => code.to_string(),
+        (None, Some(libc::SIGINT)) => "SIGINT".to_string(),
+        (None, Some(libc::SIGTERM)) => "SIGTERM".to_string(),
+        (None, Some(libc::SIGKILL)) => "SIGKILL".to_string(),
+        (None, Some(signal)) => format!("signal {}", signal),
+        (None, None) => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_exit_status() {
+        let command = ["sh", "-c", "echo out; echo err >&2; exit 3"].map(String::from);
+        let (supervisor, mut stdout, mut stderr) = Supervisor::spawn(&command).unwrap();
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).unwrap();
+        let mut err = String::new();
+        stderr.read_to_string(&mut err).unwrap();
+        assert_eq!((out.as_str(), err.as_str()), ("out\n", "err\n"));
+
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while supervisor.exit_status().is_none() && Instant::now() < deadline {
+            thread::sleep(SHUTDOWN_POLL);
+        }
+        assert_eq!(exit_label(supervisor.exit_status().unwrap()), "3");
+        assert_eq!(supervisor.command, "sh -c echo out; echo err >&2; exit 3");
+    }
+
+    #[test]
+    fn test_stopped_on_drop() {
+        let command = ["sleep", "30"].map(String::from);
+        let (supervisor, _, _) = Supervisor::spawn(&command).unwrap();
+        let exit = Arc::clone(&supervisor.exit);
+        let started = Instant::now();
+        drop(supervisor);
+        assert!(started.elapsed() < SHUTDOWN_TIMEOUT);
+        assert_eq!(exit_label(*exit.get().unwrap()), "SIGINT");
+    }
+}
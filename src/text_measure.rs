@@ -0,0 +1,110 @@
+//! Terminal cell widths of text, for the wrap and scroll math of every panel.
+//!
+//! Wide characters (CJK, most emoji) take two cells and combining marks none,
+//! so counting chars or bytes goes wrong as soon as a gem logs them.
+
+use ratatui::{
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How panels wrap long lines; `wrapped_rows` measures with the same rules.
+pub const WRAP: Wrap = Wrap { trim: true };
+
+/// Cells `text` takes on screen.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest prefix of `text` that fits in `max_width` cells. A wide
+/// character that would straddle the edge is left out.
+pub fn truncate(text: &str, max_width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > max_width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// `text` cut or padded with spaces to exactly `cells` wide.
+pub fn fit(text: &str, cells: usize) -> String {
+    let text = truncate(text, cells);
+    format!("{}{}", text, " ".repeat(cells - width(text)))
+}
+
+/// `text` made `excess` cells narrower by cutting the end of its `range`
+/// bytes and marking the cut with `…`, along with the chars that were cut.
+/// A range too narrow to give `excess` is cut down to nothing; None when
+/// cutting it gains nothing.
+pub fn elide(text: &str, range: Range<usize>, excess: usize) -> Option<(String, Range<usize>)> {
+    let part = &text[range.clone()];
+    let kept = truncate(part, width(part).saturating_sub(excess + 1));
+    if width(part) - width(kept) <= 1 {
+        return None;
+    }
+    let cut_start = range.start + kept.len();
+    let first = text[..cut_start].chars().count();
+    let cut = first..first + text[cut_start..range.end].chars().count();
+    Some((format!("{}…{}", &text[..cut_start], &text[range.end..]), cut))
+}
+
+/// Rows `line` takes when wrapped in a panel `max_width` cells wide.
+pub fn wrapped_rows(line: &Line, max_width: usize) -> usize {
+    if line.width() <= max_width || max_width == 0 {
+        return 1;
+    }
+    let max_width = u16::try_from(max_width).unwrap_or(u16::MAX);
+    Paragraph::new(line.clone())
+        .wrap(WRAP)
+        .line_count(max_width)
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width_counts_cells() {
+        assert_eq!(width("GET /posts"), 10);
+        assert_eq!(width("ユーザー"), 8);
+        assert_eq!(width("┌─┐"), 3);
+        assert_eq!(width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn test_truncate_and_fit() {
+        assert_eq!(truncate("development.log", 5), "devel");
+        assert_eq!(truncate("ユーザー", 5), "ユー");
+        assert_eq!(truncate("abc", 10), "abc");
+
+        assert_eq!(fit("web", 5), "web  ");
+        assert_eq!(fit("ユーザー", 5), "ユー ");
+    }
+
+    #[test]
+    fn test_elide() {
+        let text = "GET /posts/1/comments → CommentsController#index";
+        let (short, cut) = elide(text, 4..21, 5).unwrap();
+        assert_eq!(short, "GET /posts/1/co… → CommentsController#index");
+        assert_eq!(width(text) - width(&short), 5);
+        assert_eq!(cut, 15..21);
+        // Not enough path to give: all of it goes
+        assert_eq!(elide(text, 4..21, 40).unwrap().0, "GET … → CommentsController#index");
+        assert_eq!(elide(text, 4..21, 0), None);
+    }
+
+    #[test]
+    fn test_wrapped_rows() {
+        assert_eq!(wrapped_rows(&Line::raw("short"), 20), 1);
+        assert_eq!(wrapped_rows(&Line::raw(""), 20), 1);
+        // 12 wide chars take 24 cells
+        assert_eq!(wrapped_rows(&Line::raw("ああああああああああああ"), 10), 3);
+        assert_eq!(wrapped_rows(&Line::raw("aaaa bbbb cccc"), 9), 2);
+    }
+}
@@ -1,14 +1,21 @@
+use crate::app_state::StatusType;
 use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    str::FromStr,
+    sync::{PoisonError, RwLock},
+};
 
 pub trait ColorExt {
-    fn ansi(&self) -> &'static str;
+    fn ansi(&self) -> Cow<'static, str>;
     fn style(&self) -> Style;
     fn style_with_modifier(&self, modifier: Modifier) -> Style;
 }
 
 impl ColorExt for Color {
-    fn ansi(&self) -> &'static str {
-        match self {
+    fn ansi(&self) -> Cow<'static, str> {
+        let code = match self {
             Color::Black => "\x1b[30m",
             Color::Red => "\x1b[31m",
             Color::Green => "\x1b[32m",
@@ -25,8 +32,11 @@ impl ColorExt for Color {
             Color::LightMagenta => "\x1b[95m",
             Color::LightCyan => "\x1b[96m",
             Color::White => "\x1b[97m",
+            Color::Rgb(r, g, b) => return format!("\x1b[38;2;{};{};{}m", r, g, b).into(),
+            Color::Indexed(index) => return format!("\x1b[38;5;{}m", index).into(),
             _ => "\x1b[39m", // Default color
-        }
+        };
+        code.into()
     }
 
     fn style(&self) -> Style {
@@ -38,22 +48,290 @@ impl ColorExt for Color {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Theme {
     pub success: Color,
+    /// Codes mapped to info in `[status]`
+    pub info: Color,
     pub warning: Color,
     pub error: Color,
     pub default: Color,
     pub border: Color,
     pub active_border: Color,
+    /// Mark finished requests with ✓/!/✗ so status does not rely on color alone.
+    pub status_symbols: bool,
 }
 
-pub const THEME: Theme = Theme {
+pub const DEFAULT_THEME: Theme = Theme {
     success: Color::Green,
+    info: Color::Cyan,
     warning: Color::Yellow,
     error: Color::Red,
     default: Color::White,
     border: Color::DarkGray,
     active_border: Color::White,
+    status_symbols: false,
+};
+
+pub const HIGH_CONTRAST_THEME: Theme = Theme {
+    success: Color::LightGreen,
+    info: Color::LightCyan,
+    warning: Color::LightYellow,
+    error: Color::LightRed,
+    default: Color::White,
+    border: Color::Gray,
+    active_border: Color::LightYellow,
+    status_symbols: true,
+};
+
+/// Red and green are hard to tell apart: blue for success, magenta for errors.
+pub const DEUTERANOPIA_THEME: Theme = Theme {
+    success: Color::LightBlue,
+    info: Color::Cyan,
+    warning: Color::Yellow,
+    error: Color::LightMagenta,
+    default: Color::White,
+    border: Color::DarkGray,
+    active_border: Color::White,
+    status_symbols: true,
+};
+
+/// Red looks dark and close to green: cyan for success, bright magenta for errors.
+pub const PROTANOPIA_THEME: Theme = Theme {
+    success: Color::LightCyan,
+    info: Color::LightBlue,
+    warning: Color::LightYellow,
+    error: Color::LightMagenta,
+    default: Color::White,
+    border: Color::DarkGray,
+    active_border: Color::White,
+    status_symbols: true,
 };
 
+/// For terminals with a light background.
+pub const LIGHT_THEME: Theme = Theme {
+    success: Color::Green,
+    info: Color::Blue,
+    warning: Color::Yellow,
+    error: Color::Red,
+    default: Color::Black,
+    border: Color::Gray,
+    active_border: Color::Blue,
+    status_symbols: false,
+};
+
+/// Accent colors of the Solarized scheme.
+pub const SOLARIZED_THEME: Theme = Theme {
+    success: Color::Rgb(0x85, 0x99, 0x00),
+    info: Color::Rgb(0x2a, 0xa1, 0x98),
+    warning: Color::Rgb(0xb5, 0x89, 0x00),
+    error: Color::Rgb(0xdc, 0x32, 0x2f),
+    default: Color::Rgb(0x93, 0xa1, 0xa1),
+    border: Color::Rgb(0x58, 0x6e, 0x75),
+    active_border: Color::Rgb(0x26, 0x8b, 0xd2),
+    status_symbols: false,
+};
+
+/// No colors at all; status is told by symbols.
+pub const MONOCHROME_THEME: Theme = Theme {
+    success: Color::Reset,
+    info: Color::Reset,
+    warning: Color::Reset,
+    error: Color::Reset,
+    default: Color::Reset,
+    border: Color::Reset,
+    active_border: Color::Reset,
+    status_symbols: true,
+};
+
+/// Built-in palette, set with `theme` in the config file or `--theme`, and
+/// cycled with `Ctrl+t`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    #[serde(alias = "dark")]
+    Default,
+    Light,
+    Solarized,
+    Monochrome,
+    HighContrast,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl Palette {
+    /// In the order `Ctrl+t` cycles through them.
+    pub const ALL: [Palette; 7] = [
+        Palette::Default,
+        Palette::Light,
+        Palette::Solarized,
+        Palette::Monochrome,
+        Palette::HighContrast,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+    ];
+
+    pub fn theme(self) -> &'static Theme {
+        match self {
+            Palette::Default => &DEFAULT_THEME,
+            Palette::Light => &LIGHT_THEME,
+            Palette::Solarized => &SOLARIZED_THEME,
+            Palette::Monochrome => &MONOCHROME_THEME,
+            Palette::HighContrast => &HIGH_CONTRAST_THEME,
+            Palette::Deuteranopia => &DEUTERANOPIA_THEME,
+            Palette::Protanopia => &PROTANOPIA_THEME,
+        }
+    }
+
+    /// Name used in the config file and by `--theme`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Light => "light",
+            Palette::Solarized => "solarized",
+            Palette::Monochrome => "monochrome",
+            Palette::HighContrast => "high-contrast",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Palette::Default),
+            _ => Self::ALL.into_iter().find(|palette| palette.name() == name),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&palette| palette == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// A color written in the config file: a name (`red`, `lightblue`), `#rrggbb`
+/// or a 256-color index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ColorSpec(pub Color);
+
+impl TryFrom<String> for ColorSpec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::from_str(&value)
+            .map(ColorSpec)
+            .map_err(|_| format!("invalid color {:?}", value))
+    }
+}
+
+/// `[colors]` in the config file, laid over whichever palette is active.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ColorOverrides {
+    pub success: Option<ColorSpec>,
+    pub info: Option<ColorSpec>,
+    pub warning: Option<ColorSpec>,
+    pub error: Option<ColorSpec>,
+    pub default: Option<ColorSpec>,
+    pub border: Option<ColorSpec>,
+    pub active_border: Option<ColorSpec>,
+    pub status_symbols: Option<bool>,
+}
+
+impl ColorOverrides {
+    pub fn apply(&self, theme: &Theme) -> Theme {
+        let pick = |color: Option<ColorSpec>, fallback| color.map_or(fallback, |ColorSpec(c)| c);
+        Theme {
+            success: pick(self.success, theme.success),
+            info: pick(self.info, theme.info),
+            warning: pick(self.warning, theme.warning),
+            error: pick(self.error, theme.error),
+            default: pick(self.default, theme.default),
+            border: pick(self.border, theme.border),
+            active_border: pick(self.active_border, theme.active_border),
+            status_symbols: self.status_symbols.unwrap_or(theme.status_symbols),
+        }
+    }
+}
+
+static ACTIVE_THEME: RwLock<Theme> = RwLock::new(DEFAULT_THEME);
+
+/// The theme everything is drawn with.
+pub fn theme() -> Theme {
+    *ACTIVE_THEME.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Color of a status class in the active theme.
+pub trait StatusColor {
+    fn to_color(self) -> Color;
+}
+
+impl StatusColor for StatusType {
+    fn to_color(self) -> Color {
+        match self {
+            StatusType::Success => theme().success,
+            StatusType::Info => theme().info,
+            StatusType::Warning => theme().warning,
+            StatusType::Error => theme().error,
+            StatusType::Unknown => theme().default,
+        }
+    }
+}
+
+pub fn set_theme(palette: Palette, overrides: &ColorOverrides) {
+    *ACTIVE_THEME.write().unwrap_or_else(PoisonError::into_inner) = overrides.apply(palette.theme());
+}
+
+/// Source column colors, one per input; none of them is a status color.
+const SOURCE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightCyan,
+    Color::LightMagenta,
+    Color::LightBlue,
+];
+
+pub fn source_color(source: usize) -> Color {
+    SOURCE_COLORS[source % SOURCE_COLORS.len()]
+}
+
 pub const ANSI_RESET: &str = "\x1b[0m";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_names() {
+        for palette in Palette::ALL {
+            assert_eq!(Palette::from_name(palette.name()), Some(palette));
+        }
+        assert_eq!(Palette::from_name("dark"), Some(Palette::Default));
+        assert_eq!(Palette::from_name("neon"), None);
+        assert_eq!(Palette::Protanopia.next(), Palette::Default);
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let overrides = ColorOverrides {
+            error: Some(ColorSpec::try_from("#ff8800".to_string()).unwrap()),
+            border: Some(ColorSpec::try_from("lightblue".to_string()).unwrap()),
+            ..Default::default()
+        };
+        let theme = overrides.apply(&DEFAULT_THEME);
+        assert_eq!(theme.error, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.border, Color::LightBlue);
+        assert_eq!(theme.success, DEFAULT_THEME.success);
+        assert!(ColorSpec::try_from("reddish".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_ansi() {
+        assert_eq!(Color::Red.ansi(), "\x1b[31m");
+        assert_eq!(Color::Rgb(1, 2, 3).ansi(), "\x1b[38;2;1;2;3m");
+        assert_eq!(Color::Indexed(208).ansi(), "\x1b[38;5;208m");
+    }
+}
@@ -0,0 +1,119 @@
+//! Writing times for display, set with `[time_format]`: in the configured
+//! zone, on a 24 or 12-hour clock, with or without the date. The list,
+//! panels, popups and exports all go through `TimeFormat` so that a time
+//! reads the same wherever it shows up.
+
+use crate::config::TimeZoneMode;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Clock {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    /// `03:05 PM`, zero-padded so list columns stay aligned
+    #[serde(rename = "12h")]
+    H12,
+}
+
+/// `[time_format]` in the config file.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct TimeFormatConfig {
+    pub clock: Clock,
+    /// Lead times with the date, such as `2024-05-01 12:05`
+    pub date: bool,
+}
+
+/// How much of the time of day is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// `12:00`, or `12 PM`, for hourly headers
+    Hours,
+    Minutes,
+    Seconds,
+    Millis,
+}
+
+/// The zone and format times are displayed in; see `Config::times`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeFormat {
+    pub zone: TimeZoneMode,
+    pub clock: Clock,
+    pub date: bool,
+}
+
+impl TimeFormat {
+    pub fn new(zone: TimeZoneMode, config: TimeFormatConfig) -> Self {
+        Self {
+            zone,
+            clock: config.clock,
+            date: config.date,
+        }
+    }
+
+    /// `time` in the zone, with the date when the config asks for it.
+    pub fn format(&self, time: &DateTime<FixedOffset>, precision: Precision) -> String {
+        self.write(time, precision, self.date)
+    }
+
+    /// `time` in the zone, always with the date, for times read on their own.
+    pub fn format_dated(&self, time: &DateTime<FixedOffset>, precision: Precision) -> String {
+        self.write(time, precision, true)
+    }
+
+    /// Dated and with the offset, for exports read away from lucy.
+    pub fn format_full(&self, time: &DateTime<FixedOffset>) -> String {
+        let time = self.zone.convert(time);
+        format!(
+            "{} {}",
+            self.write(&time, Precision::Seconds, true),
+            time.format("%:z")
+        )
+    }
+
+    fn write(&self, time: &DateTime<FixedOffset>, precision: Precision, date: bool) -> String {
+        let clock = match (self.clock, precision) {
+            (Clock::H24, Precision::Hours) => "%H:00",
+            (Clock::H24, Precision::Minutes) => "%H:%M",
+            (Clock::H24, Precision::Seconds) => "%H:%M:%S",
+            (Clock::H24, Precision::Millis) => "%H:%M:%S%.3f",
+            (Clock::H12, Precision::Hours) => "%I %p",
+            (Clock::H12, Precision::Minutes) => "%I:%M %p",
+            (Clock::H12, Precision::Seconds) => "%I:%M:%S %p",
+            (Clock::H12, Precision::Millis) => "%I:%M:%S%.3f %p",
+        };
+        let pattern = if date {
+            format!("%Y-%m-%d {}", clock)
+        } else {
+            clock.to_string()
+        };
+        self.zone.convert(time).format(&pattern).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = DateTime::parse_from_rfc3339("2024-05-01T15:05:09.250+09:00").unwrap();
+        let mut times = TimeFormat {
+            zone: TimeZoneMode::Original,
+            ..Default::default()
+        };
+        assert_eq!(times.format(&time, Precision::Minutes), "15:05");
+        assert_eq!(times.format(&time, Precision::Hours), "15:00");
+        assert_eq!(times.format_dated(&time, Precision::Millis), "2024-05-01 15:05:09.250");
+
+        times.clock = Clock::H12;
+        times.date = true;
+        assert_eq!(times.format(&time, Precision::Seconds), "2024-05-01 03:05:09 PM");
+        assert_eq!(times.format(&time, Precision::Hours), "2024-05-01 03 PM");
+
+        times.zone = TimeZoneMode::Utc;
+        assert_eq!(times.format_full(&time), "2024-05-01 06:05:09 AM +00:00");
+    }
+}
@@ -0,0 +1,191 @@
+//! Headers that split the request list by the minute or hour requests
+//! started in, set with `[list] group_by` and cycled with `H`, and the
+//! dividers marking where the server restarted.
+
+use crate::time_format::{Precision, TimeFormat};
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Minute,
+    Hour,
+}
+
+impl GroupBy {
+    /// Off, by minute, by hour, then off again.
+    pub fn cycle(group_by: Option<GroupBy>) -> Option<GroupBy> {
+        match group_by {
+            None => Some(GroupBy::Minute),
+            Some(GroupBy::Minute) => Some(GroupBy::Hour),
+            Some(GroupBy::Hour) => None,
+        }
+    }
+
+    /// Header of the requests started at `time`, such as `12:05`.
+    pub fn label(self, time: &DateTime<FixedOffset>, times: TimeFormat) -> String {
+        match self {
+            GroupBy::Minute => times.format(time, Precision::Minutes),
+            GroupBy::Hour => times.format(time, Precision::Hours),
+        }
+    }
+
+    fn bucket(self, time: &DateTime<FixedOffset>) -> String {
+        match self {
+            GroupBy::Minute => time.format("%Y-%m-%d %H:%M").to_string(),
+            GroupBy::Hour => time.format("%Y-%m-%d %H").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListRow {
+    Header(String),
+    /// The request at this position of the visible list
+    Request(usize),
+}
+
+/// Where a request sits in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// When it started
+    pub time: DateTime<FixedOffset>,
+    /// The server restart it came after, if any
+    pub restart: Option<DateTime<FixedOffset>>,
+}
+
+/// Rows of the list from the request at `offset` on, until `height` rows
+/// are filled: with `group_by`, a header at the top and wherever the
+/// bucket changes, written as `times` asks, and a divider labeled by `restart_label` between
+/// requests from either side of a restart. `slot_at` gives the request at
+/// a position, or None past the end of the list.
+pub fn layout(
+    group_by: Option<GroupBy>,
+    times: TimeFormat,
+    offset: usize,
+    height: usize,
+    slot_at: impl Fn(usize) -> Option<Slot>,
+    restart_label: impl Fn(&DateTime<FixedOffset>) -> String,
+) -> Vec<ListRow> {
+    let mut rows = Vec::with_capacity(height);
+    let mut bucket = None;
+    let mut previous: Option<Slot> = None;
+    let mut position = offset;
+    while rows.len() < height {
+        let Some(slot) = slot_at(position) else {
+            break;
+        };
+        let mut headers = Vec::new();
+        // Newest first, so the row above came after the later restart
+        if let Some(restart) = previous
+            .filter(|previous| previous.restart != slot.restart)
+            .and_then(|previous| previous.restart)
+        {
+            headers.push(ListRow::Header(restart_label(&restart)));
+        }
+        if let Some(group_by) = group_by {
+            let next = group_by.bucket(&slot.time);
+            if bucket.as_ref() != Some(&next) {
+                headers.push(ListRow::Header(group_by.label(&slot.time, times)));
+                bucket = Some(next);
+            }
+        }
+        // Headers on the last rows would have nothing under them
+        if !headers.is_empty() && rows.len() + headers.len() >= height {
+            break;
+        }
+        rows.extend(headers);
+        rows.push(ListRow::Request(position));
+        previous = Some(slot);
+        position += 1;
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TimeZoneMode;
+
+    #[test]
+    fn test_layout() {
+        let times: Vec<DateTime<FixedOffset>> = [
+            "2024-05-01T12:06:30Z",
+            "2024-05-01T12:06:10Z",
+            "2024-05-01T12:05:50Z",
+            "2024-05-01T11:59:00Z",
+        ]
+        .iter()
+        .map(|time| DateTime::parse_from_rfc3339(time).unwrap())
+        .collect();
+        let slot_at = |position: usize| {
+            times.get(position).map(|&time| Slot {
+                time,
+                restart: None,
+            })
+        };
+        let restart_label = |time: &DateTime<FixedOffset>| time.format("restart %H:%M").to_string();
+        let format = TimeFormat {
+            zone: TimeZoneMode::Original,
+            ..Default::default()
+        };
+        let header = |label: &str| ListRow::Header(label.to_string());
+
+        assert_eq!(
+            layout(Some(GroupBy::Minute), format, 0, 10, slot_at, restart_label),
+            [
+                header("12:06"),
+                ListRow::Request(0),
+                ListRow::Request(1),
+                header("12:05"),
+                ListRow::Request(2),
+                header("11:59"),
+                ListRow::Request(3),
+            ]
+        );
+        // Scrolled into a bucket, its header still comes first
+        assert_eq!(
+            layout(Some(GroupBy::Minute), format, 1, 4, slot_at, restart_label),
+            [header("12:06"), ListRow::Request(1), header("12:05"), ListRow::Request(2)]
+        );
+        assert_eq!(
+            layout(Some(GroupBy::Minute), format, 0, 4, slot_at, restart_label),
+            [header("12:06"), ListRow::Request(0), ListRow::Request(1)]
+        );
+        assert_eq!(
+            layout(Some(GroupBy::Hour), format, 0, 10, slot_at, restart_label),
+            [
+                header("12:00"),
+                ListRow::Request(0),
+                ListRow::Request(1),
+                ListRow::Request(2),
+                header("11:00"),
+                ListRow::Request(3),
+            ]
+        );
+
+        // Restarted at 12:06:00, between the first two requests and the others
+        let restart = DateTime::parse_from_rfc3339("2024-05-01T12:06:00Z").unwrap();
+        let slot_at = |position: usize| {
+            times.get(position).map(|&time| Slot {
+                time,
+                restart: (time >= restart).then_some(restart),
+            })
+        };
+        assert_eq!(
+            layout(None, format, 0, 10, slot_at, restart_label),
+            [
+                ListRow::Request(0),
+                ListRow::Request(1),
+                header("restart 12:06"),
+                ListRow::Request(2),
+                ListRow::Request(3),
+            ]
+        );
+        assert_eq!(
+            layout(Some(GroupBy::Minute), format, 1, 4, slot_at, restart_label),
+            [header("12:06"), ListRow::Request(1)]
+        );
+    }
+}
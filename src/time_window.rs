@@ -0,0 +1,159 @@
+//! Narrowing the request list to the requests started within a span of time (`T`).
+
+use crate::config::TimeZoneMode;
+use crate::file_index::parse_jump_time;
+use crate::time_format::{Precision, TimeFormat};
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+    /// The last stretch of the log, counted back from its newest line like
+    /// retention ages, so replayed files behave like live streams
+    Last(TimeDelta),
+    /// From one instant to another, both included
+    Between(DateTime<FixedOffset>, DateTime<FixedOffset>),
+}
+
+impl TimeWindow {
+    /// Parses a duration (`15` minutes, `90s`, `15m`, `2h`) or two times
+    /// separated by `-` (`12:00-12:30`, `2024-05-01 23:50 - 2024-05-02 00:10`).
+    /// Times are read like `@` reads them, bare ones on the date of `reference`.
+    pub fn parse(
+        input: &str,
+        reference: DateTime<FixedOffset>,
+        zone: TimeZoneMode,
+    ) -> Option<Self> {
+        let input = input.trim();
+        if let Some(duration) = parse_duration(input) {
+            return Some(TimeWindow::Last(duration));
+        }
+        // Dates contain dashes too, so try every dash as the separator
+        input.match_indices('-').find_map(|(i, _)| {
+            let from = parse_jump_time(&input[..i], reference, zone)?;
+            let to = parse_jump_time(&input[i + 1..], reference, zone)?;
+            Some(TimeWindow::Between(from.min(to), from.max(to)))
+        })
+    }
+
+    /// Whether a request started at `timestamp` falls in the window, given
+    /// the newest timestamp seen.
+    pub fn contains(
+        &self,
+        timestamp: DateTime<FixedOffset>,
+        latest: Option<DateTime<FixedOffset>>,
+    ) -> bool {
+        match *self {
+            TimeWindow::Last(duration) => {
+                latest.is_none_or(|latest| timestamp >= latest - duration)
+            }
+            TimeWindow::Between(from, to) => from <= timestamp && timestamp <= to,
+        }
+    }
+
+    /// `15m` or `12:00:00-12:30:00`, for the list title.
+    pub fn label(&self, times: TimeFormat) -> String {
+        match *self {
+            TimeWindow::Last(duration) => {
+                let seconds = duration.num_seconds();
+                if seconds % 3600 == 0 {
+                    format!("{}h", seconds / 3600)
+                } else if seconds % 60 == 0 {
+                    format!("{}m", seconds / 60)
+                } else {
+                    format!("{}s", seconds)
+                }
+            }
+            TimeWindow::Between(from, to) => {
+                if times.zone.convert(&from).date_naive() == times.zone.convert(&to).date_naive() {
+                    format!(
+                        "{}-{}",
+                        times.format(&from, Precision::Seconds),
+                        times.format(&to, Precision::Seconds)
+                    )
+                } else {
+                    format!(
+                        "{}-{}",
+                        times.format_dated(&from, Precision::Minutes),
+                        times.format_dated(&to, Precision::Minutes)
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// `15` (minutes), `90s`, `15m` or `2h`.
+fn parse_duration(input: &str) -> Option<TimeDelta> {
+    let (number, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => input.split_at(i),
+        None => (input, "m"),
+    };
+    let number: i64 = number.parse().ok().filter(|&number| number > 0)?;
+    match unit.trim() {
+        "s" => TimeDelta::try_seconds(number),
+        "m" => TimeDelta::try_minutes(number),
+        "h" => TimeDelta::try_hours(number),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(time).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let reference = at("2024-05-01T12:00:00+09:00");
+        let parse = |input| TimeWindow::parse(input, reference, TimeZoneMode::Original);
+
+        assert_eq!(parse("15"), Some(TimeWindow::Last(TimeDelta::minutes(15))));
+        assert_eq!(parse("2h"), Some(TimeWindow::Last(TimeDelta::hours(2))));
+        assert_eq!(parse("90s"), Some(TimeWindow::Last(TimeDelta::seconds(90))));
+        assert_eq!(
+            parse("12:30-12:00"),
+            Some(TimeWindow::Between(
+                at("2024-05-01T12:00:00+09:00"),
+                at("2024-05-01T12:30:00+09:00")
+            ))
+        );
+        assert_eq!(
+            parse("2024-04-30 23:50 - 2024-05-01 00:10"),
+            Some(TimeWindow::Between(
+                at("2024-04-30T23:50:00+09:00"),
+                at("2024-05-01T00:10:00+09:00")
+            ))
+        );
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("15d"), None);
+        assert_eq!(parse("soon"), None);
+    }
+
+    #[test]
+    fn test_contains() {
+        let latest = Some(at("2024-05-01T12:00:00Z"));
+        let last = TimeWindow::Last(TimeDelta::minutes(15));
+        assert!(last.contains(at("2024-05-01T11:45:00Z"), latest));
+        assert!(!last.contains(at("2024-05-01T11:44:59Z"), latest));
+        assert!(last.contains(at("2000-01-01T00:00:00Z"), None));
+
+        let between = TimeWindow::Between(at("2024-05-01T11:00:00Z"), at("2024-05-01T11:30:00Z"));
+        assert!(between.contains(at("2024-05-01T11:30:00Z"), latest));
+        assert!(!between.contains(at("2024-05-01T11:31:00Z"), latest));
+    }
+
+    #[test]
+    fn test_label() {
+        let utc = TimeFormat {
+            zone: TimeZoneMode::Utc,
+            ..Default::default()
+        };
+        assert_eq!(TimeWindow::Last(TimeDelta::minutes(90)).label(utc), "90m");
+        assert_eq!(TimeWindow::Last(TimeDelta::hours(2)).label(utc), "2h");
+        let between = TimeWindow::Between(at("2024-05-01T11:00:00Z"), at("2024-05-01T11:30:00Z"));
+        assert_eq!(between.label(utc), "11:00:00-11:30:00");
+    }
+}
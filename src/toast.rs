@@ -0,0 +1,118 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen once shown.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// Toasts on screen at once; the rest wait their turn.
+const MAX_VISIBLE: usize = 3;
+/// Toasts kept for the notifications popup.
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub message: String,
+    pub created_at: DateTime<Local>,
+}
+
+/// Short notices shown in a corner, with a history for `N`.
+#[derive(Default)]
+pub struct Toasts {
+    /// Waiting or on screen, oldest first, with the time each was first shown
+    queue: VecDeque<(Toast, Option<Instant>)>,
+    history: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, level: ToastLevel, message: impl Into<String>) {
+        let toast = Toast {
+            level,
+            message: message.into(),
+            created_at: Local::now(),
+        };
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(toast.clone());
+        self.queue.push_back((toast, None));
+    }
+
+    /// Drops expired toasts and starts the timer of those coming on screen.
+    /// Returns true when what is on screen changed.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let before = self.queue.len();
+        self.queue
+            .retain(|(_, shown_at)| shown_at.is_none_or(|shown| now < shown + TOAST_DURATION));
+        let mut changed = self.queue.len() != before;
+        for (_, shown_at) in self.queue.iter_mut().take(MAX_VISIBLE) {
+            if shown_at.is_none() {
+                *shown_at = Some(now);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Toasts on screen, oldest first.
+    pub fn visible(&self) -> impl Iterator<Item = &Toast> {
+        self.queue
+            .iter()
+            .take_while(|(_, shown_at)| shown_at.is_some())
+            .map(|(toast, _)| toast)
+    }
+
+    /// Every recent toast, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &Toast> {
+        self.history.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages<'a>(toasts: impl Iterator<Item = &'a Toast>) -> Vec<&'a str> {
+        toasts.map(|toast| toast.message.as_str()).collect()
+    }
+
+    #[test]
+    fn test_toasts_queue_and_expire() {
+        let mut toasts = Toasts::default();
+        let start = Instant::now();
+        for message in ["a", "b", "c", "d"] {
+            toasts.push(ToastLevel::Info, message);
+        }
+        assert!(toasts.visible().next().is_none());
+
+        assert!(toasts.tick(start));
+        assert_eq!(messages(toasts.visible()), ["a", "b", "c"]);
+        assert!(!toasts.tick(start + Duration::from_secs(1)));
+
+        // "d" gets its full time once the first three expire
+        toasts.tick(start + TOAST_DURATION);
+        assert_eq!(messages(toasts.visible()), ["d"]);
+        toasts.tick(start + TOAST_DURATION * 2 - Duration::from_millis(1));
+        assert_eq!(messages(toasts.visible()), ["d"]);
+        toasts.tick(start + TOAST_DURATION * 2);
+        assert!(toasts.visible().next().is_none());
+
+        assert_eq!(messages(toasts.history()), ["d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut toasts = Toasts::default();
+        for i in 0..HISTORY_LIMIT + 5 {
+            toasts.push(ToastLevel::Error, i.to_string());
+        }
+        assert_eq!(toasts.history().count(), HISTORY_LIMIT);
+        assert_eq!(toasts.history().last().unwrap().message, "5");
+    }
+}
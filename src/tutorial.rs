@@ -0,0 +1,101 @@
+use crate::layout::Panel;
+
+/// Requests fed in on first run when nothing is piped, so the tutorial has
+/// something to point at. Lines without a timestamp are stamped on arrival.
+pub const SAMPLE_LOG: &str = r#"[3f2a9c1e] Started GET "/posts" for 127.0.0.1
+[3f2a9c1e] Processing by PostsController#index as HTML
+[3f2a9c1e]   Post Load (0.6ms)  SELECT "posts".* FROM "posts" ORDER BY "posts"."created_at" DESC LIMIT $1  [["LIMIT", 20]]
+[3f2a9c1e]   User Load (0.3ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1 LIMIT $2  [["id", 1], ["LIMIT", 1]]
+[3f2a9c1e]   User Load (0.2ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1 LIMIT $2  [["id", 2], ["LIMIT", 1]]
+[3f2a9c1e]   User Load (0.2ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1 LIMIT $2  [["id", 3], ["LIMIT", 1]]
+[3f2a9c1e]   CACHE User Load (0.0ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1 LIMIT $2  [["id", 1], ["LIMIT", 1]]
+[3f2a9c1e]   Rendered collection of posts/_post.html.erb [20 times] (Duration: 8.4ms | Allocations: 2100)
+[3f2a9c1e]   Rendered posts/index.html.erb within layouts/application (Duration: 11.2ms | Allocations: 3050)
+[3f2a9c1e]   Rendered layout layouts/application.html.erb (Duration: 14.0ms | Allocations: 4210)
+[3f2a9c1e] Completed 200 OK in 42ms (Views: 14.8ms | ActiveRecord: 1.3ms | Allocations: 5120)
+[8b41d0f7] Started POST "/posts" for 127.0.0.1
+[8b41d0f7] Processing by PostsController#create as TURBO_STREAM
+[8b41d0f7]   Parameters: {"post"=>{"title"=>"Hello", "body"=>"[FILTERED]"}}
+[8b41d0f7]   TRANSACTION (0.1ms)  BEGIN
+[8b41d0f7]   Post Create (0.8ms)  INSERT INTO "posts" ("title", "body", "created_at", "updated_at") VALUES ($1, $2, $3, $4) RETURNING "id"
+[8b41d0f7]   TRANSACTION (0.4ms)  COMMIT
+[8b41d0f7] Redirected to http://localhost:3000/posts/21
+[8b41d0f7] Completed 302 Found in 12ms (ActiveRecord: 1.3ms | Allocations: 1800)
+[c7e05a22] Started GET "/posts/999" for 127.0.0.1
+[c7e05a22] Processing by PostsController#show as HTML
+[c7e05a22]   Parameters: {"id"=>"999"}
+[c7e05a22]   Post Load (0.3ms)  SELECT "posts".* FROM "posts" WHERE "posts"."id" = $1 LIMIT $2  [["id", 999], ["LIMIT", 1]]
+[c7e05a22] Completed 404 Not Found in 4ms (ActiveRecord: 0.3ms | Allocations: 700)
+[e19b6f30] Started GET "/admin/reports" for 127.0.0.1
+[e19b6f30] Processing by Admin::ReportsController#index as HTML
+[e19b6f30]   Report Load (612.4ms)  SELECT "reports".* FROM "reports" WHERE "reports"."archived" = $1  [["archived", false]]
+[e19b6f30] Completed 500 Internal Server Error in 640ms (ActiveRecord: 612.4ms | Allocations: 9100)
+[e19b6f30] NoMethodError (undefined method `title' for nil):
+[e19b6f30] app/controllers/admin/reports_controller.rb:8:in `index'
+"#;
+
+/// Panel each tutorial step points at, in order. The step texts live in
+/// `Strings::tutorial_steps`.
+const STEP_PANELS: [Option<Panel>; 5] = [
+    None,
+    Some(Panel::RequestList),
+    Some(Panel::RequestDetail),
+    Some(Panel::SqlInfo),
+    None,
+];
+
+/// Progress through the first-run tutorial overlay.
+#[derive(Debug, Default)]
+pub struct Tutorial {
+    pub step: usize,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn step_count() -> usize {
+        STEP_PANELS.len()
+    }
+
+    /// Panel to focus for the current step, if it is about one.
+    pub fn panel(&self) -> Option<Panel> {
+        STEP_PANELS.get(self.step).copied().flatten()
+    }
+
+    /// Moves to the next step. Returns false once past the last one.
+    pub fn advance(&mut self) -> bool {
+        self.step += 1;
+        self.step < STEP_PANELS.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{EN, JA};
+    use crate::log_parser;
+
+    #[test]
+    fn test_steps() {
+        let mut tutorial = Tutorial::new();
+        assert_eq!(tutorial.panel(), None);
+        assert!(tutorial.advance());
+        assert_eq!(tutorial.panel(), Some(Panel::RequestList));
+        while tutorial.advance() {}
+        assert_eq!(tutorial.step, Tutorial::step_count());
+
+        assert_eq!(EN.tutorial_steps.len(), Tutorial::step_count());
+        assert_eq!(JA.tutorial_steps.len(), Tutorial::step_count());
+    }
+
+    #[test]
+    fn test_sample_log_parses() {
+        let request_ids: std::collections::HashSet<String> = SAMPLE_LOG
+            .lines()
+            .map(|line| log_parser::parse(line, None).unwrap().request_id)
+            .collect();
+        assert_eq!(request_ids.len(), 4);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::config;
+use regex::Regex;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::thread;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+const RELEASES_URL: &str = "https://api.github.com/repos/eudoxa/lucy/releases/latest";
+
+static RE_TAG_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""tag_name"\s*:\s*"v?([^"]+)""#).unwrap());
+
+/// Latest release version, set by the update check once it finds a newer one.
+pub type UpdateNotice = Arc<OnceLock<String>>;
+
+/// Printed by `lucy --version` and shown in the about popup.
+pub fn about_lines() -> Vec<String> {
+    let config_path =
+        config::config_path().map_or_else(|| "-".to_string(), |path| path.display().to_string());
+    vec![
+        format!("lucy {}", VERSION),
+        env!("CARGO_PKG_DESCRIPTION").to_string(),
+        format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        format!("config: {}", config_path),
+        env!("CARGO_PKG_REPOSITORY").to_string(),
+    ]
+}
+
+/// Asks GitHub for the latest release in the background. Failures, including
+/// a missing `curl`, are only logged: the check must never get in the way.
+pub fn spawn_update_check() -> UpdateNotice {
+    let notice = UpdateNotice::default();
+    let result = Arc::clone(&notice);
+    thread::spawn(move || match fetch_latest_version() {
+        Some(latest) if is_newer(&latest, VERSION) => {
+            let _ = result.set(latest);
+        }
+        Some(_) => {}
+        None => tracing::debug!("Update check failed"),
+    });
+    notice
+}
+
+fn fetch_latest_version() -> Option<String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "5", RELEASES_URL])
+        .args(["-H", "Accept: application/vnd.github+json"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_release_tag(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_release_tag(body: &str) -> Option<String> {
+    RE_TAG_NAME.captures(body).map(|caps| caps[1].to_string())
+}
+
+/// Compares dotted version numbers; pre-release suffixes are ignored and
+/// missing parts count as 0, so `0.1` and `0.1.0` are the same.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (mut latest, mut current) = (parts(latest), parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_release_tag() {
+        let body = r#"{"url": "...", "tag_name": "v0.3.1", "name": "Lucy 0.3.1"}"#;
+        assert_eq!(parse_release_tag(body), Some("0.3.1".to_string()));
+        assert_eq!(parse_release_tag(r#"{"message": "Not Found"}"#), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.0", "0.99"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-beta.1", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+        assert!(!is_newer("0.1.0", "0.1"));
+        assert!(!is_newer("0.1", "0.1.0"));
+        assert!(is_newer("0.1.1", "0.1"));
+    }
+}
@@ -0,0 +1,875 @@
+//! What the panels show, as plain data taken from `App`.
+//!
+//! The builders in `panel_components` lay these out and style them without
+//! looking at the app, so a panel can be rendered from hand-made data in
+//! snapshot tests, or by a frontend other than the terminal one.
+
+use crate::app::{App, SearchTarget};
+use crate::app_state::{GroupKind, LogEntry, LogGroup, StatusType};
+use crate::app_view::TimelineScope;
+use crate::columns::Column;
+use crate::command;
+use crate::endpoint::EndpointSummary;
+use crate::filter;
+use crate::i18n::{Strings, fill};
+use crate::icons::IconSet;
+use crate::layout::Panel;
+use crate::log_parser::strip_ansi_for_parsing;
+use crate::metrics::{self, TRAFFIC_SECONDS};
+use crate::panel_components::{
+    build_detail_log_lines, format_bytes, mark_stderr, prefix_relative_time, timeline_axis_width,
+};
+use crate::simple_formatter::Verbosity;
+use crate::supervisor;
+use crate::text_measure;
+use crate::time_format::Precision;
+use crate::time_headers::ListRow;
+use crate::time_window::TimeWindow;
+use crate::waterfall::{self, Step};
+use chrono::{DateTime, FixedOffset};
+use ratatui::text::Line;
+use std::ops::Range;
+use std::time::Instant;
+
+const INDEX_OFFSET: usize = 1;
+// Longer source names are cut in the request list
+const SOURCE_COLUMN_MAX_WIDTH: usize = 12;
+const ACTION_COLUMN_MAX_WIDTH: usize = 28;
+// Cells taken by the count, error rate and mean duration of an endpoint
+const ENDPOINT_FIGURES_WIDTH: usize = 18;
+
+/// How a panel is framed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PanelChrome {
+    pub focused: bool,
+    /// Side borders are left out so terminal selection copies only the text
+    pub copy_mode: bool,
+}
+
+impl PanelChrome {
+    fn of(app: &App, panel: Panel) -> Self {
+        Self {
+            focused: app.app_view.focused_panel == panel,
+            copy_mode: app.copy_mode_enabled,
+        }
+    }
+}
+
+/// The left panel: requests, or endpoints while that view is open (`e`).
+pub enum ListView<'a> {
+    Requests(RequestListView<'a>),
+    Endpoints(EndpointListView),
+}
+
+pub struct RequestListView<'a> {
+    pub chrome: PanelChrome,
+    /// Position, count and active filters, e.g. `[1-20/143] Jobs /posts`
+    pub title: String,
+    /// Prompt being typed or count pending, at the bottom
+    pub prompt: Option<String>,
+    /// Only the rows on screen
+    pub rows: Vec<RequestRow<'a>>,
+    /// Width of the row numbers, when they are shown
+    pub number_width: Option<usize>,
+    pub source_width: usize,
+    pub action_width: usize,
+    /// Cells a row has before it is clipped
+    pub width: usize,
+    /// Shown before each title, in this order
+    pub columns: &'a [Column],
+    pub icons: IconSet,
+    pub strings: &'static Strings,
+}
+
+pub struct RequestRow<'a> {
+    /// Time bucket shown above the row, when it starts one
+    pub header: Option<String>,
+    /// Position in the list, counting from 1
+    pub number: usize,
+    /// Start time, `HH:MM` in the configured zone
+    pub time: String,
+    pub duration_ms: Option<u64>,
+    /// Name and index of the input the request came from, with several inputs
+    pub source: Option<(&'a str, usize)>,
+    pub kind: GroupKind,
+    /// As `[list] title` has it
+    pub title: String,
+    /// Bytes of `title` the path took, shortened first when the row is too
+    /// long for the list
+    pub title_path: Option<Range<usize>>,
+    /// Controller and action that handled a request
+    pub action: Option<String>,
+    pub line_count: usize,
+    pub sql_count: usize,
+    pub status: StatusType,
+    pub status_code: Option<u16>,
+    pub finished: bool,
+    pub starred: bool,
+    pub pinned: bool,
+    /// Has a note attached (`n`)
+    pub noted: bool,
+    /// Likely a double submit
+    pub duplicate: bool,
+    /// Chars of the title the search matched
+    pub matched: Vec<usize>,
+    pub selected: bool,
+    /// In the visual selection
+    pub marked: bool,
+}
+
+pub struct EndpointListView {
+    pub chrome: PanelChrome,
+    pub title: String,
+    /// Only the endpoints on screen
+    pub rows: Vec<EndpointSummary>,
+    /// Row of `rows` under the cursor
+    pub cursor: usize,
+    pub endpoint_width: usize,
+}
+
+pub struct DetailView {
+    pub chrome: PanelChrome,
+    /// Position, request and compaction, e.g. `[1-12/40] GET /posts `
+    pub title: String,
+    pub status: StatusType,
+    pub icons: IconSet,
+    /// Only the entries on screen, each with the rows it takes
+    pub entries: Vec<DetailEntry>,
+    pub wrap: bool,
+    pub search_query: String,
+    /// The search query is being typed
+    pub searching: bool,
+    pub help: String,
+}
+
+pub struct DetailEntry {
+    pub lines: Vec<Line<'static>>,
+    /// Under the line cursor or in a mouse selection
+    pub highlighted: bool,
+}
+
+pub struct SqlView<'a> {
+    pub chrome: PanelChrome,
+    /// The selected request, for its queries, views, outbound calls and
+    /// cache operations
+    pub request: Option<&'a LogGroup>,
+    pub scroll: usize,
+    pub strings: &'static Strings,
+}
+
+/// The strip below the panels with requests on a time axis (`L`).
+pub struct TimelineView {
+    pub title: String,
+    /// Labels for both ends of the axis
+    pub axis: (String, String),
+    pub rows: TimelineRows,
+    /// Shown instead of the rows when there are none
+    pub note: Option<&'static str>,
+}
+
+pub enum TimelineRows {
+    /// Steps from the top line of the detail panel on, so scrolling the
+    /// detail panel scrolls them too
+    Steps { total_ms: f64, steps: Vec<Step> },
+    /// The requests on screen in the list, placed on the axis
+    Requests(Vec<RequestBar>),
+}
+
+pub struct RequestBar {
+    /// Index of the request in the list, to select it on click
+    pub index: usize,
+    /// Row of the bar; requests that ran at once get different lanes
+    pub lane: usize,
+    /// Cells of the axis it covers
+    pub cells: Range<usize>,
+    pub status: StatusType,
+    pub selected: bool,
+}
+
+pub struct StatusBarView {
+    pub badges: Vec<String>,
+    pub sampling: Option<String>,
+    pub segments: Vec<String>,
+    /// A request just failed
+    pub flashing: bool,
+}
+
+pub fn list(app: &App) -> ListView<'_> {
+    match app.endpoint_cursor {
+        Some(cursor) => ListView::Endpoints(endpoint_list(app, cursor)),
+        None => ListView::Requests(request_list(app)),
+    }
+}
+
+fn request_list(app: &App) -> RequestListView<'_> {
+    let visible_requests = app.visible_request_ids();
+    let total_visible = visible_requests.len();
+    let current_offset = app.app_view.get_scroll_offset(Panel::RequestList);
+    let layout = app.list_layout(current_offset);
+    let visible_count = layout
+        .iter()
+        .filter(|row| matches!(row, ListRow::Request(_)))
+        .count();
+    let marked = app.visual_range();
+
+    let mut header = None;
+    let rows: Vec<RequestRow> = layout
+        .into_iter()
+        .filter_map(|list_row| {
+            let row = match list_row {
+                ListRow::Header(label) => {
+                    header = Some(label);
+                    return None;
+                }
+                ListRow::Request(row) => row,
+            };
+            let &(original_index, request_id) = visible_requests.get(row)?;
+            let group = app.state.logs_by_request_id.get(request_id)?;
+            let title = group.list_title(app.config.list.title.as_ref());
+            Some(RequestRow {
+                header: header.take(),
+                number: row + 1,
+                time: app.config.times().format(&group.first_timestamp, Precision::Minutes),
+                duration_ms: group.duration_ms,
+                source: app
+                    .source_name(group.source)
+                    .map(|name| (name, group.source)),
+                kind: group.kind,
+                matched: filter::fuzzy_match(&title.text, &app.search_query).unwrap_or_default(),
+                title: title.text,
+                title_path: title.path,
+                action: group.title_fields.controller_action(),
+                line_count: group.entries.len(),
+                sql_count: group.sql_query_info.total_queries(),
+                status: group.status_type,
+                status_code: group.status_code,
+                finished: group.finished,
+                starred: group.starred,
+                pinned: group.pinned,
+                noted: group.note.is_some(),
+                duplicate: group.duplicate,
+                selected: original_index == app.state.selected_index,
+                marked: marked.as_ref().is_some_and(|marked| marked.contains(&row)),
+            })
+        })
+        .collect();
+
+    // As wide as the longest on screen
+    let action_width = rows
+        .iter()
+        .filter_map(|row| row.action.as_deref())
+        .map(text_measure::width)
+        .max()
+        .unwrap_or(0)
+        .min(ACTION_COLUMN_MAX_WIDTH);
+
+    let total_requests = app.state.log_group_count();
+    let scroll_info = if total_visible == 0 {
+        "0/0".to_string()
+    } else if app.is_filtered() {
+        format!("{}/{}", total_visible, total_requests)
+    } else {
+        let start_idx = current_offset + INDEX_OFFSET;
+        let end_idx = (start_idx + visible_count - INDEX_OFFSET).min(total_visible);
+        format!("{}-{}/{}", start_idx, end_idx, total_requests)
+    };
+
+    let strings = app.config.locale.strings();
+    let is_list_search = matches!(app.search_mode, Some(SearchTarget::RequestList));
+    let mut title = format!("[{}]", scroll_info);
+    match app.kind_filter {
+        Some(GroupKind::Request) => {
+            title.push(' ');
+            title.push_str(strings.requests);
+        }
+        Some(GroupKind::Job) => {
+            title.push(' ');
+            title.push_str(strings.jobs);
+        }
+        Some(GroupKind::Cable) => {
+            title.push(' ');
+            title.push_str(strings.cable);
+        }
+        Some(GroupKind::Generic | GroupKind::Unassigned) | None => {}
+    }
+    if let Some(name) = app.source_filter.and_then(|source| app.source_name(source)) {
+        title.push(' ');
+        title.push_str(&fill(strings.source_filter, &[&name]));
+    }
+    if app.starred_only {
+        title.push(' ');
+        title.push_str(strings.starred);
+    }
+    if app.errors_only {
+        title.push(' ');
+        title.push_str(strings.errors);
+    }
+    if let Some(window) = app.time_window {
+        let label = window.label(app.config.times());
+        title.push(' ');
+        match window {
+            TimeWindow::Last(_) => title.push_str(&fill(strings.time_window_last, &[&label])),
+            TimeWindow::Between(..) => title.push_str(&label),
+        }
+    }
+    if let Some(endpoint) = &app.endpoint_filter {
+        title.push(' ');
+        title.push_str(endpoint);
+    }
+    if let Some(controller) = &app.controller_filter {
+        title.push_str(&format!(" controller={}", controller));
+    }
+    if let Some(codes) = app.status_filter {
+        title.push_str(&format!(" status={}", codes));
+    }
+    if let Some(ms) = app.slow_filter {
+        title.push_str(&format!(" slow={}ms", ms));
+    }
+    if app.writes_only {
+        title.push_str(" writes");
+    }
+    if app.duplicates_only {
+        title.push_str(" duplicates");
+    }
+    if app.stderr_only {
+        title.push_str(" stderr");
+    }
+    if let Some(sort) = app.sort {
+        title.push(' ');
+        title.push_str(&fill(strings.sorted_by, &[&sort.name()]));
+    }
+    if is_list_search || !app.search_query.is_empty() {
+        title.push_str(&format!(" /{}", app.search_query));
+    }
+    let now = Instant::now();
+    if let Some(errors) = app.traffic.error_percent(now) {
+        let mut rate = fill(
+            strings.traffic_rate,
+            &[&format!("{:.1}", app.traffic.per_second(now))],
+        );
+        if errors > 0.0 {
+            rate.push(' ');
+            rate.push_str(&fill(strings.traffic_errors, &[&format!("{:.0}", errors)]));
+        }
+        // The sparkline gets the room the rest of the title leaves
+        let room = app
+            .app_view
+            .viewport_width(Panel::RequestList)
+            .saturating_sub(title.chars().count() + rate.chars().count() + 2)
+            .min(TRAFFIC_SECONDS);
+        if room > 0 {
+            title.push(' ');
+            title.push_str(&metrics::sparkline(&app.traffic.requests(now, room)));
+        }
+        title.push(' ');
+        title.push_str(&rate);
+    }
+
+    let prompt = match app.search_mode {
+        Some(SearchTarget::Jump) => Some(fill(strings.jump_prompt, &[&app.jump_query])),
+        Some(SearchTarget::Command) => Some(format!(
+            " :{}_ {}",
+            app.command_query,
+            command::complete(&app.command_query).1.join(" ")
+        )),
+        Some(SearchTarget::TimeWindow) => {
+            Some(fill(strings.time_window_prompt, &[&app.time_window_query]))
+        }
+        Some(SearchTarget::Export) => Some(fill(
+            strings.export_prompt,
+            &[&app.export_format.name(), &app.export_query],
+        )),
+        Some(SearchTarget::RequestList) => Some(format!(" /{}_ ", app.search_query)),
+        _ if !app.count_prefix.is_empty() || app.g_pending => Some(format!(
+            " {}{}_ ",
+            app.count_prefix,
+            if app.g_pending { "g" } else { "" }
+        )),
+        _ => None,
+    };
+
+    RequestListView {
+        chrome: PanelChrome::of(app, Panel::RequestList),
+        title,
+        prompt,
+        rows,
+        number_width: app
+            .config
+            .request_numbers
+            .then(|| total_visible.to_string().len()),
+        source_width: app.source_name_width().min(SOURCE_COLUMN_MAX_WIDTH),
+        action_width,
+        width: app.app_view.viewport_width(Panel::RequestList),
+        columns: &app.columns,
+        icons: app.config.icons,
+        strings,
+    }
+}
+
+fn endpoint_list(app: &App, cursor: usize) -> EndpointListView {
+    let strings = app.config.locale.strings();
+    let summaries = app.endpoint_summaries();
+    let title = fill(strings.endpoints_title, &[&summaries.len()]);
+    let cursor = cursor.min(summaries.len().saturating_sub(1));
+    let viewport_height = app.app_view.viewport_height(Panel::RequestList).max(1);
+    let offset = (cursor + 1).saturating_sub(viewport_height);
+    // Inside the border and padding, less the figures after the endpoint
+    let endpoint_width = (app.app_view.layout_info.region(Panel::RequestList).width as usize)
+        .saturating_sub(4 + ENDPOINT_FIGURES_WIDTH);
+
+    EndpointListView {
+        chrome: PanelChrome::of(app, Panel::RequestList),
+        title,
+        rows: summaries
+            .into_iter()
+            .skip(offset)
+            .take(viewport_height)
+            .collect(),
+        cursor: cursor - offset,
+        endpoint_width,
+    }
+}
+
+/// The rows `log` takes in the detail panel before wrapping, as `detail`
+/// draws them; `App` counts wrapped rows from these too.
+pub fn detail_entry_lines(
+    app: &App,
+    group: &LogGroup,
+    log: &LogEntry,
+) -> Option<Vec<Line<'static>>> {
+    let mut lines = build_detail_log_lines(
+        app.config.locale.strings(),
+        log,
+        &group.sql_query_info,
+        &app.app_view.detail_search.query,
+        app.verbosity,
+        app.app_view.expand_params,
+        app.app_view.inline_binds,
+    )?;
+    if log.stderr {
+        mark_stderr(&mut lines, app.config.icons.stderr());
+    }
+    if app.app_view.relative_times {
+        prefix_relative_time(&mut lines[0], log.timestamp - group.first_timestamp);
+    }
+    Some(lines)
+}
+
+pub fn detail(app: &App) -> DetailView {
+    let strings = app.config.locale.strings();
+    let chrome = PanelChrome::of(app, Panel::RequestDetail);
+    let help = help_text(app);
+    let search_query = app.app_view.detail_search.query.clone();
+    let searching = matches!(app.search_mode, Some(SearchTarget::DetailLog));
+    let wrap = app.verbosity == Verbosity::Full && app.app_view.wrap_detail;
+    let icons = app.config.icons;
+
+    let Some(group) = app.state.selected_group() else {
+        return DetailView {
+            chrome,
+            title: format!("[0/0] {} ", strings.logs),
+            status: StatusType::Unknown,
+            icons,
+            entries: vec![DetailEntry {
+                lines: vec![Line::from(strings.waiting_for_logs)],
+                highlighted: false,
+            }],
+            wrap,
+            search_query,
+            searching,
+            help,
+        };
+    };
+
+    let verbosity = app.verbosity;
+    let viewport_height = app.app_view.viewport_height(Panel::RequestDetail);
+    let scroll_offset = app.app_view.get_scroll_offset(Panel::RequestDetail);
+
+    let displayed = group.displayed(verbosity);
+    let total = displayed.len();
+    let start_idx = scroll_offset.min(total.saturating_sub(1));
+    // A wrapped entry can take several rows
+    let wrap_width = wrap.then(|| app.app_view.viewport_width(Panel::RequestDetail));
+
+    // Only the lines on screen are looked at, however long the request
+    let visible = displayed.from(start_idx);
+    let mut entries = Vec::new();
+    let mut rows = 0;
+    for (i, log) in visible {
+        if rows >= viewport_height {
+            break;
+        }
+        let Some(lines) = detail_entry_lines(app, group, log) else {
+            continue;
+        };
+        rows += lines
+            .iter()
+            .map(|line| wrap_width.map_or(1, |width| text_measure::wrapped_rows(line, width)))
+            .sum::<usize>();
+        let highlighted = app.highlighted_entry == Some(i)
+            || app
+                .drag_selection
+                .as_ref()
+                .is_some_and(|selection| selection.contains(i));
+        entries.push(DetailEntry { lines, highlighted });
+    }
+
+    let scroll_info = if total == 0 {
+        "0/0".to_string()
+    } else {
+        let start_idx = (scroll_offset + INDEX_OFFSET).min(total).max(1);
+        let end_idx = (start_idx + entries.len().saturating_sub(1)).min(total);
+        format!("{}-{}/{}", start_idx, end_idx, total)
+    };
+    let mut title = format!("[{}] {} ", scroll_info, detail_title(app, group));
+    if group.compacted {
+        title.push_str(if app.journal.is_some() {
+            strings.compacted_restorable
+        } else {
+            strings.compacted
+        });
+    }
+    if let Some(matches) = detail_match_label(app) {
+        title.push_str(&matches);
+    }
+
+    DetailView {
+        chrome,
+        title,
+        status: group.status_type,
+        icons,
+        entries,
+        wrap,
+        search_query,
+        searching,
+        help,
+    }
+}
+
+/// Which of the detail search's matches the panel is on, e.g. `[match 2/5] `,
+/// while there is a search.
+pub fn detail_match_label(app: &App) -> Option<String> {
+    let search = &app.app_view.detail_search;
+    if search.query.is_empty() {
+        return None;
+    }
+    let matches = app.detail_matches();
+    let current = search
+        .current
+        .and_then(|current| matches.iter().position(|&entry| entry == current))
+        .map_or(0, |position| position + 1);
+    let strings = app.config.locale.strings();
+    Some(fill(strings.search_matches, &[&current, &matches.len()]))
+}
+
+/// `GET /posts/1` for a request, the title of other groups, cut to the panel.
+pub fn detail_title(app: &App, group: &LogGroup) -> String {
+    let view_width = app.app_view.viewport_width(Panel::RequestDetail);
+    if group.kind == GroupKind::Unassigned {
+        let strings = app.config.locale.strings();
+        return fill(strings.unassigned, &[&group.entries.len()]);
+    }
+    if group.kind == GroupKind::Generic {
+        return text_measure::truncate(&group.title, view_width.saturating_sub(10)).to_string();
+    }
+
+    let entry = group.entries.iter().rev().find(|entry| {
+        let msg = &entry.message;
+        msg.contains("Started GET")
+            || msg.contains("Started POST")
+            || msg.contains("Started PUT")
+            || msg.contains("Started PATCH")
+            || msg.contains("Started DELETE")
+            || msg.contains("Started HEAD")
+            || msg.contains("Started OPTIONS")
+            || msg.contains("Started TRACE")
+    });
+
+    let Some(entry) = entry else {
+        return String::new();
+    };
+
+    let msg = strip_ansi_for_parsing(&entry.message);
+    let method = msg
+        .split_whitespace()
+        .skip_while(|&s| s != "Started")
+        .nth(1)
+        .unwrap_or("");
+
+    let url = msg
+        .find(" \"")
+        .and_then(|start| {
+            msg[start + 2..]
+                .find('"')
+                .map(|end| &msg[start + 2..start + 2 + end])
+        })
+        .unwrap_or("");
+
+    let text = format!("{} {}", method, url);
+    text_measure::truncate(&text, view_width.saturating_sub(10)).to_string()
+}
+
+pub fn sql(app: &App) -> SqlView<'_> {
+    SqlView {
+        chrome: PanelChrome::of(app, Panel::SqlInfo),
+        request: app.state.selected_group(),
+        scroll: app.app_view.get_scroll_offset(Panel::SqlInfo),
+        strings: app.config.locale.strings(),
+    }
+}
+
+pub fn timeline(app: &App) -> TimelineView {
+    match app.app_view.timeline {
+        Some(TimelineScope::AllRequests) => all_requests_timeline(app),
+        _ => request_timeline(app),
+    }
+}
+
+fn request_timeline(app: &App) -> TimelineView {
+    let strings = app.config.locale.strings();
+    let waterfall = app.state.selected_group().and_then(waterfall::build);
+    let Some(waterfall) = waterfall else {
+        return TimelineView {
+            title: fill(strings.timeline_title, &[&0, &0]),
+            axis: Default::default(),
+            rows: TimelineRows::Steps {
+                total_ms: 0.0,
+                steps: Vec::new(),
+            },
+            note: Some(match app.state.selected_group() {
+                Some(_) => strings.timeline_no_timestamps,
+                None => strings.waiting_for_logs,
+            }),
+        };
+    };
+
+    let total = format!("{:.0}", waterfall.total_ms);
+    let title = fill(strings.timeline_title, &[&waterfall.steps.len(), &total]);
+    let verbosity = app.verbosity;
+    let top_entry = app.state.selected_group().and_then(|group| {
+        group
+            .displayed(verbosity)
+            .index(app.app_view.get_scroll_offset(Panel::RequestDetail))
+    });
+    let steps: Vec<Step> = waterfall
+        .steps
+        .into_iter()
+        .filter(|step| top_entry.is_none_or(|top| step.entry >= top))
+        .collect();
+    TimelineView {
+        title,
+        axis: ("0ms".to_string(), format!("{}ms", total)),
+        note: steps.is_empty().then_some(strings.timeline_empty),
+        rows: TimelineRows::Steps {
+            total_ms: waterfall.total_ms,
+            steps,
+        },
+    }
+}
+
+fn all_requests_timeline(app: &App) -> TimelineView {
+    let strings = app.config.locale.strings();
+    let offset = app.app_view.get_scroll_offset(Panel::RequestList);
+    let viewport_height = app.app_view.viewport_height(Panel::RequestList);
+    let spans: Vec<_> = app
+        .visible_request_ids()
+        .into_iter()
+        .skip(offset)
+        .take(viewport_height)
+        .filter_map(|(index, request_id)| {
+            let group = app.state.logs_by_request_id.get(request_id)?;
+            (group.kind != GroupKind::Unassigned)
+                .then(|| (index, group, waterfall::request_span(group)))
+        })
+        .collect();
+    let from = spans.iter().map(|(_, _, (start, _))| *start).min();
+    let to = spans.iter().map(|(_, _, (_, end))| *end).max();
+    let (Some(from), Some(to)) = (from, to) else {
+        return TimelineView {
+            title: fill(strings.timeline_all_title, &[&0, &0]),
+            axis: Default::default(),
+            rows: TimelineRows::Requests(Vec::new()),
+            note: Some(strings.waiting_for_logs),
+        };
+    };
+
+    let total_ms = (to - from).num_milliseconds();
+    let axis_width = app
+        .app_view
+        .layout_info
+        .timeline()
+        .map_or(0, |area| timeline_axis_width(area.width));
+    let cell = |time: DateTime<FixedOffset>| {
+        ((time - from).num_milliseconds() as f64 / total_ms.max(1) as f64 * axis_width as f64)
+            as usize
+    };
+    let cells: Vec<Range<usize>> = spans
+        .iter()
+        .map(|(_, _, (start, end))| {
+            let start = cell(*start).min(axis_width.saturating_sub(1));
+            start..cell(*end).clamp(start + 1, axis_width.max(start + 1))
+        })
+        .collect();
+    let lanes = waterfall::pack_lanes(&cells);
+    let bars = spans
+        .iter()
+        .zip(cells)
+        .zip(lanes)
+        .map(|(((index, group, _), cells), lane)| RequestBar {
+            index: *index,
+            lane,
+            cells,
+            status: group.status_type,
+            selected: *index == app.state.selected_index,
+        })
+        .collect();
+
+    let clock = |time: &DateTime<FixedOffset>| app.config.times().format(time, Precision::Millis);
+    TimelineView {
+        title: fill(strings.timeline_all_title, &[&spans.len(), &total_ms]),
+        axis: (clock(&from), clock(&to)),
+        rows: TimelineRows::Requests(bars),
+        note: (total_ms == 0).then_some(strings.timeline_all_no_timestamps),
+    }
+}
+
+pub fn status_bar(app: &App) -> StatusBarView {
+    StatusBarView {
+        badges: input_badges(app),
+        sampling: sampling_badge(app),
+        segments: status_segments(app),
+        flashing: app.alerts.flashing(Instant::now()),
+    }
+}
+
+pub fn help_text(app: &App) -> String {
+    let strings = app.config.locale.strings();
+    if app.copy_mode_enabled {
+        let panel_name = match app.app_view.focused_panel {
+            Panel::RequestList => "RequestList",
+            Panel::RequestDetail => "RequestDetail",
+            Panel::SqlInfo => "SqlInfo",
+        };
+        return fill(strings.help_copy, &[&panel_name]);
+    }
+    if app.line_select_active() {
+        return strings.help_line_select.to_string();
+    }
+    if let Some(range) = app.visual_range() {
+        return fill(strings.help_visual, &[&range.count()]);
+    }
+    if app.endpoint_cursor.is_some() {
+        return strings.help_endpoints.to_string();
+    }
+    let help = match app.verbosity {
+        Verbosity::Full => strings.help,
+        Verbosity::Simple => strings.help_simple,
+        Verbosity::Summary => strings.help_summary,
+    };
+    let mut help = help.to_string();
+    if app.has_multiple_sources() {
+        help.push_str(strings.help_sources);
+    }
+    if app.is_filtered() {
+        help.push_str(strings.help_bulk);
+    }
+    if !app.mouse_enabled {
+        help.push_str(strings.help_no_mouse);
+    }
+    help
+}
+
+/// Ingest metrics, the time zone, and in file mode the span, offset and jump hint.
+pub fn status_segments(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let ingest = &app.state.ingest;
+    let mut segments = vec![
+        fill(
+            strings.ingest_rate,
+            &[&(app.lines_per_second.round() as u64)],
+        ),
+        fill(strings.ingest_requests, &[&app.state.request_ids.len()]),
+    ];
+    if ingest.orphan_lines > 0 {
+        segments.push(fill(strings.ingest_orphans, &[&ingest.orphan_lines]));
+    }
+    if ingest.dropped_lines > 0 {
+        segments.push(fill(strings.ingest_dropped, &[&ingest.dropped_lines]));
+    }
+    segments.push(format!("~{}", format_bytes(app.memory_estimate as u64)));
+    segments.push(fill(
+        strings.time_zone,
+        &[&app.config.time_zone.label(strings)],
+    ));
+
+    if let Some(index) = &app.file_index {
+        if let Some((first, last)) = index.span() {
+            let times = app.config.times();
+            segments.push(fill(
+                strings.span,
+                &[
+                    &times.format_dated(&first, Precision::Seconds),
+                    &times.format(&last, Precision::Seconds),
+                    &format_duration(last - first),
+                ],
+            ));
+        }
+        if let Some(request) = app
+            .state
+            .selected_request_id()
+            .and_then(|request_id| index.get(request_id))
+        {
+            segments.push(fill(strings.offset, &[&format_bytes(request.offset)]));
+        }
+        segments.push(strings.jump.to_string());
+    }
+
+    if let Some(latest) = app.update_notice.as_ref().and_then(|notice| notice.get()) {
+        segments.push(fill(strings.update_available, &[latest]));
+    }
+    segments
+}
+
+/// Badges for the state of the input: paused, following files, ended.
+pub fn input_badges(app: &App) -> Vec<String> {
+    let strings = app.config.locale.strings();
+    let mut badges = Vec::new();
+    if app.paused {
+        badges.push(fill(strings.badge_paused, &[&app.queued_lines]));
+    }
+    if app.follow {
+        badges.push(strings.badge_follow.to_string());
+    }
+    let exit_status = app.supervisor.as_ref().and_then(|supervisor| supervisor.exit_status());
+    if let Some(status) = exit_status {
+        badges.push(fill(strings.badge_exited, &[&supervisor::exit_label(status)]));
+    } else if app.input_ended && app.playback.is_none() {
+        let badge = if app.can_resume_input() {
+            strings.badge_end_of_file
+        } else {
+            strings.badge_input_ended
+        };
+        badges.push(badge.to_string());
+    }
+    badges
+}
+
+pub fn sampling_badge(app: &App) -> Option<String> {
+    let strings = app.config.locale.strings();
+    let sampler = app.state.sampler.as_ref()?;
+    Some(fill(
+        strings.sampling,
+        &[&sampler.rate(), &sampler.dropped()],
+    ))
+}
+
+fn format_duration(duration: chrono::TimeDelta) -> String {
+    let seconds = duration.num_seconds();
+    if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
@@ -0,0 +1,287 @@
+//! Requests on a time axis (`L`): the queries, renders and outgoing HTTP
+//! calls of one request as bars that end when their line was logged, or
+//! whole requests side by side to see which ones ran at once.
+
+use crate::app_state::LogGroup;
+use crate::log_parser::strip_ansi_for_parsing;
+use crate::render_info::parse_rendered;
+use crate::sql_info::query_duration_ms;
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+// GET https://api.example.com/v1/charges (120.5ms)
+// [httplog] POST http://payments.internal/refunds completed in 87ms
+static RE_HTTP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:\b(?P<method>GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS)\s+)?(?P<url>https?://[^\s\x22')]+)",
+    )
+    .unwrap()
+});
+static RE_MS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?P<ms>\d+(?:\.\d+)?)\s?ms\b").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Sql,
+    Render,
+    Http,
+}
+
+/// A timed line of the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub kind: StepKind,
+    /// `Post Load`, the template, or the method and URL
+    pub label: String,
+    /// Offset from the request's first line
+    pub start_ms: f64,
+    pub duration_ms: f64,
+    /// Position of the line in the request, oldest first
+    pub entry: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waterfall {
+    /// Length of the axis: the request's duration, or the span of its lines
+    pub total_ms: f64,
+    pub steps: Vec<Step>,
+}
+
+/// Lays out the timed lines of a request. None when its lines were all
+/// logged at the same instant, as in a file without timestamps.
+pub fn build(group: &LogGroup) -> Option<Waterfall> {
+    let start = group.entries.back()?.timestamp;
+    let end = group.entries.front()?.timestamp;
+    if end <= start {
+        return None;
+    }
+    let offset_ms = |timestamp: DateTime<FixedOffset>| {
+        (timestamp - start).num_microseconds().unwrap_or(0) as f64 / 1000.0
+    };
+
+    let steps: Vec<Step> = group
+        .entries
+        .iter()
+        .rev()
+        .enumerate()
+        .filter_map(|(entry, log)| {
+            let message = strip_ansi_for_parsing(&log.message);
+            let (kind, label, duration_ms) = timed_line(untagged(&message))?;
+            let end_ms = offset_ms(log.timestamp);
+            Some(Step {
+                kind,
+                label,
+                start_ms: (end_ms - duration_ms).max(0.0),
+                duration_ms,
+                entry,
+            })
+        })
+        .collect();
+
+    let span_ms = offset_ms(end);
+    let total_ms = group
+        .duration_ms
+        .map_or(span_ms, |ms| (ms as f64).max(span_ms));
+    Some(Waterfall { total_ms, steps })
+}
+
+/// When a request ran: from its first line to its completion, or to its
+/// latest line while it runs.
+pub fn request_span(group: &LogGroup) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+    let start = group.first_timestamp;
+    let end = match group.duration_ms {
+        Some(ms) => start + TimeDelta::milliseconds(ms as i64),
+        None => group.entries.front().map_or(start, |entry| entry.timestamp),
+    };
+    (start, end.max(start))
+}
+
+/// A lane for each bar covering `spans` of cells, so that bars sharing a
+/// lane neither overlap nor touch. The first lanes are filled first.
+pub fn pack_lanes(spans: &[Range<usize>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..spans.len()).collect();
+    order.sort_by_key(|&i| spans[i].start);
+    // Cell after the last bar of each lane
+    let mut lane_ends: Vec<usize> = Vec::new();
+    let mut lanes = vec![0; spans.len()];
+    for i in order {
+        let lane = match lane_ends.iter().position(|&end| end < spans[i].start) {
+            Some(lane) => lane,
+            None => {
+                lane_ends.push(0);
+                lane_ends.len() - 1
+            }
+        };
+        lane_ends[lane] = spans[i].end;
+        lanes[i] = lane;
+    }
+    lanes
+}
+
+/// A line without the `[request id]` style tags in front of it.
+fn untagged(mut line: &str) -> &str {
+    while let Some(rest) = line.strip_prefix('[') {
+        match rest.find("] ") {
+            Some(end) => line = &rest[end + 2..],
+            None => break,
+        }
+    }
+    line
+}
+
+fn timed_line(message: &str) -> Option<(StepKind, String, f64)> {
+    if let Some(duration_ms) = query_duration_ms(message) {
+        let name = message.split(" (").next().unwrap_or_default().trim();
+        return Some((StepKind::Sql, name.to_string(), duration_ms));
+    }
+    if let Some(rendered) = parse_rendered(message) {
+        return Some((StepKind::Render, rendered.template.to_string(), rendered.ms));
+    }
+    let http = RE_HTTP.captures(message)?;
+    let duration_ms = RE_MS.captures(&message[http.get(0)?.end()..])?["ms"]
+        .parse()
+        .ok()?;
+    let label = match http.name("method") {
+        Some(method) => format!("{} {}", method.as_str(), &http["url"]),
+        None => http["url"].to_string(),
+    };
+    Some((StepKind::Http, label, duration_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::LogEntry;
+
+    fn request(lines: &[(&str, &str)]) -> LogGroup {
+        let entry = |&(time, message): &(&str, &str)| LogEntry {
+            timestamp: DateTime::parse_from_rfc3339(time).unwrap(),
+            request_id: "r1".to_string(),
+            message: format!("[r1] {}", message),
+            ..Default::default()
+        };
+        let mut group = LogGroup::new(entry(&lines[0]));
+        for line in &lines[1..] {
+            group.add_entry(entry(line));
+        }
+        group
+    }
+
+    #[test]
+    fn test_build() {
+        let group = request(&[
+            (
+                "2024-05-01T12:00:00.000Z",
+                r#"Started GET "/posts" for 127.0.0.1"#,
+            ),
+            (
+                "2024-05-01T12:00:00.010Z",
+                "  Post Load (4.0ms)  SELECT \"posts\".* FROM \"posts\"",
+            ),
+            (
+                "2024-05-01T12:00:00.070Z",
+                "  GET https://api.example.com/feed?page=1 (50.0ms)",
+            ),
+            (
+                "2024-05-01T12:00:00.090Z",
+                "  Rendered posts/index.html.erb (Duration: 15.5ms | Allocations: 100)",
+            ),
+            ("2024-05-01T12:00:00.100Z", "Completed 200 OK in 100ms"),
+        ]);
+        let waterfall = build(&group).unwrap();
+        assert_eq!(waterfall.total_ms, 100.0);
+        assert_eq!(
+            waterfall.steps,
+            vec![
+                Step {
+                    kind: StepKind::Sql,
+                    label: "Post Load".to_string(),
+                    start_ms: 6.0,
+                    duration_ms: 4.0,
+                    entry: 1,
+                },
+                Step {
+                    kind: StepKind::Http,
+                    label: "GET https://api.example.com/feed?page=1".to_string(),
+                    start_ms: 20.0,
+                    duration_ms: 50.0,
+                    entry: 2,
+                },
+                Step {
+                    kind: StepKind::Render,
+                    label: "posts/index.html.erb".to_string(),
+                    start_ms: 74.5,
+                    duration_ms: 15.5,
+                    entry: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_without_timestamps() {
+        let group = request(&[
+            (
+                "2024-05-01T12:00:00Z",
+                r#"Started GET "/posts" for 127.0.0.1"#,
+            ),
+            (
+                "2024-05-01T12:00:00Z",
+                "  Post Load (4.0ms)  SELECT \"posts\".* FROM \"posts\"",
+            ),
+            ("2024-05-01T12:00:00Z", "Completed 200 OK in 10ms"),
+        ]);
+        assert_eq!(build(&group), None);
+    }
+
+    #[test]
+    fn test_request_span() {
+        let mut group = request(&[
+            (
+                "2024-05-01T12:00:00.000Z",
+                r#"Started GET "/posts" for 127.0.0.1"#,
+            ),
+            (
+                "2024-05-01T12:00:00.040Z",
+                "  Post Load (4.0ms)  SELECT \"posts\".* FROM \"posts\"",
+            ),
+        ]);
+        let (start, end) = request_span(&group);
+        assert_eq!((end - start).num_milliseconds(), 40);
+
+        group.add_entry(LogEntry {
+            timestamp: DateTime::parse_from_rfc3339("2024-05-01T12:00:00.090Z").unwrap(),
+            message: "[r1] Completed 200 OK in 120ms".to_string(),
+            ..Default::default()
+        });
+        let (start, end) = request_span(&group);
+        assert_eq!((end - start).num_milliseconds(), 120);
+    }
+
+    #[test]
+    fn test_pack_lanes() {
+        assert_eq!(
+            pack_lanes(&[0..4, 2..6, 5..8, 9..10, 3..4]),
+            vec![0, 1, 0, 0, 2]
+        );
+        assert_eq!(pack_lanes(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_untimed_urls() {
+        assert_eq!(
+            timed_line("Redirected to http://localhost:3000/posts"),
+            None
+        );
+        assert_eq!(
+            timed_line("[httplog] POST http://payments.internal/refunds completed in 87ms"),
+            Some((
+                StepKind::Http,
+                "POST http://payments.internal/refunds".to_string(),
+                87.0
+            ))
+        );
+    }
+}